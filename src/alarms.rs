@@ -0,0 +1,71 @@
+//! Alarms for the meetings that can't be missed: events marked high
+//! importance in Outlook, or flagged in the app with `!`, get a popup at
+//! their start that stays until it is dismissed or snoozed.
+
+use crate::app::{parse_event_time, ColorEvent};
+use crate::clock;
+use chrono::{DateTime, Duration, Local};
+use std::collections::HashSet;
+
+/// How long a snoozed alarm stays away.
+pub const SNOOZE: Duration = Duration::minutes(5);
+
+/// Events that started longer ago than this don't ring any more, e.g. when
+/// the app is opened in the middle of one.
+const RING_WINDOW: Duration = Duration::minutes(10);
+
+#[derive(Clone)]
+pub struct Alarm {
+    pub subject: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+#[derive(Default)]
+pub struct AlarmManager {
+    /// Correlation IDs of the events that already rang
+    rung: HashSet<String>,
+    /// Snoozed alarms and when they ring again
+    snoozed: Vec<(Alarm, DateTime<Local>)>,
+}
+
+/// Whether an event gets an alarm: high importance or flagged in the app.
+pub fn is_alarmed(event: &crate::api::GraphEvent, flagged: &HashSet<String>) -> bool {
+    event.importance.as_deref() == Some("high") || flagged.contains(event.correlation_id())
+}
+
+impl AlarmManager {
+    /// The next alarm to show, if one is due. Each event rings once, plus
+    /// whenever its snooze runs out.
+    pub fn due(&mut self, events: &[ColorEvent], flagged: &HashSet<String>) -> Option<Alarm> {
+        let now = clock::now();
+        self.snoozed.retain(|(alarm, _)| alarm.end > now);
+        if let Some(index) = self.snoozed.iter().position(|(_, until)| *until <= now) {
+            return Some(self.snoozed.remove(index).0);
+        }
+        for event in events.iter().map(|e| &e.event) {
+            if !is_alarmed(event, flagged) || self.rung.contains(event.correlation_id()) {
+                continue;
+            }
+            let (Some(start), Some(end)) = (
+                parse_event_time(&event.start.date_time),
+                parse_event_time(&event.end.date_time),
+            ) else {
+                continue;
+            };
+            if start <= now && now < start + RING_WINDOW {
+                self.rung.insert(event.correlation_id().to_string());
+                return Some(Alarm {
+                    subject: event.subject.clone(),
+                    start,
+                    end,
+                });
+            }
+        }
+        None
+    }
+
+    pub fn snooze(&mut self, alarm: Alarm) {
+        self.snoozed.push((alarm, clock::now() + SNOOZE));
+    }
+}
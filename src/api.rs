@@ -1,5 +1,15 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Whether a calendar or event lives on Graph, rather than on Google or in
+/// a subscribed feed, which are both read-only.
+pub fn is_graph_id(id: &str) -> bool {
+    !crate::google::is_google_id(id) && !crate::subscriptions::is_subscription_id(id)
+}
 
 // --- Data Structures for Deserializing API Responses ---
 
@@ -9,6 +19,12 @@ pub struct GraphCalendar {
     pub name: String,
     #[serde(rename = "canShare")]
     pub can_share: Option<bool>,
+    #[serde(rename = "hexColor", default)]
+    pub hex_color: Option<String>,
+    #[serde(rename = "isDefaultCalendar", default)]
+    pub is_default_calendar: Option<bool>,
+    #[serde(default)]
+    pub owner: Option<EmailAddress>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +63,73 @@ pub struct GraphEvent {
     pub attendees: Vec<Attendee>,
     pub location: Option<Location>,
     pub organizer: Option<Organizer>,
+    #[serde(rename = "responseStatus", default)]
+    pub response_status: Option<ResponseStatus>,
+    /// Link that opens the event in Outlook on the web.
+    #[serde(rename = "webLink", default)]
+    pub web_link: Option<String>,
+    /// Outlook categories, e.g. "1:1" or "Project X".
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// When the event was created and last changed (RFC 3339, UTC).
+    #[serde(rename = "createdDateTime", default)]
+    pub created_date_time: Option<String>,
+    #[serde(rename = "lastModifiedDateTime", default)]
+    pub last_modified_date_time: Option<String>,
+    /// iCalendar UID, shared by the copies of a meeting in different
+    /// calendars and kept when Graph re-creates an event with a new `id`.
+    #[serde(rename = "iCalUId", default)]
+    pub ical_uid: Option<String>,
+    #[serde(rename = "isAllDay", default)]
+    pub is_all_day: Option<bool>,
+    /// One of free, tentative, busy, oof, workingElsewhere, unknown.
+    #[serde(rename = "showAs", default)]
+    pub show_as: Option<String>,
+    /// Join link of a Teams (or other online) meeting. Graph mostly fills in
+    /// `onlineMeeting` instead; the cache keeps whichever was set here.
+    #[serde(rename = "onlineMeetingUrl", default)]
+    pub online_meeting_url: Option<String>,
+    #[serde(rename = "onlineMeeting", default, skip_serializing)]
+    pub online_meeting: Option<OnlineMeetingInfo>,
+    /// The recurring series an occurrence belongs to.
+    #[serde(rename = "seriesMasterId", default)]
+    pub series_master_id: Option<String>,
+    /// One of low, normal, high.
+    #[serde(default)]
+    pub importance: Option<String>,
+    /// The event's Outlook reminder, if it has one on.
+    #[serde(rename = "isReminderOn", default)]
+    pub is_reminder_on: Option<bool>,
+    #[serde(rename = "reminderMinutesBeforeStart", default)]
+    pub reminder_minutes_before_start: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnlineMeetingInfo {
+    pub join_url: Option<String>,
+}
+
+impl GraphEvent {
+    /// Identifies the same meeting across calendars and systems: the
+    /// iCalendar UID, or the Graph ID for events without one.
+    pub fn correlation_id(&self) -> &str {
+        self.ical_uid.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Link to join the online meeting, if it is one.
+    pub fn join_url(&self) -> Option<&str> {
+        self.online_meeting
+            .as_ref()
+            .and_then(|m| m.join_url.as_deref())
+            .or(self.online_meeting_url.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ResponseStatus {
+    /// One of none, organizer, tentativelyAccepted, accepted, declined, notResponded.
+    pub response: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -74,57 +157,471 @@ struct EventListResponse {
     next_link: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct DeltaPage {
+    value: Vec<serde_json::Value>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+    #[serde(rename = "@odata.deltaLink")]
+    delta_link: Option<String>,
+}
+
+/// What changed in a calendar's date window since the last sync: every event
+/// of the window on the first sync, only the changes after that.
+#[derive(Debug, Default)]
+pub struct EventDelta {
+    pub changed: Vec<GraphEvent>,
+    pub removed: Vec<String>,
+    /// Link that continues the sync next time.
+    pub delta_link: Option<String>,
+    /// The sync stopped at the event limit before the last page, so there is
+    /// no `delta_link` and events are missing.
+    pub truncated: bool,
+    /// Where a truncated sync stopped. Syncing from it fetches the rest.
+    pub next_link: Option<String>,
+}
+
+/// Free/busy information for one person, as returned by `getSchedule`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleInformation {
+    pub schedule_id: String,
+    #[serde(default)]
+    pub schedule_items: Vec<ScheduleItem>,
+    /// Set when the schedule could not be read, e.g. for an unknown address.
+    /// There are no items then, which doesn't mean free.
+    pub error: Option<FreeBusyError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FreeBusyError {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleItem {
+    /// One of free, tentative, busy, oof, workingElsewhere, unknown.
+    pub status: String,
+    pub start: DateTimeTimeZone,
+    pub end: DateTimeTimeZone,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleResponse {
+    value: Vec<ScheduleInformation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeSlot {
+    pub start: DateTimeTimeZone,
+    pub end: DateTimeTimeZone,
+}
+
+/// A candidate slot returned by `findMeetingTimes`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingTimeSuggestion {
+    #[serde(default)]
+    pub confidence: f64,
+    pub organizer_availability: Option<String>,
+    pub suggestion_reason: Option<String>,
+    pub meeting_time_slot: TimeSlot,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingTimeSuggestionsResult {
+    #[serde(default)]
+    pub meeting_time_suggestions: Vec<MeetingTimeSuggestion>,
+    pub empty_suggestions_reason: Option<String>,
+}
+
+/// The fields needed to create a new event. Times use the same naive UTC
+/// format as `GraphEvent`.
+#[derive(Debug, Clone)]
+pub struct NewEvent {
+    pub subject: String,
+    pub start: String,
+    pub end: String,
+    pub attendees: Vec<String>,
+    /// Meeting rooms, invited as resources and set as the location.
+    pub rooms: Vec<EmailAddress>,
+}
+
+/// Changed fields of an existing event; `None` leaves a field as it is.
+/// Times use the same naive UTC format as `GraphEvent`.
+#[derive(Debug, Clone, Default)]
+pub struct EventChanges {
+    pub subject: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphUser {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    pub mail: Option<String>,
+    pub user_principal_name: Option<String>,
+}
+
+// --- Endpoint Configuration ---
+
+/// Graph endpoint of the public Microsoft cloud.
+pub const DEFAULT_GRAPH_BASE_URL: &str = "https://graph.microsoft.com";
+
+static GRAPH_BASE_URL: OnceLock<String> = OnceLock::new();
+
+/// Points all API calls at another Graph endpoint, e.g. a national cloud.
+/// Must be called before the first request; later calls are ignored.
+pub fn set_graph_base_url(url: &str) {
+    let _ = GRAPH_BASE_URL.set(url.trim_end_matches('/').to_string());
+}
+
+/// Root of the Graph endpoint in use, without the API version.
+pub fn graph_base_url() -> &'static str {
+    GRAPH_BASE_URL
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_GRAPH_BASE_URL)
+}
+
+fn graph_url() -> String {
+    format!("{}/v1.0", graph_base_url())
+}
+
+/// How often a throttled or failed request is retried before giving up.
+const MAX_RETRIES: u32 = 4;
+
+/// Longest wait before a retry, whatever `Retry-After` asks for.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+static RETRY_NOTICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// The latest "retrying…" message, if there is a new one, for the status bar.
+pub fn take_retry_notice() -> Option<String> {
+    RETRY_NOTICE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+}
+
+/// Whether a status is worth another try: throttling or a server error.
+fn is_transient(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Waits before the next attempt: as long as `Retry-After` asks, or
+/// exponentially longer with each attempt without one.
+async fn wait_before_retry(attempt: u32, retry_after: Option<u64>, reason: &str) {
+    let delay = retry_after
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| Duration::from_secs(1 << attempt))
+        .min(MAX_RETRY_DELAY);
+    log::info!(
+        "Graph request failed ({}), retry {}/{} in {}s",
+        reason,
+        attempt + 1,
+        MAX_RETRIES,
+        delay.as_secs()
+    );
+    *RETRY_NOTICE.lock().unwrap_or_else(|e| e.into_inner()) = Some(format!(
+        "Microsoft Graph {}, retrying in {}s…",
+        reason,
+        delay.as_secs()
+    ));
+    tokio::time::sleep(delay).await;
+}
+
+/// Sends a Graph request and reads the whole response, counting it in the
+/// request metrics. Throttled, unreachable and failing servers are retried
+/// with backoff. Error statuses are returned as `reqwest` errors.
+async fn send(
+    mut request: reqwest::RequestBuilder,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut attempt = 0;
+    loop {
+        // Requests with a streamed body can't be sent twice
+        let retry = (attempt < MAX_RETRIES)
+            .then(|| request.try_clone())
+            .flatten();
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => match retry {
+                Some(retry) if e.is_connect() || e.is_timeout() => {
+                    wait_before_retry(attempt, None, "unreachable").await;
+                    request = retry;
+                    attempt += 1;
+                    continue;
+                }
+                _ => return Err(e.into()),
+            },
+        };
+        let status = response.status();
+        let status_error = response.error_for_status_ref().err();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok()?.trim().parse().ok());
+        let body = response.bytes().await?;
+        crate::metrics::record(
+            body.len(),
+            status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || status == reqwest::StatusCode::SERVICE_UNAVAILABLE,
+        );
+        match (status_error, retry) {
+            (Some(_), Some(retry)) if is_transient(status) => {
+                wait_before_retry(
+                    attempt,
+                    retry_after,
+                    &format!("returned {}", status.as_u16()),
+                )
+                .await;
+                request = retry;
+                attempt += 1;
+            }
+            (Some(e), _) => return Err(e.into()),
+            (None, _) => return Ok(body.to_vec()),
+        }
+    }
+}
+
+/// `send`, decoding the response as JSON.
+async fn send_json<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(serde_json::from_slice(&send(request).await?)?)
+}
+
+/// Most sub-requests Graph accepts in one `$batch`.
+const MAX_BATCH_REQUESTS: usize = 20;
+
+/// A sub-request of a `$batch` that Graph answered with an error status (or a
+/// body that could not be read).
+#[derive(Debug)]
+pub struct BatchError {
+    pub status: u16,
+    /// Graph's error code, e.g. "syncStateNotFound".
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl BatchError {
+    /// Whether Graph forgot the delta link, so the sync has to start over.
+    pub fn sync_state_lost(&self) -> bool {
+        self.status == 410
+            || self
+                .code
+                .as_deref()
+                .is_some_and(|code| code.eq_ignore_ascii_case("syncStateNotFound"))
+    }
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Graph returned {}: {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+#[derive(Debug, Deserialize)]
+struct BatchResponse {
+    responses: Vec<BatchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchItem {
+    id: String,
+    status: u16,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: serde_json::Value,
+}
+
+/// GETs `urls` (relative to the Graph root, or full Graph links) through
+/// `$batch`, `MAX_BATCH_REQUESTS` at a time, all with the same `Prefer`
+/// header. Returns each one's JSON body or error, in the order of `urls`.
+async fn batch_get(
+    access_token: &str,
+    urls: &[&str],
+    prefer: &str,
+) -> Result<Vec<Result<serde_json::Value, BatchError>>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let root = graph_url();
+    let mut results = Vec::with_capacity(urls.len());
+    for chunk in urls.chunks(MAX_BATCH_REQUESTS) {
+        let mut answers: Vec<Option<BatchItem>> = chunk.iter().map(|_| None).collect();
+        let mut pending: Vec<usize> = (0..chunk.len()).collect();
+        let mut attempt = 0;
+        loop {
+            let requests: Vec<serde_json::Value> = pending
+                .iter()
+                .map(|&id| {
+                    serde_json::json!({
+                        "id": id.to_string(),
+                        "method": "GET",
+                        "url": chunk[id].strip_prefix(root.as_str()).unwrap_or(chunk[id]),
+                        "headers": { "Prefer": prefer },
+                    })
+                })
+                .collect();
+            let batch: BatchResponse = send_json(
+                client
+                    .post(format!("{}/$batch", root))
+                    .bearer_auth(access_token)
+                    .json(&serde_json::json!({ "requests": requests })),
+            )
+            .await?;
+
+            // Responses come back in any order
+            for item in batch.responses {
+                if let Some(slot) = item
+                    .id
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|id| answers.get_mut(id))
+                {
+                    *slot = Some(item);
+                }
+            }
+
+            // Sub-requests are throttled on their own, so only those go again
+            let throttled: Vec<&BatchItem> = pending
+                .iter()
+                .filter_map(|&id| answers[id].as_ref())
+                .filter(|item| reqwest::StatusCode::from_u16(item.status).is_ok_and(is_transient))
+                .collect();
+            if throttled.is_empty() || attempt == MAX_RETRIES {
+                break;
+            }
+            let retry_after = throttled
+                .iter()
+                .filter_map(|item| item.headers.get("Retry-After")?.trim().parse().ok())
+                .max();
+            let reason = format!("returned {}", throttled[0].status);
+            pending = throttled
+                .iter()
+                .filter_map(|item| item.id.parse().ok())
+                .collect();
+            wait_before_retry(attempt, retry_after, &reason).await;
+            attempt += 1;
+        }
+
+        for answer in answers {
+            results.push(match answer {
+                Some(item) if (200..300).contains(&item.status) => Ok(item.body),
+                Some(item) => Err(BatchError {
+                    status: item.status,
+                    code: item.body["error"]["code"].as_str().map(str::to_string),
+                    message: item.body["error"]["message"]
+                        .as_str()
+                        .unwrap_or("no details")
+                        .to_string(),
+                }),
+                None => Err(BatchError {
+                    status: 0,
+                    code: None,
+                    message: "missing from the batch response".to_string(),
+                }),
+            });
+        }
+    }
+    Ok(results)
+}
+
 // --- API Call Functions ---
 
 pub async fn list_calendars(
     access_token: &str,
 ) -> Result<Vec<GraphCalendar>, Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get("https://graph.microsoft.com/v1.0/me/calendars")
-        .bearer_auth(access_token)
-        .send()
-        .await?;
-    let calendar_list = response.json::<CalendarListResponse>().await?;
+    let client = crate::http::client();
+    let calendar_list: CalendarListResponse = send_json(
+        client
+            .get(format!("{}/me/calendars", graph_url()))
+            .bearer_auth(access_token),
+    )
+    .await?;
     Ok(calendar_list.value)
 }
 
+pub async fn get_me(
+    access_token: &str,
+) -> Result<GraphUser, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    send_json(
+        client
+            .get(format!("{}/me", graph_url()))
+            .bearer_auth(access_token)
+            .query(&[("$select", "displayName,mail,userPrincipalName")]),
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Organization {
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrganizationListResponse {
+    value: Vec<Organization>,
+}
+
+/// Name of the signed-in account's organization (tenant). `None` for
+/// personal accounts, which don't belong to one.
+pub async fn get_organization_name(
+    access_token: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let organizations: OrganizationListResponse = send_json(
+        client
+            .get(format!("{}/organization", graph_url()))
+            .bearer_auth(access_token)
+            .query(&[("$select", "displayName")]),
+    )
+    .await?;
+    Ok(organizations
+        .value
+        .into_iter()
+        .next()
+        .and_then(|o| o.display_name))
+}
+
 pub async fn list_events(
     access_token: &str,
     calendar_id: &str,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
 ) -> Result<Vec<GraphEvent>, Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::new();
+    let client = crate::http::client();
     let mut all_events = Vec::new();
 
     // Base URL without query parameters
-    let base_url = format!(
-        "https://graph.microsoft.com/v1.0/me/calendars/{}/calendarview",
-        calendar_id
-    );
+    let base_url = format!("{}/me/calendars/{}/calendarview", graph_url(), calendar_id);
 
     // Parameters for the initial request
     let start_str = start_date.to_rfc3339();
     let end_str = end_date.to_rfc3339();
-    let select_fields = "subject,start,end,body,attendees,location,organizer".to_string();
+    let select_fields =
+        "subject,start,end,body,attendees,location,organizer,responseStatus,webLink,categories,createdDateTime,lastModifiedDateTime,iCalUId,isAllDay,showAs,onlineMeetingUrl,onlineMeeting,seriesMasterId,importance,isReminderOn,reminderMinutesBeforeStart"
+            .to_string();
     let orderby_field = "start/dateTime".to_string();
 
     // Build the first request using .query() for proper URL encoding
-    let initial_response = client
-        .get(&base_url)
-        .bearer_auth(access_token)
-        .query(&[
-            ("startDateTime", &start_str),
-            ("endDateTime", &end_str),
-            ("$select", &select_fields),
-            ("$orderby", &orderby_field),
-        ])
-        .send()
-        .await?;
+    let initial_request = client.get(&base_url).bearer_auth(access_token).query(&[
+        ("startDateTime", &start_str),
+        ("endDateTime", &end_str),
+        ("$select", &select_fields),
+        ("$orderby", &orderby_field),
+    ]);
 
     // Process the first page of results
-    let text = initial_response.text().await?;
+    let text = String::from_utf8_lossy(&send(initial_request).await?).into_owned();
     // CORRECTION: Removed the unnecessary `mut` keyword.
     let event_response: EventListResponse = serde_json::from_str(&text).map_err(|e| {
         log::error!(
@@ -141,9 +638,9 @@ pub async fn list_events(
     // Loop for subsequent pages using the nextLink provided by the API
     while let Some(url) = next_url {
         log::info!("Fetching next event page from: {}", url);
-        let response = client.get(&url).bearer_auth(access_token).send().await?;
+        let response = send(client.get(&url).bearer_auth(access_token)).await?;
 
-        let text = response.text().await?;
+        let text = String::from_utf8_lossy(&response).into_owned();
         let event_response: EventListResponse = serde_json::from_str(&text).map_err(|e| {
             log::error!(
                 "Failed to decode JSON on paginated request: {}. JSON received: {}",
@@ -159,3 +656,570 @@ pub async fn list_events(
 
     Ok(all_events)
 }
+
+/// Delta syncs the date windows of several calendars at once, continuing from
+/// each calendar's delta link when it has one. Every round of pages goes out
+/// as `$batch` requests of up to `MAX_BATCH_REQUESTS`, so many calendars cost
+/// about as many round-trips as one. `on_page` is called with the calendar's
+/// index and each page's changed events as they arrive. Once `max_events`
+/// events of a calendar have arrived, it stops there if it has more pages and
+/// is marked `truncated`, keeping the link to the next page. The outer error is for a batch that failed as a whole, the
+/// inner ones are per calendar.
+pub async fn list_events_deltas(
+    access_token: &str,
+    calendars: &[(&str, Option<&str>)],
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    max_events: usize,
+    mut on_page: impl FnMut(usize, &[GraphEvent]),
+) -> Result<Vec<Result<EventDelta, BatchError>>, Box<dyn std::error::Error + Send + Sync>> {
+    let window = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("startDateTime", &start_date.to_rfc3339())
+        .append_pair("endDateTime", &end_date.to_rfc3339())
+        .finish();
+    let mut pending: Vec<(usize, String)> = calendars
+        .iter()
+        .enumerate()
+        .map(|(index, (calendar_id, delta_link))| {
+            let url = match delta_link {
+                Some(link) => link.to_string(),
+                None => format!(
+                    "/me/calendars/{}/calendarView/delta?{}",
+                    calendar_id, window
+                ),
+            };
+            (index, url)
+        })
+        .collect();
+
+    let mut results: Vec<Result<EventDelta, BatchError>> = calendars
+        .iter()
+        .map(|_| Ok(EventDelta::default()))
+        .collect();
+    let mut fetched = vec![0; calendars.len()];
+    while !pending.is_empty() {
+        let urls: Vec<&str> = pending.iter().map(|(_, url)| url.as_str()).collect();
+        let bodies = batch_get(access_token, &urls, "odata.maxpagesize=100").await?;
+        let mut next = Vec::new();
+        for ((index, _), body) in pending.into_iter().zip(bodies) {
+            let Ok(delta) = &mut results[index] else {
+                continue;
+            };
+            let page = body.and_then(|body| {
+                serde_json::from_value::<DeltaPage>(body).map_err(|e| BatchError {
+                    status: 200,
+                    code: None,
+                    message: e.to_string(),
+                })
+            });
+            let page = match page {
+                Ok(page) => page,
+                Err(e) => {
+                    results[index] = Err(e);
+                    continue;
+                }
+            };
+            let page_start = delta.changed.len();
+            for item in page.value {
+                if item.get("@removed").is_some() {
+                    if let Some(id) = item.get("id").and_then(|id| id.as_str()) {
+                        delta.removed.push(id.to_string());
+                    }
+                    continue;
+                }
+                match serde_json::from_value::<GraphEvent>(item) {
+                    Ok(event) => delta.changed.push(event),
+                    Err(e) => log::warn!("Skipping an event the delta sync could not read: {}", e),
+                }
+            }
+            if delta.changed.len() > page_start {
+                fetched[index] += delta.changed.len() - page_start;
+                on_page(index, &delta.changed[page_start..]);
+            }
+            match page.next_link {
+                // Huge windows would otherwise follow next links for a very
+                // long time
+                Some(next_link) if fetched[index] >= max_events => {
+                    delta.truncated = true;
+                    delta.next_link = Some(next_link);
+                }
+                Some(next_link) => next.push((index, next_link)),
+                None => delta.delta_link = page.delta_link,
+            }
+        }
+        pending = next;
+    }
+    Ok(results)
+}
+
+/// Searches all of the user's events by subject on the server, optionally
+/// limited to a date range. Returns the most recent matches first.
+pub async fn search_events(
+    access_token: &str,
+    text: &str,
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+    limit: usize,
+) -> Result<Vec<GraphEvent>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let mut filter = format!("contains(subject,'{}')", text.replace('\'', "''"));
+    if let Some(start) = start_date {
+        filter.push_str(&format!(
+            " and start/dateTime ge '{}'",
+            start.format("%Y-%m-%dT%H:%M:%S")
+        ));
+    }
+    if let Some(end) = end_date {
+        filter.push_str(&format!(
+            " and start/dateTime lt '{}'",
+            end.format("%Y-%m-%dT%H:%M:%S")
+        ));
+    }
+
+    let request = client
+        .get(format!("{}/me/events", graph_url()))
+        .bearer_auth(access_token)
+        .header("Prefer", "outlook.timezone=\"UTC\"")
+        .query(&[
+            ("$filter", filter.as_str()),
+            (
+                "$select",
+                "subject,start,end,body,attendees,location,organizer,responseStatus,webLink,categories,createdDateTime,lastModifiedDateTime,iCalUId,isAllDay,showAs,onlineMeetingUrl,onlineMeeting,seriesMasterId,importance,isReminderOn,reminderMinutesBeforeStart",
+            ),
+            ("$orderby", "start/dateTime desc"),
+            ("$top", &limit.to_string()),
+        ]);
+    Ok(send_json::<EventListResponse>(request).await?.value)
+}
+
+pub async fn get_schedule(
+    access_token: &str,
+    emails: &[String],
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<ScheduleInformation>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let body = serde_json::json!({
+        "schedules": emails,
+        "startTime": {
+            "dateTime": start_date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            "timeZone": "UTC",
+        },
+        "endTime": {
+            "dateTime": end_date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            "timeZone": "UTC",
+        },
+        "availabilityViewInterval": 30,
+    });
+
+    // Ask for UTC so schedule items use the same naive UTC format as events
+    let schedule: ScheduleResponse = send_json(
+        client
+            .post(format!("{}/me/calendar/getSchedule", graph_url()))
+            .bearer_auth(access_token)
+            .header("Prefer", "outlook.timezone=\"UTC\"")
+            .json(&body),
+    )
+    .await?;
+    Ok(schedule.value)
+}
+
+#[derive(Debug, Deserialize)]
+struct RoomListResponse {
+    value: Vec<EmailAddress>,
+}
+
+/// The organization's meeting rooms, or those of one room list (by its
+/// email address). Needs the User.ReadBasic.All permission.
+pub async fn find_rooms(
+    access_token: &str,
+    room_list: Option<&str>,
+) -> Result<Vec<EmailAddress>, Box<dyn std::error::Error + Send + Sync>> {
+    let url = match room_list {
+        Some(list) => format!(
+            "{}/me/findRooms(RoomList='{}')",
+            graph_url(),
+            list.replace('\'', "''")
+        ),
+        None => format!("{}/me/findRooms", graph_url()),
+    };
+    let rooms: RoomListResponse =
+        send_json(crate::http::client().get(url).bearer_auth(access_token)).await?;
+    Ok(rooms.value)
+}
+
+pub async fn find_meeting_times(
+    access_token: &str,
+    attendees: &[String],
+    duration_minutes: i64,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<MeetingTimeSuggestionsResult, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let attendees: Vec<serde_json::Value> = attendees
+        .iter()
+        .map(|address| {
+            serde_json::json!({
+                "type": "required",
+                "emailAddress": { "address": address },
+            })
+        })
+        .collect();
+    let body = serde_json::json!({
+        "attendees": attendees,
+        "timeConstraint": {
+            "activityDomain": "work",
+            "timeSlots": [{
+                "start": {
+                    "dateTime": start_date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    "timeZone": "UTC",
+                },
+                "end": {
+                    "dateTime": end_date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    "timeZone": "UTC",
+                },
+            }],
+        },
+        "meetingDuration": format!("PT{}M", duration_minutes),
+        "maxCandidates": 10,
+        "returnSuggestionReasons": true,
+    });
+
+    send_json(
+        client
+            .post(format!("{}/me/findMeetingTimes", graph_url()))
+            .bearer_auth(access_token)
+            .header("Prefer", "outlook.timezone=\"UTC\"")
+            .json(&body),
+    )
+    .await
+}
+
+/// Creates an event in the given calendar, or the user's main calendar.
+pub async fn create_event(
+    access_token: &str,
+    event: &NewEvent,
+    calendar_id: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let mut attendees: Vec<serde_json::Value> = event
+        .attendees
+        .iter()
+        .map(|address| {
+            serde_json::json!({
+                "type": "required",
+                "emailAddress": { "address": address },
+            })
+        })
+        .collect();
+    attendees.extend(event.rooms.iter().map(|room| {
+        serde_json::json!({
+            "type": "resource",
+            "emailAddress": { "address": room.address, "name": room.name },
+        })
+    }));
+    let mut body = serde_json::json!({
+        "subject": event.subject,
+        "start": { "dateTime": event.start, "timeZone": "UTC" },
+        "end": { "dateTime": event.end, "timeZone": "UTC" },
+        "attendees": attendees,
+    });
+    if !event.rooms.is_empty() {
+        let names: Vec<&str> = event.rooms.iter().map(|room| room.name.as_str()).collect();
+        body["location"] = serde_json::json!({ "displayName": names.join("; ") });
+    }
+    let url = match calendar_id {
+        Some(id) => format!("{}/me/calendars/{}/events", graph_url(), id),
+        None => format!("{}/me/events", graph_url()),
+    };
+
+    send(client.post(url).bearer_auth(access_token).json(&body)).await?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct OrganizerResponse {
+    #[serde(rename = "isOrganizer", default)]
+    is_organizer: Option<bool>,
+}
+
+/// Updates an existing event in `calendar_id`, sending only the fields that
+/// changed. Refused for meetings someone else organizes: Graph would only
+/// change the attendee's own copy.
+pub async fn update_event(
+    access_token: &str,
+    calendar_id: &str,
+    event_id: &str,
+    changes: &EventChanges,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let url = format!(
+        "{}/me/calendars/{}/events/{}",
+        graph_url(),
+        calendar_id,
+        event_id
+    );
+    let organizer: OrganizerResponse = send_json(
+        client
+            .get(&url)
+            .bearer_auth(access_token)
+            .query(&[("$select", "isOrganizer")]),
+    )
+    .await?;
+    if organizer.is_organizer == Some(false) {
+        return Err("Only the organizer can change this meeting.".into());
+    }
+
+    let mut body = serde_json::Map::new();
+    if let Some(subject) = &changes.subject {
+        body.insert("subject".to_string(), serde_json::json!(subject));
+    }
+    if let Some(start) = &changes.start {
+        body.insert(
+            "start".to_string(),
+            serde_json::json!({ "dateTime": start, "timeZone": "UTC" }),
+        );
+    }
+    if let Some(end) = &changes.end {
+        body.insert(
+            "end".to_string(),
+            serde_json::json!({ "dateTime": end, "timeZone": "UTC" }),
+        );
+    }
+    if let Some(location) = &changes.location {
+        body.insert(
+            "location".to_string(),
+            serde_json::json!({ "displayName": location }),
+        );
+    }
+    send(client.patch(url).bearer_auth(access_token).json(&body)).await?;
+    Ok(())
+}
+
+/// Creates an event repeating every week on the day of `start`, without an
+/// end. Times are wall-clock times in `time_zone` (an IANA name), so the
+/// event keeps its time of day across DST changes.
+pub async fn create_weekly_event(
+    access_token: &str,
+    subject: &str,
+    start: chrono::NaiveDateTime,
+    end: chrono::NaiveDateTime,
+    time_zone: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let format = "%Y-%m-%dT%H:%M:%S";
+    let body = serde_json::json!({
+        "subject": subject,
+        "start": { "dateTime": start.format(format).to_string(), "timeZone": time_zone },
+        "end": { "dateTime": end.format(format).to_string(), "timeZone": time_zone },
+        "recurrence": {
+            "pattern": {
+                "type": "weekly",
+                "interval": 1,
+                "daysOfWeek": [start.format("%A").to_string().to_lowercase()],
+            },
+            "range": {
+                "type": "noEnd",
+                "startDate": start.format("%Y-%m-%d").to_string(),
+                "recurrenceTimeZone": time_zone,
+            },
+        },
+    });
+
+    send(
+        client
+            .post(format!("{}/me/events", graph_url()))
+            .bearer_auth(access_token)
+            .json(&body),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Answers a meeting invite. `action` is the Graph endpoint name: "accept",
+/// "tentativelyAccept" or "decline".
+pub async fn respond_to_event(
+    access_token: &str,
+    event_id: &str,
+    action: &str,
+    send_response: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let url = format!("{}/me/events/{}/{}", graph_url(), event_id, action);
+    send(
+        client
+            .post(&url)
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({ "sendResponse": send_response })),
+    )
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct OnlineMeetingListResponse {
+    value: Vec<OnlineMeetingId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OnlineMeetingId {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventBodyResponse {
+    body: ItemBody,
+}
+
+/// Fetches just the body of an event.
+pub async fn get_event_body(
+    access_token: &str,
+    event_id: &str,
+) -> Result<ItemBody, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let response: EventBodyResponse = send_json(
+        client
+            .get(format!("{}/me/events/{}", graph_url(), event_id))
+            .bearer_auth(access_token)
+            .query(&[("$select", "body")]),
+    )
+    .await?;
+    Ok(response.body)
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtifactListResponse {
+    #[serde(default)]
+    value: Vec<serde_json::Value>,
+}
+
+/// How many recordings and transcripts a Teams meeting has.
+#[derive(Debug, Clone, Default)]
+pub struct MeetingArtifacts {
+    pub recordings: usize,
+    pub transcripts: usize,
+}
+
+/// Looks up the recordings and transcripts of the online meeting joined with
+/// `join_url`. `None` when Graph doesn't know the meeting, e.g. because
+/// someone else organized it.
+pub async fn get_meeting_artifacts(
+    access_token: &str,
+    join_url: &str,
+) -> Result<Option<MeetingArtifacts>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let meetings: OnlineMeetingListResponse = send_json(
+        client
+            .get(format!("{}/me/onlineMeetings", graph_url()))
+            .bearer_auth(access_token)
+            .query(&[(
+                "$filter",
+                format!("JoinWebUrl eq '{}'", join_url.replace('\'', "''")),
+            )]),
+    )
+    .await?;
+    let Some(meeting) = meetings.value.into_iter().next() else {
+        return Ok(None);
+    };
+
+    let count = |kind: &'static str| {
+        let request = client
+            .get(format!(
+                "{}/me/onlineMeetings/{}/{}",
+                graph_url(),
+                meeting.id,
+                kind
+            ))
+            .bearer_auth(access_token);
+        async move {
+            send_json::<ArtifactListResponse>(request)
+                .await
+                .map(|list| list.value.len())
+        }
+    };
+    let (recordings, transcripts) = futures::join!(count("recordings"), count("transcripts"));
+    Ok(Some(MeetingArtifacts {
+        recordings: recordings?,
+        transcripts: transcripts?,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TodoList {
+    id: String,
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TodoTask {
+    title: String,
+    due_date_time: Option<DateTimeTimeZone>,
+    #[serde(default)]
+    importance: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TodoPage<T> {
+    value: Vec<T>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+/// An open Microsoft To Do task due on or before a given day. Flagged emails
+/// are tasks too, in the "Flagged emails" list.
+#[derive(Debug, Clone)]
+pub struct DueTask {
+    pub title: String,
+    pub list: String,
+    pub due: NaiveDate,
+    pub important: bool,
+}
+
+/// All pages of a To Do collection.
+async fn get_todo_pages<T: DeserializeOwned>(
+    access_token: &str,
+    url: String,
+) -> Result<Vec<T>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let mut items = Vec::new();
+    let mut next_url = Some(url);
+    while let Some(url) = next_url {
+        let page: TodoPage<T> = send_json(client.get(&url).bearer_auth(access_token)).await?;
+        items.extend(page.value);
+        next_url = page.next_link;
+    }
+    Ok(items)
+}
+
+/// Open tasks of all To Do lists (flagged emails included) due on `day` or
+/// earlier, the most overdue first. Needs the Tasks.Read permission.
+pub async fn list_due_tasks(
+    access_token: &str,
+    day: NaiveDate,
+) -> Result<Vec<DueTask>, Box<dyn std::error::Error + Send + Sync>> {
+    let lists: Vec<TodoList> =
+        get_todo_pages(access_token, format!("{}/me/todo/lists", graph_url())).await?;
+    let mut due_tasks = Vec::new();
+    for list in lists {
+        let url = format!(
+            "{}/me/todo/lists/{}/tasks?$filter=status%20ne%20'completed'&$top=100",
+            graph_url(),
+            list.id
+        );
+        let tasks: Vec<TodoTask> = get_todo_pages(access_token, url).await?;
+        // Due dates are days; the time is always midnight
+        due_tasks.extend(tasks.into_iter().filter_map(|task| {
+            let due = task.due_date_time?.date_time.get(..10)?.parse().ok()?;
+            (due <= day).then(|| DueTask {
+                title: task.title,
+                list: list.display_name.clone(),
+                due,
+                important: task.importance.as_deref() == Some("high"),
+            })
+        }));
+    }
+    due_tasks.sort_by(|a, b| a.due.cmp(&b.due).then(b.important.cmp(&a.important)));
+    Ok(due_tasks)
+}
@@ -1,16 +1,80 @@
-use crate::api::{GraphCalendar, GraphEvent};
-use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime};
-use oauth2::{
-    basic::BasicClient, reqwest::async_http_client, AuthUrl, ClientId, RedirectUrl, TokenResponse,
-    TokenUrl,
+use crate::api::{
+    EmailAddress, EventChanges, GraphCalendar, GraphEvent, MeetingTimeSuggestion, NewEvent,
+    ScheduleInformation,
 };
+use crate::clock;
+use crate::planner::{format_utc, free_slots, load_tasks, PlannerState};
+use crate::search::{SearchQuery, SearchState};
+use crate::ui::body::BodyRenderer;
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday,
+};
+use log::{error, info};
+use oauth2::TokenResponse;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
 use ratatui::widgets::{ListState, ScrollbarState};
+use regex::Regex;
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::time::{Duration as StdDuration, Instant};
 
 pub const MY_CALENDARS_ID: &str = "MY_CALENDARS";
+/// Stands for the calendars ticked in the calendar list.
+pub const CHECKED_CALENDARS_ID: &str = "CHECKED_CALENDARS";
+
+/// Parses a Graph event timestamp (naive UTC) into local time.
+pub fn parse_event_time(date_time: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc).with_timezone(&Local))
+}
+
+/// The events overlapping a new event's time. All-day events and declined
+/// invites don't count, and a meeting in several calendars counts once.
+pub fn conflicts_among(events: Vec<ColorEvent>, new_event: &NewEvent) -> Vec<ColorEvent> {
+    let (Some(start), Some(end)) = (
+        parse_event_time(&new_event.start),
+        parse_event_time(&new_event.end),
+    ) else {
+        return Vec::new();
+    };
+    let mut seen = HashSet::new();
+    events
+        .into_iter()
+        .filter(|e| {
+            let declined = e
+                .event
+                .response_status
+                .as_ref()
+                .is_some_and(|status| status.response == "declined");
+            match (
+                parse_event_time(&e.event.start.date_time),
+                parse_event_time(&e.event.end.date_time),
+            ) {
+                (Some(event_start), Some(event_end)) => {
+                    !declined
+                        && event_end - event_start < Duration::hours(24)
+                        && event_start < end
+                        && event_end > start
+                }
+                _ => false,
+            }
+        })
+        .filter(|e| seen.insert(e.event.correlation_id().to_string()))
+        .collect()
+}
+
+/// A subject as it may be shown: just "Busy" in busy-only mode. Everything
+/// that shows subjects, in the TUI or outside it, goes through this.
+pub fn display_subject(subject: &str, busy_only: bool) -> &str {
+    if busy_only {
+        "Busy"
+    } else {
+        subject
+    }
+}
 
 /// Represents the state of a view transition animation.
 pub struct Transition {
@@ -21,6 +85,7 @@ pub struct Transition {
 /// The different views available for displaying events.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum EventViewMode {
+    Dashboard,
     List,
     Month,
     Week,
@@ -28,6 +93,21 @@ pub enum EventViewMode {
     Day,
 }
 
+impl EventViewMode {
+    /// Looks up a view by the name used for `default_view` in the config file.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "dashboard" | "today" => Some(EventViewMode::Dashboard),
+            "list" => Some(EventViewMode::List),
+            "week" => Some(EventViewMode::Week),
+            "workweek" | "work_week" => Some(EventViewMode::WorkWeek),
+            "day" => Some(EventViewMode::Day),
+            "month" => Some(EventViewMode::Month),
+            _ => None,
+        }
+    }
+}
+
 /// The main screens of the application.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum CurrentView {
@@ -36,15 +116,282 @@ pub enum CurrentView {
     EventDetail,
 }
 
+/// What the text typed into the input prompt is used for. Steps of a multi-prompt
+/// flow carry the answers given so far.
+#[derive(Clone, PartialEq)]
+pub enum InputKind {
+    ScheduleOverlay,
+    FindTimeAttendees,
+    FindTimeDuration {
+        attendees: Vec<String>,
+    },
+    NewEventSubject {
+        attendees: Vec<String>,
+        start: String,
+        end: String,
+    },
+    Search {
+        cache_only: bool,
+    },
+    EditEventSubject {
+        event_id: String,
+    },
+    EditEventTime {
+        event_id: String,
+        subject: String,
+    },
+    EditEventLocation {
+        event_id: String,
+        subject: String,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+}
+
+impl InputKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            InputKind::ScheduleOverlay => " Show free/busy for (comma-separated emails) ",
+            InputKind::FindTimeAttendees => " Find a time with (comma-separated emails) ",
+            InputKind::FindTimeDuration { .. } => " Meeting length in minutes ",
+            InputKind::NewEventSubject { .. } => " Subject of the new event ",
+            InputKind::Search { cache_only: false } => {
+                " Search events (optional from:YYYY-MM-DD to:YYYY-MM-DD) "
+            }
+            InputKind::Search { cache_only: true } => {
+                " Search all cached events (optional from:YYYY-MM-DD to:YYYY-MM-DD) "
+            }
+            InputKind::EditEventSubject { .. } => " Subject ",
+            InputKind::EditEventTime { .. } => " Time (YYYY-MM-DD HH:MM - HH:MM) ",
+            InputKind::EditEventLocation { .. } => " Location (empty for none) ",
+        }
+    }
+}
+
+/// A single-line text prompt shown as a popup while the user types.
+pub struct InputPrompt {
+    pub kind: InputKind,
+    pub buffer: String,
+}
+
+/// Follow-up work for the main loop once a prompt has been submitted.
+pub enum InputAction {
+    None,
+    Refresh,
+    FindMeetingTimes,
+    /// Look up the meeting rooms for the event in `room_picker`.
+    FindRooms,
+    CreateEvent(NewEvent),
+    Search {
+        query: SearchQuery,
+        cache_only: bool,
+    },
+}
+
+/// State of the "find a time" popup. `suggestions` is `None` while the
+/// request is still running.
+pub struct FindTimeState {
+    pub attendees: Vec<String>,
+    pub duration_minutes: i64,
+    pub suggestions: Option<Vec<MeetingTimeSuggestion>>,
+    pub empty_reason: Option<String>,
+    pub list_state: ListState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    Reminder,
+    Info,
+    Error,
+}
+
+/// A message in the bottom-right corner that goes away by itself, for
+/// terminals without a desktop notification daemon.
+pub struct Toast {
+    pub kind: ToastKind,
+    pub title: String,
+    pub message: String,
+    shown_at: Instant,
+}
+
+/// A meeting room and whether it is free for the new event's time; `None`
+/// when its schedule couldn't be read.
+#[derive(Clone)]
+pub struct RoomOption {
+    pub room: EmailAddress,
+    pub free: Option<bool>,
+}
+
+/// State of the room picker shown before a new meeting is created. The first
+/// row is "No room". `rooms` is `None` while they load.
+pub struct RoomPickerState {
+    pub new_event: NewEvent,
+    pub rooms: Option<Result<Vec<RoomOption>, String>>,
+    pub list_state: ListState,
+}
+
+impl RoomPickerState {
+    /// The new event, with the selected room (if any) booked.
+    pub fn pick(self) -> NewEvent {
+        let mut new_event = self.new_event;
+        let selected = self.list_state.selected().unwrap_or(0);
+        if let (Some(Ok(rooms)), Some(index)) = (&self.rooms, selected.checked_sub(1)) {
+            new_event
+                .rooms
+                .extend(rooms.get(index).map(|r| r.room.clone()));
+        }
+        new_event
+    }
+}
+
+/// Where a new event is saved once it's clear of conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewEventTarget {
+    Outlook,
+    /// The planner's selected task; `local` blocks it without Outlook.
+    PlannerTask {
+        local: bool,
+    },
+}
+
+/// A new event overlapping existing ones, waiting for confirmation.
+pub struct ConflictPrompt {
+    pub new_event: NewEvent,
+    pub target: NewEventTarget,
+    pub conflicts: Vec<ColorEvent>,
+}
+
+/// The fields of an event that can be edited.
+#[derive(Clone, PartialEq)]
+pub struct EditableFields {
+    pub subject: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub location: String,
+}
+
+/// An edited event, shown as a diff until the changes are confirmed.
+pub struct EventEditPrompt {
+    pub calendar_id: String,
+    pub event_id: String,
+    pub old: EditableFields,
+    pub new: EditableFields,
+}
+
+impl EventEditPrompt {
+    /// The fields to send to Graph: only those that differ.
+    pub fn changes(&self) -> EventChanges {
+        let changed = |old: &str, new: &str| (old != new).then(|| new.to_string());
+        EventChanges {
+            subject: changed(&self.old.subject, &self.new.subject),
+            start: (self.old.start != self.new.start).then(|| format_utc(self.new.start)),
+            end: (self.old.end != self.new.end).then(|| format_utc(self.new.end)),
+            location: changed(&self.old.location, &self.new.location),
+        }
+    }
+}
+
+/// The signed-in account, as shown in the footer's account badge.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Account {
+    pub name: Option<String>,
+    pub address: Option<String>,
+    /// The organization (tenant); `None` for personal accounts.
+    pub organization: Option<String>,
+}
+
+/// Shown when a change to Outlook is refused for lack of write access.
+pub const READ_ONLY_MESSAGE: &str = "Read-only: events can't be created, edited or answered";
+
+/// How long a status message stays in the footer.
+const STATUS_MESSAGE_DURATION: StdDuration = StdDuration::from_secs(5);
+/// How long a toast stays in the corner.
+const TOAST_DURATION: StdDuration = StdDuration::from_secs(8);
+/// Toasts shown at once; the oldest makes room for a new one.
+const MAX_TOASTS: usize = 3;
+/// Resolutions of the day view's time grid, in minutes per row.
+const DAY_GRID_ZOOM_LEVELS: [i64; 3] = [15, 30, 60];
+
+/// Formats an event's time for the edit prompt, e.g. "2025-06-03 14:00 - 15:00".
+/// The end gets a date too when it's on another day.
+fn format_time_range(start: DateTime<Local>, end: DateTime<Local>) -> String {
+    if start.date_naive() == end.date_naive() {
+        format!(
+            "{} - {}",
+            start.format("%Y-%m-%d %H:%M"),
+            end.format("%H:%M")
+        )
+    } else {
+        format!(
+            "{} - {}",
+            start.format("%Y-%m-%d %H:%M"),
+            end.format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+/// Parses the time typed into the edit prompt, in the format of `format_time_range`.
+fn parse_time_range(text: &str) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+    let invalid = || {
+        format!(
+            "\"{}\" is not a time like \"2025-06-03 14:00 - 15:00\"",
+            text
+        )
+    };
+    let (start, end) = text.split_once(" - ").ok_or_else(invalid)?;
+    let start =
+        NaiveDateTime::parse_from_str(start.trim(), "%Y-%m-%d %H:%M").map_err(|_| invalid())?;
+    let end = match NaiveTime::parse_from_str(end.trim(), "%H:%M") {
+        Ok(time) => start.date().and_time(time),
+        Err(_) => {
+            NaiveDateTime::parse_from_str(end.trim(), "%Y-%m-%d %H:%M").map_err(|_| invalid())?
+        }
+    };
+    if end <= start {
+        return Err(format!("\"{}\" ends before it starts", text));
+    }
+    let local = |naive: NaiveDateTime| {
+        naive
+            .and_local_timezone(Local)
+            .earliest()
+            .ok_or_else(|| format!("{} doesn't exist in the local time zone", naive))
+    };
+    Ok((local(start)?, local(end)?))
+}
+
+fn parse_email_list(text: &str) -> Vec<String> {
+    text.split([',', ';', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 use crate::notifications::NotificationManager;
+use crate::status::StatusManager;
 use crate::ui::{Symbols, Theme};
 
 /// Holds the entire state of the application.
 pub struct App {
-    pub client_id: String,
     pub access_token: String,
     pub calendars: Vec<ColorCalendar>,
+    /// Events of the selected calendar(s), before hiding any calendar.
+    pub loaded_events: Vec<ColorEvent>,
+    /// The events shown: `loaded_events` minus the hidden calendars and
+    /// those outside the category filter.
     pub events: Vec<ColorEvent>,
+    /// Calendars toggled off in the legend.
+    pub hidden_calendars: HashSet<String>,
+    /// Calendars ticked in the calendar list, shown together.
+    pub checked_calendars: HashSet<String>,
+    /// The List view nests each day's events under their calendars.
+    pub group_by_calendar: bool,
+    /// Calendars folded to their heading in the grouped List view. Planned
+    /// blocks, which have no calendar, go by the empty string.
+    pub collapsed_calendars: HashSet<String>,
+    /// Categories ticked in the category menu. When any is ticked, only
+    /// events with at least one of them are shown.
+    pub category_filter: HashSet<String>,
     pub calendar_list_state: ListState,
     pub event_list_state: ListState,
     pub current_view: CurrentView,
@@ -53,20 +400,131 @@ pub struct App {
     pub detail_view_scroll: u16,
     pub displayed_date: NaiveDate,
     pub transition: Option<Transition>,
+    /// Until when the screen is shown inverted (terminal alert flash)
+    pub flash_until: Option<Instant>,
     pub calendar_list_area: Rect,
     pub event_list_area: Rect,
     pub help_area: Rect,
+    pub footer_breadcrumb_area: Rect,
     pub show_help: bool,
+    /// Whether the diagnostics popup (request metrics) is open
+    pub show_diagnostics: bool,
+    /// Popup of the reminders held back by do not disturb
+    pub show_missed: bool,
     pub show_legend: bool,
+    pub legend_state: ListState,
+    /// Area of the legend popup, recorded while drawing for clicks.
+    pub legend_area: Rect,
+    pub show_categories: bool,
+    /// Categories listed in the category menu.
+    pub known_categories: Vec<String>,
+    pub category_state: ListState,
+    /// Area of the category menu, recorded while drawing for clicks.
+    pub category_area: Rect,
     pub calendar_list_scroll_state: ScrollbarState,
     pub event_list_scroll_state: ScrollbarState,
     pub detail_scroll_state: ScrollbarState,
+    pub detail_links: Vec<crate::links::DetectedLink>,
+    /// Recordings and transcripts of ended Teams meetings, by event ID. An
+    /// entry is added when the lookup starts; `None` until (or unless) found.
+    pub meeting_artifacts: HashMap<String, Option<crate::api::MeetingArtifacts>>,
+    /// In minimal mode, the body of the event last opened in the detail view,
+    /// by event ID; `None` while it loads.
+    pub fetched_body: Option<(String, Option<crate::api::ItemBody>)>,
+    pub show_all_attendees: bool,
+    /// The detail view shows the event body's source instead of rendering it.
+    pub show_raw_body: bool,
+    pub input: Option<InputPrompt>,
+    pub schedule_emails: Vec<String>,
+    pub schedule_overlay: Vec<ScheduleInformation>,
+    /// Tasks due today or overdue, shown on the dashboard with `dashboard_tasks`.
+    pub due_tasks: Vec<crate::api::DueTask>,
+    /// Why the due tasks could not be loaded, shown in their place.
+    pub due_tasks_error: Option<String>,
+    pub find_time: Option<FindTimeState>,
+    pub room_picker: Option<RoomPickerState>,
+    pub status_message: Option<(String, Instant)>,
+    pub toasts: Vec<Toast>,
+    /// Whether to toast the end of the running sync, asked for with `r`
+    pub announce_sync: bool,
+    /// Whether the last sync failed, so an outage is toasted only once
+    pub sync_failing: bool,
+    pub rules: Vec<crate::rules::Rule>,
+    pub planner: Option<PlannerState>,
+    pub conflict_prompt: Option<ConflictPrompt>,
+    pub edit_prompt: Option<EventEditPrompt>,
+    /// Asking whether to log out.
+    pub logout_prompt: bool,
+    /// Why logging in again is suggested, while the prompt is open.
+    pub relogin_prompt: Option<String>,
+    /// The open login prompt is for write access, asked for from then on.
+    pub relogin_for_write: bool,
+    /// Logged out from the app; the login is forgotten once the TUI is closed.
+    pub logged_out: bool,
+    pub search: Option<SearchState>,
+    /// The read-only SQL console over the cache, opened with `:`.
+    pub sql_console: Option<crate::sql_console::SqlConsole>,
+    pub show_past: bool,
+    pub day_peek: Option<NaiveDate>,
+    /// Day drilled into from a grid; its first event gets selected once the
+    /// list has loaded.
+    pub focus_date: Option<NaiveDate>,
+    /// Months the List view syncs before and after the displayed month, grown
+    /// by scrolling past either end of the list
+    pub list_months_extended: (u32, u32),
+    /// Event selected when the list was extended, and whether to step forward
+    /// or back from it once the new month is in
+    list_extend_anchor: Option<(String, bool)>,
+    /// Scroll the event list so the "now" separator is in the middle, once
+    pub center_list_on_now: bool,
+    /// Week view a day was zoomed in from with Enter, returned to with `b`
+    pub day_drilled_from: Option<EventViewMode>,
+    pub day_grid_minutes: i64,
+    /// Events shown on each line of the day view and the columns they span
+    /// (relative to the view's inner area), recorded while drawing for clicks.
+    pub day_grid_rows: Vec<Vec<(Range<u16>, usize)>>,
+    /// Set while drawing when the terminal is too small for the full layout.
+    pub compact: bool,
+    /// Shows every event as a plain "Busy" block, for shared screens.
+    pub busy_only: bool,
+    /// When the shown calendars' date window was last synced, if ever.
+    pub last_synced: Option<DateTime<Utc>>,
+    /// The event limit the last sync stopped at, leaving events out.
+    pub sync_truncated: Option<usize>,
+    /// Counts the refreshes, so events loaded for an earlier view are dropped.
+    pub refresh_generation: u64,
+    /// The day the cache was last warmed up around, so it happens once a day.
+    pub cache_warmed_on: Option<NaiveDate>,
+    /// Who is signed in, for the account badge in the footer.
+    pub account: Option<Account>,
+    /// Email domains of the organization's own people, lowercase. Meetings
+    /// organized from any other domain are external.
+    pub internal_domains: Vec<String>,
+    /// Loaded events close to a DST change, by event ID, with the change.
+    pub dst_transitions: HashMap<String, DateTime<Local>>,
+    /// The last DST change a status message warned about.
+    dst_warned: Option<DateTime<Local>>,
     pub db_pool: SqlitePool,
     pub theme: Theme,
     pub symbols: Symbols,
     pub notification_manager: NotificationManager,
+    pub alarms: crate::alarms::AlarmManager,
+    /// The alarm on screen, until it is dismissed or snoozed.
+    pub alarm: Option<crate::alarms::Alarm>,
+    /// Correlation IDs of the events flagged with `!` for an alarm.
+    pub flagged_events: HashSet<String>,
+    /// Past meetings marked with `m`: attended (`true`) or skipped, by
+    /// correlation ID.
+    pub attendance: HashMap<String, bool>,
+    pub status_manager: StatusManager,
     #[allow(dead_code)]
     pub settings: crate::config::Settings,
+    /// Identifies this instance in the sync lease
+    pub instance_id: String,
+    /// Whether this instance holds the sync lease. Others only read the cache.
+    pub is_primary: bool,
+    /// When the syncing instance last changed the cached events, as last seen
+    pub events_changed_at: Option<String>,
 }
 
 // CORRECTION: These structs are now public so other modules can use them.
@@ -82,11 +540,53 @@ pub struct ColorEvent {
     pub event: GraphEvent,
     pub color: Color,
     pub icon: Option<String>,
+    /// Calendar the event belongs to; `None` for locally planned blocks.
+    pub calendar_id: Option<String>,
+}
+
+/// A row of the List view when it's grouped by calendar.
+pub enum ListRow {
+    Day(NaiveDate),
+    /// A calendar's heading within a day, over its events there (indices
+    /// into `events`). A collapsed heading stands in for all of them.
+    Calendar {
+        calendar_id: Option<String>,
+        events: Vec<usize>,
+        collapsed: bool,
+    },
+    Event(usize),
+}
+
+impl ListRow {
+    /// The event selected on this row, for rows that can be selected.
+    pub fn event(&self) -> Option<usize> {
+        match self {
+            ListRow::Event(index) => Some(*index),
+            ListRow::Calendar {
+                events,
+                collapsed: true,
+                ..
+            } => events.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// Whether the row is where event `index` shows.
+    pub fn shows(&self, index: usize) -> bool {
+        match self {
+            ListRow::Event(i) => *i == index,
+            ListRow::Calendar {
+                events,
+                collapsed: true,
+                ..
+            } => events.contains(&index),
+            _ => false,
+        }
+    }
 }
 
 impl App {
     pub fn new(
-        client_id: String,
         access_token: String,
         db_pool: SqlitePool,
         theme: Theme,
@@ -100,62 +600,132 @@ impl App {
         let mut event_list_state = ListState::default();
         event_list_state.select(None);
 
+        // Land directly on an event view if configured, showing all calendars
+        let default_view = settings
+            .default_view
+            .as_deref()
+            .and_then(EventViewMode::from_name);
+
         App {
-            client_id,
             access_token,
             calendars: Vec::new(),
+            loaded_events: Vec::new(),
             events: Vec::new(),
+            hidden_calendars: HashSet::new(),
+            checked_calendars: HashSet::new(),
+            group_by_calendar: false,
+            collapsed_calendars: HashSet::new(),
+            category_filter: HashSet::new(),
             calendar_list_state,
             event_list_state,
-            current_view: CurrentView::Calendars,
-            event_view_mode: EventViewMode::List,
+            current_view: if default_view.is_some() {
+                CurrentView::Events
+            } else {
+                CurrentView::Calendars
+            },
+            event_view_mode: default_view.unwrap_or(EventViewMode::List),
             current_calendar_id: None,
             detail_view_scroll: 0,
-            displayed_date: Local::now().date_naive(),
+            displayed_date: clock::now().date_naive(),
             transition: None,
+            flash_until: None,
             calendar_list_area: Rect::default(),
             event_list_area: Rect::default(),
             help_area: Rect::default(),
+            footer_breadcrumb_area: Rect::default(),
             show_help: false,
+            show_diagnostics: false,
+            show_missed: false,
             show_legend: false,
+            legend_state: ListState::default(),
+            legend_area: Rect::default(),
+            show_categories: false,
+            known_categories: Vec::new(),
+            category_state: ListState::default(),
+            category_area: Rect::default(),
             calendar_list_scroll_state: ScrollbarState::default(),
             event_list_scroll_state: ScrollbarState::default(),
             detail_scroll_state: ScrollbarState::default(),
+            detail_links: Vec::new(),
+            meeting_artifacts: HashMap::new(),
+            fetched_body: None,
+            show_all_attendees: false,
+            show_raw_body: false,
+            input: None,
+            schedule_emails: Vec::new(),
+            schedule_overlay: Vec::new(),
+            due_tasks: Vec::new(),
+            due_tasks_error: None,
+            find_time: None,
+            room_picker: None,
+            status_message: None,
+            toasts: Vec::new(),
+            announce_sync: false,
+            sync_failing: false,
+            planner: None,
+            conflict_prompt: None,
+            edit_prompt: None,
+            logout_prompt: false,
+            logged_out: false,
+            search: None,
+            sql_console: None,
+            relogin_prompt: None,
+            relogin_for_write: false,
+            show_past: false,
+            day_peek: None,
+            focus_date: None,
+            day_drilled_from: None,
+            center_list_on_now: false,
+            list_months_extended: (0, 0),
+            list_extend_anchor: None,
+            day_grid_minutes: 30,
+            day_grid_rows: Vec::new(),
+            compact: false,
+            busy_only: settings.busy_only.unwrap_or(false),
+            last_synced: None,
+            account: None,
+            sync_truncated: None,
+            refresh_generation: 0,
+            cache_warmed_on: None,
+            internal_domains: settings
+                .internal_domains
+                .iter()
+                .flatten()
+                .map(|domain| domain.trim_start_matches('@').to_lowercase())
+                .collect(),
+            dst_transitions: HashMap::new(),
+            dst_warned: None,
+            rules: crate::rules::compile_rules(settings.rules.as_deref().unwrap_or_default()),
             db_pool,
             theme,
             symbols,
             notification_manager,
+            alarms: Default::default(),
+            alarm: None,
+            flagged_events: HashSet::new(),
+            attendance: HashMap::new(),
+            status_manager: StatusManager::new(settings.status_integration.clone()),
             settings,
+            instance_id: format!("{}-{}", std::process::id(), Utc::now().timestamp_millis()),
+            is_primary: true,
+            events_changed_at: None,
         }
     }
 
-    fn create_oauth_client(&self) -> BasicClient {
-        let client_id = ClientId::new(self.client_id.clone());
-        let auth_url = AuthUrl::new(
-            "https://login.microsoftonline.com/common/oauth2/v2.0/authorize".to_string(),
-        )
-        .unwrap();
-        let token_url = Some(
-            TokenUrl::new("https://login.microsoftonline.com/common/oauth2/v2.0/token".to_string())
-                .unwrap(),
-        );
-        let redirect_url = RedirectUrl::new("http://localhost:8080".to_string()).unwrap();
-
-        BasicClient::new(client_id, None, auth_url, token_url).set_redirect_uri(redirect_url)
-    }
-
     pub async fn refresh_auth_token(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(refresh_token) = crate::auth::load_refresh_token() {
-            let client = self.create_oauth_client();
+            let client = crate::auth::oauth_client(&self.settings).map_err(|e| e.to_string())?;
             let token_result = client
                 .exchange_refresh_token(&refresh_token)
-                .request_async(async_http_client)
+                .request_async(crate::http::oauth_http_client)
                 .await;
 
             if let Ok(refreshed_token) = token_result {
                 self.access_token = refreshed_token.access_token().secret().clone();
+                crate::auth::note_granted_scopes(&self.settings, refreshed_token.scopes());
                 if let Some(new_refresh_token) = refreshed_token.refresh_token() {
-                    crate::auth::save_refresh_token(new_refresh_token.secret())?;
+                    crate::auth::save_refresh_token(new_refresh_token.secret())
+                        .map_err(|e| e.to_string())?;
                 }
                 return Ok(());
             }
@@ -163,7 +733,34 @@ impl App {
         Err("Failed to refresh token.".into())
     }
 
+    /// How many events a sync fetches per calendar at most before it stops
+    /// following pages, from `max_events_per_fetch`.
+    pub fn max_events_per_fetch(&self) -> usize {
+        self.settings.max_events_per_fetch.unwrap_or(1000)
+    }
+
+    /// Whether `minimal_mode` is on: no cached bodies and no animations.
+    pub fn minimal_mode(&self) -> bool {
+        self.settings.minimal_mode.unwrap_or(false)
+    }
+
+    /// How event bodies are rendered in the detail view, from `body_renderer`
+    /// unless the raw source is toggled on.
+    pub fn body_renderer(&self) -> BodyRenderer {
+        if self.show_raw_body {
+            return BodyRenderer::Raw;
+        }
+        self.settings
+            .body_renderer
+            .as_deref()
+            .and_then(BodyRenderer::from_name)
+            .unwrap_or(BodyRenderer::Plain)
+    }
+
     pub fn start_transition(&mut self, ms: u64) {
+        if self.minimal_mode() {
+            return;
+        }
         self.transition = Some(Transition {
             start: Instant::now(),
             duration: StdDuration::from_millis(ms),
@@ -171,17 +768,70 @@ impl App {
     }
 
     pub fn toggle_event_view(&mut self) {
+        self.list_months_extended = (0, 0);
+        self.day_drilled_from = None;
         self.event_view_mode = match self.event_view_mode {
+            EventViewMode::Dashboard => EventViewMode::List,
             EventViewMode::List => EventViewMode::Week,
             EventViewMode::Week => EventViewMode::WorkWeek,
             EventViewMode::WorkWeek => EventViewMode::Day,
             EventViewMode::Day => EventViewMode::Month,
-            EventViewMode::Month => EventViewMode::List,
+            EventViewMode::Month => EventViewMode::Dashboard,
         };
         self.start_transition(300);
     }
 
+    /// Whether events are currently shown as a list, which the compact layout
+    /// does for every view but the dashboard.
+    pub fn shows_list(&self) -> bool {
+        match self.event_view_mode {
+            EventViewMode::List => true,
+            EventViewMode::Dashboard => false,
+            _ => self.compact,
+        }
+    }
+
+    /// First weekday and number of days shown by the week views. With
+    /// `hide_weekends` the Week view drops Saturday and Sunday as well.
+    pub fn week_layout(&self) -> (Weekday, i64) {
+        if self.event_view_mode == EventViewMode::WorkWeek || self.hides_weekends() {
+            (Weekday::Mon, 5)
+        } else {
+            (Weekday::Sun, 7)
+        }
+    }
+
+    pub fn hides_weekends(&self) -> bool {
+        self.settings.hide_weekends.unwrap_or(false)
+    }
+
+    /// Columns of the Monday-first month grid, as offsets from Monday.
+    pub fn month_columns(&self) -> Vec<usize> {
+        if self.hides_weekends() {
+            (0..5).collect()
+        } else {
+            (0..7).collect()
+        }
+    }
+
+    /// How many days back the "Past" list goes (`past_days`, default 14).
+    pub fn past_days(&self) -> i64 {
+        self.settings.past_days.unwrap_or(14)
+    }
+
+    /// Switches the list view between the displayed month and the recent past.
+    pub fn toggle_past(&mut self) {
+        self.show_past = !self.show_past;
+        self.list_months_extended = (0, 0);
+        if self.show_past {
+            self.event_view_mode = EventViewMode::List;
+        }
+        self.displayed_date = clock::now().date_naive();
+        self.event_list_state.select(None);
+    }
+
     pub fn next_month(&mut self) {
+        self.list_months_extended = (0, 0);
         let (year, month) = (self.displayed_date.year(), self.displayed_date.month());
         let new_month = if month == 12 { 1 } else { month + 1 };
         let new_year = if month == 12 { year + 1 } else { year };
@@ -189,6 +839,7 @@ impl App {
     }
 
     pub fn previous_month(&mut self) {
+        self.list_months_extended = (0, 0);
         let (year, month) = (self.displayed_date.year(), self.displayed_date.month());
         let new_month = if month == 1 { 12 } else { month - 1 };
         let new_year = if month == 1 { year - 1 } else { year };
@@ -203,7 +854,233 @@ impl App {
         self.displayed_date -= Duration::weeks(1);
     }
 
+    /// Indices of the events shown on the dashboard: today's remaining events and
+    /// upcoming invitations that haven't been answered yet.
+    pub fn dashboard_event_indices(&self) -> (Vec<usize>, Vec<usize>) {
+        let now = clock::now();
+        let today = now.date_naive();
+        let mut remaining_today = Vec::new();
+        let mut invitations = Vec::new();
+
+        for (i, color_event) in self.events.iter().enumerate() {
+            let event = &color_event.event;
+            let (Some(start), Some(end)) = (
+                parse_event_time(&event.start.date_time),
+                parse_event_time(&event.end.date_time),
+            ) else {
+                continue;
+            };
+            if end <= now {
+                continue;
+            }
+            if start.date_naive() <= today && end.date_naive() >= today {
+                remaining_today.push(i);
+            }
+            let unanswered = event
+                .response_status
+                .as_ref()
+                .is_some_and(|r| r.response == "notResponded");
+            if unanswered {
+                invitations.push(i);
+            }
+        }
+        (remaining_today, invitations)
+    }
+
+    /// Indices of the events that take place (at least partly) on `day`, in
+    /// local time. An event ending at midnight doesn't spill into the next day.
+    pub fn event_indices_on_day(&self, day: NaiveDate) -> Vec<usize> {
+        self.events
+            .iter()
+            .enumerate()
+            .filter(|(_, color_event)| {
+                let event = &color_event.event;
+                let (Some(start), Some(end)) = (
+                    parse_event_time(&event.start.date_time),
+                    parse_event_time(&event.end.date_time),
+                ) else {
+                    return false;
+                };
+                let last_day = if end > start && end.time() == NaiveTime::MIN {
+                    end.date_naive().pred_opt().unwrap()
+                } else {
+                    end.date_naive()
+                };
+                start.date_naive() <= day && last_day >= day
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The day the month view is focused on: the selected event's day, or the
+    /// displayed date when nothing is selected.
+    pub fn focused_day(&self) -> NaiveDate {
+        self.get_selected_event()
+            .and_then(|e| parse_event_time(&e.event.start.date_time))
+            .map(|start| start.date_naive())
+            .unwrap_or(self.displayed_date)
+    }
+
+    /// Moves the selection through the events listed on the dashboard.
+    fn step_dashboard_selection(&mut self, forward: bool) {
+        let (mut indices, invitations) = self.dashboard_event_indices();
+        for i in invitations {
+            if !indices.contains(&i) {
+                indices.push(i);
+            }
+        }
+        if indices.is_empty() {
+            return;
+        }
+        let len = indices.len();
+        let position = self
+            .event_list_state
+            .selected()
+            .and_then(|selected| indices.iter().position(|&i| i == selected));
+        let next = match (position, forward) {
+            (Some(p), true) => (p + 1) % len,
+            (Some(p), false) => (p + len - 1) % len,
+            (None, true) => 0,
+            (None, false) => len - 1,
+        };
+        self.event_list_state.select(Some(indices[next]));
+    }
+
+    /// Whether the list is grouped by calendar right now.
+    pub fn list_grouped(&self) -> bool {
+        self.group_by_calendar && self.shows_list()
+    }
+
+    /// Turns grouping the List view by calendar on or off.
+    pub fn toggle_list_grouping(&mut self) {
+        self.group_by_calendar = !self.group_by_calendar;
+        self.set_status(if self.group_by_calendar {
+            "Grouping the list by calendar (z folds one)"
+        } else {
+            "List no longer grouped by calendar"
+        });
+    }
+
+    /// Folds the selected event's calendar in the grouped list, or unfolds it.
+    pub fn toggle_calendar_collapse(&mut self) -> bool {
+        if !self.list_grouped() {
+            return false;
+        }
+        let Some(event) = self.get_selected_event() else {
+            return false;
+        };
+        let key = event.calendar_id.clone().unwrap_or_default();
+        if !self.collapsed_calendars.remove(&key) {
+            self.collapsed_calendars.insert(key);
+        }
+        true
+    }
+
+    /// Whether the selection is on a folded calendar heading.
+    pub fn selection_folded(&self) -> bool {
+        let Some(event) = self.get_selected_event() else {
+            return false;
+        };
+        self.list_grouped()
+            && self
+                .collapsed_calendars
+                .contains(event.calendar_id.as_deref().unwrap_or_default())
+    }
+
+    /// Name a calendar's heading shows in the grouped list.
+    pub fn calendar_name(&self, calendar_id: Option<&str>) -> String {
+        match calendar_id {
+            Some(id) => self
+                .calendars
+                .iter()
+                .find(|c| c.calendar.id == id)
+                .map_or_else(|| "Other calendar".to_string(), |c| c.calendar.name.clone()),
+            None => "Planned".to_string(),
+        }
+    }
+
+    /// The rows of the grouped List view: each day, then each calendar with
+    /// events that day (in calendar list order), then its events unless the
+    /// calendar is collapsed.
+    pub fn list_rows(&self) -> Vec<ListRow> {
+        let order = |calendar_id: &Option<String>| {
+            calendar_id.as_ref().map_or(usize::MAX, |id| {
+                self.calendars
+                    .iter()
+                    .position(|c| &c.calendar.id == id)
+                    .unwrap_or(usize::MAX - 1)
+            })
+        };
+        // One group per day and calendar
+        let mut groups: Vec<(NaiveDate, Option<String>, Vec<usize>)> = Vec::new();
+        for (index, color_event) in self.events.iter().enumerate() {
+            let day = parse_event_time(&color_event.event.start.date_time)
+                .map(|start| start.date_naive())
+                .unwrap_or(NaiveDate::MIN);
+            match groups
+                .iter_mut()
+                .find(|(d, id, _)| *d == day && *id == color_event.calendar_id)
+            {
+                Some((_, _, events)) => events.push(index),
+                None => groups.push((day, color_event.calendar_id.clone(), vec![index])),
+            }
+        }
+        groups.sort_by_key(|(day, id, _)| (*day, order(id)));
+        let mut rows = Vec::new();
+        let mut last_day = None;
+        for (day, calendar_id, events) in groups {
+            if last_day != Some(day) {
+                rows.push(ListRow::Day(day));
+                last_day = Some(day);
+            }
+            let collapsed = self
+                .collapsed_calendars
+                .contains(calendar_id.as_deref().unwrap_or_default());
+            rows.push(ListRow::Calendar {
+                calendar_id,
+                events: events.clone(),
+                collapsed,
+            });
+            if !collapsed {
+                rows.extend(events.into_iter().map(ListRow::Event));
+            }
+        }
+        rows
+    }
+
+    /// Moves the selection through the selectable rows of the grouped list.
+    fn step_grouped_selection(&mut self, forward: bool) {
+        let rows = self.list_rows();
+        let stops: Vec<usize> = rows.iter().filter_map(ListRow::event).collect();
+        if stops.is_empty() {
+            return;
+        }
+        let len = stops.len();
+        let position = self.event_list_state.selected().and_then(|selected| {
+            rows.iter()
+                .filter(|row| row.event().is_some())
+                .position(|row| row.shows(selected))
+        });
+        let next = match (position, forward) {
+            (Some(p), true) => (p + 1) % len,
+            (Some(p), false) => (p + len - 1) % len,
+            (None, true) => 0,
+            (None, false) => len - 1,
+        };
+        self.event_list_state.select(Some(stops[next]));
+    }
+
     pub fn next_item(&mut self) {
+        if self.current_view == CurrentView::Events
+            && self.event_view_mode == EventViewMode::Dashboard
+        {
+            self.step_dashboard_selection(true);
+            return;
+        }
+        if self.current_view == CurrentView::Events && self.list_grouped() {
+            self.step_grouped_selection(true);
+            return;
+        }
         let (state, len) = match self.current_view {
             CurrentView::Calendars => (&mut self.calendar_list_state, self.calendars.len() + 2),
             CurrentView::Events => (&mut self.event_list_state, self.events.len()),
@@ -218,6 +1095,16 @@ impl App {
     }
 
     pub fn previous_item(&mut self) {
+        if self.current_view == CurrentView::Events
+            && self.event_view_mode == EventViewMode::Dashboard
+        {
+            self.step_dashboard_selection(false);
+            return;
+        }
+        if self.current_view == CurrentView::Events && self.list_grouped() {
+            self.step_grouped_selection(false);
+            return;
+        }
         let (state, len) = match self.current_view {
             CurrentView::Calendars => (&mut self.calendar_list_state, self.calendars.len() + 2),
             CurrentView::Events => (&mut self.event_list_state, self.events.len()),
@@ -231,6 +1118,83 @@ impl App {
         state.select(Some(i));
     }
 
+    /// Index of the first event in the list that hasn't ended yet, where the
+    /// "now" separator goes. `None` unless there are events on both sides,
+    /// and in the grouped list, where events aren't in time order.
+    pub fn now_separator(&self) -> Option<usize> {
+        if self.list_grouped() {
+            return None;
+        }
+        let now = clock::now();
+        let index = self
+            .events
+            .iter()
+            .position(|e| parse_event_time(&e.event.end.date_time).is_some_and(|end| end > now))?;
+        (index > 0).then_some(index)
+    }
+
+    /// Event under a row of the list, given the row's position counted from
+    /// the top of the list (not the screen). `None` for the separator.
+    pub fn event_at_list_row(&self, row: usize) -> Option<usize> {
+        if self.list_grouped() {
+            return self.list_rows().get(row).and_then(ListRow::event);
+        }
+        let index = match self.now_separator() {
+            Some(separator) if row == separator => return None,
+            Some(separator) if row > separator => row - 1,
+            _ => row,
+        };
+        (index < self.events.len()).then_some(index)
+    }
+
+    /// Whether moving the List view's selection past this end of the list
+    /// should load another month instead of wrapping around.
+    pub fn at_list_end(&self, forward: bool) -> bool {
+        if self.event_view_mode != EventViewMode::List || self.show_past {
+            return false;
+        }
+        let Some(selected) = self.event_list_state.selected() else {
+            return false;
+        };
+        if self.list_grouped() {
+            let rows = self.list_rows();
+            let mut selectable = rows.iter().filter(|row| row.event().is_some());
+            let edge = if forward {
+                selectable.next_back()
+            } else {
+                selectable.next()
+            };
+            return edge.is_some_and(|row| row.shows(selected));
+        }
+        let len = self.events.len();
+        len > 0 && selected == if forward { len - 1 } else { 0 }
+    }
+
+    /// Widens the List view's sync range by the month after (or before) the
+    /// months already loaded, even when those had no events. The selection
+    /// moves on once it's loaded.
+    pub fn extend_list(&mut self, forward: bool) {
+        let index = if forward {
+            self.events.len().saturating_sub(1)
+        } else {
+            0
+        };
+        let Some(edge) = self.events.get(index) else {
+            return;
+        };
+        let first_of_month = self.displayed_date.with_day(1).unwrap();
+        let (before, after) = &mut self.list_months_extended;
+        let target = if forward {
+            *after += 1;
+            first_of_month + Months::new(*after)
+        } else {
+            *before += 1;
+            first_of_month - Months::new(*before)
+        };
+        self.list_extend_anchor = Some((edge.event.id.clone(), forward));
+        self.set_status(&format!("Loading {}...", target.format("%B %Y")));
+    }
+
     pub fn jump_to_next_day(&mut self) {
         if let Some(selected_index) = self.event_list_state.selected() {
             if let Some(current_event) = self.events.get(selected_index) {
@@ -314,6 +1278,22 @@ impl App {
         None
     }
 
+    /// Selects a shown event and opens its details, e.g. from a notification.
+    pub fn show_event(&mut self, event_id: &str) {
+        let Some(index) = self.events.iter().position(|e| e.event.id == event_id) else {
+            self.set_status("That event is no longer shown.");
+            return;
+        };
+        self.event_list_state.select(Some(index));
+        self.detail_view_scroll = 0;
+        self.current_view = CurrentView::EventDetail;
+    }
+
+    /// ID of the selected event, to find it again after the events change.
+    pub fn selected_event_id(&self) -> Option<String> {
+        self.get_selected_event().map(|e| e.event.id.clone())
+    }
+
     pub fn scroll_down(&mut self) {
         self.detail_view_scroll = self.detail_view_scroll.saturating_add(1);
     }
@@ -322,38 +1302,1120 @@ impl App {
         self.detail_view_scroll = self.detail_view_scroll.saturating_sub(1);
     }
 
-    pub fn select_nearest_event(&mut self) {
-        if self.events.is_empty() {
-            self.event_list_state.select(None);
+    /// Whether an event has more attendees than the configured large meeting threshold.
+    pub fn is_large_meeting(&self, event: &GraphEvent) -> bool {
+        event.attendees.len() > self.settings.large_meeting_threshold.unwrap_or(50)
+    }
+
+    /// Whether the shown events may be out of date: the last sync is more
+    /// than two refresh intervals ago, or there never was one.
+    pub fn sync_is_stale(&self) -> bool {
+        let interval = self.settings.refresh_interval_minutes.unwrap_or(5) as i64;
+        self.last_synced
+            .is_none_or(|synced_at| clock::now_utc() - synced_at > Duration::minutes(2 * interval))
+    }
+
+    /// Whether an email address is outside the internal domains. Nothing is
+    /// external while the internal domains are unknown.
+    fn is_external_address(&self, address: &str) -> bool {
+        let Some((_, domain)) = address.rsplit_once('@') else {
+            return false;
+        };
+        !self.internal_domains.is_empty() && !self.internal_domains.contains(&domain.to_lowercase())
+    }
+
+    /// Whether an event was organized from outside the internal domains.
+    pub fn is_external(&self, event: &GraphEvent) -> bool {
+        event
+            .organizer
+            .as_ref()
+            .is_some_and(|o| self.is_external_address(&o.email_address.address))
+    }
+
+    /// The domains of an event's attendees from outside the internal
+    /// domains, lowercase and sorted. Empty unless the warning is on.
+    pub fn external_attendee_domains(&self, event: &GraphEvent) -> Vec<String> {
+        if !self.settings.external_participants_warning.unwrap_or(true) {
+            return Vec::new();
+        }
+        let mut domains: Vec<String> = event
+            .attendees
+            .iter()
+            .filter_map(|a| a.email_address.as_ref())
+            .filter(|e| self.is_external_address(&e.address))
+            .filter_map(|e| Some(e.address.rsplit_once('@')?.1.to_lowercase()))
+            .collect();
+        domains.sort();
+        domains.dedup();
+        domains
+    }
+
+    /// How external meetings stand out, as (badge, color).
+    fn external_marking(&self) -> (bool, bool) {
+        match self
+            .settings
+            .external_meetings
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("badge") => (true, false),
+            Some("color") => (false, true),
+            Some("both") => (true, true),
+            _ => (false, false),
+        }
+    }
+
+    /// The subject shown for an event; just "Busy" in busy-only mode.
+    pub fn display_subject<'a>(&self, event: &'a GraphEvent) -> &'a str {
+        display_subject(&event.subject, self.busy_only)
+    }
+
+    pub fn toggle_busy_only(&mut self) {
+        self.busy_only = !self.busy_only;
+        self.notification_manager.set_busy_only(self.busy_only);
+        self.set_status(if self.busy_only {
+            "Busy-only mode: subjects hidden (B to show)"
+        } else {
+            "Busy-only mode off"
+        });
+    }
+
+    /// Marker icons shown in front of an event's subject in the event views.
+    pub fn event_badges(&self, event: &GraphEvent) -> String {
+        let mut badges = String::new();
+        if self.busy_only {
+            return badges;
+        }
+        if self.is_large_meeting(event) {
+            badges.push_str(&self.symbols.large_meeting);
+        }
+        if self.dst_transitions.contains_key(&event.id) {
+            badges.push_str(&self.symbols.dst_warning);
+        }
+        if (self.external_marking().0 && self.is_external(event))
+            || !self.external_attendee_domains(event).is_empty()
+        {
+            badges.push_str(&self.symbols.external);
+        }
+        badges
+    }
+
+    /// Opens the n-th (1-based) link detected in the event detail view.
+    pub fn open_detail_link(&self, n: usize) {
+        if let Some(link) = n.checked_sub(1).and_then(|i| self.detail_links.get(i)) {
+            info!("Opening link: {}", link.url);
+            if let Err(e) = webbrowser::open(&link.url) {
+                error!("Failed to open link {}: {}", link.url, e);
+            }
+        }
+    }
+
+    /// Flags the selected event for an alarm at its start, or unflags it.
+    /// Returns whether the flags changed.
+    pub fn toggle_flag(&mut self) -> bool {
+        let Some(event) = self.get_selected_event().map(|e| e.event.clone()) else {
+            return false;
+        };
+        let id = event.correlation_id().to_string();
+        if self.flagged_events.remove(&id) {
+            self.set_status(&format!("No alarm for \"{}\"", event.subject));
+        } else {
+            self.flagged_events.insert(id);
+            self.set_status(&format!("Alarm set for the start of \"{}\"", event.subject));
+        }
+        true
+    }
+
+    /// Marks the selected meeting, once over, as attended, then skipped, then
+    /// unmarked again. Returns the event and its new mark, to be saved.
+    pub fn cycle_attendance(&mut self) -> Option<(GraphEvent, Option<bool>)> {
+        let event = self.get_selected_event()?.event.clone();
+        let ended = parse_event_time(&event.end.date_time).is_some_and(|end| end <= clock::now());
+        if !ended {
+            self.set_status("Only meetings that are over can be marked attended or skipped.");
+            return None;
+        }
+        let key = event.correlation_id().to_string();
+        let mark = match self.attendance.get(&key) {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        let status = match mark {
+            Some(true) => "Attended",
+            Some(false) => "Skipped",
+            None => "Unmarked",
+        };
+        match mark {
+            Some(attended) => self.attendance.insert(key, attended),
+            None => self.attendance.remove(&key),
+        };
+        self.set_status(&format!("{}: {}", status, event.subject));
+        Some((event, mark))
+    }
+
+    /// Opens the selected event in Outlook on the web.
+    pub fn open_in_outlook(&mut self) {
+        let Some(link) = self
+            .get_selected_event()
+            .and_then(|e| e.event.web_link.clone())
+        else {
+            self.set_status("This event has no Outlook link.");
+            return;
+        };
+        info!("Opening event in Outlook: {}", link);
+        if let Err(e) = webbrowser::open(&link) {
+            error!("Failed to open {}: {}", link, e);
+            self.set_status("Could not open the browser.");
+        }
+    }
+
+    /// Saves the selected event as an .ics file in `ics_export_dir` (the
+    /// Downloads folder by default), to forward it outside Outlook.
+    pub fn export_selected_event(&mut self) {
+        let Some(event) = self.get_selected_event().map(|e| e.event.clone()) else {
             return;
+        };
+        let fetched_body = self
+            .fetched_body
+            .as_ref()
+            .filter(|(id, _)| *id == event.id)
+            .and_then(|(_, body)| body.as_ref());
+        let dir = crate::ics::export_dir(self.settings.ics_export_dir.as_deref());
+        match crate::ics::export_event(&event, fetched_body, &dir) {
+            Ok(path) => self.set_status(&format!("Saved {}", path.display())),
+            Err(e) => {
+                error!("Failed to export \"{}\": {}", event.subject, e);
+                self.set_status(&format!("Could not save the .ics file: {}", e));
+            }
+        }
+    }
+
+    /// Where to join an event's online meeting: Graph's join link when it has
+    /// one, otherwise the best conferencing link in its location or body.
+    pub fn join_link(&self, event: &crate::api::GraphEvent) -> Option<crate::links::MeetingLink> {
+        if let Some(url) = event.join_url() {
+            return Some(crate::links::MeetingLink {
+                provider: crate::links::meeting_provider(url).unwrap_or("Online meeting"),
+                url: url.to_string(),
+            });
         }
+        let fetched_body = self
+            .fetched_body
+            .as_ref()
+            .filter(|(id, _)| *id == event.id)
+            .and_then(|(_, body)| body.as_ref());
+        let body = event.body.as_ref().or(fetched_body);
+        crate::links::detect_meeting_link(&[
+            event
+                .location
+                .as_ref()
+                .map_or("", |l| l.display_name.as_str()),
+            body.map_or("", |b| b.content.as_str()),
+        ])
+    }
 
-        let now = Local::now().naive_local();
-        let mut nearest_index = 0;
-        let mut min_diff = i64::MAX;
+    /// Opens the selected event's meeting in the browser.
+    pub fn join_selected_meeting(&mut self) {
+        let link = self
+            .get_selected_event()
+            .and_then(|e| self.join_link(&e.event));
+        self.open_meeting_link(link);
+    }
 
-        for (i, color_event) in self.events.iter().enumerate() {
-            if let Ok(start) = NaiveDateTime::parse_from_str(
-                &color_event.event.start.date_time,
-                "%Y-%m-%dT%H:%M:%S%.f",
-            ) {
-                let diff = start.signed_duration_since(now).num_seconds().abs();
-                if diff < min_diff {
-                    min_diff = diff;
-                    nearest_index = i;
+    /// Opens the meeting of a shown event, e.g. from a notification's "Join"
+    /// button.
+    pub fn join_meeting(&mut self, event_id: &str) {
+        let link = self
+            .events
+            .iter()
+            .find(|e| e.event.id == event_id)
+            .and_then(|e| self.join_link(&e.event));
+        self.open_meeting_link(link);
+    }
+
+    fn open_meeting_link(&mut self, link: Option<crate::links::MeetingLink>) {
+        let Some(link) = link else {
+            self.set_status("No meeting link found for this event.");
+            return;
+        };
+        info!("Joining {} meeting: {}", link.provider, link.url);
+        if let Err(e) = webbrowser::open(&link.url) {
+            error!("Failed to open {}: {}", link.url, e);
+            self.set_status("Could not open the browser.");
+        }
+    }
+
+    /// Opens the prompt for the colleagues whose free/busy is overlaid on the
+    /// week and day views, prefilled with the current list.
+    pub fn open_schedule_prompt(&mut self) {
+        self.input = Some(InputPrompt {
+            kind: InputKind::ScheduleOverlay,
+            buffer: self.schedule_emails.join(", "),
+        });
+    }
+
+    /// Opens the first prompt of the "find a time" flow, prefilled with the
+    /// colleagues currently overlaid.
+    pub fn open_find_time_prompt(&mut self) {
+        self.input = Some(InputPrompt {
+            kind: InputKind::FindTimeAttendees,
+            buffer: self.schedule_emails.join(", "),
+        });
+    }
+
+    /// Applies the text of a submitted input prompt and tells the main loop
+    /// what to do next.
+    pub fn submit_input(&mut self, prompt: InputPrompt) -> InputAction {
+        let text = prompt.buffer.trim();
+        match prompt.kind {
+            InputKind::ScheduleOverlay => {
+                self.schedule_emails = parse_email_list(text);
+                self.schedule_overlay.clear();
+                InputAction::Refresh
+            }
+            InputKind::FindTimeAttendees => {
+                let attendees = parse_email_list(text);
+                if attendees.is_empty() {
+                    self.set_status("Enter at least one attendee to find a time.");
+                    return InputAction::None;
+                }
+                self.input = Some(InputPrompt {
+                    kind: InputKind::FindTimeDuration { attendees },
+                    buffer: "30".to_string(),
+                });
+                InputAction::None
+            }
+            InputKind::FindTimeDuration { attendees } => match text.parse::<i64>() {
+                Ok(duration_minutes) if duration_minutes > 0 => {
+                    self.find_time = Some(FindTimeState {
+                        attendees,
+                        duration_minutes,
+                        suggestions: None,
+                        empty_reason: None,
+                        list_state: ListState::default(),
+                    });
+                    InputAction::FindMeetingTimes
+                }
+                _ => {
+                    self.set_status(&format!("Invalid meeting length: {}", text));
+                    InputAction::None
+                }
+            },
+            InputKind::NewEventSubject {
+                attendees,
+                start,
+                end,
+            } => {
+                if text.is_empty() {
+                    self.set_status("Event not created: the subject is empty.");
+                    return InputAction::None;
+                }
+                let new_event = NewEvent {
+                    subject: text.to_string(),
+                    start,
+                    end,
+                    attendees,
+                    rooms: Vec::new(),
+                };
+                if !self.settings.book_rooms.unwrap_or(false) {
+                    return InputAction::CreateEvent(new_event);
+                }
+                self.room_picker = Some(RoomPickerState {
+                    new_event,
+                    rooms: None,
+                    list_state: ListState::default().with_selected(Some(0)),
+                });
+                InputAction::FindRooms
+            }
+            InputKind::Search { cache_only } => match SearchQuery::parse(text) {
+                Ok(query) => InputAction::Search { query, cache_only },
+                Err(e) => {
+                    self.set_status(&e);
+                    InputAction::None
+                }
+            },
+            InputKind::EditEventSubject { event_id } => {
+                if text.is_empty() {
+                    self.set_status("Not saved: the subject is empty.");
+                    return InputAction::None;
+                }
+                let Some(old) = self.editable_fields(&event_id) else {
+                    self.set_status("Not saved: the event is no longer loaded.");
+                    return InputAction::None;
+                };
+                self.input = Some(InputPrompt {
+                    kind: InputKind::EditEventTime {
+                        event_id,
+                        subject: text.to_string(),
+                    },
+                    buffer: format_time_range(old.start, old.end),
+                });
+                InputAction::None
+            }
+            InputKind::EditEventTime { event_id, subject } => match parse_time_range(text) {
+                Ok((start, end)) => {
+                    let location = self
+                        .editable_fields(&event_id)
+                        .map(|old| old.location)
+                        .unwrap_or_default();
+                    self.input = Some(InputPrompt {
+                        kind: InputKind::EditEventLocation {
+                            event_id,
+                            subject,
+                            start,
+                            end,
+                        },
+                        buffer: location,
+                    });
+                    InputAction::None
+                }
+                Err(e) => {
+                    self.set_status(&format!("Not saved: {}", e));
+                    InputAction::None
+                }
+            },
+            InputKind::EditEventLocation {
+                event_id,
+                subject,
+                start,
+                end,
+            } => {
+                let Some(old) = self.editable_fields(&event_id) else {
+                    self.set_status("Not saved: the event is no longer loaded.");
+                    return InputAction::None;
+                };
+                let new = EditableFields {
+                    subject,
+                    start,
+                    end,
+                    location: text.to_string(),
+                };
+                let Some(calendar_id) = self
+                    .events
+                    .iter()
+                    .find(|e| e.event.id == event_id)
+                    .and_then(|e| e.calendar_id.clone())
+                else {
+                    self.set_status("Not saved: the event is no longer loaded.");
+                    return InputAction::None;
+                };
+                if new == old {
+                    self.set_status("Nothing to change.");
+                } else {
+                    self.edit_prompt = Some(EventEditPrompt {
+                        calendar_id,
+                        event_id,
+                        old,
+                        new,
+                    });
+                }
+                InputAction::None
+            }
+        }
+    }
+
+    /// Stores the suggestions returned by `findMeetingTimes`, best first.
+    pub fn set_meeting_suggestions(
+        &mut self,
+        mut suggestions: Vec<MeetingTimeSuggestion>,
+        empty_reason: Option<String>,
+    ) {
+        if let Some(find_time) = &mut self.find_time {
+            suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+            find_time.list_state.select(if suggestions.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+            find_time.suggestions = Some(suggestions);
+            find_time.empty_reason = empty_reason;
+        }
+    }
+
+    /// Fills the room picker, free rooms first.
+    pub fn set_rooms(&mut self, rooms: Result<Vec<RoomOption>, String>) {
+        if let Some(picker) = &mut self.room_picker {
+            picker.rooms = Some(rooms.map(|mut rooms| {
+                rooms.sort_by(|a, b| {
+                    (a.free != Some(true))
+                        .cmp(&(b.free != Some(true)))
+                        .then_with(|| a.room.name.cmp(&b.room.name))
+                });
+                rooms
+            }));
+        }
+    }
+
+    /// Opens the search prompt. A `cache_only` search looks through every
+    /// cached event offline instead of asking Graph too.
+    pub fn open_search_prompt(&mut self, cache_only: bool) {
+        self.input = Some(InputPrompt {
+            kind: InputKind::Search { cache_only },
+            buffer: String::new(),
+        });
+    }
+
+    /// Closes the search popup and shows the day of the selected result.
+    /// Returns whether events must be reloaded.
+    pub fn open_search_result(&mut self) -> bool {
+        let Some(search) = self.search.take() else {
+            return false;
+        };
+        let Some(start) = search
+            .selected()
+            .and_then(|r| parse_event_time(&r.event.start.date_time))
+        else {
+            return false;
+        };
+        // Results of a cached search can come from any calendar
+        if search.cache_only {
+            self.current_calendar_id = None;
+        }
+        self.current_view = CurrentView::Events;
+        self.event_view_mode = EventViewMode::Day;
+        self.displayed_date = start.date_naive();
+        true
+    }
+
+    /// Starts editing the selected event: its subject, time and location are
+    /// asked for in turn, prefilled with the current values.
+    pub fn open_edit_prompt(&mut self) {
+        let Some(selected) = self.get_selected_event() else {
+            return;
+        };
+        if selected.calendar_id.is_none() {
+            self.set_status("Planned blocks can't be edited.");
+            return;
+        }
+        if selected
+            .calendar_id
+            .as_deref()
+            .is_some_and(|id| !crate::api::is_graph_id(id))
+        {
+            self.set_status("Google and subscribed calendars are read-only here.");
+            return;
+        }
+        if !crate::auth::can_write() {
+            self.refuse_write();
+            return;
+        }
+        if selected.event.is_all_day == Some(true) {
+            self.set_status("All-day events can't be edited here.");
+            return;
+        }
+        self.input = Some(InputPrompt {
+            kind: InputKind::EditEventSubject {
+                event_id: selected.event.id.clone(),
+            },
+            buffer: selected.event.subject.clone(),
+        });
+    }
+
+    /// Current values of the editable fields of a loaded event.
+    fn editable_fields(&self, event_id: &str) -> Option<EditableFields> {
+        let event = &self.events.iter().find(|e| e.event.id == event_id)?.event;
+        Some(EditableFields {
+            subject: event.subject.clone(),
+            start: parse_event_time(&event.start.date_time)?,
+            end: parse_event_time(&event.end.date_time)?,
+            location: event
+                .location
+                .as_ref()
+                .map(|l| l.display_name.clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Turns the selected suggestion into a prompt for the new event's subject.
+    pub fn pick_meeting_suggestion(&mut self) {
+        let Some(find_time) = self.find_time.take() else {
+            return;
+        };
+        let selected = find_time
+            .list_state
+            .selected()
+            .zip(find_time.suggestions.as_ref())
+            .and_then(|(i, suggestions)| suggestions.get(i));
+        if let Some(suggestion) = selected {
+            self.input = Some(InputPrompt {
+                kind: InputKind::NewEventSubject {
+                    attendees: find_time.attendees.clone(),
+                    start: suggestion.meeting_time_slot.start.date_time.clone(),
+                    end: suggestion.meeting_time_slot.end.date_time.clone(),
+                },
+                buffer: String::new(),
+            });
+        }
+    }
+
+    /// Turns down a change for lack of write access. Unless `read_only` is
+    /// set, offers to log in again asking for it.
+    fn refuse_write(&mut self) {
+        if self.settings.read_only.unwrap_or(false) {
+            self.set_status(READ_ONLY_MESSAGE);
+            return;
+        }
+        self.relogin_for_write = true;
+        self.relogin_prompt = Some(
+            "Changing events needs write access to your calendars. Log in again to grant it?"
+                .to_string(),
+        );
+    }
+
+    /// Whether a new event can be created for `target`, refusing it when
+    /// writing to Outlook isn't allowed.
+    pub fn may_create(&mut self, target: NewEventTarget) -> bool {
+        if target != (NewEventTarget::PlannerTask { local: true }) && !crate::auth::can_write() {
+            self.refuse_write();
+            return false;
+        }
+        true
+    }
+
+    /// Looks at the conflicts found for a new event before it's saved,
+    /// handing it back when it can be saved right away. Otherwise the
+    /// conflicts are put up for confirmation, or the event is refused when it
+    /// overlaps focus time and `block_focus_time` is set.
+    pub fn guard_new_event(
+        &mut self,
+        new_event: NewEvent,
+        target: NewEventTarget,
+        conflicts: Vec<ColorEvent>,
+    ) -> Option<NewEvent> {
+        if conflicts.is_empty() {
+            return Some(new_event);
+        }
+        if self.settings.block_focus_time.unwrap_or(false) {
+            let pattern = self
+                .settings
+                .focus_time_pattern
+                .as_deref()
+                .unwrap_or("(?i)focus time");
+            let focus_time = Regex::new(pattern)
+                .ok()
+                .and_then(|re| conflicts.iter().find(|e| re.is_match(&e.event.subject)));
+            if let Some(focus_time) = focus_time {
+                let message = format!(
+                    "Not created: \"{}\" overlaps the focus time \"{}\"",
+                    new_event.subject, focus_time.event.subject
+                );
+                self.set_status(&message);
+                return None;
+            }
+        }
+        self.conflict_prompt = Some(ConflictPrompt {
+            new_event,
+            target,
+            conflicts,
+        });
+        None
+    }
+
+    /// Opens the focus-day planner over today's events. Returns whether the
+    /// displayed date moved to today, in which case events must be reloaded.
+    pub fn open_planner(&mut self) -> bool {
+        let tasks_file = self
+            .settings
+            .planner_tasks_file
+            .as_ref()
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|| crate::config::get_config_dir().join("tasks.md"));
+        let tasks = load_tasks(&tasks_file);
+        let (workday_start, workday_end) = self.settings.workday_hours();
+        let slots = free_slots(&self.events, workday_start, workday_end);
+        self.planner = Some(PlannerState::new(tasks_file, tasks, slots));
+
+        let today = clock::now().date_naive();
+        let moved = self.displayed_date != today;
+        self.displayed_date = today;
+        moved
+    }
+
+    /// Recomputes the planner's free slots after the events changed.
+    pub fn refresh_planner_slots(&mut self) {
+        let (workday_start, workday_end) = self.settings.workday_hours();
+        if let Some(planner) = &mut self.planner {
+            planner.slots = free_slots(&self.events, workday_start, workday_end);
+            planner
+                .slot_state
+                .select((!planner.slots.is_empty()).then_some(0));
+        }
+    }
+
+    /// Shows more (`finer`) or fewer minutes per row in the day grid.
+    pub fn zoom_day_grid(&mut self, finer: bool) {
+        let current = DAY_GRID_ZOOM_LEVELS
+            .iter()
+            .position(|&minutes| minutes == self.day_grid_minutes)
+            .unwrap_or(1);
+        let next = if finer {
+            current.saturating_sub(1)
+        } else {
+            (current + 1).min(DAY_GRID_ZOOM_LEVELS.len() - 1)
+        };
+        self.day_grid_minutes = DAY_GRID_ZOOM_LEVELS[next];
+    }
+
+    /// Shows a short message in the footer for a few seconds.
+    pub fn set_status(&mut self, message: &str) {
+        info!("Status: {}", message);
+        self.status_message = Some((message.to_string(), Instant::now()));
+    }
+
+    /// The status message to show, if it hasn't expired yet.
+    pub fn current_status(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() < STATUS_MESSAGE_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Shows a toast in the bottom-right corner.
+    pub fn toast(&mut self, kind: ToastKind, title: &str, message: &str) {
+        info!("Toast: {}: {}", title, message);
+        self.toasts
+            .retain(|toast| toast.shown_at.elapsed() < TOAST_DURATION);
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            kind,
+            title: title.to_string(),
+            message: message.to_string(),
+            shown_at: Instant::now(),
+        });
+    }
+
+    /// The toasts to show, oldest first.
+    pub fn current_toasts(&self) -> impl Iterator<Item = &Toast> {
+        self.toasts
+            .iter()
+            .filter(|toast| toast.shown_at.elapsed() < TOAST_DURATION)
+    }
+
+    /// Calendars shown by the current selection: one calendar, "My Calendars",
+    /// the ticked ones or all of them.
+    pub fn calendars_in_context(&self) -> Vec<ColorCalendar> {
+        match &self.current_calendar_id {
+            Some(id) if id == MY_CALENDARS_ID => self.own_calendars(),
+            Some(id) if id == CHECKED_CALENDARS_ID => self
+                .calendars
+                .iter()
+                .filter(|c| self.checked_calendars.contains(&c.calendar.id))
+                .cloned()
+                .collect(),
+            Some(id) => self
+                .calendars
+                .iter()
+                .filter(|c| c.calendar.id == *id)
+                .cloned()
+                .collect(),
+            None => self.visible_calendars(),
+        }
+    }
+
+    /// The user's own calendars, as "My Calendars" shows them: those they
+    /// can share, unlike calendars others shared with them.
+    pub fn own_calendars(&self) -> Vec<ColorCalendar> {
+        self.calendars
+            .iter()
+            .filter(|c| c.calendar.can_share.unwrap_or(false))
+            .cloned()
+            .collect()
+    }
+
+    /// The calendars "All Calendars" shows.
+    pub fn visible_calendars(&self) -> Vec<ColorCalendar> {
+        self.calendars
+            .iter()
+            .filter(|c| !self.settings.excluded_from_all(&c.calendar))
+            .cloned()
+            .collect()
+    }
+
+    /// Name of the current selection, as shown in titles and the footer.
+    pub fn context_name(&self) -> String {
+        match self.current_calendar_id.as_deref() {
+            None => "All Calendars".to_string(),
+            Some(MY_CALENDARS_ID) => "My Calendars".to_string(),
+            Some(CHECKED_CALENDARS_ID) => {
+                let calendars = self.calendars_in_context();
+                match calendars.as_slice() {
+                    [calendar] => calendar.calendar.name.clone(),
+                    [first, second] => {
+                        format!("{} + {}", first.calendar.name, second.calendar.name)
+                    }
+                    _ => format!("{} Calendars", calendars.len()),
                 }
             }
+            Some(id) => self
+                .calendars
+                .iter()
+                .find(|c| c.calendar.id == id)
+                .map(|c| c.calendar.name.clone())
+                .unwrap_or_else(|| "All Calendars".to_string()),
+        }
+    }
+
+    /// Ticks or unticks the calendar on a row of the calendar list (the first
+    /// two rows are "All Calendars" and "My Calendars").
+    pub fn toggle_calendar_check(&mut self, row: usize) {
+        let Some(calendar) = row.checked_sub(2).and_then(|i| self.calendars.get(i)) else {
+            return;
+        };
+        let id = calendar.calendar.id.clone();
+        if !self.checked_calendars.remove(&id) {
+            self.checked_calendars.insert(id);
+        }
+    }
+
+    /// Ticks the calendars saved by an earlier session, ignoring calendars
+    /// that no longer exist. When the app starts on an event view, the ticked
+    /// calendars are shown.
+    pub fn restore_checked_calendars(&mut self, ids: Vec<String>) {
+        self.checked_calendars = ids
+            .into_iter()
+            .filter(|id| self.calendars.iter().any(|c| &c.calendar.id == id))
+            .collect();
+        if !self.checked_calendars.is_empty() && self.current_view == CurrentView::Events {
+            self.current_calendar_id = Some(CHECKED_CALENDARS_ID.to_string());
+        }
+    }
+
+    /// Shows the events of a row of the calendar list. On a calendar's row,
+    /// the ticked calendars are shown if there are any.
+    pub fn open_calendar_row(&mut self, row: usize) {
+        self.current_calendar_id = match row {
+            0 => None,
+            1 => Some(MY_CALENDARS_ID.to_string()),
+            _ if !self.checked_calendars.is_empty() => Some(CHECKED_CALENDARS_ID.to_string()),
+            _ => match self.calendars.get(row - 2) {
+                Some(calendar) => Some(calendar.calendar.id.clone()),
+                None => return,
+            },
+        };
+        self.current_view = CurrentView::Events;
+        self.start_transition(300);
+    }
+
+    /// Stores freshly loaded events and shows those of the visible calendars.
+    pub fn set_events(&mut self, events: Vec<ColorEvent>) {
+        self.loaded_events = events;
+        self.dst_transitions = self
+            .loaded_events
+            .iter()
+            .filter_map(|e| Some((e.event.id.clone(), crate::dst::nearby_transition(&e.event)?)))
+            .collect();
+        self.warn_upcoming_dst();
+        self.apply_filters();
+    }
+
+    /// Adds events that arrived while a sync is still running, replacing
+    /// older copies of them.
+    pub fn merge_events(&mut self, events: Vec<ColorEvent>) {
+        let mut loaded = std::mem::take(&mut self.loaded_events);
+        for event in events {
+            match loaded.iter_mut().find(|e| e.event.id == event.event.id) {
+                Some(existing) => *existing = event,
+                None => loaded.push(event),
+            }
+        }
+        loaded.sort_by(|a, b| a.event.start.date_time.cmp(&b.event.start.date_time));
+        self.set_events(loaded);
+    }
+
+    /// Puts up a status message, once per change, when the clocks change
+    /// within a week and events are close to it.
+    fn warn_upcoming_dst(&mut self) {
+        let now = clock::now();
+        let Some(transition) = self
+            .dst_transitions
+            .values()
+            .filter(|t| **t > now && **t - now < Duration::days(7))
+            .min()
+            .copied()
+        else {
+            return;
+        };
+        if self.dst_warned == Some(transition) {
+            return;
+        }
+        self.dst_warned = Some(transition);
+        let count = self
+            .dst_transitions
+            .values()
+            .filter(|t| **t == transition)
+            .count();
+        self.set_status(&format!(
+            "Clocks change on {}: check the time of {} event(s) around it",
+            transition.format("%a %d %b"),
+            count
+        ));
+    }
+
+    /// Rebuilds `events` from `loaded_events`, leaving out hidden calendars,
+    /// events without a ticked category and repeated copies of a meeting. The
+    /// selected event stays selected wherever it moved; if it's gone, the
+    /// selection is clamped to the new list.
+    pub fn apply_filters(&mut self) {
+        let selected_id = self.selected_event_id();
+        // A meeting in several of the shown calendars (e.g. a delegate's and
+        // your own) is listed once
+        let mut seen = HashSet::new();
+        let (_, color_external) = self.external_marking();
+        self.events = self
+            .loaded_events
+            .iter()
+            .filter(|e| {
+                e.calendar_id
+                    .as_ref()
+                    .is_none_or(|id| !self.hidden_calendars.contains(id))
+            })
+            .filter(|e| {
+                self.category_filter.is_empty()
+                    || e.event
+                        .categories
+                        .iter()
+                        .any(|c| self.category_filter.contains(c))
+            })
+            .filter(|e| seen.insert(e.event.correlation_id()))
+            .map(|e| {
+                let mut e = e.clone();
+                if color_external && self.is_external(&e.event) {
+                    e.color = self.theme.peach;
+                }
+                e
+            })
+            .collect();
+        if let Some(index) =
+            selected_id.and_then(|id| self.events.iter().position(|e| e.event.id == id))
+        {
+            self.event_list_state.select(Some(index));
+            return;
         }
+        let len = self.events.len();
+        if let Some(selected) = self.event_list_state.selected() {
+            self.event_list_state
+                .select((len > 0).then(|| selected.min(len - 1)));
+        }
+    }
+
+    pub fn open_legend(&mut self) {
+        self.show_legend = true;
+        self.legend_state.select(Some(0));
+    }
+
+    /// Moves the legend selection by `delta`, wrapping around.
+    pub fn move_legend_selection(&mut self, delta: isize) {
+        let len = self.calendars_in_context().len() as isize;
+        if len == 0 {
+            return;
+        }
+        let current = self.legend_state.selected().unwrap_or(0) as isize;
+        self.legend_state
+            .select(Some((current + delta).rem_euclid(len) as usize));
+    }
+
+    /// Shows or hides the events of the n-th calendar listed in the legend.
+    pub fn toggle_legend_calendar(&mut self, index: usize) {
+        let Some(calendar) = self.calendars_in_context().into_iter().nth(index) else {
+            return;
+        };
+        let id = calendar.calendar.id;
+        if !self.hidden_calendars.remove(&id) {
+            self.hidden_calendars.insert(id);
+        }
+        self.legend_state.select(Some(index));
+        self.apply_filters();
+    }
+
+    /// Opens the category menu with the categories of the cached events.
+    /// Ticked categories stay listed even if no event uses them anymore.
+    pub fn open_category_menu(&mut self, cached: Vec<String>) {
+        let mut categories = cached;
+        for category in self
+            .loaded_events
+            .iter()
+            .flat_map(|e| e.event.categories.iter())
+            .chain(self.category_filter.iter())
+        {
+            if !categories.contains(category) {
+                categories.push(category.clone());
+            }
+        }
+        categories.sort_by_key(|c| c.to_lowercase());
+        self.known_categories = categories;
+        self.show_categories = true;
+        self.category_state
+            .select((!self.known_categories.is_empty()).then_some(0));
+    }
+
+    /// Moves the category menu selection by `delta`, wrapping around.
+    pub fn move_category_selection(&mut self, delta: isize) {
+        let len = self.known_categories.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let current = self.category_state.selected().unwrap_or(0) as isize;
+        self.category_state
+            .select(Some((current + delta).rem_euclid(len) as usize));
+    }
+
+    /// Ticks or unticks the n-th category of the category menu.
+    pub fn toggle_category(&mut self, index: usize) {
+        let Some(category) = self.known_categories.get(index).cloned() else {
+            return;
+        };
+        if !self.category_filter.remove(&category) {
+            self.category_filter.insert(category);
+        }
+        self.category_state.select(Some(index));
+        self.apply_filters();
+    }
+
+    /// Unticks every category, showing all events again.
+    pub fn clear_category_filter(&mut self) {
+        self.category_filter.clear();
+        self.apply_filters();
+    }
+
+    /// Switches from a grid to the list, on a clicked day. Its first event is
+    /// selected now and again after the refresh that follows.
+    pub fn drill_into_list(&mut self, date: NaiveDate) {
+        self.displayed_date = date;
+        self.event_view_mode = EventViewMode::List;
+        self.focus_date = Some(date);
+        self.select_first_event_on(date);
+        self.start_transition(300);
+    }
+
+    /// Zooms the focused day column of a week view into the Day view.
+    pub fn drill_into_day(&mut self) {
+        let date = self.focused_day();
+        self.day_drilled_from = Some(self.event_view_mode);
+        self.event_view_mode = EventViewMode::Day;
+        self.displayed_date = date;
+        self.focus_date = Some(date);
+        self.select_first_event_on(date);
+        self.start_transition(300);
+    }
+
+    /// Returns from a day zoomed into to its week, keeping the day focused.
+    /// False when the Day view wasn't reached that way.
+    pub fn drill_back_to_week(&mut self) -> bool {
+        let Some(mode) = self.day_drilled_from.take() else {
+            return false;
+        };
+        if self.event_view_mode != EventViewMode::Day {
+            return false;
+        }
+        let date = self.displayed_date;
+        self.event_view_mode = mode;
+        self.focus_date = Some(date);
+        self.select_first_event_on(date);
+        self.start_transition(300);
+        true
+    }
+
+    /// Selects the first event still running on or starting after `date`.
+    pub fn select_first_event_on(&mut self, date: NaiveDate) {
+        let index = self.events.iter().position(|e| {
+            parse_event_time(&e.event.end.date_time).is_some_and(|end| {
+                end.date_naive() > date || (end.date_naive() == date && end.time() > NaiveTime::MIN)
+            })
+        });
+        self.event_list_state
+            .select(index.or_else(|| self.events.len().checked_sub(1)));
+    }
+
+    /// Selects an event after a refresh: the one after (or before) the end the
+    /// list was extended from, the first of a day drilled into, the one
+    /// selected before the refresh (`previous`) if it's still there, or the
+    /// one nearest to now.
+    pub fn select_after_load(&mut self, previous: Option<&str>) {
+        if let Some((id, forward)) = self.list_extend_anchor.take() {
+            if let Some(index) = self.events.iter().position(|e| e.event.id == id) {
+                let next = if forward {
+                    (index + 1).min(self.events.len() - 1)
+                } else {
+                    index.saturating_sub(1)
+                };
+                self.event_list_state.select(Some(next));
+                return;
+            }
+        }
+        if let Some(date) = self.focus_date.take() {
+            self.select_first_event_on(date);
+        } else if previous.is_none() || self.selected_event_id().as_deref() != previous {
+            self.select_nearest_event();
+        }
+    }
+
+    /// Selects the event in progress or starting next, falling back to the
+    /// most recent one when nothing is ahead. With `initial_selection =
+    /// "nearest"`, selects the event starting closest to now instead. All-day
+    /// events only count as in progress when there is nothing else.
+    pub fn select_nearest_event(&mut self) {
+        if self.events.is_empty() {
+            self.event_list_state.select(None);
+            return;
+        }
+
+        let now = clock::now();
+        let times: Vec<_> = self
+            .events
+            .iter()
+            .map(|e| {
+                Some((
+                    parse_event_time(&e.event.start.date_time)?,
+                    parse_event_time(&e.event.end.date_time)?,
+                ))
+            })
+            .collect();
+        let prefer_upcoming = self
+            .settings
+            .initial_selection
+            .as_deref()
+            .is_none_or(|s| s.eq_ignore_ascii_case("upcoming"));
+
+        let nearest_index = if prefer_upcoming {
+            let ahead = |(start, end): (DateTime<Local>, DateTime<Local>), all_day: bool| {
+                end > now && (all_day || end - start < Duration::hours(24))
+            };
+            let find_ahead = |all_day: bool| {
+                times
+                    .iter()
+                    .position(|t| t.is_some_and(|t| ahead(t, all_day)))
+            };
+            find_ahead(false)
+                .or_else(|| find_ahead(true))
+                .or_else(|| {
+                    times
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, t)| Some((i, t.as_ref()?.1)))
+                        .max_by_key(|(_, end)| *end)
+                        .map(|(i, _)| i)
+                })
+                .unwrap_or(0)
+        } else {
+            times
+                .iter()
+                .enumerate()
+                .filter_map(|(i, t)| Some((i, (t.as_ref()?.0 - now).num_seconds().abs())))
+                .min_by_key(|(_, diff)| *diff)
+                .map(|(i, _)| i)
+                .unwrap_or(0)
+        };
 
         self.event_list_state.select(Some(nearest_index));
+        self.center_list_on_now = true;
 
         // Also update displayed_date to match the event
-        if let Some(event) = self.events.get(nearest_index) {
-            if let Ok(start) =
-                NaiveDateTime::parse_from_str(&event.event.start.date_time, "%Y-%m-%dT%H:%M:%S%.f")
-            {
-                self.displayed_date = start.date();
-            }
+        if let Some(Some((start, _))) = times.get(nearest_index) {
+            self.displayed_date = start.date_naive();
         }
     }
 }
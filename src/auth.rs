@@ -1,117 +1,400 @@
+use crate::config::Settings;
+use crate::token_file;
 use keyring::Entry;
 use log::{info, warn};
 use oauth2::basic::BasicClient;
-use oauth2::reqwest::async_http_client;
 use oauth2::{
-    AuthUrl, AuthorizationCode, ClientId, CsrfToken, PkceCodeChallenge, RedirectUrl, RefreshToken,
-    Scope, TokenResponse, TokenUrl,
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl,
+    RefreshToken, Scope, TokenResponse, TokenUrl,
 };
-use std::io::{BufRead, BufReader, Write};
-use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
 use url::Url;
 
 const KEYRING_SERVICE: &str = "365cal-tui";
 const KEYRING_USERNAME: &str = "microsoft_refresh_token";
+const TOKEN_FILE: &str = "token.enc";
+
+/// How long a browser login may take before it is given up, so an abandoned
+/// login doesn't keep the port and the caller waiting forever.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Keyring service the secrets are stored under; profiles get their own.
+pub fn keyring_service() -> String {
+    match crate::config::profile() {
+        Some(profile) => format!("{}:{}", KEYRING_SERVICE, profile),
+        None => KEYRING_SERVICE.to_string(),
+    }
+}
+
+/// Where the refresh token is kept, from `token_storage`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenStorage {
+    /// The system keyring, falling back to the encrypted file when there is
+    /// no keyring to use.
+    Keyring,
+    /// Always the encrypted file.
+    File,
+}
+
+impl TokenStorage {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "keyring" => Some(Self::Keyring),
+            "file" => Some(Self::File),
+            _ => None,
+        }
+    }
+}
+
+static TOKEN_STORAGE: OnceLock<TokenStorage> = OnceLock::new();
+
+/// Selects the token storage. Must be called before the first login.
+pub fn set_token_storage(storage: TokenStorage) {
+    let _ = TOKEN_STORAGE.set(storage);
+}
+
+fn token_storage() -> TokenStorage {
+    TOKEN_STORAGE
+        .get()
+        .copied()
+        .unwrap_or(TokenStorage::Keyring)
+}
+
+/// Saves a refresh token to the token storage: the keyring entry
+/// `keyring_username`, or the encrypted token file `file_name`.
+pub fn save_token(
+    keyring_username: &str,
+    file_name: &str,
+    refresh_token: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if token_storage() == TokenStorage::Keyring {
+        let saved = Entry::new(&keyring_service(), keyring_username)
+            .and_then(|entry| entry.set_password(refresh_token));
+        match saved {
+            Ok(()) => {
+                // Don't leave an older copy behind from when the keyring was missing
+                let _ = token_file::delete(file_name);
+                return Ok(());
+            }
+            Err(e) => warn!(
+                "Could not save the refresh token to the keyring ({}); using the encrypted token file",
+                e
+            ),
+        }
+    }
+    token_file::save(file_name, refresh_token)
+}
+
+/// The refresh token `save_token` saved, if any.
+pub fn load_token(keyring_username: &str, file_name: &str) -> Option<String> {
+    if token_storage() == TokenStorage::Keyring {
+        if let Ok(entry) = Entry::new(&keyring_service(), keyring_username) {
+            if let Ok(token_secret) = entry.get_password() {
+                return Some(token_secret);
+            }
+        }
+    }
+    token_file::load(file_name)
+}
+
+/// Deletes a refresh token from the keyring, whatever the token storage, and
+/// its token file.
+pub fn forget_token(
+    keyring_username: &str,
+    file_name: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if let Ok(entry) = Entry::new(&keyring_service(), keyring_username) {
+        match entry.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => warn!("Could not delete the refresh token from the keyring: {}", e),
+        }
+    }
+    token_file::delete(file_name)
+}
 
 // CORREÇÃO: Funções tornadas públicas
-pub fn save_refresh_token(refresh_token: &str) -> Result<(), keyring::Error> {
-    let entry = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME)?;
-    entry.set_password(refresh_token)
+pub fn save_refresh_token(
+    refresh_token: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    save_token(KEYRING_USERNAME, TOKEN_FILE, refresh_token)
 }
 
 pub fn load_refresh_token() -> Option<RefreshToken> {
-    if let Ok(entry) = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME) {
-        if let Ok(token_secret) = entry.get_password() {
-            return Some(RefreshToken::new(token_secret));
+    load_token(KEYRING_USERNAME, TOKEN_FILE).map(RefreshToken::new)
+}
+
+pub fn delete_refresh_token() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if token_storage() == TokenStorage::Keyring {
+        if let Ok(entry) = Entry::new(&keyring_service(), KEYRING_USERNAME) {
+            let _ = entry.delete_password();
         }
     }
-    None
+    token_file::delete(TOKEN_FILE)
 }
 
-pub fn delete_refresh_token() -> Result<(), keyring::Error> {
-    let entry = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME)?;
-    entry.delete_password()
+/// Forgets the login: the refresh token in the keyring, and the token file
+/// along with its key.
+pub fn forget_credentials() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    forget_token(KEYRING_USERNAME, TOKEN_FILE)?;
+    token_file::forget(TOKEN_FILE)
 }
 
-pub async fn authenticate(
-    client_id_str: String,
-) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client_id = ClientId::new(client_id_str);
-    let client_secret = None;
-    let auth_url =
-        AuthUrl::new("https://login.microsoftonline.com/common/oauth2/v2.0/authorize".to_string())?;
-    let token_url = Some(TokenUrl::new(
-        "https://login.microsoftonline.com/common/oauth2/v2.0/token".to_string(),
-    )?);
+/// Login endpoint of the public Microsoft cloud.
+pub const DEFAULT_LOGIN_BASE_URL: &str = "https://login.microsoftonline.com";
+
+/// Tenant signed in to when `tenant_id` isn't set: any work, school or
+/// personal account.
+const DEFAULT_TENANT: &str = "common";
+
+/// The OAuth client for the configured login endpoint (`login_base_url`) and
+/// tenant (`tenant_id`), with the `client_secret` if the app registration
+/// requires one.
+pub fn oauth_client(
+    settings: &Settings,
+) -> Result<BasicClient, Box<dyn std::error::Error + Send + Sync>> {
+    let login_base_url = settings
+        .login_base_url
+        .as_deref()
+        .unwrap_or(DEFAULT_LOGIN_BASE_URL)
+        .trim_end_matches('/');
+    let tenant = settings.tenant_id.as_deref().unwrap_or(DEFAULT_TENANT);
+    let client_id = ClientId::new(settings.client_id.clone());
+    let client_secret = settings.client_secret.clone().map(ClientSecret::new);
+    let auth_url = AuthUrl::new(format!(
+        "{}/{}/oauth2/v2.0/authorize",
+        login_base_url, tenant
+    ))?;
+    let token_url = Some(TokenUrl::new(format!(
+        "{}/{}/oauth2/v2.0/token",
+        login_base_url, tenant
+    ))?);
 
     let redirect_url = RedirectUrl::new("http://localhost:8080".to_string())?;
 
-    let client = BasicClient::new(client_id, client_secret, auth_url, token_url)
-        .set_redirect_uri(redirect_url);
+    Ok(
+        BasicClient::new(client_id, client_secret, auth_url, token_url)
+            .set_redirect_uri(redirect_url),
+    )
+}
+
+/// Graph permission names have to be qualified with the resource on national
+/// clouds; the public cloud accepts them bare.
+fn graph_scope(name: &str) -> Scope {
+    let graph_base_url = crate::api::graph_base_url();
+    if graph_base_url == crate::api::DEFAULT_GRAPH_BASE_URL {
+        Scope::new(name.to_string())
+    } else {
+        Scope::new(format!("{}/{}", graph_base_url, name))
+    }
+}
+
+/// `app_state` key set once the user asked for write access by trying to
+/// change an event.
+const WRITE_ACCESS_KEY: &str = "write_access";
+
+/// Whether write access was asked for, in this session or an earlier one.
+static WRITE_ACCESS_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the login asks for Calendars.ReadWrite: when a feature that changes
+/// events is configured (`rules`, `office_hours`, `book_rooms`) or the user
+/// asked for it by trying to change one. `read_only` turns it off.
+pub fn wants_write(settings: &Settings) -> bool {
+    !settings.read_only.unwrap_or(false)
+        && (WRITE_ACCESS_REQUESTED.load(Ordering::Relaxed)
+            || settings
+                .rules
+                .as_ref()
+                .is_some_and(|rules| !rules.is_empty())
+            || settings.office_hours.is_some()
+            || settings.book_rooms.unwrap_or(false))
+}
+
+/// Reads whether write access was asked for in an earlier session. Called
+/// before the first login.
+pub async fn load_write_access(db_pool: &sqlx::SqlitePool) {
+    if let Ok(Some(_)) = crate::db::get_state(db_pool, WRITE_ACCESS_KEY).await {
+        WRITE_ACCESS_REQUESTED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Asks for write access from the next login on.
+pub async fn request_write_access(db_pool: &sqlx::SqlitePool) {
+    WRITE_ACCESS_REQUESTED.store(true, Ordering::Relaxed);
+    if let Err(e) = crate::db::set_state(db_pool, WRITE_ACCESS_KEY, "1").await {
+        log::error!("Failed to remember the request for write access: {}", e);
+    }
+}
+
+/// Permissions asked for at login when `scopes` isn't set. Calendars.Read,
+/// unless a feature needs to change events (see `wants_write`).
+fn default_scopes(settings: &Settings) -> Vec<&'static str> {
+    let mut scopes = vec!["User.Read"];
+    if wants_write(settings) {
+        scopes.push("Calendars.ReadWrite");
+    } else {
+        scopes.push("Calendars.Read");
+    }
+    if settings.meeting_recordings.unwrap_or(false) {
+        scopes.extend([
+            "OnlineMeetings.Read",
+            "OnlineMeetingRecording.Read.All",
+            "OnlineMeetingTranscript.Read.All",
+        ]);
+    }
+    if settings.dashboard_tasks.unwrap_or(false) {
+        scopes.push("Tasks.Read");
+    }
+    if settings.book_rooms.unwrap_or(false) {
+        scopes.push("User.ReadBasic.All");
+    }
+    scopes
+}
+
+/// The permissions to ask for at login: `scopes` from the config, or the
+/// defaults for the enabled features. offline_access keeps the login.
+fn requested_scopes(settings: &Settings) -> Vec<Scope> {
+    let mut scopes = vec![Scope::new("offline_access".to_string())];
+    match &settings.scopes {
+        // Full URIs and OpenID scopes are passed on as they are
+        Some(names) => scopes.extend(names.iter().map(|name| {
+            if name.contains('/') || matches!(name.as_str(), "openid" | "profile" | "email") {
+                Scope::new(name.clone())
+            } else {
+                graph_scope(name)
+            }
+        })),
+        None => scopes.extend(default_scopes(settings).into_iter().map(graph_scope)),
+    }
+    scopes
+}
+
+/// Whether events can be created, edited and answered. Off with `read_only`,
+/// and when the login granted less than Calendars.ReadWrite, e.g. because it
+/// was never asked for.
+static CAN_WRITE: AtomicBool = AtomicBool::new(true);
+
+pub fn can_write() -> bool {
+    CAN_WRITE.load(Ordering::Relaxed)
+}
+
+/// Notes what a new token allows. Microsoft lists the granted scopes with each
+/// token; when it doesn't, the requested ones are assumed.
+pub fn note_granted_scopes(settings: &Settings, granted: Option<&Vec<Scope>>) {
+    let writes = |scope: &str| scope.contains("Calendars.ReadWrite");
+    let can_write = !settings.read_only.unwrap_or(false)
+        && match granted {
+            Some(scopes) => scopes.iter().any(|s| writes(s.as_str())),
+            None => requested_scopes(settings)
+                .iter()
+                .any(|s| writes(s.as_str())),
+        };
+    if !can_write {
+        info!("Read-only access: Calendars.ReadWrite was not granted.");
+    }
+    CAN_WRITE.store(can_write, Ordering::Relaxed);
+}
+
+/// An access token, and how it was come by.
+pub struct Login {
+    pub access_token: String,
+    /// Logged in in the browser, rather than with the saved login.
+    pub interactive: bool,
+}
+
+/// `app_state` key with the time of the last browser login.
+const LOGGED_IN_AT_KEY: &str = "logged_in_at";
+
+/// Remembers that the user just logged in in the browser.
+pub async fn record_login(db_pool: &sqlx::SqlitePool) {
+    let now = crate::clock::now_utc().to_rfc3339();
+    if let Err(e) = crate::db::set_state(db_pool, LOGGED_IN_AT_KEY, &now).await {
+        log::error!("Failed to save the login time: {}", e);
+    }
+}
+
+/// Days since the last browser login. Logins from before it was recorded
+/// count from now.
+pub async fn login_age_days(db_pool: &sqlx::SqlitePool) -> i64 {
+    let logged_in_at = crate::db::get_state(db_pool, LOGGED_IN_AT_KEY)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|value| chrono::DateTime::parse_from_rfc3339(&value).ok());
+    match logged_in_at {
+        Some(at) => (crate::clock::now_utc() - at.with_timezone(&chrono::Utc)).num_days(),
+        None => {
+            record_login(db_pool).await;
+            0
+        }
+    }
+}
+
+/// Logs in with the saved refresh token, or in the browser when there is
+/// none or it no longer works.
+pub async fn authenticate(
+    settings: &Settings,
+) -> Result<Login, Box<dyn std::error::Error + Send + Sync>> {
+    let client = oauth_client(settings)?;
 
     if let Some(saved_refresh_token) = load_refresh_token() {
-        info!("Attempting to refresh access token from system keyring...");
+        info!("Attempting to refresh the access token with the saved refresh token...");
         let token_result = client
             .exchange_refresh_token(&saved_refresh_token)
-            .request_async(async_http_client)
+            .request_async(crate::http::oauth_http_client)
             .await;
         if let Ok(refreshed_token) = token_result {
             info!("Token refreshed successfully!");
+            note_granted_scopes(settings, refreshed_token.scopes());
             if let Some(new_refresh_token) = refreshed_token.refresh_token() {
                 save_refresh_token(new_refresh_token.secret())?;
             }
-            return Ok(refreshed_token.access_token().secret().clone());
+            return Ok(Login {
+                access_token: refreshed_token.access_token().secret().clone(),
+                interactive: false,
+            });
         } else {
             warn!("Could not refresh token. Deleting old token and starting full login...");
             let _ = delete_refresh_token();
         }
     }
 
+    println!("To continue, please open your browser and log in...");
+    Ok(Login {
+        access_token: browser_login(settings).await?,
+        interactive: true,
+    })
+}
+
+/// Logs in in the browser, waiting for the redirect on localhost:8080, and
+/// saves the new refresh token.
+pub async fn browser_login(
+    settings: &Settings,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let client = oauth_client(settings)?;
     let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
     let (authorize_url, _csrf_state) = client
         .authorize_url(CsrfToken::new_random)
-        .add_scope(Scope::new("offline_access".to_string()))
-        .add_scope(Scope::new("User.Read".to_string()))
-        .add_scope(Scope::new("Calendars.Read".to_string()))
+        .add_scopes(requested_scopes(settings))
         .set_pkce_challenge(pkce_challenge)
         .url();
 
     info!("Open this URL in your browser to log in: {}", authorize_url);
-    println!("To continue, please open your browser and log in...");
     webbrowser::open(authorize_url.as_str())?;
 
-    let listener = TcpListener::bind("127.0.0.1:8080")?;
-    let mut code_option = None;
-    if let Some(Ok(mut stream)) = listener.incoming().next() {
-        let mut reader = BufReader::new(&stream);
-        let mut request_line = String::new();
-        reader.read_line(&mut request_line)?;
-        if let Some(url_part) = request_line.split_whitespace().nth(1) {
-            if url_part.contains("code=") {
-                let full_url = Url::parse(&("http://localhost".to_string() + url_part))?;
-                if let Some((_, value)) = full_url.query_pairs().find(|(key, _)| key == "code") {
-                    code_option = Some(value.into_owned());
-                }
-            }
-        }
-        let message = "Login successful! You can now close this tab.";
-        let response = format!(
-            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
-            message.len(),
-            message
-        );
-        stream.write_all(response.as_bytes())?;
-    }
-
-    if let Some(code) = code_option {
+    if let Some(code) = receive_authorization_code().await? {
         let token_result = client
             .exchange_code(AuthorizationCode::new(code))
             .set_pkce_verifier(pkce_verifier)
-            .request_async(async_http_client)
+            .request_async(crate::http::oauth_http_client)
             .await;
         if let Ok(token) = token_result {
+            note_granted_scopes(settings, token.scopes());
             if let Some(refresh_token) = token.refresh_token() {
-                info!("Saving refresh token to system keyring...");
+                info!("Saving the refresh token...");
                 save_refresh_token(refresh_token.secret())?;
             }
             return Ok(token.access_token().secret().clone());
@@ -120,3 +403,39 @@ pub async fn authenticate(
 
     Err("Authentication failed".into())
 }
+
+/// Waits up to `LOGIN_TIMEOUT` for the login page to redirect to
+/// localhost:8080 and returns the authorization code it brought along, if any.
+pub async fn receive_authorization_code(
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let listener = TcpListener::bind("127.0.0.1:8080").await?;
+    let redirect = async {
+        let (mut stream, _) = listener.accept().await?;
+        let mut request_line = String::new();
+        BufReader::new(&mut stream)
+            .read_line(&mut request_line)
+            .await?;
+        Ok::<_, std::io::Error>((stream, request_line))
+    };
+    let (mut stream, request_line) = tokio::time::timeout(LOGIN_TIMEOUT, redirect)
+        .await
+        .map_err(|_| "The login in the browser timed out")??;
+
+    let mut code_option = None;
+    if let Some(url_part) = request_line.split_whitespace().nth(1) {
+        if url_part.contains("code=") {
+            let full_url = Url::parse(&("http://localhost".to_string() + url_part))?;
+            if let Some((_, value)) = full_url.query_pairs().find(|(key, _)| key == "code") {
+                code_option = Some(value.into_owned());
+            }
+        }
+    }
+    let message = "Login successful! You can now close this tab.";
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
+        message.len(),
+        message
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(code_option)
+}
@@ -0,0 +1,1035 @@
+use crate::api::{self, GraphCalendar, NewEvent};
+use crate::app::display_subject;
+use crate::clock;
+use crate::config::Settings;
+use crate::db;
+use crate::google;
+use crate::office_hours::{next_date_on, OfficeHours};
+use crate::planner::format_utc;
+use crate::review::WeeklyReview;
+use crate::rules::RuleAction;
+use crate::search::{SearchQuery, SearchState};
+use crate::subscriptions;
+use crate::ui::dashboard::format_countdown;
+use crate::ui::text::truncate_to_width;
+use crate::{Command, RulesCommand};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use futures::future::join_all;
+use log::error;
+use sqlx::sqlite::SqlitePool;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Runs a non-interactive subcommand instead of the TUI.
+pub async fn run(
+    command: Command,
+    settings: &Settings,
+    access_token: &str,
+    calendars: &[GraphCalendar],
+    db_pool: &SqlitePool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let writes = matches!(
+        command,
+        Command::Add { .. }
+            | Command::OfficeHours { publish: true }
+            | Command::Rules {
+                command: RulesCommand::Undo { .. }
+            }
+    );
+    if writes && !crate::auth::can_write() {
+        if settings.read_only.unwrap_or(false) {
+            return Err(crate::app::READ_ONLY_MESSAGE.into());
+        }
+        crate::auth::request_write_access(db_pool).await;
+        crate::auth::delete_refresh_token()?;
+        return Err(
+            "This needs write access to your calendars. Run it again to log in and grant it."
+                .into(),
+        );
+    }
+    match command {
+        Command::Review {
+            weeks_ago,
+            markdown,
+            output,
+        } => review(access_token, calendars, weeks_ago, markdown, output).await,
+        Command::Rules { command } => rules(command, access_token, db_pool).await,
+        Command::Add {
+            subject,
+            start,
+            date,
+            from,
+            to,
+            duration,
+            calendar,
+        } => {
+            let times = EventTimes {
+                start: start.as_deref(),
+                date: date.as_deref(),
+                from: from.as_deref(),
+                to: to.as_deref(),
+                duration,
+            };
+            add(access_token, calendars, subject, times, calendar.as_deref()).await
+        }
+        Command::OfficeHours { publish } => office_hours(settings, access_token, publish).await,
+        Command::Agenda {
+            days,
+            sync,
+            calendar,
+        } => {
+            let access_token = sync.then_some(access_token);
+            agenda(
+                settings,
+                access_token,
+                calendars,
+                db_pool,
+                days,
+                calendar.as_deref(),
+            )
+            .await
+        }
+        Command::Next { format, calendar } => {
+            next(settings, calendars, db_pool, &format, calendar.as_deref()).await
+        }
+        Command::Calendars { refresh, json } => {
+            if refresh {
+                let mut calendars = api::list_calendars(access_token).await?;
+                if settings.google.is_some() {
+                    calendars.extend(google::list_calendars().await?);
+                }
+                calendars.extend(subscriptions::configure(
+                    settings.subscriptions.as_deref().unwrap_or_default(),
+                ));
+                db::save_calendars(db_pool, &calendars).await?;
+                list_calendars(settings, &calendars, json)
+            } else {
+                list_calendars(settings, calendars, json)
+            }
+        }
+        Command::Search {
+            text,
+            from,
+            to,
+            server,
+            limit,
+            json,
+        } => {
+            let query = search_query(&text, from.as_deref(), to.as_deref())?;
+            let access_token = server.then_some(access_token);
+            search(&query, access_token, calendars, db_pool, limit, json).await
+        }
+        Command::Busy {
+            date,
+            from,
+            to,
+            user,
+            json,
+        } => {
+            let days = match (date, from, to) {
+                (_, Some(from), Some(to)) => (parse_date(&from)?, parse_date(&to)?),
+                (Some(date), _, _) => (parse_date(&date)?, parse_date(&date)?),
+                _ => (clock::now().date_naive(), clock::now().date_naive()),
+            };
+            busy(settings, access_token, days, user, json).await
+        }
+        Command::Attendance { weeks } => attendance(db_pool, weeks).await,
+        Command::Logout { clear_cache } => logout(clear_cache).await,
+    }
+}
+
+/// What the database remembers about the signed-in account.
+const ACCOUNT_STATE_KEYS: [&str; 3] = ["account", "account_domain", "logged_in_at"];
+
+/// Forgets the login. The cached events stay unless `clear_cache`, but not
+/// who they belonged to, so the next account isn't mistaken for this one.
+pub async fn logout(clear_cache: bool) -> Result<(), Box<dyn Error + Send + Sync>> {
+    crate::auth::forget_credentials()?;
+    google::forget_login();
+
+    let db_path = crate::config::get_db_path();
+    if clear_cache {
+        // SQLite keeps recent writes in the -wal file next to the database
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = db_path.clone().into_os_string();
+            path.push(suffix);
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        println!("Logged out and deleted the cache at {}.", db_path.display());
+        return Ok(());
+    }
+
+    if db_path.exists() {
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+        let db_pool = db::init_db(&db_url).await?;
+        for key in ACCOUNT_STATE_KEYS {
+            db::delete_state(&db_pool, key).await?;
+        }
+        db_pool.close().await;
+    }
+    println!("Logged out. The next start opens the browser to log in again.");
+    Ok(())
+}
+
+/// A calendar's events, from Google or the feed for Google and subscribed
+/// calendars, and from Graph for the others.
+async fn list_events(
+    access_token: &str,
+    calendar_id: &str,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<api::GraphEvent>, Box<dyn Error + Send + Sync>> {
+    if google::is_google_id(calendar_id) {
+        google::list_events(calendar_id, start_date, end_date).await
+    } else if subscriptions::is_subscription_id(calendar_id) {
+        subscriptions::list_events(calendar_id, start_date, end_date).await
+    } else {
+        api::list_events(access_token, calendar_id, start_date, end_date).await
+    }
+}
+
+/// Local midnight at the start of `date`, in UTC.
+pub fn to_utc(date: NaiveDate) -> chrono::DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_local_timezone(Local)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+async fn review(
+    access_token: &str,
+    calendars: &[GraphCalendar],
+    weeks_ago: u32,
+    markdown: bool,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut week_start = clock::now().date_naive() - Duration::weeks(weeks_ago as i64);
+    while week_start.weekday() != chrono::Weekday::Mon {
+        week_start = week_start.pred_opt().unwrap();
+    }
+    let (start_date, end_date) = (to_utc(week_start), to_utc(week_start + Duration::days(7)));
+
+    let results = join_all(
+        calendars
+            .iter()
+            .map(|c| list_events(access_token, &c.id, start_date, end_date)),
+    )
+    .await;
+    let mut events = Vec::new();
+    for result in results {
+        match result {
+            Ok(calendar_events) => events.extend(calendar_events),
+            Err(e) => error!("Error fetching events for the review: {}", e),
+        }
+    }
+
+    let me = api::get_me(access_token).await.ok();
+    let own_email = me.and_then(|me| me.mail.or(me.user_principal_name));
+    let review = WeeklyReview::from_events(&events, week_start, own_email.as_deref());
+    let report = if markdown {
+        review.to_markdown()
+    } else {
+        review.to_text()
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, report)?;
+            println!("Review written to {}", path.display());
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
+async fn rules(
+    command: RulesCommand,
+    access_token: &str,
+    db_pool: &SqlitePool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match command {
+        RulesCommand::Log { limit } => {
+            let entries = db::get_rule_actions(db_pool, limit).await?;
+            if entries.is_empty() {
+                println!("No invites have been answered by rules yet.");
+            }
+            for entry in entries {
+                println!(
+                    "{:>4}  {}  {:<9}  {}{}",
+                    entry.id,
+                    entry.applied_at,
+                    entry.action,
+                    entry.subject,
+                    if entry.undone { "  (undone)" } else { "" }
+                );
+                println!("      rule: {}", entry.rule);
+            }
+            Ok(())
+        }
+        RulesCommand::Undo { id, response } => {
+            let action = RuleAction::from_name(&response)
+                .ok_or_else(|| format!("Unknown response \"{}\"", response))?;
+            let entry = db::get_rule_action(db_pool, id)
+                .await?
+                .ok_or_else(|| format!("No rule log entry with ID {}", id))?;
+            if entry.undone {
+                return Err(format!("Entry {} was already undone", id).into());
+            }
+            // Outlook removes declined invites from the calendar, so there is
+            // no event left to answer again
+            if entry.action == RuleAction::Decline.name() {
+                return Err(format!(
+                    "Entry {} declined \"{}\", which can't be undone; ask the organizer to send the invite again",
+                    id, entry.subject
+                )
+                .into());
+            }
+            api::respond_to_event(access_token, &entry.event_id, action.endpoint(), true).await?;
+            db::mark_rule_action_undone(db_pool, id).await?;
+            println!(
+                "\"{}\": {} replaced with {}",
+                entry.subject,
+                entry.action,
+                action.name()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// When an event made with `add` takes place, as given on the command line:
+/// `--start` and `--duration`, or `--date`, `--from` and `--to`.
+struct EventTimes<'a> {
+    start: Option<&'a str>,
+    date: Option<&'a str>,
+    from: Option<&'a str>,
+    to: Option<&'a str>,
+    duration: u32,
+}
+
+fn to_local(time: NaiveDateTime) -> Result<DateTime<Local>, String> {
+    time.and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| format!("{} doesn't exist in the local time zone", time))
+}
+
+impl EventTimes<'_> {
+    fn resolve(&self) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+        let start = match (self.start, self.from) {
+            (Some(start), _) => NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M")
+                .map_err(|_| format!("Invalid start \"{}\", use \"YYYY-MM-DD HH:MM\"", start))?,
+            (None, Some(from)) => {
+                let date = match self.date {
+                    Some(date) => parse_date(date)?,
+                    None => clock::now().date_naive(),
+                };
+                date.and_time(parse_clock_time(from)?)
+            }
+            (None, None) => return Err("Give a start time with --start or --from".to_string()),
+        };
+        let end = match self.to {
+            Some(to) => start.date().and_time(parse_clock_time(to)?),
+            None => start + Duration::minutes(self.duration as i64),
+        };
+        if end <= start {
+            return Err(format!(
+                "The event would end at {}, before it starts",
+                end.format("%H:%M")
+            ));
+        }
+        Ok((to_local(start)?, to_local(end)?))
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date \"{}\", use \"YYYY-MM-DD\"", date))
+}
+
+fn parse_clock_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| format!("Invalid time \"{}\", use \"HH:MM\"", time))
+}
+
+async fn add(
+    access_token: &str,
+    calendars: &[GraphCalendar],
+    subject: String,
+    times: EventTimes<'_>,
+    calendar: Option<&str>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (start, end) = times.resolve()?;
+    let calendar = match calendar {
+        Some(entry) => Some(
+            calendars
+                .iter()
+                .find(|c| c.name.to_lowercase() == entry.to_lowercase() || c.id == entry)
+                .ok_or_else(|| format!("No calendar named \"{}\"", entry))?,
+        ),
+        None => None,
+    };
+    if calendar.is_some_and(|c| !api::is_graph_id(&c.id)) {
+        return Err("Google and subscribed calendars are read-only here".into());
+    }
+
+    let new_event = NewEvent {
+        subject,
+        start: format_utc(start),
+        end: format_utc(end),
+        attendees: Vec::new(),
+        rooms: Vec::new(),
+    };
+    api::create_event(access_token, &new_event, calendar.map(|c| c.id.as_str())).await?;
+    println!(
+        "Created \"{}\" on {} {}-{}{}",
+        new_event.subject,
+        start.format("%a %d %b"),
+        start.format("%H:%M"),
+        end.format("%H:%M"),
+        calendar
+            .map(|c| format!(" in {}", c.name))
+            .unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Prints the attendance rate of each recurring series over the last `weeks`
+/// weeks, from the meetings marked with `m`.
+pub async fn attendance(
+    db_pool: &SqlitePool,
+    weeks: u32,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let since = to_utc(clock::now().date_naive() - Duration::weeks(weeks.max(1) as i64));
+    let series =
+        db::attendance_by_series(db_pool, &since.format("%Y-%m-%dT%H:%M:%S").to_string()).await?;
+    if series.is_empty() {
+        println!(
+            "No meetings marked in the last {} weeks; press `m` on a past meeting to mark it.",
+            weeks
+        );
+        return Ok(());
+    }
+    println!("Attendance over the last {} weeks:", weeks);
+    for entry in series {
+        let name = match entry.series_master_id {
+            Some(_) => entry.subject,
+            None => "One-off meetings".to_string(),
+        };
+        println!(
+            "{:>5}%  {:>7}  {}",
+            entry.attended * 100 / entry.marked.max(1),
+            format!("{}/{}", entry.attended, entry.marked),
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Prints the calendars with their IDs, Outlook colors, owners and which is
+/// the default, plus the color set in `[calendar_overrides]`, if any.
+pub fn list_calendars(
+    settings: &Settings,
+    calendars: &[GraphCalendar],
+    json: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if calendars.is_empty() && !json {
+        println!("No calendars cached yet; run with --refresh or start the app once.");
+        return Ok(());
+    }
+    let override_color = |calendar: &GraphCalendar| {
+        settings
+            .calendar_overrides
+            .as_ref()?
+            .get(&calendar.name.to_lowercase())?
+            .color
+            .clone()
+    };
+    // Graph leaves the color empty for calendars that never had one picked
+    let hex_color =
+        |calendar: &GraphCalendar| calendar.hex_color.clone().filter(|color| !color.is_empty());
+
+    if json {
+        let value: Vec<_> = calendars
+            .iter()
+            .map(|calendar| {
+                serde_json::json!({
+                    "id": calendar.id,
+                    "name": calendar.name,
+                    "color": hex_color(calendar),
+                    "override_color": override_color(calendar),
+                    "owner": calendar.owner.as_ref().map(|o| o.address.as_str()),
+                    "owner_name": calendar.owner.as_ref().map(|o| o.name.as_str()),
+                    "is_default": calendar.is_default_calendar.unwrap_or(false),
+                    "can_share": calendar.can_share.unwrap_or(false),
+                    "excluded_from_all": settings.excluded_from_all(calendar),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    for calendar in calendars {
+        println!(
+            "{}{}",
+            calendar.name,
+            if calendar.is_default_calendar == Some(true) {
+                "  (default)"
+            } else {
+                ""
+            }
+        );
+        println!("    id:     {}", calendar.id);
+        if let Some(owner) = &calendar.owner {
+            println!("    owner:  {} <{}>", owner.name, owner.address);
+        }
+        let color = match (hex_color(calendar), override_color(calendar)) {
+            (Some(color), Some(custom)) => Some(format!("{} (shown as {})", color, custom)),
+            (None, Some(custom)) => Some(format!("{} (from Settings.toml)", custom)),
+            (color, None) => color,
+        };
+        if let Some(color) = color {
+            println!("    color:  {}", color);
+        }
+    }
+    Ok(())
+}
+
+/// The search of the `search` subcommand, from its arguments.
+pub fn search_query(
+    text: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<SearchQuery, String> {
+    Ok(SearchQuery {
+        text: text.trim().to_string(),
+        from: from.map(parse_date).transpose()?,
+        to: to.map(parse_date).transpose()?,
+    })
+}
+
+/// Prints the cached events matching `query`, most recent first. With an
+/// access token, Graph is searched too and its matches that aren't cached
+/// are added.
+pub async fn search(
+    query: &SearchQuery,
+    access_token: Option<&str>,
+    calendars: &[GraphCalendar],
+    db_pool: &SqlitePool,
+    limit: usize,
+    json: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if query.text.is_empty() {
+        return Err("Enter something to search for".into());
+    }
+    let mut state = SearchState::new(query.clone(), access_token.is_none());
+    state.merge_cached_results(
+        db::search_events(db_pool, &query.text, query.start(), query.end(), limit).await?,
+    );
+    if let Some(access_token) = access_token {
+        let result =
+            api::search_events(access_token, &query.text, query.start(), query.end(), limit)
+                .await
+                .map_err(|e| e.to_string());
+        state.merge_server_results(result);
+    }
+    if let Some(e) = &state.server_error {
+        eprintln!("WARNING: Could not search Graph, showing the cache: {}", e);
+    }
+
+    let ids: Vec<String> = state.results.iter().map(|r| r.event.id.clone()).collect();
+    let event_calendars = db::get_event_calendars(db_pool, &ids).await?;
+    let calendar_of = |event_id: &str| {
+        let calendar_id = event_calendars.get(event_id)?;
+        calendars.iter().find(|c| &c.id == calendar_id)
+    };
+
+    if json {
+        let value: Vec<_> = state
+            .results
+            .iter()
+            .map(|result| {
+                let event = &result.event;
+                let calendar = calendar_of(&event.id);
+                let time =
+                    |value: &str| crate::app::parse_event_time(value).map(|t| t.to_rfc3339());
+                serde_json::json!({
+                    "id": event.id,
+                    "ical_uid": event.ical_uid,
+                    "subject": event.subject,
+                    "start": time(&event.start.date_time),
+                    "end": time(&event.end.date_time),
+                    "is_all_day": event.is_all_day.unwrap_or(false),
+                    "calendar": calendar.map(|c| c.name.as_str()),
+                    "calendar_id": calendar.map(|c| c.id.as_str()),
+                    "source": result.source.label(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    if state.results.is_empty() {
+        println!("No events match \"{}\".", query.text);
+        return Ok(());
+    }
+    for result in &state.results {
+        let event = &result.event;
+        let start = crate::app::parse_event_time(&event.start.date_time);
+        let end = crate::app::parse_event_time(&event.end.date_time);
+        let when = match (start, end) {
+            (Some(start), _) if event.is_all_day == Some(true) => {
+                format!("{}  all day    ", start.format("%a %Y-%m-%d"))
+            }
+            (Some(start), Some(end)) => format!(
+                "{}  {}-{}",
+                start.format("%a %Y-%m-%d"),
+                start.format("%H:%M"),
+                end.format("%H:%M")
+            ),
+            _ => event.start.date_time.clone(),
+        };
+        let calendar = calendar_of(&event.id)
+            .map(|c| c.name.as_str())
+            .unwrap_or("(not cached)");
+        println!("{}  {}", when, event.subject);
+        println!("    {}  {}", calendar, event.id);
+    }
+    Ok(())
+}
+
+/// `getSchedule` answers for at most this many days at once.
+const MAX_SCHEDULE_DAYS: i64 = 62;
+
+/// A stretch of the working day that is free, or taken with the given status.
+struct Availability {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    status: String,
+}
+
+/// Splits the working hours of each day from `first_day` to `last_day` into
+/// free and busy blocks. Overlapping items merge, and a block mixing
+/// statuses is plain "busy".
+fn availability(
+    items: &[api::ScheduleItem],
+    (first_day, last_day): (NaiveDate, NaiveDate),
+    (workday_start, workday_end): (NaiveTime, NaiveTime),
+) -> Vec<Availability> {
+    let mut taken: Vec<(DateTime<Local>, DateTime<Local>, &str)> = items
+        .iter()
+        .filter(|item| item.status != "free")
+        .filter_map(|item| {
+            let start = crate::app::parse_event_time(&item.start.date_time)?;
+            let end = crate::app::parse_event_time(&item.end.date_time)?;
+            Some((start, end, item.status.as_str()))
+        })
+        .collect();
+    taken.sort_by_key(|(start, _, _)| *start);
+
+    let mut blocks = Vec::new();
+    let mut day = first_day;
+    while day <= last_day {
+        let window = (
+            to_local(day.and_time(workday_start)),
+            to_local(day.and_time(workday_end)),
+        );
+        day += Duration::days(1);
+        let (Ok(day_start), Ok(day_end)) = window else {
+            continue;
+        };
+
+        let mut cursor = day_start;
+        let mut busy: Option<Availability> = None;
+        for (start, end, status) in &taken {
+            let (start, end) = ((*start).max(day_start), (*end).min(day_end));
+            if start >= end {
+                continue;
+            }
+            if let Some(block) = busy.as_mut().filter(|block| start <= block.end) {
+                block.end = block.end.max(end);
+                if block.status != *status {
+                    block.status = "busy".to_string();
+                }
+                continue;
+            }
+            if let Some(block) = busy.take() {
+                cursor = block.end;
+                blocks.push(block);
+            }
+            if start > cursor {
+                blocks.push(Availability {
+                    start: cursor,
+                    end: start,
+                    status: "free".to_string(),
+                });
+            }
+            busy = Some(Availability {
+                start,
+                end,
+                status: status.to_string(),
+            });
+        }
+        if let Some(block) = busy.take() {
+            cursor = block.end;
+            blocks.push(block);
+        }
+        if cursor < day_end {
+            blocks.push(Availability {
+                start: cursor,
+                end: day_end,
+                status: "free".to_string(),
+            });
+        }
+    }
+    blocks
+}
+
+/// Prints the free and busy blocks within the working hours of the given
+/// days, for the signed-in account or the colleagues in `users`.
+async fn busy(
+    settings: &Settings,
+    access_token: &str,
+    (first_day, last_day): (NaiveDate, NaiveDate),
+    users: Vec<String>,
+    json: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if last_day < first_day {
+        return Err(format!("{} is before {}", last_day, first_day).into());
+    }
+    if (last_day - first_day).num_days() >= MAX_SCHEDULE_DAYS {
+        return Err(format!("Ask for at most {} days at once", MAX_SCHEDULE_DAYS).into());
+    }
+    let users = if users.is_empty() {
+        let me = api::get_me(access_token).await?;
+        vec![me
+            .mail
+            .or(me.user_principal_name)
+            .ok_or("The signed-in account has no email address")?]
+    } else {
+        users
+    };
+
+    let (start_date, end_date) = (to_utc(first_day), to_utc(last_day + Duration::days(1)));
+    let schedules = api::get_schedule(access_token, &users, start_date, end_date).await?;
+    let workday = settings.workday_hours();
+
+    if json {
+        let value: Vec<_> = schedules
+            .iter()
+            .map(|schedule| {
+                if let Some(error) = &schedule.error {
+                    return serde_json::json!({ "email": schedule.schedule_id, "error": error.message });
+                }
+                let blocks: Vec<_> =
+                    availability(&schedule.schedule_items, (first_day, last_day), workday)
+                        .into_iter()
+                        .map(|block| {
+                            serde_json::json!({
+                                "start": block.start.to_rfc3339(),
+                                "end": block.end.to_rfc3339(),
+                                "status": block.status,
+                            })
+                        })
+                        .collect();
+                serde_json::json!({ "email": schedule.schedule_id, "blocks": blocks })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    for (i, schedule) in schedules.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", schedule.schedule_id);
+        if let Some(error) = &schedule.error {
+            println!("  Could not read the schedule: {}", error.message);
+            continue;
+        }
+        let mut day = None;
+        for block in availability(&schedule.schedule_items, (first_day, last_day), workday) {
+            if day != Some(block.start.date_naive()) {
+                day = Some(block.start.date_naive());
+                println!("  {}", block.start.format("%A, %d %B"));
+            }
+            println!(
+                "    {}-{}  {}",
+                block.start.format("%H:%M"),
+                block.end.format("%H:%M"),
+                block.status
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The cached events of `calendars` overlapping the range, with their local
+/// start and end, by start time. Declined meetings are left out, and a
+/// meeting in several calendars is listed once.
+async fn cached_events(
+    db_pool: &SqlitePool,
+    calendars: &[&GraphCalendar],
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Local>, DateTime<Local>, api::GraphEvent)>, Box<dyn Error + Send + Sync>>
+{
+    let mut events = Vec::new();
+    for calendar in calendars {
+        events.extend(db::get_events(db_pool, &calendar.id).await?);
+    }
+    let mut seen = std::collections::HashSet::new();
+    let mut events: Vec<_> = events
+        .into_iter()
+        .filter(|e| {
+            e.response_status
+                .as_ref()
+                .is_none_or(|r| r.response != "declined")
+        })
+        .filter_map(|e| {
+            let start = crate::app::parse_event_time(&e.start.date_time)?;
+            let end = crate::app::parse_event_time(&e.end.date_time)?;
+            (end > start_date && start < end_date).then_some((start, end, e))
+        })
+        .filter(|(_, _, e)| seen.insert(e.correlation_id().to_string()))
+        .collect();
+    events.sort_by_key(|(start, _, _)| *start);
+    Ok(events)
+}
+
+/// Calendars picked with `--calendar`, or those "All Calendars" shows.
+fn shown_calendars<'a>(
+    settings: &Settings,
+    calendars: &'a [GraphCalendar],
+    calendar: Option<&str>,
+) -> Result<Vec<&'a GraphCalendar>, String> {
+    let calendars: Vec<&GraphCalendar> = match calendar {
+        Some(entry) => vec![calendars
+            .iter()
+            .find(|c| c.name.to_lowercase() == entry.to_lowercase() || c.id == entry)
+            .ok_or_else(|| format!("No calendar named \"{}\"", entry))?],
+        None => calendars
+            .iter()
+            .filter(|c| !settings.excluded_from_all(c))
+            .collect(),
+    };
+    if calendars.is_empty() {
+        return Err(
+            "No calendars cached yet; start the app once or run `agenda --sync`".to_string(),
+        );
+    }
+    Ok(calendars)
+}
+
+/// Escapes text for Pango markup, which Waybar renders its text and tooltip
+/// as, so subjects like "Q&A <draft>" show up as they are.
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Prints the meeting in progress or coming up next today, from the cache,
+/// as a line of text or as a Waybar custom module's JSON (`--format waybar`).
+/// The Waybar tooltip lists the rest of today's agenda.
+pub async fn next(
+    settings: &Settings,
+    calendars: &[GraphCalendar],
+    db_pool: &SqlitePool,
+    format: &str,
+    calendar: Option<&str>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let waybar = match format {
+        "text" => false,
+        "waybar" => true,
+        _ => return Err(format!("Unknown format \"{}\", use text or waybar", format).into()),
+    };
+    let calendars = shown_calendars(settings, calendars, calendar)?;
+    let now = clock::now();
+    let today = now.date_naive();
+    let (start_date, end_date) = (now.with_timezone(&Utc), to_utc(today + Duration::days(1)));
+    // All-day events would otherwise be running all day
+    let events: Vec<_> = cached_events(db_pool, &calendars, start_date, end_date)
+        .await?
+        .into_iter()
+        .filter(|(start, end, _)| *end - *start < Duration::hours(24))
+        .collect();
+
+    let soon_minutes = settings.notification_minutes_before.unwrap_or(15);
+    let busy_only = settings.busy_only.unwrap_or(false);
+    let (text, class) = match events.first() {
+        Some((start, end, event)) if *start <= now => (
+            format!(
+                "Now: {} ({} left)",
+                truncate_to_width(display_subject(&event.subject, busy_only), 40),
+                format_countdown(*end - now)
+            ),
+            "current",
+        ),
+        Some((start, _, event)) => (
+            format!(
+                "{} {} (in {})",
+                start.format("%H:%M"),
+                truncate_to_width(display_subject(&event.subject, busy_only), 40),
+                format_countdown(*start - now)
+            ),
+            if *start - now <= Duration::minutes(soon_minutes as i64) {
+                "soon"
+            } else {
+                "upcoming"
+            },
+        ),
+        None => ("No more meetings today".to_string(), "free"),
+    };
+
+    if !waybar {
+        println!("{}", text);
+        return Ok(());
+    }
+    let tooltip = if events.is_empty() {
+        "Nothing else today".to_string()
+    } else {
+        events
+            .iter()
+            .map(|(start, end, event)| {
+                format!(
+                    "{}-{}  {}",
+                    start.format("%H:%M"),
+                    end.format("%H:%M"),
+                    display_subject(&event.subject, busy_only)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let value = serde_json::json!({
+        "text": escape_markup(&text),
+        "tooltip": escape_markup(&tooltip),
+        "class": class,
+    });
+    println!("{}", value);
+    Ok(())
+}
+
+/// Prints the events of `days` days from today, from the cache. With an
+/// access token, those days are synced from Graph first.
+pub async fn agenda(
+    settings: &Settings,
+    access_token: Option<&str>,
+    calendars: &[GraphCalendar],
+    db_pool: &SqlitePool,
+    days: u32,
+    calendar: Option<&str>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let calendars = shown_calendars(settings, calendars, calendar)?;
+    let today = clock::now().date_naive();
+    let last_day = today + Duration::days(days.max(1) as i64 - 1);
+    let (start_date, end_date) = (to_utc(today), to_utc(last_day + Duration::days(1)));
+
+    if let Some(access_token) = access_token {
+        let results = join_all(
+            calendars
+                .iter()
+                .map(|c| list_events(access_token, &c.id, start_date, end_date)),
+        )
+        .await;
+        for (calendar, result) in calendars.iter().zip(results) {
+            match result {
+                Ok(mut events) => {
+                    if settings.minimal_mode.unwrap_or(false) {
+                        for event in &mut events {
+                            event.body = None;
+                        }
+                    }
+                    db::save_events_with_range(
+                        db_pool,
+                        &events,
+                        &calendar.id,
+                        &start_date,
+                        &end_date,
+                    )
+                    .await?;
+                }
+                Err(e) => eprintln!(
+                    "WARNING: Could not sync {}, showing the cache: {}",
+                    calendar.name, e
+                ),
+            }
+        }
+    }
+
+    let events = cached_events(db_pool, &calendars, start_date, end_date).await?;
+    if events.is_empty() {
+        println!("Nothing on the agenda.");
+        return Ok(());
+    }
+    let mut day = None;
+    for (start, end, event) in events {
+        // Events that started before today are listed under today
+        let event_day = start.date_naive().max(today);
+        if day != Some(event_day) {
+            if day.is_some() {
+                println!();
+            }
+            println!("{}", event_day.format("%A, %d %B"));
+            day = Some(event_day);
+        }
+        let time = if event.is_all_day == Some(true) {
+            "all day".to_string()
+        } else {
+            format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+        };
+        let location = event
+            .location
+            .as_ref()
+            .map(|l| l.display_name.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| format!(" ({})", l))
+            .unwrap_or_default();
+        println!("  {:<11}  {}{}", time, event.subject, location);
+    }
+    Ok(())
+}
+
+async fn office_hours(
+    settings: &Settings,
+    access_token: &str,
+    publish: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(config) = &settings.office_hours else {
+        return Err("No [office_hours] section in Settings.toml".into());
+    };
+    let office_hours = OfficeHours::from_config(config)?;
+    // Recurring events follow the local wall clock, DST changes included
+    let time_zone = iana_time_zone::get_timezone().unwrap_or_else(|_| "UTC".to_string());
+    print!("{}", office_hours.snippet(&time_zone));
+
+    if publish {
+        let today = clock::now().date_naive();
+        for block in &office_hours.blocks {
+            let date = next_date_on(block.weekday, today);
+            api::create_weekly_event(
+                access_token,
+                &office_hours.subject,
+                date.and_time(block.start),
+                date.and_time(block.end),
+                &time_zone,
+            )
+            .await?;
+            println!(
+                "Published {} {}-{}, weekly from {}",
+                block.weekday,
+                block.start.format("%H:%M"),
+                block.end.format("%H:%M"),
+                date
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! The current time as the app sees it. Everything that depends on "now"
+//! (which event is selected, notifications, the footer clock, "in 5 min")
+//! asks here instead of the system clock, so `--fake-now` can pin it for
+//! demos and screenshots, and the logic can be driven with a fixed clock.
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use std::sync::OnceLock;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at one moment.
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+impl FixedClock {
+    /// Parses a local time like "2025-06-03 09:30", as given to `--fake-now`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M")
+            .ok()
+            .and_then(|naive| naive.and_local_timezone(Local).earliest())
+            .map(|time| Self(time.with_timezone(&Utc)))
+            .ok_or_else(|| format!("\"{}\" is not a time like \"2025-06-03 09:30\"", text))
+    }
+}
+
+static CLOCK: OnceLock<Box<dyn Clock>> = OnceLock::new();
+
+#[cfg(test)]
+thread_local! {
+    static TEST_CLOCK: std::cell::RefCell<Option<Box<dyn Clock>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Replaces the system clock. Must be called before anything asks for the time.
+pub fn set_clock(clock: impl Clock + 'static) {
+    let _ = CLOCK.set(Box::new(clock));
+}
+
+/// Sets the clock for the current thread only. Tests run in parallel on
+/// their own threads, so each can pin its own time without touching `CLOCK`.
+#[cfg(test)]
+pub fn set_thread_clock(clock: impl Clock + 'static) {
+    TEST_CLOCK.with(|test_clock| *test_clock.borrow_mut() = Some(Box::new(clock)));
+}
+
+pub fn now_utc() -> DateTime<Utc> {
+    #[cfg(test)]
+    if let Some(now) =
+        TEST_CLOCK.with(|test_clock| test_clock.borrow().as_ref().map(|clock| clock.now()))
+    {
+        return now;
+    }
+    CLOCK.get_or_init(|| Box::new(SystemClock)).now()
+}
+
+pub fn now() -> DateTime<Local> {
+    now_utc().with_timezone(&Local)
+}
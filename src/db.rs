@@ -1,39 +1,383 @@
-use crate::api::{DateTimeTimeZone, GraphCalendar, GraphEvent, ItemBody};
-use chrono::{DateTime, Utc};
-use sqlx::{sqlite::SqlitePool, Row};
+use crate::api::{
+    DateTimeTimeZone, EmailAddress, GraphCalendar, GraphEvent, ItemBody, NewEvent, ResponseStatus,
+};
+use crate::clock;
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::{info, warn};
+use sqlx::{sqlite::SqlitePool, Row, SqliteConnection};
+use std::collections::HashMap;
 use std::error::Error;
 
 pub async fn init_db(db_url: &str) -> Result<SqlitePool, Box<dyn Error + Send + Sync>> {
     let pool = SqlitePool::connect(db_url).await?;
+    migrate(&pool).await?;
+    Ok(pool)
+}
 
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS calendars (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            can_share BOOLEAN
-        );",
-    )
-    .execute(&pool)
-    .await?;
+/// One step of a schema migration.
+enum Step {
+    Sql(&'static str),
+    /// `ALTER TABLE ... ADD COLUMN`, skipped when the column is already there.
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+    /// The full-text index over `events`, skipped when it exists.
+    SearchIndex,
+}
 
-    sqlx::query(
-        "CREATE TABLE IF NOT EXISTS events (
+/// Schema migrations, oldest first. The database's `user_version` counts how
+/// many have been applied. Databases from before the versioning start at 0
+/// with any of the first ten already in place, so their steps must be
+/// idempotent; later migrations can be plain SQL.
+const MIGRATIONS: &[&[Step]] = &[
+    // 1: calendars and cached events
+    &[
+        Step::Sql(
+            "CREATE TABLE IF NOT EXISTS calendars (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                can_share BOOLEAN
+            );",
+        ),
+        Step::Sql(
+            "CREATE TABLE IF NOT EXISTS events (
+                id TEXT PRIMARY KEY,
+                subject TEXT NOT NULL,
+                start_time TEXT NOT NULL,
+                start_time_zone TEXT,
+                end_time TEXT NOT NULL,
+                end_time_zone TEXT,
+                body_preview TEXT,
+                attendees TEXT,
+                calendar_id TEXT NOT NULL,
+                FOREIGN KEY(calendar_id) REFERENCES calendars(id)
+            );",
+        ),
+    ],
+    // 2: what the invite rules did, for `rules log` and `rules undo`
+    &[Step::Sql(
+        "CREATE TABLE IF NOT EXISTS rule_actions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_id TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            rule TEXT NOT NULL,
+            action TEXT NOT NULL,
+            applied_at TEXT NOT NULL,
+            undone BOOLEAN NOT NULL DEFAULT 0
+        );",
+    )],
+    // 3: time blocks planned locally, never sent to Graph
+    &[Step::Sql(
+        "CREATE TABLE IF NOT EXISTS local_events (
             id TEXT PRIMARY KEY,
             subject TEXT NOT NULL,
             start_time TEXT NOT NULL,
-            start_time_zone TEXT,
-            end_time TEXT NOT NULL,
-            end_time_zone TEXT,
-            body_preview TEXT,
-            attendees TEXT,
+            end_time TEXT NOT NULL
+        );",
+    )],
+    // 4: Outlook links, and offline search
+    &[
+        Step::AddColumn {
+            table: "events",
+            column: "web_link",
+            definition: "TEXT",
+        },
+        Step::SearchIndex,
+    ],
+    // 5: categories, as a JSON array
+    &[Step::AddColumn {
+        table: "events",
+        column: "categories",
+        definition: "TEXT",
+    }],
+    // 6: small bits of UI state kept between sessions
+    &[Step::Sql(
+        "CREATE TABLE IF NOT EXISTS app_state (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )],
+    // 7: when events were created and last changed
+    &[
+        Step::AddColumn {
+            table: "events",
+            column: "created_at",
+            definition: "TEXT",
+        },
+        Step::AddColumn {
+            table: "events",
+            column: "modified_at",
+            definition: "TEXT",
+        },
+    ],
+    // 8: where each calendar's incremental sync left off, per date window
+    &[Step::Sql(
+        "CREATE TABLE IF NOT EXISTS sync_state (
             calendar_id TEXT NOT NULL,
-            FOREIGN KEY(calendar_id) REFERENCES calendars(id)
+            range_start TEXT NOT NULL,
+            range_end TEXT NOT NULL,
+            delta_link TEXT NOT NULL,
+            PRIMARY KEY (calendar_id, range_start, range_end)
+        );",
+    )],
+    // 9: which of the instances sharing this database syncs and notifies
+    &[Step::Sql(
+        "CREATE TABLE IF NOT EXISTS instance_lease (
+            name TEXT PRIMARY KEY,
+            holder TEXT NOT NULL,
+            expires_at TEXT NOT NULL
+        );",
+    )],
+    // 10: iCalendar UIDs
+    &[Step::AddColumn {
+        table: "events",
+        column: "ical_uid",
+        definition: "TEXT",
+    }],
+    // 11: enough metadata for the views to work from the cache alone
+    &[
+        Step::Sql("ALTER TABLE events ADD COLUMN is_all_day BOOLEAN;"),
+        Step::Sql("ALTER TABLE events ADD COLUMN show_as TEXT;"),
+        Step::Sql("ALTER TABLE events ADD COLUMN response_status TEXT;"),
+        Step::Sql("ALTER TABLE events ADD COLUMN online_meeting_url TEXT;"),
+        Step::Sql("ALTER TABLE events ADD COLUMN series_master_id TEXT;"),
+    ],
+    // 12: when each calendar's date window was last synced
+    &[Step::Sql(
+        "ALTER TABLE sync_state ADD COLUMN synced_at TEXT;",
+    )],
+    // 13: importance, for alarms on high-importance events
+    &[Step::AddColumn {
+        table: "events",
+        column: "importance",
+        definition: "TEXT",
+    }],
+    // 14: meetings marked as attended or skipped
+    &[Step::Sql(
+        "CREATE TABLE IF NOT EXISTS attendance (
+            event_key TEXT PRIMARY KEY,
+            series_master_id TEXT,
+            subject TEXT NOT NULL,
+            start_time TEXT NOT NULL,
+            attended BOOLEAN NOT NULL,
+            marked_at TEXT NOT NULL
         );",
+    )],
+    // 15: calendar colors, owners and which one is the default
+    &[
+        Step::Sql("ALTER TABLE calendars ADD COLUMN hex_color TEXT;"),
+        Step::Sql("ALTER TABLE calendars ADD COLUMN is_default BOOLEAN;"),
+        Step::Sql("ALTER TABLE calendars ADD COLUMN owner_name TEXT;"),
+        Step::Sql("ALTER TABLE calendars ADD COLUMN owner_address TEXT;"),
+    ],
+    // 16: Outlook reminders, the lead time of notifications
+    &[
+        Step::Sql("ALTER TABLE events ADD COLUMN is_reminder_on BOOLEAN;"),
+        Step::Sql("ALTER TABLE events ADD COLUMN reminder_minutes INTEGER;"),
+        // Delta syncs only bring changed events; one full sync fills these
+        // and the importance of migration 13 for the events cached before
+        Step::Sql("DELETE FROM sync_state;"),
+    ],
+    // 17: full syncs spread over several refreshes, and when each event was
+    // last synced, to drop those such a sync no longer brought
+    &[
+        Step::Sql("ALTER TABLE sync_state ADD COLUMN full_sync_started_at TEXT;"),
+        Step::Sql("ALTER TABLE events ADD COLUMN synced_at TEXT;"),
+    ],
+];
+
+/// Brings the schema up to date, one migration per transaction.
+async fn migrate(pool: &SqlitePool) -> Result<(), Box<dyn Error + Send + Sync>> {
+    migrate_to(pool, MIGRATIONS.len()).await
+}
+
+/// Applies the migrations up to schema version `target`.
+async fn migrate_to(pool: &SqlitePool, target: usize) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    let version = version.max(0) as usize;
+    if version > MIGRATIONS.len() {
+        warn!(
+            "Database schema version {} is newer than this build knows ({}).",
+            version,
+            MIGRATIONS.len()
+        );
+        return Ok(());
+    }
+
+    for (index, steps) in MIGRATIONS.iter().enumerate().take(target).skip(version) {
+        let mut tx = pool.begin().await?;
+        for step in *steps {
+            match step {
+                Step::Sql(statement) => {
+                    sqlx::query(statement).execute(&mut *tx).await?;
+                }
+                Step::AddColumn {
+                    table,
+                    column,
+                    definition,
+                } => add_column_if_missing(&mut tx, table, column, definition).await?,
+                Step::SearchIndex => init_search_index(&mut tx).await?,
+            }
+        }
+        // The pragma can't take a bound parameter
+        sqlx::query(&format!("PRAGMA user_version = {}", index + 1))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        info!("Migrated the database to schema version {}", index + 1);
+    }
+    Ok(())
+}
+
+/// Takes or renews the sync lease for `holder`. Succeeds when nobody holds it,
+/// `holder` already does, or the current holder let it expire (crashed, or
+/// its machine is asleep). Returns whether `holder` holds the lease now.
+pub async fn acquire_lease(
+    pool: &SqlitePool,
+    holder: &str,
+    ttl: chrono::Duration,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    // The system clock, even with --fake-now: the instances sharing the
+    // lease must agree on when it expires
+    let now = Utc::now();
+    let timestamp = |time: DateTime<Utc>| time.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let result = sqlx::query(
+        "INSERT INTO instance_lease (name, holder, expires_at) VALUES ('sync', ?, ?)
+         ON CONFLICT(name) DO UPDATE SET holder = excluded.holder, expires_at = excluded.expires_at
+         WHERE instance_lease.holder = excluded.holder OR instance_lease.expires_at < ?",
     )
-    .execute(&pool)
+    .bind(holder)
+    .bind(timestamp(now + ttl))
+    .bind(timestamp(now))
+    .execute(pool)
     .await?;
+    Ok(result.rows_affected() > 0)
+}
 
-    Ok(pool)
+/// Gives up the sync lease so another instance can take over right away.
+pub async fn release_lease(
+    pool: &SqlitePool,
+    holder: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("DELETE FROM instance_lease WHERE name = 'sync' AND holder = ?")
+        .bind(holder)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_state(
+    pool: &SqlitePool,
+    key: &str,
+) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    let row = sqlx::query("SELECT value FROM app_state WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|row| row.get("value")))
+}
+
+pub async fn set_state(
+    pool: &SqlitePool,
+    key: &str,
+    value: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("INSERT OR REPLACE INTO app_state (key, value) VALUES (?, ?)")
+        .bind(key)
+        .bind(value)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_state(
+    pool: &SqlitePool,
+    key: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("DELETE FROM app_state WHERE key = ?")
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Full-text index over the cached events, kept in sync with `events` by
+/// triggers. `INSERT OR REPLACE` doesn't fire delete triggers, so stale
+/// entries are dropped before each insert instead.
+async fn init_search_index(
+    conn: &mut SqliteConnection,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let exists = sqlx::query("SELECT name FROM sqlite_master WHERE name = 'events_fts'")
+        .fetch_optional(&mut *conn)
+        .await?
+        .is_some();
+    if exists {
+        return Ok(());
+    }
+
+    let statements = [
+        "CREATE VIRTUAL TABLE events_fts USING fts5(
+            event_id UNINDEXED,
+            subject,
+            body,
+            attendees
+        );",
+        "CREATE TRIGGER events_fts_before_insert BEFORE INSERT ON events BEGIN
+            DELETE FROM events_fts WHERE event_id = NEW.id;
+        END;",
+        "CREATE TRIGGER events_fts_after_insert AFTER INSERT ON events BEGIN
+            INSERT INTO events_fts (event_id, subject, body, attendees)
+            VALUES (NEW.id, NEW.subject, NEW.body_preview, NEW.attendees);
+        END;",
+        "CREATE TRIGGER events_fts_after_delete AFTER DELETE ON events BEGIN
+            DELETE FROM events_fts WHERE event_id = OLD.id;
+        END;",
+        // Index what was cached before the index existed
+        "INSERT INTO events_fts (event_id, subject, body, attendees)
+            SELECT id, subject, body_preview, attendees FROM events;",
+    ];
+    for statement in statements {
+        sqlx::query(statement).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Turns typed words into an FTS5 query matching all of them, each as a
+/// prefix ("budg rev" finds "Budget review"). Quoting keeps FTS5 operators
+/// and punctuation in the input from being interpreted.
+fn fts_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Adds a column to a table created by an older version of the app.
+async fn add_column_if_missing(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let columns = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(&mut *conn)
+        .await?;
+    let exists = columns
+        .iter()
+        .any(|row| row.get::<String, _>("name") == column);
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
 }
 
 pub async fn save_calendars(
@@ -41,12 +385,21 @@ pub async fn save_calendars(
     calendars: &[GraphCalendar],
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     for cal in calendars {
-        sqlx::query("INSERT OR REPLACE INTO calendars (id, name, can_share) VALUES (?, ?, ?)")
-            .bind(&cal.id)
-            .bind(&cal.name)
-            .bind(cal.can_share)
-            .execute(pool)
-            .await?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO calendars (
+                id, name, can_share, hex_color, is_default, owner_name, owner_address
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&cal.id)
+        .bind(&cal.name)
+        .bind(cal.can_share)
+        // Empty rather than NULL, which marks calendars cached before migration 15
+        .bind(cal.hex_color.as_deref().unwrap_or_default())
+        .bind(cal.is_default_calendar)
+        .bind(cal.owner.as_ref().map(|o| &o.name))
+        .bind(cal.owner.as_ref().map(|o| &o.address))
+        .execute(pool)
+        .await?;
     }
     Ok(())
 }
@@ -54,21 +407,77 @@ pub async fn save_calendars(
 pub async fn get_calendars(
     pool: &SqlitePool,
 ) -> Result<Vec<GraphCalendar>, Box<dyn Error + Send + Sync>> {
-    let rows = sqlx::query("SELECT id, name, can_share FROM calendars")
+    let rows = sqlx::query("SELECT * FROM calendars")
         .fetch_all(pool)
         .await?;
 
     let mut calendars = Vec::new();
     for row in rows {
+        let owner_address: Option<String> = row.get("owner_address");
         calendars.push(GraphCalendar {
             id: row.get("id"),
             name: row.get("name"),
             can_share: row.get("can_share"),
+            hex_color: row.get("hex_color"),
+            is_default_calendar: row.get("is_default"),
+            owner: owner_address.map(|address| EmailAddress {
+                name: row
+                    .get::<Option<String>, _>("owner_name")
+                    .unwrap_or_default(),
+                address,
+            }),
         });
     }
     Ok(calendars)
 }
 
+/// Inserts or replaces one event of a calendar.
+async fn insert_event(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    event: &GraphEvent,
+    calendar_id: &str,
+) -> Result<(), sqlx::Error> {
+    let attendees_json = serde_json::to_string(&event.attendees).unwrap_or_default();
+    let body_content = event.body.as_ref().map(|b| b.content.clone());
+    let categories_json = serde_json::to_string(&event.categories).unwrap_or_default();
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO events (
+            id, subject, start_time, start_time_zone, end_time, end_time_zone, 
+            body_preview, attendees, calendar_id, web_link, categories,
+            created_at, modified_at, ical_uid, is_all_day, show_as,
+            response_status, online_meeting_url, series_master_id, importance,
+            is_reminder_on, reminder_minutes, synced_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&event.id)
+    .bind(&event.subject)
+    .bind(&event.start.date_time)
+    .bind(&event.start._time_zone)
+    .bind(&event.end.date_time)
+    .bind(&event.end._time_zone)
+    .bind(body_content)
+    .bind(attendees_json)
+    .bind(calendar_id)
+    .bind(&event.web_link)
+    .bind(categories_json)
+    .bind(&event.created_date_time)
+    .bind(&event.last_modified_date_time)
+    .bind(&event.ical_uid)
+    .bind(event.is_all_day)
+    .bind(&event.show_as)
+    .bind(event.response_status.as_ref().map(|s| &s.response))
+    .bind(event.join_url())
+    .bind(&event.series_master_id)
+    .bind(&event.importance)
+    .bind(event.is_reminder_on)
+    .bind(event.reminder_minutes_before_start)
+    .bind(clock::now_utc().to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
 pub async fn save_events_with_range(
     pool: &SqlitePool,
     events: &[GraphEvent],
@@ -90,32 +499,156 @@ pub async fn save_events_with_range(
         .await?;
 
     for event in events {
-        let attendees_json = serde_json::to_string(&event.attendees).unwrap_or_default();
-        let body_content = event.body.as_ref().map(|b| b.content.clone());
-
-        sqlx::query(
-            "INSERT OR REPLACE INTO events (
-                id, subject, start_time, start_time_zone, end_time, end_time_zone, 
-                body_preview, attendees, calendar_id
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind(&event.id)
-        .bind(&event.subject)
-        .bind(&event.start.date_time)
-        .bind(&event.start._time_zone)
-        .bind(&event.end.date_time)
-        .bind(&event.end._time_zone)
-        .bind(body_content)
-        .bind(attendees_json)
-        .bind(calendar_id)
-        .execute(&mut *tx)
-        .await?;
+        insert_event(&mut tx, event, calendar_id).await?;
     }
 
     tx.commit().await?;
     Ok(())
 }
 
+/// Applies the changes of an incremental sync: changed events are inserted
+/// or replaced, removed ones deleted.
+pub async fn apply_event_changes(
+    pool: &SqlitePool,
+    calendar_id: &str,
+    changed: &[GraphEvent],
+    removed: &[String],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut tx = pool.begin().await?;
+    for event in changed {
+        insert_event(&mut tx, event, calendar_id).await?;
+    }
+    for id in removed {
+        sqlx::query("DELETE FROM events WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// The Graph delta link that continues the sync of a calendar's date window.
+pub async fn get_delta_link(
+    pool: &SqlitePool,
+    calendar_id: &str,
+    start_range: &DateTime<Utc>,
+    end_range: &DateTime<Utc>,
+) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    let row = sqlx::query(
+        "SELECT delta_link FROM sync_state
+         WHERE calendar_id = ? AND range_start = ? AND range_end = ?",
+    )
+    .bind(calendar_id)
+    .bind(start_range.to_rfc3339())
+    .bind(end_range.to_rfc3339())
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| row.get("delta_link")))
+}
+
+/// Saves where the sync of a calendar's date window left off. While a full
+/// sync is spread over several refreshes, `full_sync_started` is when its
+/// first page was stored.
+pub async fn save_delta_link(
+    pool: &SqlitePool,
+    calendar_id: &str,
+    start_range: &DateTime<Utc>,
+    end_range: &DateTime<Utc>,
+    delta_link: &str,
+    full_sync_started: Option<&DateTime<Utc>>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO sync_state (calendar_id, range_start, range_end, delta_link, synced_at, full_sync_started_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(calendar_id)
+    .bind(start_range.to_rfc3339())
+    .bind(end_range.to_rfc3339())
+    .bind(delta_link)
+    .bind(clock::now_utc().to_rfc3339())
+    .bind(full_sync_started.map(|t| t.to_rfc3339()))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// When the full sync of a calendar's date window that is still in progress
+/// started, or `None` if there is none.
+pub async fn full_sync_started(
+    pool: &SqlitePool,
+    calendar_id: &str,
+    start_range: &DateTime<Utc>,
+    end_range: &DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, Box<dyn Error + Send + Sync>> {
+    let started: Option<String> = sqlx::query_scalar(
+        "SELECT full_sync_started_at FROM sync_state
+         WHERE calendar_id = ? AND range_start = ? AND range_end = ?",
+    )
+    .bind(calendar_id)
+    .bind(start_range.to_rfc3339())
+    .bind(end_range.to_rfc3339())
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+    Ok(started
+        .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+        .map(|t| t.with_timezone(&Utc)))
+}
+
+/// Deletes the cached events of a calendar's date window that weren't synced
+/// since `since`: the ones a full sync that started then didn't bring.
+pub async fn delete_events_synced_before(
+    pool: &SqlitePool,
+    calendar_id: &str,
+    start_range: &DateTime<Utc>,
+    end_range: &DateTime<Utc>,
+    since: &DateTime<Utc>,
+) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    // The same window as `save_events_with_range` replaces
+    let result = sqlx::query(
+        "DELETE FROM events
+         WHERE calendar_id = ? AND start_time >= ? AND start_time <= ?
+           AND (synced_at IS NULL OR synced_at < ?)",
+    )
+    .bind(calendar_id)
+    .bind(start_range.to_rfc3339())
+    .bind(end_range.to_rfc3339())
+    .bind(since.to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// When the calendars' date window was last synced: the oldest of their
+/// sync times, or `None` if one of them never was.
+pub async fn last_synced(
+    pool: &SqlitePool,
+    calendar_ids: &[String],
+    start_range: &DateTime<Utc>,
+    end_range: &DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, Box<dyn Error + Send + Sync>> {
+    let mut oldest: Option<DateTime<Utc>> = None;
+    for calendar_id in calendar_ids {
+        let synced_at: Option<String> = sqlx::query_scalar(
+            "SELECT synced_at FROM sync_state
+             WHERE calendar_id = ? AND range_start = ? AND range_end = ?",
+        )
+        .bind(calendar_id)
+        .bind(start_range.to_rfc3339())
+        .bind(end_range.to_rfc3339())
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+        let Some(synced_at) = synced_at.and_then(|t| DateTime::parse_from_rfc3339(&t).ok()) else {
+            return Ok(None);
+        };
+        let synced_at = synced_at.with_timezone(&Utc);
+        oldest = Some(oldest.map_or(synced_at, |oldest| oldest.min(synced_at)));
+    }
+    Ok(oldest)
+}
+
 pub async fn get_events(
     pool: &SqlitePool,
     calendar_id: &str,
@@ -125,31 +658,526 @@ pub async fn get_events(
         .fetch_all(pool)
         .await?;
 
+    Ok(rows.iter().map(event_from_row).collect())
+}
+
+/// Cached events of a calendar overlapping the range, for lookups that don't
+/// need the whole cache.
+pub async fn get_events_between(
+    pool: &SqlitePool,
+    calendar_id: &str,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+) -> Result<Vec<GraphEvent>, Box<dyn Error + Send + Sync>> {
+    // Graph's times are UTC without an offset, so they compare as text
+    let format = |time: &DateTime<Utc>| time.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let rows = sqlx::query(
+        "SELECT * FROM events WHERE calendar_id = ? AND start_time < ? AND end_time > ?",
+    )
+    .bind(calendar_id)
+    .bind(format(end))
+    .bind(format(start))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(event_from_row).collect())
+}
+
+fn event_from_row(row: &sqlx::sqlite::SqliteRow) -> GraphEvent {
+    let start_time: String = row.get("start_time");
+    let start_time_zone: String = row.get("start_time_zone");
+    let end_time: String = row.get("end_time");
+    let end_time_zone: String = row.get("end_time_zone");
+    let body_preview: Option<String> = row.get("body_preview");
+    let attendees_json: String = row.get("attendees");
+    let categories_json: Option<String> = row.get("categories");
+
+    GraphEvent {
+        id: row.get("id"),
+        subject: row.get("subject"),
+        start: DateTimeTimeZone {
+            date_time: start_time,
+            _time_zone: start_time_zone,
+        },
+        end: DateTimeTimeZone {
+            date_time: end_time,
+            _time_zone: end_time_zone,
+        },
+        body: body_preview.map(|c| ItemBody { content: c }),
+        attendees: serde_json::from_str(&attendees_json).unwrap_or_default(),
+        location: None,
+        organizer: None,
+        response_status: row
+            .get::<Option<String>, _>("response_status")
+            .map(|response| ResponseStatus { response }),
+        web_link: row.get("web_link"),
+        categories: categories_json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        created_date_time: row.get("created_at"),
+        last_modified_date_time: row.get("modified_at"),
+        ical_uid: row.get("ical_uid"),
+        is_all_day: row.get("is_all_day"),
+        show_as: row.get("show_as"),
+        online_meeting_url: row.get("online_meeting_url"),
+        online_meeting: None,
+        series_master_id: row.get("series_master_id"),
+        importance: row.get("importance"),
+        is_reminder_on: row.get("is_reminder_on"),
+        reminder_minutes_before_start: row.get("reminder_minutes"),
+    }
+}
+
+/// Every category used by a cached event, sorted by name.
+pub async fn get_categories(
+    pool: &SqlitePool,
+) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let rows = sqlx::query(
+        "SELECT DISTINCT value FROM events, json_each(events.categories)
+         WHERE json_valid(events.categories) ORDER BY value COLLATE NOCASE",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(|row| row.get("value")).collect())
+}
+
+/// Cached events of all calendars matching every word of `text` in their
+/// subject, description or attendees, most recent first. The range bounds
+/// compare against the stored (UTC) start times.
+pub async fn search_events(
+    pool: &SqlitePool,
+    text: &str,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    limit: usize,
+) -> Result<Vec<GraphEvent>, Box<dyn Error + Send + Sync>> {
+    let format = |time: DateTime<Utc>| time.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let rows = sqlx::query(
+        "SELECT events.* FROM events_fts
+         JOIN events ON events.id = events_fts.event_id
+         WHERE events_fts MATCH ?
+           AND (? IS NULL OR events.start_time >= ?)
+           AND (? IS NULL OR events.start_time < ?)
+         ORDER BY events.start_time DESC
+         LIMIT ?",
+    )
+    .bind(fts_query(text))
+    .bind(start.map(format))
+    .bind(start.map(format))
+    .bind(end.map(format))
+    .bind(end.map(format))
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(event_from_row).collect())
+}
+
+/// The calendar of each of the given events that is cached, by event ID.
+pub async fn get_event_calendars(
+    pool: &SqlitePool,
+    event_ids: &[String],
+) -> Result<HashMap<String, String>, Box<dyn Error + Send + Sync>> {
+    let rows = sqlx::query(
+        "SELECT id, calendar_id FROM events WHERE id IN (SELECT value FROM json_each(?))",
+    )
+    .bind(serde_json::to_string(event_ids)?)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .iter()
+        .map(|row| (row.get("id"), row.get("calendar_id")))
+        .collect())
+}
+
+pub async fn save_local_event(
+    pool: &SqlitePool,
+    event: &NewEvent,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("INSERT INTO local_events (id, subject, start_time, end_time) VALUES (?, ?, ?, ?)")
+        // The system clock keeps the IDs unique under --fake-now
+        .bind(format!("local-{}", Utc::now().timestamp_millis()))
+        .bind(&event.subject)
+        .bind(&event.start)
+        .bind(&event.end)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_local_events(
+    pool: &SqlitePool,
+) -> Result<Vec<GraphEvent>, Box<dyn Error + Send + Sync>> {
+    let rows = sqlx::query("SELECT * FROM local_events")
+        .fetch_all(pool)
+        .await?;
+
     let mut events = Vec::new();
     for row in rows {
-        let start_time: String = row.get("start_time");
-        let start_time_zone: String = row.get("start_time_zone");
-        let end_time: String = row.get("end_time");
-        let end_time_zone: String = row.get("end_time_zone");
-        let body_preview: Option<String> = row.get("body_preview");
-        let attendees_json: String = row.get("attendees");
-
         events.push(GraphEvent {
             id: row.get("id"),
             subject: row.get("subject"),
             start: DateTimeTimeZone {
-                date_time: start_time,
-                _time_zone: start_time_zone,
+                date_time: row.get("start_time"),
+                _time_zone: "UTC".to_string(),
             },
             end: DateTimeTimeZone {
-                date_time: end_time,
-                _time_zone: end_time_zone,
+                date_time: row.get("end_time"),
+                _time_zone: "UTC".to_string(),
             },
-            body: body_preview.map(|c| ItemBody { content: c }),
-            attendees: serde_json::from_str(&attendees_json).unwrap_or_default(),
+            body: None,
+            attendees: Vec::new(),
             location: None,
             organizer: None,
+            response_status: None,
+            web_link: None,
+            categories: Vec::new(),
+            created_date_time: None,
+            last_modified_date_time: None,
+            ical_uid: None,
+            is_all_day: Some(false),
+            show_as: Some("busy".to_string()),
+            online_meeting_url: None,
+            online_meeting: None,
+            series_master_id: None,
+            importance: None,
+            is_reminder_on: None,
+            reminder_minutes_before_start: None,
         });
     }
     Ok(events)
 }
+
+/// One invite answered by a `[[rules]]` entry.
+pub struct RuleActionEntry {
+    pub id: i64,
+    pub event_id: String,
+    pub subject: String,
+    pub rule: String,
+    pub action: String,
+    pub applied_at: String,
+    pub undone: bool,
+}
+
+pub async fn log_rule_action(
+    pool: &SqlitePool,
+    event_id: &str,
+    subject: &str,
+    rule: &str,
+    action: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query(
+        "INSERT INTO rule_actions (event_id, subject, rule, action, applied_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(event_id)
+    .bind(subject)
+    .bind(rule)
+    .bind(action)
+    .bind(clock::now().format("%Y-%m-%d %H:%M").to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Whether a rule has already answered this event. Undone entries count too,
+/// so an undo isn't immediately redone by the next sync.
+pub async fn rule_action_exists(
+    pool: &SqlitePool,
+    event_id: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let row = sqlx::query("SELECT COUNT(*) AS count FROM rule_actions WHERE event_id = ?")
+        .bind(event_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(row.get::<i64, _>("count") > 0)
+}
+
+fn rule_action_from_row(row: &sqlx::sqlite::SqliteRow) -> RuleActionEntry {
+    RuleActionEntry {
+        id: row.get("id"),
+        event_id: row.get("event_id"),
+        subject: row.get("subject"),
+        rule: row.get("rule"),
+        action: row.get("action"),
+        applied_at: row.get("applied_at"),
+        undone: row.get("undone"),
+    }
+}
+
+pub async fn get_rule_actions(
+    pool: &SqlitePool,
+    limit: u32,
+) -> Result<Vec<RuleActionEntry>, Box<dyn Error + Send + Sync>> {
+    let rows = sqlx::query("SELECT * FROM rule_actions ORDER BY id DESC LIMIT ?")
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().map(rule_action_from_row).collect())
+}
+
+pub async fn get_rule_action(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<Option<RuleActionEntry>, Box<dyn Error + Send + Sync>> {
+    let row = sqlx::query("SELECT * FROM rule_actions WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.as_ref().map(rule_action_from_row))
+}
+
+pub async fn mark_rule_action_undone(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sqlx::query("UPDATE rule_actions SET undone = 1 WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Whether each marked meeting was attended, by correlation ID.
+pub async fn get_attendance(
+    pool: &SqlitePool,
+) -> Result<HashMap<String, bool>, Box<dyn Error + Send + Sync>> {
+    let rows = sqlx::query("SELECT event_key, attended FROM attendance")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| (row.get("event_key"), row.get("attended")))
+        .collect())
+}
+
+/// Marks a meeting as attended or skipped, or clears the mark with `None`.
+pub async fn set_attendance(
+    pool: &SqlitePool,
+    event: &GraphEvent,
+    attended: Option<bool>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(attended) = attended else {
+        sqlx::query("DELETE FROM attendance WHERE event_key = ?")
+            .bind(event.correlation_id())
+            .execute(pool)
+            .await?;
+        return Ok(());
+    };
+    sqlx::query(
+        "INSERT OR REPLACE INTO attendance
+            (event_key, series_master_id, subject, start_time, attended, marked_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(event.correlation_id())
+    .bind(&event.series_master_id)
+    .bind(&event.subject)
+    .bind(&event.start.date_time)
+    .bind(attended)
+    .bind(clock::now_utc().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Attendance of one recurring series, or of all one-off meetings together
+/// (`series_master_id` is `None`).
+pub struct SeriesAttendance {
+    pub series_master_id: Option<String>,
+    /// Subject of the most recent marked occurrence
+    pub subject: String,
+    pub attended: i64,
+    pub marked: i64,
+}
+
+/// Attendance per recurring series of the meetings starting after `since`
+/// (compared with the stored UTC start times), plus one entry for one-off
+/// meetings. Least attended first.
+pub async fn attendance_by_series(
+    pool: &SqlitePool,
+    since: &str,
+) -> Result<Vec<SeriesAttendance>, Box<dyn Error + Send + Sync>> {
+    // SQLite takes the bare `subject` from the row with the latest start
+    let rows = sqlx::query(
+        "SELECT series_master_id, subject, MAX(start_time) AS last_start,
+                SUM(attended) AS attended, COUNT(*) AS marked
+         FROM attendance WHERE start_time >= ?
+         GROUP BY series_master_id
+         ORDER BY CAST(SUM(attended) AS REAL) / COUNT(*), COUNT(*) DESC",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .iter()
+        .map(|row| SeriesAttendance {
+            series_master_id: row.get("series_master_id"),
+            subject: row.get("subject"),
+            attended: row.get("attended"),
+            marked: row.get("marked"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// One connection, so every query sees the same in-memory database.
+    async fn memory_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_only_events_overlapping_the_range() {
+        let pool = memory_pool().await;
+        migrate(&pool).await.unwrap();
+        sqlx::query("INSERT INTO calendars (id, name) VALUES ('cal', 'Work')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (id, start, end) in [
+            (
+                "before",
+                "2025-06-03T07:00:00.0000000",
+                "2025-06-03T08:00:00.0000000",
+            ),
+            (
+                "during",
+                "2025-06-03T08:30:00.0000000",
+                "2025-06-03T09:30:00.0000000",
+            ),
+            (
+                "after",
+                "2025-06-03T10:00:00.0000000",
+                "2025-06-03T11:00:00.0000000",
+            ),
+        ] {
+            sqlx::query(
+                "INSERT INTO events (id, subject, start_time, end_time, attendees, calendar_id)
+                 VALUES (?, ?, ?, ?, '[]', 'cal')",
+            )
+            .bind(id)
+            .bind(id)
+            .bind(start)
+            .bind(end)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let at = |hour| Utc.with_ymd_and_hms(2025, 6, 3, hour, 0, 0).unwrap();
+
+        let events = get_events_between(&pool, "cal", &at(9), &at(10))
+            .await
+            .unwrap();
+
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["during"]);
+    }
+
+    #[tokio::test]
+    async fn a_finished_full_sync_drops_the_events_it_did_not_bring() {
+        let pool = memory_pool().await;
+        migrate(&pool).await.unwrap();
+        sqlx::query("INSERT INTO calendars (id, name) VALUES ('cal', 'Work')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let at = |day| Utc.with_ymd_and_hms(2025, 6, day, 9, 0, 0).unwrap();
+        for (id, start, synced_at) in [
+            ("deleted", "2025-06-03T09:00:00.0000000", None),
+            ("stale", "2025-06-04T09:00:00.0000000", Some(at(2))),
+            ("first round", "2025-06-05T09:00:00.0000000", Some(at(10))),
+            ("last round", "2025-06-06T09:00:00.0000000", Some(at(11))),
+            ("outside", "2025-07-03T09:00:00.0000000", None),
+        ] {
+            sqlx::query(
+                "INSERT INTO events (id, subject, start_time, end_time, attendees, calendar_id, synced_at)
+                 VALUES (?, ?, ?, ?, '[]', 'cal', ?)",
+            )
+            .bind(id)
+            .bind(id)
+            .bind(start)
+            .bind(start)
+            .bind(synced_at.map(|t| t.to_rfc3339()))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let removed = delete_events_synced_before(&pool, "cal", &at(1), &at(30), &at(10))
+            .await
+            .unwrap();
+
+        assert_eq!(removed, 2);
+        let mut ids: Vec<String> = get_events(&pool, "cal")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["first round", "last round", "outside"]);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_start_of_a_full_sync_until_its_last_page() {
+        let pool = memory_pool().await;
+        migrate(&pool).await.unwrap();
+        let at = |day| Utc.with_ymd_and_hms(2025, 6, day, 0, 0, 0).unwrap();
+
+        save_delta_link(&pool, "cal", &at(1), &at(30), "next", Some(&at(10)))
+            .await
+            .unwrap();
+        let started = full_sync_started(&pool, "cal", &at(1), &at(30)).await;
+        assert_eq!(started.unwrap(), Some(at(10)));
+
+        save_delta_link(&pool, "cal", &at(1), &at(30), "delta", None)
+            .await
+            .unwrap();
+        let started = full_sync_started(&pool, "cal", &at(1), &at(30)).await;
+        assert_eq!(started.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn migrates_a_cache_from_version_14() {
+        let pool = memory_pool().await;
+        migrate_to(&pool, 14).await.unwrap();
+        sqlx::query("INSERT INTO calendars (id, name, can_share) VALUES ('cal', 'Work', 1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO events (id, subject, start_time, end_time, attendees, calendar_id)
+             VALUES ('event', 'Standup', '2025-06-03T09:00:00', '2025-06-03T09:15:00', '[]', 'cal')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        migrate(&pool).await.unwrap();
+
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(version as usize, MIGRATIONS.len());
+        let calendars = get_calendars(&pool).await.unwrap();
+        assert_eq!(calendars.len(), 1);
+        assert!(calendars[0].hex_color.is_none());
+        assert_eq!(get_events(&pool, "cal").await.unwrap().len(), 1);
+
+        // Listing the calendars again fills in the new columns
+        let mut calendar = calendars[0].clone();
+        calendar.hex_color = Some("#a4c2f4".to_string());
+        save_calendars(&pool, &[calendar]).await.unwrap();
+        let calendars = get_calendars(&pool).await.unwrap();
+        assert_eq!(calendars[0].hex_color.as_deref(), Some("#a4c2f4"));
+        assert_eq!(get_events(&pool, "cal").await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,43 @@
+//! Daylight saving time changes. Events right before, during or after the
+//! clocks change are easy to misread, especially when they were set up in a
+//! time zone that switches on another date.
+
+use crate::api::GraphEvent;
+use crate::app::parse_event_time;
+use chrono::{DateTime, Duration, Local};
+
+/// How close to a change an event has to be to get flagged.
+const MARGIN_HOURS: i64 = 12;
+
+/// The moment the local UTC offset changes between `from` and `to`, to the
+/// minute. Assumes there is at most one change in between.
+pub fn transition_between(from: DateTime<Local>, to: DateTime<Local>) -> Option<DateTime<Local>> {
+    let offset = |time: DateTime<Local>| time.offset().local_minus_utc();
+    if offset(from) == offset(to) {
+        return None;
+    }
+    let (mut low, mut high) = (from, to);
+    while high - low > Duration::minutes(1) {
+        let middle = low + (high - low) / 2;
+        if offset(middle) == offset(low) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(high)
+}
+
+/// The local DST change during or within `MARGIN_HOURS` of an event, if any.
+/// All-day and multi-day events are left alone.
+pub fn nearby_transition(event: &GraphEvent) -> Option<DateTime<Local>> {
+    let start = parse_event_time(&event.start.date_time)?;
+    let end = parse_event_time(&event.end.date_time)?;
+    if end - start >= Duration::hours(24) {
+        return None;
+    }
+    transition_between(
+        start - Duration::hours(MARGIN_HOURS),
+        end + Duration::hours(MARGIN_HOURS),
+    )
+}
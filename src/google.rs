@@ -0,0 +1,430 @@
+//! Google Calendar, read-only, next to the Microsoft calendars. The calendars
+//! of the account set up in `[google]` are listed with IDs starting with
+//! "google:", and their events are converted to the Graph shape so the cache,
+//! the views and the notifications treat them like any other. Each sync
+//! fetches the whole date window again; Google's sync tokens don't work with
+//! a window.
+
+use crate::api::{
+    Attendee, DateTimeTimeZone, EmailAddress, GraphCalendar, GraphEvent, ItemBody, Location,
+    Organizer, ResponseStatus,
+};
+use crate::config::GoogleConfig;
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{info, warn};
+use oauth2::basic::BasicClient;
+use oauth2::{
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl,
+    RefreshToken, Scope, TokenResponse, TokenUrl,
+};
+use serde::Deserialize;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Start of the IDs of Google calendars and events, so they never mix with
+/// Graph's.
+pub const ID_PREFIX: &str = "google:";
+
+const API_URL: &str = "https://www.googleapis.com/calendar/v3";
+const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const SCOPE: &str = "https://www.googleapis.com/auth/calendar.readonly";
+const KEYRING_USERNAME: &str = "google_refresh_token";
+/// Token file of the Google login where the keyring isn't used.
+const TOKEN_FILE: &str = "google_token.enc";
+
+/// Most events asked for per page; Google allows up to 2500.
+const PAGE_SIZE: &str = "2500";
+
+static CONFIG: OnceLock<GoogleConfig> = OnceLock::new();
+
+/// The access token and when it stops working.
+static ACCESS_TOKEN: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+/// Uses the Google account set up in `[google]`. Must be called before the
+/// first login; later calls are ignored.
+pub fn configure(config: &GoogleConfig) {
+    let _ = CONFIG.set(config.clone());
+}
+
+/// Whether a calendar or event came from Google.
+pub fn is_google_id(id: &str) -> bool {
+    id.starts_with(ID_PREFIX)
+}
+
+fn config() -> Result<&'static GoogleConfig, Error> {
+    CONFIG
+        .get()
+        .ok_or_else(|| "No [google] section in Settings.toml".into())
+}
+
+fn oauth_client(config: &GoogleConfig) -> Result<BasicClient, Error> {
+    Ok(BasicClient::new(
+        ClientId::new(config.client_id.clone()),
+        Some(ClientSecret::new(config.client_secret.clone())),
+        AuthUrl::new(AUTH_URL.to_string())?,
+        Some(TokenUrl::new(TOKEN_URL.to_string())?),
+    )
+    .set_redirect_uri(RedirectUrl::new("http://localhost:8080".to_string())?))
+}
+
+/// Forgets the Google login, for `logout`.
+pub fn forget_login() {
+    if let Err(e) = crate::auth::forget_token(KEYRING_USERNAME, TOKEN_FILE) {
+        warn!("Could not delete the Google login: {}", e);
+    }
+}
+
+fn keep_access_token(token: &impl TokenResponse<oauth2::basic::BasicTokenType>) -> String {
+    let access_token = token.access_token().secret().clone();
+    // Renewed a minute early, so it doesn't run out in the middle of a sync
+    let lifetime = token
+        .expires_in()
+        .unwrap_or(Duration::from_secs(3600))
+        .saturating_sub(Duration::from_secs(60));
+    *ACCESS_TOKEN.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some((access_token.clone(), Instant::now() + lifetime));
+    access_token
+}
+
+/// A valid access token, renewed with the saved login when needed. Never
+/// opens the browser; see `login` for that.
+pub async fn access_token() -> Result<String, Error> {
+    if let Some((token, expires)) = ACCESS_TOKEN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        if Instant::now() < expires {
+            return Ok(token);
+        }
+    }
+    let refresh_token =
+        crate::auth::load_token(KEYRING_USERNAME, TOKEN_FILE).ok_or("Not logged in to Google")?;
+    let token = oauth_client(config()?)?
+        .exchange_refresh_token(&RefreshToken::new(refresh_token))
+        .request_async(crate::http::oauth_http_client)
+        .await
+        .map_err(|e| format!("Google turned down the saved login: {}", e))?;
+    Ok(keep_access_token(&token))
+}
+
+/// Logs in to Google with the saved login, or in the browser when there is
+/// none or it no longer works.
+pub async fn login() -> Result<(), Error> {
+    match access_token().await {
+        Ok(_) => return Ok(()),
+        Err(e) => info!("Logging in to Google in the browser: {}", e),
+    }
+    let client = oauth_client(config()?)?;
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    // Google only hands out a refresh token with offline access, and only
+    // at the first consent unless asked again
+    let (authorize_url, _csrf_state) = client
+        .authorize_url(CsrfToken::new_random)
+        .add_scope(Scope::new(SCOPE.to_string()))
+        .add_extra_param("access_type", "offline")
+        .add_extra_param("prompt", "consent")
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    println!("To add your Google calendars, log in to Google in the browser...");
+    info!(
+        "Open this URL in your browser to log in to Google: {}",
+        authorize_url
+    );
+    webbrowser::open(authorize_url.as_str())?;
+
+    let code = crate::auth::receive_authorization_code()
+        .await?
+        .ok_or("Google login failed")?;
+    let token = client
+        .exchange_code(AuthorizationCode::new(code))
+        .set_pkce_verifier(pkce_verifier)
+        .request_async(crate::http::oauth_http_client)
+        .await
+        .map_err(|e| format!("Google login failed: {}", e))?;
+    if let Some(refresh_token) = token.refresh_token() {
+        if let Err(e) =
+            crate::auth::save_token(KEYRING_USERNAME, TOKEN_FILE, refresh_token.secret())
+        {
+            warn!("Could not save the Google login: {}", e);
+        }
+    }
+    keep_access_token(&token);
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarListEntry {
+    id: String,
+    summary: String,
+    summary_override: Option<String>,
+    background_color: Option<String>,
+    #[serde(default)]
+    primary: bool,
+    access_role: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarListResponse {
+    #[serde(default)]
+    items: Vec<CalendarListEntry>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventTime {
+    /// All-day events have a date, the others a date and time.
+    date: Option<String>,
+    date_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Person {
+    email: Option<String>,
+    display_name: Option<String>,
+    response_status: Option<String>,
+    #[serde(rename = "self", default)]
+    is_self: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Event {
+    id: String,
+    status: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    start: EventTime,
+    end: EventTime,
+    #[serde(default)]
+    attendees: Vec<Person>,
+    organizer: Option<Person>,
+    html_link: Option<String>,
+    #[serde(rename = "iCalUID")]
+    ical_uid: Option<String>,
+    hangout_link: Option<String>,
+    transparency: Option<String>,
+    recurring_event_id: Option<String>,
+    created: Option<String>,
+    updated: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventsResponse {
+    #[serde(default)]
+    items: Vec<Event>,
+    next_page_token: Option<String>,
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(
+    url: &str,
+    query: &[(&str, &str)],
+) -> Result<T, Error> {
+    let response = crate::http::client()
+        .get(url)
+        .bearer_auth(access_token().await?)
+        .query(query)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.json().await?)
+}
+
+/// The calendars of the Google account, limited to the `calendars` of
+/// `[google]` when that's set.
+pub async fn list_calendars() -> Result<Vec<GraphCalendar>, Error> {
+    let config = config()?;
+    let mut entries = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let mut query = vec![("maxResults", "250")];
+        if let Some(token) = &page_token {
+            query.push(("pageToken", token.as_str()));
+        }
+        let page: CalendarListResponse =
+            get_json(&format!("{}/users/me/calendarList", API_URL), &query).await?;
+        entries.extend(page.items);
+        match page.next_page_token {
+            Some(token) => page_token = Some(token),
+            None => break,
+        }
+    }
+
+    Ok(entries
+        .into_iter()
+        .filter(|entry| {
+            config.calendars.as_ref().is_none_or(|wanted| {
+                wanted.iter().any(|w| {
+                    *w == entry.id
+                        || w.eq_ignore_ascii_case(&entry.summary)
+                        || entry
+                            .summary_override
+                            .as_ref()
+                            .is_some_and(|name| w.eq_ignore_ascii_case(name))
+                })
+            })
+        })
+        .map(|entry| GraphCalendar {
+            id: format!("{}{}", ID_PREFIX, entry.id),
+            name: entry.summary_override.unwrap_or(entry.summary),
+            can_share: Some(entry.access_role.as_deref() == Some("owner")),
+            hex_color: entry.background_color,
+            is_default_calendar: Some(entry.primary),
+            owner: None,
+        })
+        .collect())
+}
+
+/// Graph's naive UTC format, which the cache and the views expect.
+fn graph_time(time: &EventTime) -> Option<String> {
+    if let Some(date_time) = &time.date_time {
+        let utc = DateTime::parse_from_rfc3339(date_time)
+            .ok()?
+            .with_timezone(&Utc);
+        return Some(utc.format("%Y-%m-%dT%H:%M:%S").to_string());
+    }
+    // Graph gives all-day events as midnight, not converted to UTC
+    let date = NaiveDate::parse_from_str(time.date.as_deref()?, "%Y-%m-%d").ok()?;
+    Some(format!("{}T00:00:00", date))
+}
+
+/// Google's descriptions are HTML or plain text; the detail view renders HTML.
+fn description_html(description: &str) -> String {
+    if description.contains('<') {
+        return description.to_string();
+    }
+    description
+        .replace('&', "&amp;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+fn to_graph_event(event: Event) -> Option<GraphEvent> {
+    let email = |person: &Person| {
+        person.email.as_ref().map(|address| EmailAddress {
+            name: person
+                .display_name
+                .clone()
+                .unwrap_or_else(|| address.clone()),
+            address: address.clone(),
+        })
+    };
+    let response = event
+        .attendees
+        .iter()
+        .find(|a| a.is_self)
+        .and_then(|a| a.response_status.as_deref())
+        .map(|status| match status {
+            "accepted" => "accepted",
+            "declined" => "declined",
+            "tentative" => "tentativelyAccepted",
+            _ => "notResponded",
+        });
+
+    Some(GraphEvent {
+        id: format!("{}{}", ID_PREFIX, event.id),
+        subject: event.summary.unwrap_or_else(|| "(No title)".to_string()),
+        start: DateTimeTimeZone {
+            date_time: graph_time(&event.start)?,
+            _time_zone: "UTC".to_string(),
+        },
+        end: DateTimeTimeZone {
+            date_time: graph_time(&event.end)?,
+            _time_zone: "UTC".to_string(),
+        },
+        body: event.description.map(|description| ItemBody {
+            content: description_html(&description),
+        }),
+        attendees: event
+            .attendees
+            .iter()
+            .map(|a| Attendee {
+                email_address: email(a),
+            })
+            .collect(),
+        location: event.location.map(|display_name| Location { display_name }),
+        organizer: event
+            .organizer
+            .as_ref()
+            .and_then(email)
+            .map(|email_address| Organizer { email_address }),
+        response_status: response.map(|response| ResponseStatus {
+            response: response.to_string(),
+        }),
+        web_link: event.html_link,
+        categories: Vec::new(),
+        created_date_time: event.created,
+        last_modified_date_time: event.updated,
+        ical_uid: event.ical_uid,
+        is_all_day: Some(event.start.date.is_some()),
+        show_as: Some(
+            if event.transparency.as_deref() == Some("transparent") {
+                "free"
+            } else {
+                "busy"
+            }
+            .to_string(),
+        ),
+        online_meeting_url: event.hangout_link,
+        online_meeting: None,
+        series_master_id: event
+            .recurring_event_id
+            .map(|id| format!("{}{}", ID_PREFIX, id)),
+        importance: None,
+        is_reminder_on: None,
+        reminder_minutes_before_start: None,
+    })
+}
+
+/// The events of a Google calendar overlapping the range, recurring ones as
+/// single occurrences. Cancelled events are left out.
+pub async fn list_events(
+    calendar_id: &str,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<GraphEvent>, Error> {
+    let google_id = calendar_id.strip_prefix(ID_PREFIX).unwrap_or(calendar_id);
+    let url = format!(
+        "{}/calendars/{}/events",
+        API_URL,
+        url::form_urlencoded::byte_serialize(google_id.as_bytes()).collect::<String>()
+    );
+    let (time_min, time_max) = (start_date.to_rfc3339(), end_date.to_rfc3339());
+
+    let mut events = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let mut query = vec![
+            ("timeMin", time_min.as_str()),
+            ("timeMax", time_max.as_str()),
+            ("singleEvents", "true"),
+            ("orderBy", "startTime"),
+            ("maxResults", PAGE_SIZE),
+        ];
+        if let Some(token) = &page_token {
+            query.push(("pageToken", token.as_str()));
+        }
+        let page: EventsResponse = get_json(&url, &query).await?;
+        events.extend(
+            page.items
+                .into_iter()
+                .filter(|e| e.status.as_deref() != Some("cancelled"))
+                .filter_map(to_graph_event),
+        );
+        match page.next_page_token {
+            Some(token) => page_token = Some(token),
+            None => break,
+        }
+    }
+    Ok(events)
+}
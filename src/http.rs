@@ -0,0 +1,112 @@
+//! The HTTP client every request goes through: Graph, the login endpoints
+//! (via oauth2) and the status integrations. Proxies come from the usual
+//! HTTP_PROXY / HTTPS_PROXY / NO_PROXY environment variables, or from the
+//! `proxy` setting, which wins over them. Networks that inspect TLS can add
+//! their root CAs with `ca_certificate`.
+
+use std::sync::OnceLock;
+
+static PROXY: OnceLock<String> = OnceLock::new();
+static EXTRA_ROOTS: OnceLock<Vec<reqwest::Certificate>> = OnceLock::new();
+
+/// Sends all requests through `url`. Must be called before `init`;
+/// later calls are ignored.
+pub fn set_proxy(url: &str) {
+    let _ = PROXY.set(url.to_string());
+}
+
+/// Whether `url` can be used as a proxy, for checking the setting.
+pub fn is_valid_proxy(url: &str) -> bool {
+    reqwest::Proxy::all(url).is_ok()
+}
+
+/// Reads the certificates of a PEM bundle.
+pub fn read_ca_bundle(path: &str) -> Result<Vec<reqwest::Certificate>, String> {
+    let pem = std::fs::read(path).map_err(|e| e.to_string())?;
+    match reqwest::Certificate::from_pem_bundle(&pem) {
+        Ok(certificates) if certificates.is_empty() => Err("no certificates in it".to_string()),
+        Ok(certificates) => Ok(certificates),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Trusts the certificates in `certificates` on top of the system's roots.
+/// Must be called before `init`; later calls are ignored.
+pub fn add_root_certificates(certificates: Vec<reqwest::Certificate>) {
+    let _ = EXTRA_ROOTS.set(certificates);
+}
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static OAUTH_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn builder() -> Result<reqwest::ClientBuilder, String> {
+    let mut builder = reqwest::Client::builder();
+    for certificate in EXTRA_ROOTS.get().into_iter().flatten() {
+        builder = builder.add_root_certificate(certificate.clone());
+    }
+    Ok(match PROXY.get() {
+        Some(url) => {
+            let proxy = reqwest::Proxy::all(url)
+                .map_err(|e| format!("The proxy \"{}\" can't be used: {}", url, e))?;
+            builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_env()))
+        }
+        None => builder,
+    })
+}
+
+/// Builds the clients with the proxy and certificates set above. Called at
+/// startup, so a client that can't be built stops the app instead of
+/// quietly sending requests around the proxy.
+pub fn init() -> Result<(), String> {
+    let failed = |e: reqwest::Error| format!("Could not set up the HTTP client: {}", e);
+    let client = builder()?.build().map_err(failed)?;
+    // Redirects are not followed, as oauth2 advises for token endpoints
+    let oauth_client = builder()?
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(failed)?;
+    let _ = CLIENT.set(client);
+    let _ = OAUTH_CLIENT.set(oauth_client);
+    Ok(())
+}
+
+/// The shared client. Cloning it is cheap; connections are pooled.
+pub fn client() -> reqwest::Client {
+    CLIENT.get().expect("http::init runs at startup").clone()
+}
+
+/// Sends oauth2's token requests with our client, so they use the proxy too.
+pub async fn oauth_http_client(
+    request: oauth2::HttpRequest,
+) -> Result<oauth2::HttpResponse, reqwest::Error> {
+    let client = OAUTH_CLIENT.get().expect("http::init runs at startup");
+
+    // oauth2 speaks an older version of the `http` crate than reqwest
+    let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes())
+        .unwrap_or(reqwest::Method::POST);
+    let mut builder = client
+        .request(method, request.url.as_str())
+        .body(request.body);
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+    let response = builder.send().await?;
+
+    let status_code = oauth2::http::StatusCode::from_u16(response.status().as_u16())
+        .unwrap_or(oauth2::http::StatusCode::BAD_GATEWAY);
+    let mut headers = oauth2::http::HeaderMap::new();
+    for (name, value) in response.headers() {
+        if let (Ok(name), Ok(value)) = (
+            oauth2::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+            oauth2::http::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let body = response.bytes().await?.to_vec();
+    Ok(oauth2::HttpResponse {
+        status_code,
+        headers,
+        body,
+    })
+}
@@ -0,0 +1,978 @@
+//! iCalendar (.ics): single events written to files, to forward an invite or
+//! import it into another calendar outside Outlook, and subscribed feeds read
+//! into events like Graph's.
+
+use crate::api::{DateTimeTimeZone, EmailAddress, GraphEvent, ItemBody, Location, Organizer};
+use crate::clock;
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Longest line before it is folded, in bytes (RFC 5545 section 3.1).
+const MAX_LINE_BYTES: usize = 75;
+
+/// Escapes commas, semicolons, backslashes and newlines in a text value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into continuation lines of at most 75 bytes, never
+/// inside a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_BYTES {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Graph's naive UTC time, e.g. "2025-03-04T10:00:00.0000000".
+fn parse_utc(date_time: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+/// The event as a VCALENDAR with one VEVENT. `body` stands in for the
+/// event's own when that wasn't kept (minimal mode).
+pub fn to_ics(event: &GraphEvent, body: Option<&ItemBody>) -> Option<String> {
+    let start = parse_utc(&event.start.date_time)?;
+    let end = parse_utc(&event.end.date_time)?;
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//365cal-tui//EN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!(
+            "UID:{}",
+            event.ical_uid.as_deref().unwrap_or(event.id.as_str())
+        ),
+        format!(
+            "DTSTAMP:{}",
+            clock::now().with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+        ),
+    ];
+    if event.is_all_day == Some(true) {
+        lines.push(format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+    } else {
+        lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%SZ")));
+    }
+    lines.push(format!("SUMMARY:{}", escape(&event.subject)));
+    if let Some(location) = event
+        .location
+        .as_ref()
+        .map(|l| l.display_name.trim())
+        .filter(|l| !l.is_empty())
+    {
+        lines.push(format!("LOCATION:{}", escape(location)));
+    }
+    if let Some(body) = event.body.as_ref().or(body) {
+        let text = html2text::from_read(body.content.as_bytes(), 200)
+            .unwrap_or_else(|_| body.content.clone());
+        let text = text.trim();
+        if !text.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape(text)));
+        }
+    }
+    if let Some(organizer) = &event.organizer {
+        let email = &organizer.email_address;
+        lines.push(format!(
+            "ORGANIZER;CN=\"{}\":mailto:{}",
+            email.name.replace('"', "'"),
+            email.address
+        ));
+    }
+    for email in event
+        .attendees
+        .iter()
+        .filter_map(|a| a.email_address.as_ref())
+    {
+        lines.push(format!(
+            "ATTENDEE;CN=\"{}\":mailto:{}",
+            email.name.replace('"', "'"),
+            email.address
+        ));
+    }
+    if !event.categories.is_empty() {
+        let categories: Vec<String> = event.categories.iter().map(|c| escape(c)).collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+    if let Some(link) = &event.web_link {
+        lines.push(format!("URL:{}", link));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    Some(lines.iter().map(|line| fold(line)).collect())
+}
+
+/// A file name from the subject, without characters file systems refuse.
+fn file_stem(subject: &str) -> String {
+    let stem: String = subject
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let stem = stem.trim().trim_matches('.');
+    if stem.is_empty() {
+        "event".to_string()
+    } else {
+        stem.chars().take(100).collect()
+    }
+}
+
+/// Writes the event to `<subject>.ics` in `dir`, numbering the name when
+/// such a file is already there. Returns the path written.
+pub fn export_event(
+    event: &GraphEvent,
+    body: Option<&ItemBody>,
+    dir: &Path,
+) -> Result<PathBuf, String> {
+    let ics = to_ics(event, body).ok_or("The event has no valid start or end")?;
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let stem = file_stem(&event.subject);
+    let mut path = dir.join(format!("{}.ics", stem));
+    let mut copy = 2;
+    while path.exists() {
+        path = dir.join(format!("{} ({}).ics", stem, copy));
+        copy += 1;
+    }
+    fs::write(&path, ics).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Where exported events go: `ics_export_dir`, or the Downloads folder.
+pub fn export_dir(configured: Option<&str>) -> PathBuf {
+    match configured {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::download_dir()
+            .or_else(|| dirs::home_dir().map(|home| home.join("Downloads")))
+            .unwrap_or_else(|| PathBuf::from(".")),
+    }
+}
+
+/// Most occurrences of one recurring event returned, so a rule without an
+/// end can't run on forever.
+const MAX_OCCURRENCES: usize = 5000;
+
+/// Lines with their continuation lines joined back on.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Undoes `escape`.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+/// A content line: "NAME;PARAM=value:VALUE".
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Property> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut quoted = false;
+        let (split, _) = line.char_indices().find(|&(_, c)| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            c == ':' && !quoted
+        })?;
+        let mut head = line[..split].split(';');
+        let name = head.next()?.to_ascii_uppercase();
+        let params = head
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.to_ascii_uppercase(),
+                    value.trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+        Some(Property {
+            name,
+            params,
+            value: line[split + 1..].to_string(),
+        })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// How a time is meant: a date (all-day), UTC, in a named time zone, or
+/// floating, i.e. in whatever zone the reader is in.
+#[derive(Clone, Copy, PartialEq)]
+enum Zone {
+    Date,
+    Utc,
+    Named(Tz),
+    Floating,
+}
+
+/// A wall-clock time and how it is meant. Recurrences step the wall clock,
+/// so a weekly 09:00 meeting stays at 09:00 across daylight saving changes.
+#[derive(Clone, Copy)]
+struct Time {
+    local: NaiveDateTime,
+    zone: Zone,
+}
+
+impl Time {
+    fn parse(value: &str, tzid: Option<&str>) -> Option<Time> {
+        if value.len() == 8 {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+            return Some(Time {
+                local: date.and_hms_opt(0, 0, 0)?,
+                zone: Zone::Date,
+            });
+        }
+        let (value, utc) = match value.strip_suffix('Z') {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        // Zones that aren't IANA names (Outlook writes Windows ones) are
+        // taken as the local zone, which is usually right for shared feeds
+        let zone = match tzid {
+            _ if utc => Zone::Utc,
+            Some(tzid) => tzid
+                .trim_start_matches('/')
+                .parse::<Tz>()
+                .map(Zone::Named)
+                .unwrap_or(Zone::Floating),
+            None => Zone::Floating,
+        };
+        Some(Time { local, zone })
+    }
+
+    /// Dates stay at midnight, as Graph gives all-day events.
+    fn to_utc(self) -> Option<DateTime<Utc>> {
+        // A time skipped by a daylight saving change counts as an hour later
+        fn resolve<Z: TimeZone>(zone: &Z, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+            zone.from_local_datetime(&local)
+                .earliest()
+                .or_else(|| {
+                    zone.from_local_datetime(&(local + Duration::hours(1)))
+                        .earliest()
+                })
+                .map(|time| time.with_timezone(&Utc))
+        }
+        match self.zone {
+            Zone::Date | Zone::Utc => Some(self.local.and_utc()),
+            Zone::Named(tz) => resolve(&tz, self.local),
+            Zone::Floating => resolve(&Local, self.local),
+        }
+    }
+}
+
+/// "PT1H30M", "P1D", "-P1W".
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match c {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    _ => Duration::seconds(n),
+                };
+            }
+            _ => return None,
+        }
+    }
+    Some(total * sign)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The parts of an RRULE that are read. BYSETPOS, BYWEEKNO and the like
+/// aren't; events using them show their first occurrence only.
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<Time>,
+    /// Weekdays, with "2TU" (second Tuesday) or "-1FR" (last Friday) as
+    /// an ordinal within the month.
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    /// Months, 1 to 12, the occurrences are limited to.
+    by_month: Vec<u32>,
+}
+
+impl Recurrence {
+    fn parse(value: &str) -> Option<Recurrence> {
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+        let mut frequency = None;
+        for (key, value) in value.split(';').filter_map(|part| part.split_once('=')) {
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        "MONTHLY" => Some(Frequency::Monthly),
+                        "YEARLY" => Some(Frequency::Yearly),
+                        _ => return None,
+                    }
+                }
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|&i| i > 0)?,
+                "COUNT" => recurrence.count = Some(value.parse().ok()?),
+                "UNTIL" => recurrence.until = Some(Time::parse(value, None)?),
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let split = day.len().checked_sub(2)?;
+                        let weekday = match &day[split..] {
+                            "MO" => Weekday::Mon,
+                            "TU" => Weekday::Tue,
+                            "WE" => Weekday::Wed,
+                            "TH" => Weekday::Thu,
+                            "FR" => Weekday::Fri,
+                            "SA" => Weekday::Sat,
+                            "SU" => Weekday::Sun,
+                            _ => return None,
+                        };
+                        let ordinal = match &day[..split] {
+                            "" => None,
+                            n => Some(n.parse().ok()?),
+                        };
+                        recurrence.by_day.push((ordinal, weekday));
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for day in value.split(',') {
+                        recurrence.by_month_day.push(day.parse().ok()?);
+                    }
+                }
+                "BYMONTH" => {
+                    for month in value.split(',') {
+                        let month = month.parse().ok().filter(|m| (1..=12).contains(m))?;
+                        recurrence.by_month.push(month);
+                    }
+                }
+                "WKST" => {}
+                _ => return None,
+            }
+        }
+        recurrence.frequency = frequency?;
+        // Yearly days without months count through the whole year ("20MO"
+        // is the 20th Monday), which isn't read
+        if recurrence.frequency == Frequency::Yearly
+            && recurrence.by_month.is_empty()
+            && !(recurrence.by_day.is_empty() && recurrence.by_month_day.is_empty())
+        {
+            return None;
+        }
+        Some(recurrence)
+    }
+
+    /// The candidate dates in the month starting on `first`: the BYDAY and
+    /// BYMONTHDAY days, or `start`'s day of the month without them.
+    fn month_dates(&self, start: NaiveDate, first: NaiveDate) -> Option<Vec<NaiveDate>> {
+        let next = first.checked_add_months(chrono::Months::new(1))?;
+        let days_in_month = (next - first).num_days() as i32;
+        let mut dates = Vec::new();
+        for &(ordinal, weekday) in &self.by_day {
+            let matching: Vec<NaiveDate> = first
+                .iter_days()
+                .take_while(|date| *date < next)
+                .filter(|date| date.weekday() == weekday)
+                .collect();
+            match ordinal {
+                None => dates.extend(matching),
+                Some(n) if n > 0 => dates.extend(matching.get(n as usize - 1)),
+                Some(n) => dates.extend(
+                    matching
+                        .len()
+                        .checked_sub(n.unsigned_abs() as usize)
+                        .map(|i| matching[i]),
+                ),
+            }
+        }
+        let month_days = if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            vec![start.day() as i32]
+        } else {
+            self.by_month_day.clone()
+        };
+        for day in month_days {
+            // Negative days count from the end; months without the day are skipped
+            let day = if day < 0 {
+                days_in_month + day + 1
+            } else {
+                day
+            };
+            if (1..=days_in_month).contains(&day) {
+                dates.push(first.with_day(day as u32)?);
+            }
+        }
+        Some(dates)
+    }
+
+    /// The last period starting well before `date`, so the periods before it
+    /// can be skipped.
+    fn period_before(&self, start: NaiveDate, date: NaiveDate) -> u32 {
+        let periods = match self.frequency {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => {
+                let monday =
+                    start.num_days_from_ce() - start.weekday().num_days_from_monday() as i32;
+                (date.num_days_from_ce() - monday) as i64 / 7
+            }
+            Frequency::Monthly => {
+                (date.year() - start.year()) as i64 * 12 + date.month0() as i64
+                    - start.month0() as i64
+            }
+            Frequency::Yearly => (date.year() - start.year()) as i64,
+        };
+        // One period early, for times shifted across the day by their zone
+        (periods / self.interval as i64 - 1).clamp(0, u32::MAX as i64) as u32
+    }
+
+    /// First day of the `period`th period (day, week, month or year) after
+    /// `start`'s, and the candidate dates in it, in order.
+    fn period_dates(&self, start: NaiveDate, period: u32) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = period.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(Duration::days(step as i64))?;
+                let on_day = (self.by_day.is_empty()
+                    || self.by_day.iter().any(|&(_, day)| day == date.weekday()))
+                    && (self.by_month.is_empty() || self.by_month.contains(&date.month()));
+                Some((date, if on_day { vec![date] } else { Vec::new() }))
+            }
+            Frequency::Weekly => {
+                let monday = start
+                    .checked_sub_signed(Duration::days(
+                        start.weekday().num_days_from_monday() as i64
+                    ))?
+                    .checked_add_signed(Duration::weeks(step as i64))?;
+                let mut days: Vec<Weekday> = self.by_day.iter().map(|&(_, day)| day).collect();
+                if days.is_empty() {
+                    days.push(start.weekday());
+                }
+                let mut dates: Vec<NaiveDate> = days
+                    .into_iter()
+                    .map(|day| monday + Duration::days(day.num_days_from_monday() as i64))
+                    .filter(|date| {
+                        self.by_month.is_empty() || self.by_month.contains(&date.month())
+                    })
+                    .collect();
+                dates.sort();
+                dates.dedup();
+                Some((monday, dates))
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + step as i64;
+                let first =
+                    NaiveDate::from_ymd_opt((months / 12) as i32, (months % 12) as u32 + 1, 1)?;
+                let mut dates =
+                    if self.by_month.is_empty() || self.by_month.contains(&first.month()) {
+                        self.month_dates(start, first)?
+                    } else {
+                        Vec::new()
+                    };
+                dates.sort();
+                dates.dedup();
+                Some((first, dates))
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(step as i32)?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                let months = if self.by_month.is_empty() {
+                    vec![start.month()]
+                } else {
+                    self.by_month.clone()
+                };
+                // February 29 only comes around in leap years
+                let mut dates = Vec::new();
+                for month in months {
+                    let first_of_month = NaiveDate::from_ymd_opt(year, month, 1)?;
+                    dates.extend(self.month_dates(start, first_of_month)?);
+                }
+                dates.sort();
+                dates.dedup();
+                Some((first, dates))
+            }
+        }
+    }
+
+    /// The occurrences starting from `from` and before `window_end`, of those
+    /// following `start`. Without a COUNT, which has to be counted from the
+    /// first occurrence, the periods before `from` are skipped, so old series
+    /// don't use up `MAX_OCCURRENCES` before the window.
+    fn occurrences(
+        &self,
+        start: Time,
+        from: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Vec<Time> {
+        let until = self.until.and_then(Time::to_utc);
+        let mut occurrences = Vec::new();
+        let mut counted = 0;
+        let mut period = match self.count {
+            Some(_) => 0,
+            None => self.period_before(start.local.date(), from.date_naive()),
+        };
+        while let Some((period_start, dates)) = self.period_dates(start.local.date(), period) {
+            if period_start.and_time(start.local.time()).and_utc() >= window_end + Duration::days(1)
+            {
+                break;
+            }
+            for date in dates {
+                let time = Time {
+                    local: date.and_time(start.local.time()),
+                    zone: start.zone,
+                };
+                if time.local < start.local {
+                    continue;
+                }
+                let Some(utc) = time.to_utc() else {
+                    continue;
+                };
+                if until.is_some_and(|until| utc > until)
+                    || utc >= window_end
+                    || self.count.is_some_and(|count| counted >= count)
+                    || occurrences.len() >= MAX_OCCURRENCES
+                {
+                    return occurrences;
+                }
+                counted += 1;
+                if utc >= from {
+                    occurrences.push(time);
+                }
+            }
+            period += 1;
+        }
+        occurrences
+    }
+}
+
+/// A VEVENT as read from a feed.
+#[derive(Default)]
+struct FeedEvent {
+    uid: String,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    url: Option<String>,
+    organizer: Option<EmailAddress>,
+    start: Option<Time>,
+    end: Option<Time>,
+    duration: Option<Duration>,
+    rrule: Option<String>,
+    exdates: Vec<Time>,
+    recurrence_id: Option<Time>,
+    transparent: bool,
+    cancelled: bool,
+}
+
+fn read_events(text: &str) -> Vec<FeedEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<FeedEvent> = None;
+    // Depth of components (alarms) inside the current event
+    let mut nested = 0;
+    for line in unfold(text) {
+        let Some(property) = Property::parse(&line) else {
+            continue;
+        };
+        let is_event = property.value.eq_ignore_ascii_case("VEVENT");
+        match property.name.as_str() {
+            "BEGIN" if is_event => {
+                current = Some(FeedEvent::default());
+                nested = 0;
+                continue;
+            }
+            "END" if is_event => {
+                events.extend(current.take());
+                continue;
+            }
+            "BEGIN" => {
+                nested += 1;
+                continue;
+            }
+            "END" => {
+                nested -= 1;
+                continue;
+            }
+            _ => {}
+        }
+        let Some(event) = current.as_mut().filter(|_| nested == 0) else {
+            continue;
+        };
+        let tzid = property.param("TZID").map(str::to_string);
+        let tzid = tzid.as_deref();
+        match property.name.as_str() {
+            "UID" => event.uid = property.value,
+            "SUMMARY" => event.summary = Some(unescape(&property.value)),
+            "DESCRIPTION" => event.description = Some(unescape(&property.value)),
+            "LOCATION" => event.location = Some(unescape(&property.value)),
+            "URL" => event.url = Some(property.value),
+            "ORGANIZER" => {
+                let address = property
+                    .value
+                    .get(..7)
+                    .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+                    .map_or(property.value.as_str(), |_| &property.value[7..]);
+                event.organizer = Some(EmailAddress {
+                    name: property.param("CN").unwrap_or(address).to_string(),
+                    address: address.to_string(),
+                });
+            }
+            "DTSTART" => event.start = Time::parse(&property.value, tzid),
+            "DTEND" => event.end = Time::parse(&property.value, tzid),
+            "DURATION" => event.duration = parse_duration(&property.value),
+            "RRULE" => event.rrule = Some(property.value),
+            "EXDATE" => event.exdates.extend(
+                property
+                    .value
+                    .split(',')
+                    .filter_map(|value| Time::parse(value, tzid)),
+            ),
+            "RECURRENCE-ID" => event.recurrence_id = Time::parse(&property.value, tzid),
+            "TRANSP" => event.transparent = property.value.eq_ignore_ascii_case("TRANSPARENT"),
+            "STATUS" => event.cancelled = property.value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Plain text as HTML, for the detail view.
+fn text_to_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+/// One occurrence of a feed event, as Graph would give it.
+fn to_graph_event(
+    calendar_id: &str,
+    event: &FeedEvent,
+    start: Time,
+    length: Duration,
+) -> Option<GraphEvent> {
+    let end = Time {
+        local: start.local + length,
+        zone: start.zone,
+    };
+    let graph_time = |time: Time| -> Option<String> {
+        Some(time.to_utc()?.format("%Y-%m-%dT%H:%M:%S").to_string())
+    };
+    let start_utc = start.to_utc()?;
+    Some(GraphEvent {
+        // Feeds can share UIDs (two holiday calendars), and occurrences do
+        id: format!(
+            "{}#{}@{}",
+            calendar_id,
+            event.uid,
+            start_utc.format("%Y%m%dT%H%M%SZ")
+        ),
+        subject: event
+            .summary
+            .clone()
+            .unwrap_or_else(|| "(No title)".to_string()),
+        start: DateTimeTimeZone {
+            date_time: graph_time(start)?,
+            _time_zone: "UTC".to_string(),
+        },
+        end: DateTimeTimeZone {
+            date_time: graph_time(end)?,
+            _time_zone: "UTC".to_string(),
+        },
+        body: event.description.as_ref().map(|description| ItemBody {
+            content: text_to_html(description),
+        }),
+        attendees: Vec::new(),
+        location: event
+            .location
+            .clone()
+            .map(|display_name| Location { display_name }),
+        organizer: event
+            .organizer
+            .clone()
+            .map(|email_address| Organizer { email_address }),
+        response_status: None,
+        web_link: event.url.clone(),
+        categories: Vec::new(),
+        created_date_time: None,
+        last_modified_date_time: None,
+        ical_uid: Some(event.uid.clone()).filter(|uid| !uid.is_empty()),
+        is_all_day: Some(start.zone == Zone::Date),
+        show_as: Some(if event.transparent { "free" } else { "busy" }.to_string()),
+        online_meeting_url: None,
+        online_meeting: None,
+        series_master_id: None,
+        importance: None,
+        is_reminder_on: None,
+        reminder_minutes_before_start: None,
+    })
+}
+
+/// The events of an iCalendar feed overlapping the window, recurring ones
+/// as single occurrences, with IDs under `calendar_id`. Occurrences moved or
+/// cancelled in the feed (RECURRENCE-ID, EXDATE) are taken into account.
+pub fn read_feed(
+    text: &str,
+    calendar_id: &str,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<GraphEvent> {
+    let events = read_events(text);
+    let moved: HashSet<(&str, DateTime<Utc>)> = events
+        .iter()
+        .filter_map(|e| Some((e.uid.as_str(), e.recurrence_id?.to_utc()?)))
+        .collect();
+
+    let mut graph_events = Vec::new();
+    for event in &events {
+        let Some(start) = event.start else {
+            continue;
+        };
+        if event.cancelled {
+            continue;
+        }
+        let length = match (event.end, event.duration) {
+            (Some(end), _) => end.local - start.local,
+            (None, Some(duration)) => duration,
+            (None, None) if start.zone == Zone::Date => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+        let starts = match event
+            .rrule
+            .as_deref()
+            .filter(|_| event.recurrence_id.is_none())
+        {
+            Some(rrule) => match Recurrence::parse(rrule) {
+                Some(recurrence) => {
+                    recurrence.occurrences(start, window_start - length, window_end)
+                }
+                None => {
+                    log::warn!("Unsupported recurrence \"{}\" of \"{}\"", rrule, event.uid);
+                    vec![start]
+                }
+            },
+            None => vec![start],
+        };
+        let excluded: HashSet<DateTime<Utc>> =
+            event.exdates.iter().filter_map(|t| t.to_utc()).collect();
+        for start in starts {
+            let Some(start_utc) = start.to_utc() else {
+                continue;
+            };
+            let is_exception = event.recurrence_id.is_none()
+                && event.rrule.is_some()
+                && moved.contains(&(event.uid.as_str(), start_utc));
+            if excluded.contains(&start_utc) || is_exception {
+                continue;
+            }
+            let overlaps = start_utc < window_end
+                && (start_utc + length > window_start
+                    || (length == Duration::zero() && start_utc >= window_start));
+            if !overlaps {
+                continue;
+            }
+            graph_events.extend(to_graph_event(calendar_id, event, start, length));
+        }
+    }
+    graph_events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        Time::parse(value, None).unwrap().local.and_utc()
+    }
+
+    /// The start dates of the occurrences of `rrule` from `start` in the window.
+    fn dates(rrule: &str, start: &str, window: (&str, &str)) -> Vec<NaiveDate> {
+        Recurrence::parse(rrule)
+            .unwrap()
+            .occurrences(
+                Time::parse(start, None).unwrap(),
+                utc(window.0),
+                utc(window.1),
+            )
+            .iter()
+            .map(|time| time.local.date())
+            .collect()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn yearly_by_month_and_day() {
+        // The fourth Thursday of November
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH",
+                "20201126T100000Z",
+                ("20200101T000000Z", "20240101T000000Z")
+            ),
+            [
+                date(2020, 11, 26),
+                date(2021, 11, 25),
+                date(2022, 11, 24),
+                date(2023, 11, 23)
+            ]
+        );
+        // The last Sunday of March and of October
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=3,10;BYDAY=-1SU",
+                "20250330T010000Z",
+                ("20250101T000000Z", "20260101T000000Z")
+            ),
+            [date(2025, 3, 30), date(2025, 10, 26)]
+        );
+    }
+
+    #[test]
+    fn yearly_by_month_day() {
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=1,7;BYMONTHDAY=-1",
+                "20250131T090000Z",
+                ("20250101T000000Z", "20260201T000000Z")
+            ),
+            [date(2025, 1, 31), date(2025, 7, 31), date(2026, 1, 31)]
+        );
+    }
+
+    #[test]
+    fn yearly_days_without_months_are_not_read() {
+        assert!(Recurrence::parse("FREQ=YEARLY;BYDAY=20MO").is_none());
+        assert!(Recurrence::parse("FREQ=YEARLY;BYMONTHDAY=1").is_none());
+    }
+
+    #[test]
+    fn by_month_limits_daily_and_monthly() {
+        assert_eq!(
+            dates(
+                "FREQ=DAILY;BYMONTH=1",
+                "20251230T090000Z",
+                ("20251201T000000Z", "20260103T000000Z")
+            ),
+            [date(2026, 1, 1), date(2026, 1, 2)]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=MONTHLY;BYMONTH=2,4",
+                "20250115T090000Z",
+                ("20250101T000000Z", "20260101T000000Z")
+            ),
+            [date(2025, 2, 15), date(2025, 4, 15)]
+        );
+    }
+
+    #[test]
+    fn old_series_reach_the_window() {
+        // More than MAX_OCCURRENCES days before the window
+        assert_eq!(
+            dates(
+                "FREQ=DAILY",
+                "20000101T090000Z",
+                ("20260301T000000Z", "20260303T000000Z")
+            ),
+            [date(2026, 3, 1), date(2026, 3, 2)]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO",
+                "20000103T090000Z",
+                ("20260301T000000Z", "20260331T000000Z")
+            ),
+            [date(2026, 3, 9), date(2026, 3, 23)]
+        );
+    }
+
+    #[test]
+    fn count_is_counted_from_the_first_occurrence() {
+        assert_eq!(
+            dates(
+                "FREQ=DAILY;COUNT=10",
+                "20260301T090000Z",
+                ("20260308T000000Z", "20260401T000000Z")
+            ),
+            [date(2026, 3, 8), date(2026, 3, 9), date(2026, 3, 10)]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// A ticket or pull request reference found in an event's subject or body.
+#[derive(Clone)]
+pub struct DetectedLink {
+    pub label: String,
+    pub url: String,
+}
+
+fn github_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"https?://github\.com/([\w.-]+)/([\w.-]+)/(?:issues|pull)/(\d+)").unwrap()
+    })
+}
+
+fn jira_url_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"https?://[^\s"'<>]+/browse/([A-Z][A-Z0-9]+-\d+)"#).unwrap())
+}
+
+// Tokens that look like issue keys but are encodings/standards.
+const NOT_ISSUE_PREFIXES: &[&str] = &["UTF", "ISO", "SHA", "RFC", "COVID"];
+
+fn jira_key_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b([A-Z][A-Z0-9]{1,9}-\d+)\b").unwrap())
+}
+
+/// Finds GitHub issue/PR URLs, Jira browse URLs and, when a Jira base URL is
+/// configured, bare issue keys like `PROJ-123`. Results are de-duplicated by URL.
+pub fn detect_links(texts: &[&str], jira_base_url: Option<&str>) -> Vec<DetectedLink> {
+    let mut links: Vec<DetectedLink> = Vec::new();
+    let mut push = |label: String, url: String| {
+        if !links.iter().any(|l| l.url == url) {
+            links.push(DetectedLink { label, url });
+        }
+    };
+
+    for text in texts {
+        for caps in github_regex().captures_iter(text) {
+            push(
+                format!("{}/{}#{}", &caps[1], &caps[2], &caps[3]),
+                caps[0].to_string(),
+            );
+        }
+
+        for caps in jira_url_regex().captures_iter(text) {
+            push(caps[1].to_string(), caps[0].to_string());
+        }
+
+        if let Some(base) = jira_base_url {
+            let base = base.trim_end_matches('/');
+            for caps in jira_key_regex().captures_iter(text) {
+                let prefix = caps[1].split('-').next().unwrap_or_default();
+                if NOT_ISSUE_PREFIXES.contains(&prefix) {
+                    continue;
+                }
+                push(caps[1].to_string(), format!("{}/browse/{}", base, &caps[1]));
+            }
+        }
+    }
+
+    links
+}
+
+/// A link to join an online meeting, and the service hosting it.
+#[derive(Clone)]
+pub struct MeetingLink {
+    pub provider: &'static str,
+    pub url: String,
+}
+
+// In order of preference when a body links to more than one service. Teams
+// comes last: Outlook appends a Teams link to many invites by default, so a
+// Zoom or Webex link next to it is the one the organizer meant.
+const MEETING_PROVIDERS: &[(&str, &str)] = &[
+    (
+        "Zoom",
+        r#"https://(?:[\w-]+\.)?zoom(?:gov)?\.(?:us|com)/(?:j|my|w|s)/[^\s"'<>)\]]+"#,
+    ),
+    (
+        "Webex",
+        r#"https://[\w-]+\.webex\.com/(?:meet/|join/|[\w-]+/j\.php\?|wbxmjs/)[^\s"'<>)\]]*"#,
+    ),
+    (
+        "Google Meet",
+        r#"https://meet\.google\.com/[a-z]{3}-[a-z]{4}-[a-z]{3}"#,
+    ),
+    (
+        "GoTo",
+        r#"https://(?:meet\.goto\.com|global\.gotomeeting\.com/join|app\.gotomeeting\.com)/[^\s"'<>)\]]+"#,
+    ),
+    ("Chime", r#"https://chime\.aws/\d+"#),
+    ("Jitsi", r#"https://meet\.jit\.si/[^\s"'<>)\]]+"#),
+    ("Whereby", r#"https://whereby\.com/[^\s"'<>)\]]+"#),
+    (
+        "Teams",
+        r#"https://teams\.(?:microsoft|live)\.com/(?:l/meetup-join|meet)/[^\s"'<>)\]]+"#,
+    ),
+];
+
+fn meeting_regexes() -> &'static [(&'static str, Regex)] {
+    static RE: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
+    RE.get_or_init(|| {
+        MEETING_PROVIDERS
+            .iter()
+            .map(|(provider, pattern)| (*provider, Regex::new(pattern).unwrap()))
+            .collect()
+    })
+}
+
+fn safelinks_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"https://[\w.-]+\.safelinks\.protection\.outlook\.com/\?url=([^&\s"'<>]+)[^\s"'<>]*"#,
+        )
+        .unwrap()
+    })
+}
+
+/// Replaces Defender "Safe Links" wrappers with the URLs they point to, so a
+/// wrapped Zoom link is still recognised as one.
+fn unwrap_safelinks(text: &str) -> std::borrow::Cow<'_, str> {
+    safelinks_regex().replace_all(text, |caps: &regex::Captures| {
+        url::form_urlencoded::parse(format!("u={}", &caps[1]).as_bytes())
+            .next()
+            .map(|(_, url)| url.into_owned())
+            .unwrap_or_else(|| caps[0].to_string())
+    })
+}
+
+/// Names the service a meeting link belongs to, if it is a known one.
+pub fn meeting_provider(url: &str) -> Option<&'static str> {
+    meeting_regexes()
+        .iter()
+        .find(|(_, re)| re.is_match(url))
+        .map(|(provider, _)| *provider)
+}
+
+/// Finds the best conferencing link in an event's location and body, for
+/// invites that only carry one in the text. Bodies may be HTML; links in
+/// `href`s are found as well as visible ones.
+pub fn detect_meeting_link(texts: &[&str]) -> Option<MeetingLink> {
+    let texts: Vec<_> = texts.iter().map(|text| unwrap_safelinks(text)).collect();
+    meeting_regexes().iter().find_map(|(provider, re)| {
+        texts.iter().find_map(|text| {
+            re.find(text).map(|m| MeetingLink {
+                provider,
+                url: m.as_str().replace("&amp;", "&"),
+            })
+        })
+    })
+}
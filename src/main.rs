@@ -1,56 +1,396 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use log::{error, info};
+use log::{error, info, warn};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::path::PathBuf;
 use tokio::sync::mpsc;
 use tokio::time::{self, Duration};
 
+mod alarms;
 mod api;
 mod app;
 mod auth;
+mod cli;
+mod clock;
 mod config;
 mod db;
+mod dst;
+mod google;
+mod http;
+mod ics;
+mod links;
+mod metrics;
 mod notifications;
+mod office_hours;
+mod planner;
+mod review;
+mod rules;
+mod search;
+mod sql_console;
+mod status;
+mod subscriptions;
+mod token_file;
 mod tui;
 mod ui;
 
+/// How long the Google login at startup may take before the app starts
+/// without the Google calendars.
+const GOOGLE_LOGIN_TIMEOUT: Duration = Duration::from_secs(120);
+
 pub enum AppEvent {
     Refresh,
-    EventsLoaded(Vec<app::ColorEvent>),
+    /// Events read from the local database, shown until the sync finishes.
+    CachedEventsLoaded(u64, Vec<app::ColorEvent>),
+    /// Events from one page of a running sync, merged into those shown.
+    EventsPageLoaded(u64, Vec<app::ColorEvent>),
+    /// All events of a refresh. Like the two above, it carries the refresh
+    /// generation it belongs to.
+    EventsLoaded(u64, Vec<app::ColorEvent>),
+    ScheduleLoaded(Vec<api::ScheduleInformation>),
+    /// To Do tasks and flagged emails due today or earlier, for the dashboard,
+    /// or why they could not be loaded.
+    DueTasksLoaded(Result<Vec<api::DueTask>, String>),
+    /// The cached events a new event overlaps, found before it's saved.
+    ConflictsChecked(api::NewEvent, app::NewEventTarget, Vec<app::ColorEvent>),
+    /// Today's meetings in the user's own calendars, for the chat status.
+    TodaysMeetingsLoaded(Vec<api::GraphEvent>),
+    MeetingTimesFound(Result<api::MeetingTimeSuggestionsResult, String>),
+    /// Meeting rooms for the room picker, with their availability.
+    RoomsFound(Result<Vec<app::RoomOption>, String>),
+    /// Outcome of creating an event, with the planner task it schedules.
+    EventCreated(Result<String, String>, Option<planner::ScheduledTask>),
+    EventUpdated(Result<String, String>),
+    RulesApplied(usize),
+    CachedSearchResults(Vec<api::GraphEvent>),
+    /// Outcome of a query typed into the SQL console.
+    SqlQueryFinished(Result<sql_console::QueryResult, String>),
+    SearchResults(Result<Vec<api::GraphEvent>, String>),
+    CategoriesLoaded(Vec<String>),
+    LocalBlockSaved(Result<String, String>, planner::ScheduledTask),
+    /// Whether this instance holds the sync lease, and when the events were
+    /// last changed by whoever does.
+    LeaseChecked {
+        primary: bool,
+        events_changed_at: Option<String>,
+    },
+    MeetingArtifactsLoaded(String, api::MeetingArtifacts),
+    /// The body of an event, fetched on demand in minimal mode.
+    EventBodyLoaded(String, api::ItemBody),
+    /// When the shown calendars were last synced, from the database.
+    LastSyncedLoaded(Option<chrono::DateTime<chrono::Utc>>),
+    /// A sync of the shown calendars is over; the errors of those that failed.
+    SyncFinished(Vec<String>),
+    /// The event limit when a sync stopped there, `None` when it got everything.
+    SyncTruncated(Option<usize>),
+    /// The cache warm-up stopped on an expired token; it starts over after
+    /// the next sync.
+    CacheWarmUpInterrupted,
+    /// Graph turned down the access token the app started with.
+    LoginRejected,
+    /// Outcome of logging in again from the TUI: the new access token.
+    LoggedIn(Result<String, String>),
+    /// The signed-in account, looked up after the login.
+    AccountLoaded(app::Account),
+    /// A button clicked on a desktop notification.
+    NotificationAction(notifications::NotificationAction),
     TokenExpired,
 }
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long)]
     debug: bool,
+
+    /// Read a Slack/Zulip API token from stdin and store it in the system keyring
+    #[arg(long)]
+    set_status_token: bool,
+
+    /// Use a named profile, with its own settings, database and keyring entries
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Read the settings from this file, and keep the database next to it
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Pretend it's this local time, "YYYY-MM-DD HH:MM" (for demos and screenshots)
+    #[arg(long, global = true)]
+    fake_now: Option<String>,
+
+    /// Start with the default theme and symbols, ignoring the custom sections of Settings.toml
+    #[arg(long)]
+    safe_mode: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Summarize a past week: meetings, hours, declined invites and collaborators
+    Review {
+        /// How many weeks back to look (1 = last week)
+        #[arg(long, default_value_t = 1)]
+        weeks_ago: u32,
+
+        /// Print the report as Markdown
+        #[arg(long)]
+        markdown: bool,
+
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Inspect or undo what the `[[rules]]` did to incoming invites
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    /// Create an event without opening the TUI
+    Add {
+        /// Subject of the event
+        subject: String,
+
+        /// Local start time, "YYYY-MM-DD HH:MM"
+        #[arg(long, required_unless_present = "from", conflicts_with_all = ["date", "from"])]
+        start: Option<String>,
+
+        /// Day of the event, "YYYY-MM-DD", with --from (default: today)
+        #[arg(long, requires = "from")]
+        date: Option<String>,
+
+        /// Local start time on --date, "HH:MM"
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Local end time, "HH:MM" (instead of --duration)
+        #[arg(long, conflicts_with = "duration")]
+        to: Option<String>,
+
+        /// Length in minutes
+        #[arg(long, default_value_t = 30)]
+        duration: u32,
+
+        /// Calendar to add it to, by name or ID (default: your main calendar)
+        #[arg(long)]
+        calendar: Option<String>,
+    },
+    /// Print the `[office_hours]` slots as a snippet to share
+    OfficeHours {
+        /// Also create a weekly recurring event for each block
+        #[arg(long)]
+        publish: bool,
+    },
+    /// Print today's events (or the next few days') from the cache
+    Agenda {
+        /// How many days to print, starting today
+        #[arg(long, default_value_t = 1)]
+        days: u32,
+
+        /// Sync the days from Graph first (needs to log in)
+        #[arg(long)]
+        sync: bool,
+
+        /// Only this calendar, by name or ID (default: those "All Calendars" shows)
+        #[arg(long)]
+        calendar: Option<String>,
+    },
+    /// Print the meeting in progress or coming up next today, from the cache
+    Next {
+        /// Output format: text, or waybar for a Waybar custom module's JSON
+        #[arg(long, default_value = "text")]
+        format: String,
+
+        /// Only this calendar, by name or ID (default: those "All Calendars" shows)
+        #[arg(long)]
+        calendar: Option<String>,
+    },
+    /// List the calendars with their IDs, colors and owners, from the cache
+    Calendars {
+        /// Fetch the list from Graph first (needs to log in)
+        #[arg(long)]
+        refresh: bool,
+
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search the cached events, printing their dates, calendars and IDs
+    Search {
+        /// Words to look for in the subject, description or attendees
+        text: String,
+
+        /// Only events starting on or after this day, "YYYY-MM-DD"
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Only events starting on or before this day, "YYYY-MM-DD"
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Also search Graph, for events outside the synced window (needs to log in)
+        #[arg(long)]
+        server: bool,
+
+        /// Most events listed from each source
+        #[arg(long, default_value_t = search::MAX_SEARCH_RESULTS)]
+        limit: usize,
+
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the free and busy blocks within working hours, yours or a colleague's
+    Busy {
+        /// Day to look at, "YYYY-MM-DD" (default: today)
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        date: Option<String>,
+
+        /// First day of a range, "YYYY-MM-DD"
+        #[arg(long, requires = "to")]
+        from: Option<String>,
+
+        /// Last day of the range, "YYYY-MM-DD"
+        #[arg(long, requires = "from")]
+        to: Option<String>,
+
+        /// Email address of a colleague to look up instead (repeatable)
+        #[arg(long)]
+        user: Vec<String>,
+
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print how many meetings of each recurring series you attended (marked with `m`)
+    Attendance {
+        /// How many weeks back to look
+        #[arg(long, default_value_t = 12)]
+        weeks: u32,
+    },
+    /// Forget the login, e.g. before switching accounts or machines
+    Logout {
+        /// Also delete the cached events and settings made in the app
+        #[arg(long)]
+        clear_cache: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RulesCommand {
+    /// List the most recent invites answered by a rule
+    Log {
+        #[arg(short, long, default_value_t = 20)]
+        limit: u32,
+    },
+    /// Answer an invite differently than a rule did (declines can't be undone)
+    Undo {
+        /// Entry ID from `rules log`
+        id: i64,
+
+        /// Response to send instead: accept, tentative or decline
+        #[arg(long = "as", default_value = "tentative")]
+        response: String,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let cli = Cli::parse();
 
-    let settings = config::load_config().map_err(|e| {
-        println!("ERROR: Could not find or read the configuration file.");
-        println!("Please ensure 'Settings.toml' exists at ~/.config/365cal-tui/");
-        e
-    })?;
+    if let Some(profile) = &cli.profile {
+        config::set_profile(profile)?;
+    }
+    if let Some(path) = &cli.config {
+        config::set_config_file(path)?;
+    }
+    if let Some(fake_now) = &cli.fake_now {
+        clock::set_clock(clock::FixedClock::parse(fake_now)?);
+    }
+
+    let (mut settings, config_warnings, config_error) = match config::load_config() {
+        Ok((settings, warnings)) => (settings, warnings, None),
+        // Safe mode is the way past a configuration that doesn't load
+        Err(e) if cli.safe_mode => (config::load_safe_config(), Vec::new(), Some(e.to_string())),
+        Err(e) => {
+            println!("ERROR: Could not load the configuration file.");
+            println!("Please check {}:", config::get_config_path().display());
+            println!("{}", e);
+            return Err(e.into());
+        }
+    };
+
+    if cli.safe_mode {
+        settings.reset_to_safe_mode();
+    }
 
     let app_settings = settings.clone();
 
+    if let Some(graph_base_url) = &settings.graph_base_url {
+        api::set_graph_base_url(graph_base_url);
+    }
+    if let Some(proxy) = &settings.proxy {
+        http::set_proxy(proxy);
+    }
+    if let Some(path) = &settings.ca_certificate {
+        // Unreadable bundles were already reported as config warnings
+        if let Ok(certificates) = http::read_ca_bundle(path) {
+            http::add_root_certificates(certificates);
+        }
+    }
+    if let Some(storage) = settings
+        .token_storage
+        .as_deref()
+        .and_then(auth::TokenStorage::from_name)
+    {
+        auth::set_token_storage(storage);
+    }
+
     let enable_logging = cli.debug || settings.enable_debug_log.unwrap_or(false);
     if enable_logging {
-        simple_logging::log_to_file("365cal-tui.log", log::LevelFilter::Debug)?;
+        let log_file = match config::profile() {
+            Some(profile) => format!("365cal-tui-{}.log", profile),
+            None => "365cal-tui.log".to_string(),
+        };
+        simple_logging::log_to_file(log_file, log::LevelFilter::Debug)?;
     }
 
     info!("Application started.");
 
+    if let Err(e) = http::init() {
+        error!("{}", e);
+        println!("ERROR: {}", e);
+        return Err(e.into());
+    }
+
+    // Printed before the TUI starts, so they're still on screen after it exits
+    for warning in &config_warnings {
+        warn!("{}", warning);
+        eprintln!("WARNING: {}", warning);
+    }
+
+    if cli.set_status_token {
+        let Some(status_config) = &settings.status_integration else {
+            println!("ERROR: No [status_integration] section found in Settings.toml.");
+            return Ok(());
+        };
+        println!(
+            "Paste your {} API token and press Enter:",
+            status_config.provider
+        );
+        let mut token = String::new();
+        io::stdin().read_line(&mut token)?;
+        status::save_token(status_config, token.trim())?;
+        println!("Token saved to the system keyring.");
+        return Ok(());
+    }
+
     let (tx, rx) = mpsc::channel(1);
     let refresh_interval_minutes = settings.refresh_interval_minutes.unwrap_or(5);
     let refresh_duration = Duration::from_secs(refresh_interval_minutes * 60);
@@ -67,12 +407,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
     });
 
+    // Logging out mustn't ask for a login first
+    if let Some(Command::Logout { clear_cache }) = cli.command {
+        return cli::logout(clear_cache).await;
+    }
+
     // DB Init
-    let config_dir = dirs::config_dir()
-        .ok_or("Could not find config directory")?
-        .join("365cal-tui");
+    let config_dir = config::get_config_dir();
     std::fs::create_dir_all(&config_dir)?;
-    let db_path = config_dir.join("365cal.db");
+    let db_path = config::get_db_path();
     // Use mode=rwc to create if missing
     let db_url = format!("sqlite://{}?mode=rwc", db_path.to_string_lossy());
 
@@ -80,16 +423,121 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     // Load calendars from DB
     let mut calendars = db::get_calendars(&db_pool).await?;
+    if settings.google.is_none() {
+        calendars.retain(|c| !google::is_google_id(&c.id));
+    }
+    // Subscriptions come from Settings.toml, and are kept in the database so
+    // their events have a calendar there
+    let subscribed =
+        subscriptions::configure(settings.subscriptions.as_deref().unwrap_or_default());
+    db::save_calendars(&db_pool, &subscribed).await?;
+    calendars.retain(|c| !subscriptions::is_subscription_id(&c.id));
+    calendars.extend(subscribed);
+
+    // Printing the cached agenda, e.g. from cron, needs no login
+    if let Some(Command::Agenda {
+        days,
+        sync: false,
+        calendar,
+    }) = &cli.command
+    {
+        return cli::agenda(
+            &settings,
+            None,
+            &calendars,
+            &db_pool,
+            *days,
+            calendar.as_deref(),
+        )
+        .await;
+    }
+
+    // Status bars poll this every few seconds, so it never logs in
+    if let Some(Command::Next { format, calendar }) = &cli.command {
+        return cli::next(&settings, &calendars, &db_pool, format, calendar.as_deref()).await;
+    }
+
+    if let Some(Command::Calendars {
+        refresh: false,
+        json,
+    }) = cli.command
+    {
+        return cli::list_calendars(&settings, &calendars, json);
+    }
+
+    if let Some(Command::Search {
+        text,
+        from,
+        to,
+        server: false,
+        limit,
+        json,
+    }) = &cli.command
+    {
+        let query = cli::search_query(text, from.as_deref(), to.as_deref())?;
+        return cli::search(&query, None, &calendars, &db_pool, *limit, *json).await;
+    }
 
-    // Guardamos o client_id para passá-lo para o AppState
-    let client_id_for_app = settings.client_id.clone();
-    let access_token = auth::authenticate(settings.client_id).await?;
+    if let Some(Command::Attendance { weeks }) = cli.command {
+        return cli::attendance(&db_pool, weeks).await;
+    }
 
-    // If DB empty, fetch from API
-    if calendars.is_empty() {
+    auth::load_write_access(&db_pool).await;
+    let login = auth::authenticate(&settings).await?;
+    let access_token = login.access_token;
+    let login_age_days = if login.interactive {
+        auth::record_login(&db_pool).await;
+        0
+    } else {
+        auth::login_age_days(&db_pool).await
+    };
+
+    // If DB empty, fetch from API. Calendars cached before colors and owners
+    // were kept have a NULL color, so they are listed again once.
+    let graph_cached: Vec<_> = calendars
+        .iter()
+        .filter(|c| api::is_graph_id(&c.id))
+        .collect();
+    if graph_cached.is_empty() || graph_cached.iter().any(|c| c.hex_color.is_none()) {
         info!("Fetching calendars from API...");
-        calendars = api::list_calendars(&access_token).await?;
-        db::save_calendars(&db_pool, &calendars).await?;
+        let graph_calendars = api::list_calendars(&access_token).await?;
+        db::save_calendars(&db_pool, &graph_calendars).await?;
+        calendars.retain(|c| !api::is_graph_id(&c.id));
+        calendars.splice(0..0, graph_calendars);
+    }
+
+    // Google calendars are an extra; without them the app works as before
+    if let Some(google_config) = &settings.google {
+        google::configure(google_config);
+        // A login nobody finishes in the browser must not hold up the app
+        let login = time::timeout(GOOGLE_LOGIN_TIMEOUT, google::login())
+            .await
+            .unwrap_or_else(|_| Err("no login within two minutes".into()));
+        match login {
+            Ok(()) if !calendars.iter().any(|c| google::is_google_id(&c.id)) => {
+                info!("Fetching Google calendars...");
+                match google::list_calendars().await {
+                    Ok(google_calendars) => {
+                        db::save_calendars(&db_pool, &google_calendars).await?;
+                        calendars.extend(google_calendars);
+                    }
+                    Err(e) => warn!("Could not list the Google calendars: {}", e),
+                }
+            }
+            Ok(()) => {}
+            Err(e) => {
+                warn!("Google login failed: {}", e);
+                eprintln!(
+                    "WARNING: Google login failed, its calendars are left out: {}",
+                    e
+                );
+                calendars.retain(|c| !google::is_google_id(&c.id));
+            }
+        }
+    }
+
+    if let Some(command) = cli.command {
+        return cli::run(command, &settings, &access_token, &calendars, &db_pool).await;
     }
 
     // CORREÇÃO: Passando o client_id e db_pool para o construtor do App
@@ -135,15 +583,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         if let Some(s) = config_symbols.down_arrow {
             symbols.down_arrow = s;
         }
+        if let Some(s) = config_symbols.large_meeting {
+            symbols.large_meeting = s;
+        }
+        if let Some(s) = config_symbols.external {
+            symbols.external = s;
+        }
     }
 
-    let notification_manager = notifications::NotificationManager::new(
+    let notification_minutes_before = settings.notification_minutes_before.unwrap_or(15);
+    let mut notification_manager = notifications::NotificationManager::new(
         settings.enable_notifications.unwrap_or(true),
-        settings.notification_minutes_before.unwrap_or(15),
-    );
+        notification_minutes_before,
+    )
+    .with_actions(tx.clone());
+    if let Some(travel) = &settings.travel {
+        notification_manager =
+            notification_manager.with_travel_times(notifications::TravelTimes::from_config(travel));
+    }
+    if let Some(sound) = &settings.notification_sound {
+        notification_manager =
+            notification_manager.with_sound(notifications::NotificationSound::from_setting(sound));
+    }
+    if let Some(quiet_hours) = settings
+        .dnd
+        .as_deref()
+        .and_then(|dnd| notifications::QuietHours::from_entries(dnd).ok())
+    {
+        notification_manager = notification_manager.with_quiet_hours(quiet_hours);
+    }
+    if let Some(alert) = settings
+        .terminal_alert
+        .as_deref()
+        .and_then(notifications::TerminalAlert::from_name)
+    {
+        notification_manager = notification_manager.with_terminal_alert(
+            alert,
+            settings
+                .terminal_alert_minutes_before
+                .unwrap_or(notification_minutes_before),
+        );
+    }
 
     let mut app = app::App::new(
-        client_id_for_app,
         access_token,
         db_pool,
         theme,
@@ -170,7 +652,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         .into_iter()
         .enumerate()
         .map(|(i, calendar)| {
-            let mut color = colors[i % colors.len()];
+            // Google calendars keep their Google color, subscriptions the configured one
+            let mut color = calendar
+                .hex_color
+                .as_deref()
+                .filter(|_| !api::is_graph_id(&calendar.id))
+                .and_then(|c| c.parse::<ratatui::style::Color>().ok())
+                .unwrap_or(colors[i % colors.len()]);
             let mut icon = None;
 
             if let Some(overrides) = &settings.calendar_overrides {
@@ -192,6 +680,130 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         })
         .collect();
 
+    if let Some(muted) = &settings.muted_calendars {
+        let muted_ids = app
+            .calendars
+            .iter()
+            .map(|c| &c.calendar)
+            .filter(|c| {
+                muted
+                    .iter()
+                    .any(|entry| entry.to_lowercase() == c.name.to_lowercase() || *entry == c.id)
+            })
+            .map(|c| c.id.clone())
+            .collect();
+        app.notification_manager.mute_calendars(muted_ids);
+    }
+
+    if let Some(overrides) = &settings.calendar_overrides {
+        let urgencies = app
+            .calendars
+            .iter()
+            .map(|c| &c.calendar)
+            .filter_map(|c| {
+                let urgency = overrides.get(&c.name.to_lowercase())?.urgency.as_deref()?;
+                Some((c.id.clone(), notifications::Urgency::from_name(urgency)?))
+            })
+            .collect();
+        app.notification_manager.set_calendar_urgencies(urgencies);
+    }
+    app.notification_manager.set_busy_only(app.busy_only);
+
+    // Without configured internal domains, the account's own domain is it
+    let marks_external = settings
+        .external_meetings
+        .as_deref()
+        .is_some_and(|marking| !marking.eq_ignore_ascii_case("off"))
+        || settings.external_participants_warning.unwrap_or(true);
+    if marks_external && app.internal_domains.is_empty() {
+        match account_domain(&app.access_token, &app.db_pool).await {
+            Ok(Some(domain)) => app.internal_domains.push(domain),
+            Ok(None) => {}
+            Err(e) => error!("Failed to look up the account's domain: {}", e),
+        }
+    }
+
+    // The last known account shows until the lookup is back, also offline
+    match db::get_state(&app.db_pool, "account").await {
+        Ok(Some(value)) => app.account = serde_json::from_str(&value).ok(),
+        Ok(None) => {}
+        Err(e) => error!("Failed to read the account: {}", e),
+    }
+    spawn_account_lookup(app.access_token.clone(), app.db_pool.clone(), tx.clone());
+
+    // Calendars ticked in the calendar list in an earlier session
+    match db::get_state(&app.db_pool, "checked_calendars").await {
+        Ok(Some(value)) => {
+            app.restore_checked_calendars(serde_json::from_str(&value).unwrap_or_default())
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to read the ticked calendars: {}", e),
+    }
+
+    match db::get_attendance(&app.db_pool).await {
+        Ok(attendance) => app.attendance = attendance,
+        Err(e) => error!("Failed to read the attendance marks: {}", e),
+    }
+
+    match db::get_state(&app.db_pool, "list_grouping").await {
+        Ok(Some(value)) => {
+            let value: serde_json::Value = serde_json::from_str(&value).unwrap_or_default();
+            app.group_by_calendar = value["grouped"].as_bool().unwrap_or(false);
+            app.collapsed_calendars =
+                serde_json::from_value(value["collapsed"].clone()).unwrap_or_default();
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to read the list grouping: {}", e),
+    }
+
+    // Events flagged for an alarm in an earlier session
+    match db::get_state(&app.db_pool, "flagged_events").await {
+        Ok(Some(value)) => app.flagged_events = serde_json::from_str(&value).unwrap_or_default(),
+        Ok(None) => {}
+        Err(e) => error!("Failed to read the flagged events: {}", e),
+    }
+
+    let relogin_after_days = app.settings.relogin_after_days.unwrap_or(90);
+    if relogin_after_days > 0 && login_age_days >= relogin_after_days as i64 {
+        app.relogin_prompt = Some(format!(
+            "You last logged in {} days ago. Log in again now, rather than be logged out in the middle of something?",
+            login_age_days
+        ));
+    }
+
+    // Read access is all that's asked for until something needs more
+    if auth::wants_write(&app.settings) && !auth::can_write() {
+        app.set_status(app::READ_ONLY_MESSAGE);
+    }
+
+    if !config_warnings.is_empty() {
+        app.set_status(&format!(
+            "{} problem(s) in Settings.toml, listed when you quit",
+            config_warnings.len()
+        ));
+    }
+
+    // Only one instance per database syncs and notifies, others are viewers
+    match db::acquire_lease(&app.db_pool, &app.instance_id, tui::LEASE_TTL).await {
+        Ok(primary) => app.is_primary = primary,
+        Err(e) => error!("Failed to take the sync lease: {}", e),
+    }
+    if !app.is_primary {
+        info!("Another instance holds the sync lease. Running as a viewer.");
+        app.set_status("Another instance is syncing; this one only shows the cache");
+    }
+
+    if let Some(error) = &config_error {
+        app.set_status(&format!(
+            "Safe mode: Settings.toml did not load, using the defaults ({}). Press , to edit it",
+            error
+        ));
+    } else if cli.safe_mode {
+        app.set_status(
+            "Safe mode: theme, symbols and custom sections ignored. Press , to edit Settings.toml",
+        );
+    }
+
     // Initial load from DB might have events, so select nearest
     if !app.events.is_empty() {
         app.select_nearest_event();
@@ -207,6 +819,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     )?;
     terminal.show_cursor()?;
 
+    app.status_manager.clear().await;
+    if app.is_primary {
+        if let Err(e) = db::release_lease(&app.db_pool, &app.instance_id).await {
+            error!("Failed to release the sync lease: {}", e);
+        }
+    }
+
+    if app.logged_out {
+        cli::logout(false).await?;
+    }
+
     info!("Application terminated.");
 
     if let Err(err) = res {
@@ -215,3 +838,65 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     Ok(())
 }
+
+/// Looks up who is signed in, for the account badge, and keeps it for the
+/// next start.
+fn spawn_account_lookup(
+    access_token: String,
+    db_pool: sqlx::SqlitePool,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    tokio::spawn(async move {
+        // Also tells whether the login is still accepted
+        let me = match api::get_me(&access_token).await {
+            Ok(me) => me,
+            Err(e) => {
+                error!("Failed to look up the signed-in account: {}", e);
+                if tui::is_unauthorized(e.as_ref()) {
+                    let _ = tx.send(AppEvent::LoginRejected).await;
+                }
+                return;
+            }
+        };
+        // Personal accounts have no organization to look up
+        let organization = api::get_organization_name(&access_token)
+            .await
+            .unwrap_or_else(|e| {
+                info!("No organization for the signed-in account: {}", e);
+                None
+            });
+        let account = app::Account {
+            name: me.display_name,
+            address: me.mail.or(me.user_principal_name),
+            organization,
+        };
+        match serde_json::to_string(&account) {
+            Ok(value) => {
+                if let Err(e) = db::set_state(&db_pool, "account", &value).await {
+                    error!("Failed to save the account: {}", e);
+                }
+            }
+            Err(e) => error!("Failed to serialize the account: {}", e),
+        }
+        let _ = tx.send(AppEvent::AccountLoaded(account)).await;
+    });
+}
+
+/// The email domain of the signed-in account, looked up once and then kept
+/// in the database.
+async fn account_domain(
+    access_token: &str,
+    db_pool: &sqlx::SqlitePool,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(domain) = db::get_state(db_pool, "account_domain").await? {
+        return Ok(Some(domain));
+    }
+    let me = api::get_me(access_token).await?;
+    let address = me.mail.or(me.user_principal_name).unwrap_or_default();
+    let Some((_, domain)) = address.rsplit_once('@') else {
+        return Ok(None);
+    };
+    let domain = domain.to_lowercase();
+    db::set_state(db_pool, "account_domain", &domain).await?;
+    Ok(Some(domain))
+}
@@ -0,0 +1,55 @@
+//! Counts of the Graph requests made per hour, shown in the diagnostics popup
+//! so the refresh interval can be tuned against what a sync actually costs.
+
+use chrono::{DateTime, Local, Timelike};
+use std::sync::Mutex;
+
+/// How many hours of history are kept.
+const HOURS_KEPT: usize = 24;
+
+#[derive(Debug, Clone)]
+pub struct HourStats {
+    /// Start of the hour.
+    pub hour: DateTime<Local>,
+    pub requests: u32,
+    /// Bytes of response bodies received.
+    pub bytes: u64,
+    /// Responses telling us to slow down (429 or 503).
+    pub throttled: u32,
+}
+
+static HOURS: Mutex<Vec<HourStats>> = Mutex::new(Vec::new());
+
+/// Records one finished request.
+pub fn record(bytes: usize, throttled: bool) {
+    let now = crate::clock::now();
+    let hour = now
+        .with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(now);
+
+    let mut hours = HOURS.lock().unwrap_or_else(|e| e.into_inner());
+    if hours.last().is_none_or(|last| last.hour != hour) {
+        hours.push(HourStats {
+            hour,
+            requests: 0,
+            bytes: 0,
+            throttled: 0,
+        });
+        if hours.len() > HOURS_KEPT {
+            hours.remove(0);
+        }
+    }
+    if let Some(current) = hours.last_mut() {
+        current.requests += 1;
+        current.bytes += bytes as u64;
+        current.throttled += u32::from(throttled);
+    }
+}
+
+/// The recorded hours, most recent first. Hours without requests are absent.
+pub fn hourly() -> Vec<HourStats> {
+    let hours = HOURS.lock().unwrap_or_else(|e| e.into_inner());
+    hours.iter().rev().cloned().collect()
+}
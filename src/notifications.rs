@@ -1,13 +1,297 @@
 use crate::api::GraphEvent;
-use chrono::Local;
-use log::{debug, error, info};
+use crate::app::{display_subject, ColorEvent};
+use crate::clock;
+use crate::config::TravelConfig;
+use crate::AppEvent;
+use chrono::{DateTime, Datelike, Local, NaiveTime, Utc, Weekday};
+use log::{debug, error, info, warn};
 use notify_rust::Notification;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::Sender;
+
+/// Wait before the first retry of a notification that could not be shown;
+/// doubles with every further attempt.
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// How long a notification keeps being retried, e.g. while the notification
+/// daemon is still starting after login.
+const RETRY_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// How the terminal itself signals an imminent event. Useful over SSH, where
+/// desktop notifications never arrive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TerminalAlert {
+    Bell,
+    Flash,
+    Both,
+}
+
+impl TerminalAlert {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bell" => Some(Self::Bell),
+            "flash" => Some(Self::Flash),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+
+    pub fn rings_bell(self) -> bool {
+        matches!(self, Self::Bell | Self::Both)
+    }
+
+    pub fn flashes(self) -> bool {
+        matches!(self, Self::Flash | Self::Both)
+    }
+}
+
+/// Reminders this many minutes or less before the start are critical, so
+/// they break through focus modes.
+const CRITICAL_MINUTES: i64 = 2;
+
+/// How insistent a desktop notification is. Critical ones don't time out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "normal" => Some(Self::Normal),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    /// The calendar's urgency if it has one, else critical when the event
+    /// is about to start and normal before.
+    fn of_reminder(calendar_urgency: Option<Self>, start_time_utc: DateTime<Utc>) -> Self {
+        calendar_urgency.unwrap_or_else(|| {
+            if start_time_utc - clock::now_utc() <= chrono::Duration::minutes(CRITICAL_MINUTES) {
+                Self::Critical
+            } else {
+                Self::Normal
+            }
+        })
+    }
+}
+
+/// Players tried in turn for `notification_sound`, the first one installed
+/// plays it.
+#[cfg(target_os = "macos")]
+const SOUND_PLAYERS: &[&str] = &["afplay"];
+#[cfg(not(target_os = "macos"))]
+const SOUND_PLAYERS: &[&str] = &["paplay", "pw-play", "aplay"];
+
+/// What is played when a reminder fires.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationSound {
+    Bell,
+    File(PathBuf),
+}
+
+impl NotificationSound {
+    /// "bell", or else the path of a sound file.
+    pub fn from_setting(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("bell") {
+            Self::Bell
+        } else {
+            Self::File(PathBuf::from(value))
+        }
+    }
+}
+
+/// Plays a sound file in the background with the first player installed.
+/// Nothing is heard when none is, or the file can't be played.
+pub fn play_sound(path: &Path) {
+    let path = path.to_path_buf();
+    std::thread::spawn(move || {
+        for player in SOUND_PLAYERS {
+            let status = Command::new(player)
+                .arg(&path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+            match status {
+                Ok(status) if status.success() => return,
+                Ok(status) => {
+                    warn!("{} could not play {}: {}", player, path.display(), status);
+                    return;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    warn!("Failed to run {}: {}", player, e);
+                    return;
+                }
+            }
+        }
+        warn!("No sound player found for {}", path.display());
+    });
+}
+
+/// Do not disturb: when reminders are held back, from `dnd`.
+#[derive(Default)]
+pub struct QuietHours {
+    /// Daily ranges; one that ends before it starts goes past midnight
+    ranges: Vec<(NaiveTime, NaiveTime)>,
+    days: Vec<Weekday>,
+}
+
+impl QuietHours {
+    /// Reads entries like "22:00-08:00", "weekend" or "friday".
+    pub fn from_entries(entries: &[String]) -> Result<Self, String> {
+        let mut quiet_hours = Self::default();
+        for entry in entries {
+            let entry = entry.trim();
+            if entry.eq_ignore_ascii_case("weekend") {
+                quiet_hours.days.extend([Weekday::Sat, Weekday::Sun]);
+            } else if let Ok(day) = entry.parse::<Weekday>() {
+                quiet_hours.days.push(day);
+            } else if let Some((start, end)) = entry.split_once('-') {
+                let parse = |time: &str| {
+                    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                        .map_err(|_| format!("\"{}\" is not a time like \"22:00\"", time.trim()))
+                };
+                quiet_hours.ranges.push((parse(start)?, parse(end)?));
+            } else {
+                return Err(format!(
+                    "\"{}\" is not a time range like \"22:00-08:00\", \"weekend\" or a weekday",
+                    entry
+                ));
+            }
+        }
+        Ok(quiet_hours)
+    }
+
+    /// Whether reminders are held back at the given time.
+    pub fn contains(&self, time: DateTime<Local>) -> bool {
+        let time_of_day = time.time();
+        self.days.contains(&time.weekday())
+            || self.ranges.iter().any(|&(start, end)| {
+                if start <= end {
+                    start <= time_of_day && time_of_day < end
+                } else {
+                    time_of_day >= start || time_of_day < end
+                }
+            })
+    }
+}
+
+/// A reminder held back by do not disturb.
+pub struct MissedReminder {
+    pub subject: String,
+    pub start_time_utc: DateTime<Utc>,
+}
+
+// Locations that mean an online meeting, which needs no travel.
+const ONLINE_LOCATIONS: &[&str] = &["microsoft teams meeting", "online", "zoom", "webex"];
+
+/// Travel time to an event's location, from the `[travel]` settings.
+#[derive(Default)]
+pub struct TravelTimes {
+    home: Vec<String>,
+    /// Lowercased locations, longest first so the most specific one wins
+    by_location: Vec<(String, u64)>,
+    default_minutes: u64,
+}
+
+impl TravelTimes {
+    pub fn from_config(config: &TravelConfig) -> Self {
+        let mut by_location: Vec<(String, u64)> = config
+            .minutes
+            .iter()
+            .flatten()
+            .map(|(location, minutes)| (location.to_lowercase(), *minutes))
+            .collect();
+        by_location.sort_by_key(|(location, _)| std::cmp::Reverse(location.len()));
+        Self {
+            home: config
+                .home
+                .iter()
+                .flatten()
+                .map(|location| location.to_lowercase())
+                .collect(),
+            by_location,
+            default_minutes: config.default_minutes.unwrap_or(0),
+        }
+    }
+
+    /// Minutes it takes to get to the event; 0 for online meetings.
+    pub fn minutes(&self, event: &GraphEvent) -> u64 {
+        let location = event
+            .location
+            .as_ref()
+            .map(|l| l.display_name.trim().to_lowercase())
+            .unwrap_or_default();
+        if location.is_empty()
+            || location.contains("://")
+            || ONLINE_LOCATIONS.contains(&location.as_str())
+            || self
+                .home
+                .iter()
+                .any(|home| location.contains(home.as_str()))
+        {
+            return 0;
+        }
+        self.by_location
+            .iter()
+            .find(|(name, _)| location.contains(name.as_str()))
+            .map_or(self.default_minutes, |(_, minutes)| *minutes)
+    }
+}
+
+/// A button clicked on a notification, with the ID of its event.
+#[derive(Debug, Clone)]
+pub enum NotificationAction {
+    /// Join the event's online meeting.
+    Join(String),
+    /// Show the event's details.
+    Open(String),
+}
+
+/// A notification the desktop did not take, waiting for another try.
+struct PendingNotification {
+    event_id: String,
+    subject: String,
+    start_time_utc: DateTime<Utc>,
+    travel_minutes: u64,
+    joinable: bool,
+    calendar_urgency: Option<Urgency>,
+    first_failed: Instant,
+    attempts: u32,
+    retry_at: Instant,
+}
 
 pub struct NotificationManager {
+    /// Correlation IDs, so a meeting in several calendars is announced once
     notified_events: HashSet<String>,
     minutes_before: u64,
     enabled: bool,
+    terminal_alert: Option<(TerminalAlert, u64)>,
+    sound: Option<NotificationSound>,
+    alerted_events: HashSet<String>,
+    /// IDs of calendars whose events are never announced
+    muted_calendars: HashSet<String>,
+    /// Urgency of the reminders of calendars, by ID
+    calendar_urgencies: HashMap<String, Urgency>,
+    pending: Vec<PendingNotification>,
+    travel: TravelTimes,
+    quiet_hours: QuietHours,
+    /// Whether do not disturb was on at the last check
+    was_quiet: bool,
+    missed: Vec<MissedReminder>,
+    /// Where the buttons clicked on notifications are sent
+    actions: Option<Sender<AppEvent>>,
+    /// Whether notifications say "Busy" instead of the subject
+    busy_only: bool,
 }
 
 impl NotificationManager {
@@ -16,75 +300,471 @@ impl NotificationManager {
             notified_events: HashSet::new(),
             minutes_before,
             enabled,
+            terminal_alert: None,
+            sound: None,
+            alerted_events: HashSet::new(),
+            muted_calendars: HashSet::new(),
+            calendar_urgencies: HashMap::new(),
+            pending: Vec::new(),
+            travel: TravelTimes::default(),
+            quiet_hours: QuietHours::default(),
+            was_quiet: false,
+            missed: Vec::new(),
+            actions: None,
+            busy_only: false,
         }
     }
 
-    pub fn check_and_notify(&mut self, events: &[GraphEvent]) {
+    /// Also alert in the terminal `minutes_before` an event starts.
+    pub fn with_terminal_alert(mut self, alert: TerminalAlert, minutes_before: u64) -> Self {
+        self.terminal_alert = Some((alert, minutes_before));
+        self
+    }
+
+    /// Plays a sound when reminders fire.
+    pub fn with_sound(mut self, sound: NotificationSound) -> Self {
+        self.sound = Some(sound);
+        self
+    }
+
+    /// Announces events elsewhere earlier by their travel time.
+    pub fn with_travel_times(mut self, travel: TravelTimes) -> Self {
+        self.travel = travel;
+        self
+    }
+
+    /// Adds "Open" and "Join" buttons to notifications, whose clicks come
+    /// back to the main loop as `AppEvent::NotificationAction`.
+    pub fn with_actions(mut self, tx: Sender<AppEvent>) -> Self {
+        self.actions = Some(tx);
+        self
+    }
+
+    /// Gives the reminders of the given calendars a fixed urgency.
+    pub fn set_calendar_urgencies(&mut self, urgencies: HashMap<String, Urgency>) {
+        self.calendar_urgencies = urgencies;
+    }
+
+    /// Holds reminders back during the quiet hours and lists them as missed.
+    pub fn with_quiet_hours(mut self, quiet_hours: QuietHours) -> Self {
+        self.quiet_hours = quiet_hours;
+        self
+    }
+
+    /// Reminders held back by do not disturb, oldest first.
+    pub fn missed(&self) -> &[MissedReminder] {
+        &self.missed
+    }
+
+    pub fn clear_missed(&mut self) {
+        self.missed.clear();
+    }
+
+    /// Whether do not disturb just ended with reminders missed meanwhile.
+    /// True once for each end.
+    pub fn quiet_hours_ended(&mut self) -> bool {
+        let quiet = self.quiet_hours.contains(clock::now());
+        let ended = self.was_quiet && !quiet && !self.missed.is_empty();
+        self.was_quiet = quiet;
+        ended
+    }
+
+    /// Hides the subjects in notifications, for busy-only mode.
+    pub fn set_busy_only(&mut self, busy_only: bool) {
+        self.busy_only = busy_only;
+    }
+
+    /// Stops announcing events of the given calendars.
+    pub fn mute_calendars(&mut self, calendar_ids: HashSet<String>) {
+        self.muted_calendars = calendar_ids;
+    }
+
+    /// The configured terminal alert, if any.
+    pub fn terminal_alert(&self) -> Option<TerminalAlert> {
+        self.terminal_alert.map(|(alert, _)| alert)
+    }
+
+    /// The sound played when reminders fire, if any.
+    pub fn sound(&self) -> Option<&NotificationSound> {
+        self.sound.as_ref()
+    }
+
+    /// Subjects and start times of events that just came within the terminal
+    /// alert window. Each event is returned once.
+    pub fn terminal_alerts_due(&mut self, events: &[ColorEvent]) -> Vec<(String, DateTime<Utc>)> {
+        let Some((_, minutes_before)) = self.terminal_alert else {
+            return Vec::new();
+        };
+        if self.quiet_hours.contains(clock::now()) {
+            return Vec::new();
+        }
+        let now_utc = clock::now_utc();
+
+        let mut due = Vec::new();
+        for (event, _) in audible(&self.muted_calendars, events) {
+            let Some(start_time_utc) = start_time_utc(event) else {
+                continue;
+            };
+            let lead = minutes_before + self.travel.minutes(event);
+            if start_time_utc > now_utc
+                && start_time_utc <= now_utc + chrono::Duration::minutes(lead as i64)
+                && self
+                    .alerted_events
+                    .insert(event.correlation_id().to_string())
+            {
+                due.push((event.subject.clone(), start_time_utc));
+            }
+        }
+        due
+    }
+
+    /// Notifies about events that just came within the notification window.
+    /// Returns their subjects and start times, to be shown in the TUI too.
+    /// Those the desktop did not take are retried by `retry_failed`.
+    pub fn check_and_notify(&mut self, events: &[ColorEvent]) -> Vec<(String, DateTime<Utc>)> {
+        let mut announced = Vec::new();
         if !self.enabled {
+            return announced;
+        }
+
+        let now_utc = clock::now_utc();
+        let quiet = self.quiet_hours.contains(clock::now());
+
+        for (event, calendar_id) in audible(&self.muted_calendars, events) {
+            let Some(start_time_utc) = start_time_utc(event) else {
+                continue;
+            };
+            let travel_minutes = self.travel.minutes(event);
+            let lead = self.reminder_minutes(event) + travel_minutes;
+            let joinable = has_meeting_link(event);
+            let calendar_urgency =
+                calendar_id.and_then(|id| self.calendar_urgencies.get(id).copied());
+
+            if start_time_utc <= now_utc
+                || start_time_utc > now_utc + chrono::Duration::minutes(lead as i64)
+                || !self
+                    .notified_events
+                    .insert(event.correlation_id().to_string())
+            {
+                continue;
+            }
+            if quiet {
+                info!(
+                    "Do not disturb, holding back the reminder for: {}",
+                    event.subject
+                );
+                self.missed.push(MissedReminder {
+                    subject: event.subject.clone(),
+                    start_time_utc,
+                });
+                continue;
+            }
+            announced.push((event.subject.clone(), start_time_utc));
+            if !send_notification(
+                &event.id,
+                display_subject(&event.subject, self.busy_only),
+                start_time_utc,
+                travel_minutes,
+                joinable,
+                Urgency::of_reminder(calendar_urgency, start_time_utc),
+                self.actions.as_ref(),
+            ) {
+                let now = Instant::now();
+                self.pending.push(PendingNotification {
+                    event_id: event.id.clone(),
+                    subject: event.subject.clone(),
+                    start_time_utc,
+                    travel_minutes,
+                    joinable,
+                    calendar_urgency,
+                    first_failed: now,
+                    attempts: 1,
+                    retry_at: now + FIRST_RETRY_DELAY,
+                });
+            }
+        }
+        announced
+    }
+
+    /// Minutes before the event its notification is due: its Outlook
+    /// reminder when it has one on, else `notification_minutes_before`.
+    fn reminder_minutes(&self, event: &GraphEvent) -> u64 {
+        match (event.is_reminder_on, event.reminder_minutes_before_start) {
+            // A reminder at the start is shown in the minute before, while
+            // the event is still ahead
+            (Some(true), Some(minutes)) if minutes >= 0 => (minutes as u64).max(1),
+            _ => self.minutes_before,
+        }
+    }
+
+    /// Tries again to show notifications that failed, with backoff, until
+    /// they are shown, the event starts or `RETRY_WINDOW` is over.
+    pub fn retry_failed(&mut self) {
+        if self.quiet_hours.contains(clock::now()) {
             return;
         }
+        let now = Instant::now();
+        let now_utc = clock::now_utc();
+        let actions = self.actions.as_ref();
+        let busy_only = self.busy_only;
+        self.pending.retain_mut(|pending| {
+            if now < pending.retry_at {
+                return true;
+            }
+            if pending.start_time_utc <= now_utc
+                || now.duration_since(pending.first_failed) >= RETRY_WINDOW
+            {
+                warn!("Giving up on the notification for: {}", pending.subject);
+                return false;
+            }
+            if send_notification(
+                &pending.event_id,
+                display_subject(&pending.subject, busy_only),
+                pending.start_time_utc,
+                pending.travel_minutes,
+                pending.joinable,
+                Urgency::of_reminder(pending.calendar_urgency, pending.start_time_utc),
+                actions,
+            ) {
+                return false;
+            }
+            pending.retry_at = now + FIRST_RETRY_DELAY * 2u32.pow(pending.attempts);
+            pending.attempts += 1;
+            true
+        });
+    }
+}
 
-        let now_utc = chrono::Utc::now();
-        let threshold_time_utc = now_utc + chrono::Duration::minutes(self.minutes_before as i64);
+/// Shows a desktop notification. Returns whether it could be shown.
+fn send_notification(
+    event_id: &str,
+    subject: &str,
+    start_time_utc: DateTime<Utc>,
+    travel_minutes: u64,
+    joinable: bool,
+    urgency: Urgency,
+    actions: Option<&Sender<AppEvent>>,
+) -> bool {
+    info!("Sending notification for event: {}", subject);
 
-        for event in events {
-            let start_time_str = &event.start.date_time;
+    // Convert to Local time for display
+    let local_time = start_time_utc.with_timezone(&Local);
+    let time_display = local_time.format("%H:%M").to_string();
 
-            // Parse as NaiveDateTime first
-            let start_naive =
-                match chrono::NaiveDateTime::parse_from_str(start_time_str, "%Y-%m-%dT%H:%M:%S%.f")
-                {
-                    Ok(t) => t,
-                    Err(_) => {
-                        match chrono::NaiveDateTime::parse_from_str(
-                            start_time_str,
-                            "%Y-%m-%dT%H:%M:%S",
-                        ) {
-                            Ok(t) => t,
-                            Err(e) => {
-                                debug!(
-                                    "Failed to parse event time for notification: {} - {}",
-                                    start_time_str, e
-                                );
-                                continue;
-                            }
-                        }
-                    }
-                };
+    let mut body = format!("Starting at {}", time_display);
+    if travel_minutes > 0 {
+        body.push_str(&format!(", {} min away", travel_minutes));
+    }
 
-            // Assume the API returns UTC times (which is standard for Graph API)
-            let start_time_utc = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(
-                start_naive,
-                chrono::Utc,
-            );
+    let mut notification = Notification::new();
+    notification
+        .summary(subject)
+        .body(&body)
+        .appname("365cal-tui")
+        .icon("calendar");
+    // Urgency is a hint of freedesktop daemons and Windows
+    #[cfg(any(all(unix, not(target_os = "macos")), target_os = "windows"))]
+    notification.urgency(match urgency {
+        Urgency::Low => notify_rust::Urgency::Low,
+        Urgency::Normal => notify_rust::Urgency::Normal,
+        Urgency::Critical => notify_rust::Urgency::Critical,
+    });
+    // Only freedesktop notification daemons tell which button was clicked
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if actions.is_some() {
+        // "default" is a click on the notification itself
+        notification
+            .action("default", "Open")
+            .action("open", "Open");
+        if joinable {
+            notification.action("join", "Join");
+        }
+    }
 
-            if start_time_utc > now_utc && start_time_utc <= threshold_time_utc {
-                if !self.notified_events.contains(&event.id) {
-                    self.send_notification(&event.subject, start_time_utc);
-                    self.notified_events.insert(event.id.clone());
-                }
+    match notification.show() {
+        Ok(handle) => {
+            #[cfg(all(unix, not(target_os = "macos")))]
+            if let Some(tx) = actions {
+                forward_action(handle, event_id.to_string(), tx.clone());
             }
+            #[cfg(not(all(unix, not(target_os = "macos"))))]
+            let _ = (handle, event_id, joinable, urgency, actions);
+            true
+        }
+        Err(e) => {
+            error!("Failed to send notification: {}", e);
+            false
         }
     }
+}
 
-    fn send_notification(&self, subject: &str, start_time_utc: chrono::DateTime<chrono::Utc>) {
-        info!("Sending notification for event: {}", subject);
+/// Waits on a thread for a button to be clicked on the notification and
+/// passes it to the main loop. The thread ends when the notification closes.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn forward_action(handle: notify_rust::NotificationHandle, event_id: String, tx: Sender<AppEvent>) {
+    std::thread::spawn(move || {
+        handle.wait_for_action(|action| {
+            let action = match action {
+                "join" => NotificationAction::Join(event_id),
+                "default" | "open" => NotificationAction::Open(event_id),
+                _ => return,
+            };
+            debug!("Notification action: {:?}", action);
+            let _ = tx.blocking_send(AppEvent::NotificationAction(action));
+        });
+    });
+}
 
-        // Convert to Local time for display
-        let local_time = start_time_utc.with_timezone(&Local);
-        let time_display = local_time.format("%H:%M").to_string();
+/// Whether the event has an online meeting to join, in its join URL,
+/// location or body.
+fn has_meeting_link(event: &GraphEvent) -> bool {
+    event.join_url().is_some()
+        || crate::links::detect_meeting_link(&[
+            event
+                .location
+                .as_ref()
+                .map_or("", |l| l.display_name.as_str()),
+            event.body.as_ref().map_or("", |b| b.content.as_str()),
+        ])
+        .is_some()
+}
 
-        let body = format!("Starting at {}", time_display);
+/// Events of calendars that aren't muted, with their calendar's ID.
+fn audible<'a>(
+    muted_calendars: &'a HashSet<String>,
+    events: &'a [ColorEvent],
+) -> impl Iterator<Item = (&'a GraphEvent, Option<&'a str>)> {
+    events
+        .iter()
+        .filter(|e| {
+            e.calendar_id
+                .as_ref()
+                .is_none_or(|id| !muted_calendars.contains(id))
+        })
+        .map(|e| (&e.event, e.calendar_id.as_deref()))
+}
 
-        let result = Notification::new()
-            .summary(subject)
-            .body(&body)
-            .appname("365cal-tui")
-            .icon("calendar")
-            .show();
+fn start_time_utc(event: &GraphEvent) -> Option<DateTime<Utc>> {
+    let start_time_str = &event.start.date_time;
 
-        if let Err(e) = result {
-            error!("Failed to send notification: {}", e);
+    // Parse as NaiveDateTime first
+    let start_naive =
+        match chrono::NaiveDateTime::parse_from_str(start_time_str, "%Y-%m-%dT%H:%M:%S%.f") {
+            Ok(t) => t,
+            Err(_) => {
+                match chrono::NaiveDateTime::parse_from_str(start_time_str, "%Y-%m-%dT%H:%M:%S") {
+                    Ok(t) => t,
+                    Err(e) => {
+                        debug!(
+                            "Failed to parse event time for notification: {} - {}",
+                            start_time_str, e
+                        );
+                        return None;
+                    }
+                }
+            }
+        };
+
+    // Assume the API returns UTC times (which is standard for Graph API)
+    Some(DateTime::<Utc>::from_naive_utc_and_offset(start_naive, Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, FixedClock};
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 3, 9, 30, 0).unwrap()
+    }
+
+    fn start_at(minutes_from_now: i64) -> DateTime<Utc> {
+        now() + chrono::Duration::minutes(minutes_from_now)
+    }
+
+    #[test]
+    fn reminder_is_critical_just_before_the_start() {
+        clock::set_thread_clock(FixedClock(now()));
+        assert_eq!(Urgency::of_reminder(None, start_at(2)), Urgency::Critical);
+        assert_eq!(Urgency::of_reminder(None, start_at(-1)), Urgency::Critical);
+    }
+
+    #[test]
+    fn reminder_is_normal_ahead_of_time() {
+        clock::set_thread_clock(FixedClock(now()));
+        assert_eq!(Urgency::of_reminder(None, start_at(15)), Urgency::Normal);
+    }
+
+    fn entries(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|e| e.to_string()).collect()
+    }
+
+    fn quiet_hours(list: &[&str]) -> QuietHours {
+        QuietHours::from_entries(&entries(list)).unwrap()
+    }
+
+    /// 2025-06-03 is a Tuesday.
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 6, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn overnight_quiet_hours_span_midnight() {
+        let quiet = quiet_hours(&["22:00-08:00"]);
+        assert!(quiet.contains(local(3, 22, 0)));
+        assert!(quiet.contains(local(3, 23, 59)));
+        assert!(quiet.contains(local(4, 0, 0)));
+        assert!(quiet.contains(local(4, 7, 59)));
+        assert!(!quiet.contains(local(4, 8, 0)));
+        assert!(!quiet.contains(local(3, 21, 59)));
+        assert!(!quiet.contains(local(3, 12, 0)));
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let quiet = quiet_hours(&["12:00-13:30"]);
+        assert!(quiet.contains(local(3, 12, 0)));
+        assert!(quiet.contains(local(3, 13, 29)));
+        assert!(!quiet.contains(local(3, 13, 30)));
+        assert!(!quiet.contains(local(3, 11, 59)));
+    }
+
+    #[test]
+    fn a_range_ending_where_it_starts_holds_nothing_back() {
+        let quiet = quiet_hours(&["12:00-12:00"]);
+        assert!(!quiet.contains(local(3, 11, 59)));
+        assert!(!quiet.contains(local(3, 12, 0)));
+        assert!(!quiet.contains(local(3, 12, 1)));
+    }
+
+    #[test]
+    fn quiet_days_last_all_day() {
+        let quiet = quiet_hours(&["weekend", "Wednesday"]);
+        // Saturday, Sunday and Wednesday
+        for day in [7, 8, 4] {
+            assert!(quiet.contains(local(day, 0, 0)));
+            assert!(quiet.contains(local(day, 23, 59)));
         }
+        assert!(!quiet.contains(local(3, 12, 0)));
+        assert!(!quiet.contains(local(6, 12, 0)));
+    }
+
+    #[test]
+    fn quiet_hours_reject_unknown_entries() {
+        assert!(QuietHours::from_entries(&entries(&["lunch"])).is_err());
+        assert!(QuietHours::from_entries(&entries(&["22:00-8"])).is_err());
+        assert!(QuietHours::from_entries(&entries(&[" 22:00 - 08:00 ", "sat"])).is_ok());
+    }
+
+    #[test]
+    fn calendar_urgency_wins() {
+        clock::set_thread_clock(FixedClock(now()));
+        assert_eq!(
+            Urgency::of_reminder(Some(Urgency::Low), start_at(1)),
+            Urgency::Low
+        );
     }
 }
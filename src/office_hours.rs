@@ -0,0 +1,124 @@
+use crate::config::OfficeHoursConfig;
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+
+/// Subject of the office hours events when `subject` isn't set.
+const DEFAULT_SUBJECT: &str = "Office hours";
+
+/// Length of the bookable slots when `slot_minutes` isn't set.
+const DEFAULT_SLOT_MINUTES: u32 = 30;
+
+/// One weekly block of office hours.
+pub struct OfficeHoursBlock {
+    pub weekday: Weekday,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// Weekly office hours from the `[office_hours]` section: blocks published as
+/// recurring events, and cut into slots for a snippet to share.
+pub struct OfficeHours {
+    pub subject: String,
+    pub slot: Duration,
+    pub blocks: Vec<OfficeHoursBlock>,
+}
+
+/// Parses "14:00-16:00" into its start and end.
+fn parse_range(range: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| format!("\"{}\" is not a time range like \"14:00-16:00\"", range))?;
+    let parse = |time: &str| {
+        NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|_| format!("\"{}\" is not a time like \"14:00\"", time.trim()))
+    };
+    let (start, end) = (parse(start)?, parse(end)?);
+    if end <= start {
+        return Err(format!("\"{}\" ends before it starts", range));
+    }
+    Ok((start, end))
+}
+
+impl OfficeHours {
+    pub fn from_config(config: &OfficeHoursConfig) -> Result<Self, String> {
+        let slot_minutes = config.slot_minutes.unwrap_or(DEFAULT_SLOT_MINUTES);
+        if slot_minutes == 0 {
+            return Err("slot_minutes must be at least 1".to_string());
+        }
+        let days = [
+            (Weekday::Mon, &config.monday),
+            (Weekday::Tue, &config.tuesday),
+            (Weekday::Wed, &config.wednesday),
+            (Weekday::Thu, &config.thursday),
+            (Weekday::Fri, &config.friday),
+            (Weekday::Sat, &config.saturday),
+            (Weekday::Sun, &config.sunday),
+        ];
+        let mut blocks = Vec::new();
+        for (weekday, ranges) in days {
+            // Several blocks a day are separated by commas
+            for range in ranges.iter().flat_map(|r| r.split(',')) {
+                let (start, end) = parse_range(range)?;
+                blocks.push(OfficeHoursBlock {
+                    weekday,
+                    start,
+                    end,
+                });
+            }
+        }
+        if blocks.is_empty() {
+            return Err("no weekday has office hours".to_string());
+        }
+        Ok(Self {
+            subject: config
+                .subject
+                .clone()
+                .unwrap_or_else(|| DEFAULT_SUBJECT.to_string()),
+            slot: Duration::minutes(slot_minutes as i64),
+            blocks,
+        })
+    }
+
+    /// Start times of the slots a block is cut into. A last slot that doesn't
+    /// fit completely is left out.
+    pub fn slots(&self, block: &OfficeHoursBlock) -> Vec<NaiveTime> {
+        let mut slots = Vec::new();
+        let mut start = block.start;
+        while start + self.slot <= block.end && start + self.slot > start {
+            slots.push(start);
+            start += self.slot;
+        }
+        slots
+    }
+
+    /// Plain-text list of the slots, to paste into a chat or an email.
+    pub fn snippet(&self, time_zone: &str) -> String {
+        let mut text = format!(
+            "{} ({} min slots, {}):\n",
+            self.subject,
+            self.slot.num_minutes(),
+            time_zone
+        );
+        for block in &self.blocks {
+            let slots: Vec<String> = self
+                .slots(block)
+                .iter()
+                .map(|start| start.format("%H:%M").to_string())
+                .collect();
+            text.push_str(&format!(
+                "  {} {}-{}: {}\n",
+                block.weekday,
+                block.start.format("%H:%M"),
+                block.end.format("%H:%M"),
+                slots.join(", ")
+            ));
+        }
+        text
+    }
+}
+
+/// The first date on or after `from` that falls on `weekday`.
+pub fn next_date_on(weekday: Weekday, from: NaiveDate) -> NaiveDate {
+    let days_ahead =
+        (weekday.num_days_from_monday() + 7 - from.weekday().num_days_from_monday()) % 7;
+    from + Duration::days(days_ahead as i64)
+}
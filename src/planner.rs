@@ -0,0 +1,291 @@
+//! Focus-day planning: a list of unscheduled tasks kept in a local notes file,
+//! next to today's free slots, so tasks can be dropped into the calendar.
+
+use crate::api::NewEvent;
+use crate::app::{parse_event_time, ColorEvent};
+use crate::clock;
+use chrono::{DateTime, Duration, Local, NaiveTime, Timelike, Utc};
+use ratatui::widgets::ListState;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Slots shorter than this aren't worth offering.
+const MIN_SLOT_MINUTES: i64 = 15;
+/// Length of tasks that don't say how long they take.
+const DEFAULT_TASK_MINUTES: i64 = 30;
+
+#[derive(Clone)]
+pub struct PlannerTask {
+    pub title: String,
+    pub minutes: i64,
+    /// Line of the notes file the task came from.
+    line: usize,
+}
+
+#[derive(Clone, Copy)]
+pub struct FreeSlot {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl FreeSlot {
+    pub fn minutes(&self) -> i64 {
+        (self.end - self.start).num_minutes()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PlannerPane {
+    Tasks,
+    Slots,
+}
+
+pub struct PlannerState {
+    pub tasks_file: PathBuf,
+    pub tasks: Vec<PlannerTask>,
+    pub slots: Vec<FreeSlot>,
+    pub focus: PlannerPane,
+    pub task_state: ListState,
+    pub slot_state: ListState,
+}
+
+/// Reads open tasks from a notes file. Each non-empty line is a task;
+/// Markdown checkboxes are understood ("- [x]" lines are done) and a trailing
+/// "(45m)" or "(1h)" sets the task's length.
+pub fn load_tasks(path: &Path) -> Vec<PlannerTask> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let duration_re = Regex::new(r"\s*\((\d+)\s*(m|min|h)\)\s*$").unwrap();
+
+    let mut tasks = Vec::new();
+    for (line, text) in content.lines().enumerate() {
+        let text = text.trim();
+        if text.starts_with("- [x]") || text.starts_with("- [X]") || text.starts_with('#') {
+            continue;
+        }
+        let text = text
+            .strip_prefix("- [ ]")
+            .or_else(|| text.strip_prefix("- "))
+            .or_else(|| text.strip_prefix("* "))
+            .unwrap_or(text)
+            .trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let (title, minutes) = match duration_re.captures(text) {
+            Some(caps) => {
+                let amount: i64 = caps[1].parse().unwrap_or(DEFAULT_TASK_MINUTES);
+                let minutes = if &caps[2] == "h" { amount * 60 } else { amount };
+                (text[..caps.get(0).unwrap().start()].to_string(), minutes)
+            }
+            None => (text.to_string(), DEFAULT_TASK_MINUTES),
+        };
+        tasks.push(PlannerTask {
+            title,
+            minutes,
+            line,
+        });
+    }
+    tasks
+}
+
+/// A task scheduled from the planner, ticked off once its event is saved.
+pub struct ScheduledTask {
+    tasks_file: PathBuf,
+    task: PlannerTask,
+}
+
+impl ScheduledTask {
+    pub fn mark_done(&self) {
+        if let Err(e) = mark_task_done(&self.tasks_file, &self.task) {
+            log::error!("Failed to update {}: {}", self.tasks_file.display(), e);
+        }
+    }
+}
+
+/// Ticks a task off in its notes file, turning its line into "- [x] ...".
+fn mark_task_done(path: &Path, task: &PlannerTask) -> std::io::Result<()> {
+    let content = fs::read_to_string(path)?;
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    if let Some(line) = lines.get_mut(task.line) {
+        let text = line.trim();
+        let text = text
+            .strip_prefix("- [ ]")
+            .or_else(|| text.strip_prefix("- "))
+            .or_else(|| text.strip_prefix("* "))
+            .unwrap_or(text)
+            .trim();
+        *line = format!("- [x] {}", text);
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(path, content)
+}
+
+/// Gaps between today's timed events within the working hours, from now on.
+pub fn free_slots(
+    events: &[ColorEvent],
+    workday_start: NaiveTime,
+    workday_end: NaiveTime,
+) -> Vec<FreeSlot> {
+    let now = clock::now();
+    let today = now.date_naive();
+    let (Some(day_start), Some(day_end)) = (
+        today
+            .and_time(workday_start)
+            .and_local_timezone(Local)
+            .single(),
+        today
+            .and_time(workday_end)
+            .and_local_timezone(Local)
+            .single(),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut busy: Vec<(DateTime<Local>, DateTime<Local>)> = events
+        .iter()
+        .filter_map(|e| {
+            let start = parse_event_time(&e.event.start.date_time)?;
+            let end = parse_event_time(&e.event.end.date_time)?;
+            // All-day events don't block time
+            (end - start < Duration::hours(24) && end > day_start && start < day_end)
+                .then_some((start, end))
+        })
+        .collect();
+    busy.sort();
+
+    // Start planning at the next multiple of five minutes
+    let past_mark = (now.minute() % 5) as i64 * 60 + now.second() as i64;
+    let now = if past_mark > 0 {
+        now + Duration::seconds(300 - past_mark)
+    } else {
+        now
+    };
+    let now = now.with_nanosecond(0).unwrap();
+
+    let mut slots = Vec::new();
+    let mut cursor = day_start.max(now);
+    for (start, end) in busy {
+        if start > cursor {
+            slots.push(FreeSlot {
+                start: cursor,
+                end: start.min(day_end),
+            });
+        }
+        cursor = cursor.max(end);
+    }
+    if cursor < day_end {
+        slots.push(FreeSlot {
+            start: cursor,
+            end: day_end,
+        });
+    }
+    slots.retain(|slot| slot.minutes() >= MIN_SLOT_MINUTES);
+    slots
+}
+
+impl PlannerState {
+    pub fn new(tasks_file: PathBuf, tasks: Vec<PlannerTask>, slots: Vec<FreeSlot>) -> Self {
+        let select_first = |len: usize| {
+            let mut state = ListState::default();
+            state.select((len > 0).then_some(0));
+            state
+        };
+        PlannerState {
+            task_state: select_first(tasks.len()),
+            slot_state: select_first(slots.len()),
+            tasks_file,
+            tasks,
+            slots,
+            focus: PlannerPane::Tasks,
+        }
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            PlannerPane::Tasks => PlannerPane::Slots,
+            PlannerPane::Slots => PlannerPane::Tasks,
+        };
+    }
+
+    /// Moves the selection of the focused pane by `delta`, wrapping around.
+    pub fn move_selection(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            PlannerPane::Tasks => (&mut self.task_state, self.tasks.len()),
+            PlannerPane::Slots => (&mut self.slot_state, self.slots.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).rem_euclid(len as isize) as usize));
+    }
+
+    /// Indices of the selected task and slot.
+    fn selection(&self) -> Result<(usize, usize), String> {
+        let task_index = self.task_state.selected().ok_or("No task selected")?;
+        let slot_index = self.slot_state.selected().ok_or("No free slot selected")?;
+        if task_index >= self.tasks.len() || slot_index >= self.slots.len() {
+            return Err("Nothing to schedule".to_string());
+        }
+        Ok((task_index, slot_index))
+    }
+
+    /// The event `assign` would create, without scheduling anything yet.
+    pub fn next_event(&self) -> Result<NewEvent, String> {
+        let (task_index, slot_index) = self.selection()?;
+        let task = &self.tasks[task_index];
+        let slot = &self.slots[slot_index];
+        let end = (slot.start + Duration::minutes(task.minutes)).min(slot.end);
+        Ok(NewEvent {
+            subject: task.title.clone(),
+            start: format_utc(slot.start),
+            end: format_utc(end),
+            attendees: Vec::new(),
+            rooms: Vec::new(),
+        })
+    }
+
+    /// Schedules the selected task at the start of the selected slot. The task
+    /// leaves the list and the slot shrinks accordingly; it's ticked off in the
+    /// notes file with `ScheduledTask::mark_done` once the event is saved.
+    pub fn assign(&mut self) -> Result<(NewEvent, ScheduledTask), String> {
+        let new_event = self.next_event()?;
+        let (task_index, slot_index) = self.selection()?;
+        let task = &self.tasks[task_index];
+        let slot = &mut self.slots[slot_index];
+        let end = (slot.start + Duration::minutes(task.minutes)).min(slot.end);
+
+        slot.start = end;
+        if slot.minutes() < MIN_SLOT_MINUTES {
+            self.slots.remove(slot_index);
+        }
+        let scheduled = ScheduledTask {
+            tasks_file: self.tasks_file.clone(),
+            task: self.tasks.remove(task_index),
+        };
+
+        for (state, len) in [
+            (&mut self.task_state, self.tasks.len()),
+            (&mut self.slot_state, self.slots.len()),
+        ] {
+            state.select(match state.selected() {
+                _ if len == 0 => None,
+                Some(i) => Some(i.min(len - 1)),
+                None => Some(0),
+            });
+        }
+        Ok((new_event, scheduled))
+    }
+}
+
+/// Formats a time the way Graph returns event times (naive UTC).
+pub fn format_utc(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y-%m-%dT%H:%M:%S%.f")
+        .to_string()
+}
@@ -0,0 +1,173 @@
+use crate::api::GraphEvent;
+use crate::app::parse_event_time;
+use chrono::{Duration, NaiveDate};
+use std::collections::{HashMap, HashSet};
+
+/// How many collaborators the review lists.
+const TOP_COLLABORATORS: usize = 5;
+
+/// Summary of one week of meetings, for weekly retros.
+pub struct WeeklyReview {
+    pub week_start: NaiveDate,
+    pub meetings_attended: usize,
+    pub total_minutes: i64,
+    pub declined: Vec<String>,
+    pub busiest_day: Option<(NaiveDate, i64)>,
+    pub top_collaborators: Vec<(String, usize)>,
+}
+
+fn format_minutes(minutes: i64) -> String {
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+impl WeeklyReview {
+    /// Builds the review from the events of the week starting at `week_start`.
+    /// Meetings are events with attendees; all-day events are ignored.
+    pub fn from_events(
+        events: &[GraphEvent],
+        week_start: NaiveDate,
+        own_email: Option<&str>,
+    ) -> Self {
+        let week_end = week_start + Duration::days(7);
+        let own_email = own_email.map(str::to_lowercase);
+        let mut seen = HashSet::new();
+        let mut review = WeeklyReview {
+            week_start,
+            meetings_attended: 0,
+            total_minutes: 0,
+            declined: Vec::new(),
+            busiest_day: None,
+            top_collaborators: Vec::new(),
+        };
+        let mut minutes_per_day: HashMap<NaiveDate, i64> = HashMap::new();
+        let mut collaborators: HashMap<String, (String, usize)> = HashMap::new();
+
+        for event in events {
+            // The same meeting shows up once per calendar it's on
+            if !seen.insert(event.correlation_id()) {
+                continue;
+            }
+
+            let (Some(start), Some(end)) = (
+                parse_event_time(&event.start.date_time),
+                parse_event_time(&event.end.date_time),
+            ) else {
+                continue;
+            };
+            let day = start.date_naive();
+            if day < week_start || day >= week_end || end - start >= Duration::hours(24) {
+                continue;
+            }
+            if event.attendees.is_empty() {
+                continue;
+            }
+
+            let response = event
+                .response_status
+                .as_ref()
+                .map(|r| r.response.as_str())
+                .unwrap_or("none");
+            if response == "declined" {
+                review.declined.push(event.subject.clone());
+                continue;
+            }
+
+            let minutes = (end - start).num_minutes();
+            review.meetings_attended += 1;
+            review.total_minutes += minutes;
+            *minutes_per_day.entry(day).or_default() += minutes;
+
+            for attendee in &event.attendees {
+                let Some(email) = &attendee.email_address else {
+                    continue;
+                };
+                let address = email.address.to_lowercase();
+                if own_email.as_deref() == Some(address.as_str()) {
+                    continue;
+                }
+                let entry = collaborators
+                    .entry(address)
+                    .or_insert_with(|| (email.name.clone(), 0));
+                entry.1 += 1;
+            }
+        }
+
+        review.busiest_day = minutes_per_day
+            .into_iter()
+            .max_by_key(|(day, minutes)| (*minutes, std::cmp::Reverse(*day)));
+
+        let mut collaborators: Vec<(String, usize)> = collaborators.into_values().collect();
+        collaborators.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        collaborators.truncate(TOP_COLLABORATORS);
+        review.top_collaborators = collaborators;
+        review
+    }
+
+    fn week_label(&self) -> String {
+        format!(
+            "{} to {}",
+            self.week_start.format("%a %d %b %Y"),
+            (self.week_start + Duration::days(6)).format("%a %d %b %Y")
+        )
+    }
+
+    fn busiest_day_label(&self) -> String {
+        match self.busiest_day {
+            Some((day, minutes)) => format!("{} ({})", day.format("%A"), format_minutes(minutes)),
+            None => "-".to_string(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!("Weekly review: {}\n\n", self.week_label());
+        out.push_str(&format!("Meetings attended: {}\n", self.meetings_attended));
+        out.push_str(&format!(
+            "Time in meetings:  {}\n",
+            format_minutes(self.total_minutes)
+        ));
+        out.push_str(&format!("Declined invites:  {}\n", self.declined.len()));
+        out.push_str(&format!(
+            "Busiest day:       {}\n",
+            self.busiest_day_label()
+        ));
+        out.push_str("\nTop collaborators:\n");
+        if self.top_collaborators.is_empty() {
+            out.push_str("  -\n");
+        }
+        for (name, count) in &self.top_collaborators {
+            out.push_str(&format!("  {} ({} meetings)\n", name, count));
+        }
+        out
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Weekly review: {}\n\n", self.week_label());
+        out.push_str("| Metric | Value |\n|---|---|\n");
+        out.push_str(&format!(
+            "| Meetings attended | {} |\n",
+            self.meetings_attended
+        ));
+        out.push_str(&format!(
+            "| Time in meetings | {} |\n",
+            format_minutes(self.total_minutes)
+        ));
+        out.push_str(&format!("| Declined invites | {} |\n", self.declined.len()));
+        out.push_str(&format!("| Busiest day | {} |\n", self.busiest_day_label()));
+
+        out.push_str("\n## Top collaborators\n\n");
+        if self.top_collaborators.is_empty() {
+            out.push_str("_None_\n");
+        }
+        for (name, count) in &self.top_collaborators {
+            out.push_str(&format!("- {} ({} meetings)\n", name, count));
+        }
+
+        if !self.declined.is_empty() {
+            out.push_str("\n## Declined\n\n");
+            for subject in &self.declined {
+                out.push_str(&format!("- {}\n", subject));
+            }
+        }
+        out
+    }
+}
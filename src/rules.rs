@@ -0,0 +1,286 @@
+use crate::api::{respond_to_event, GraphEvent};
+use crate::app::parse_event_time;
+use crate::clock;
+use crate::config::RuleConfig;
+use log::{error, info, warn};
+use regex::Regex;
+use sqlx::sqlite::SqlitePool;
+
+/// How a rule answers an invite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleAction {
+    Accept,
+    Tentative,
+    Decline,
+}
+
+impl RuleAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "accept" => Some(RuleAction::Accept),
+            "tentative" => Some(RuleAction::Tentative),
+            "decline" => Some(RuleAction::Decline),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleAction::Accept => "accept",
+            RuleAction::Tentative => "tentative",
+            RuleAction::Decline => "decline",
+        }
+    }
+
+    /// The Graph endpoint that sends this response.
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            RuleAction::Accept => "accept",
+            RuleAction::Tentative => "tentativelyAccept",
+            RuleAction::Decline => "decline",
+        }
+    }
+
+    /// The `responseStatus.response` value an event has after this action.
+    pub fn response(&self) -> &'static str {
+        match self {
+            RuleAction::Accept => "accepted",
+            RuleAction::Tentative => "tentativelyAccepted",
+            RuleAction::Decline => "declined",
+        }
+    }
+}
+
+/// A compiled `[[rules]]` entry. Every pattern that is set must match.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub description: String,
+    subject: Option<Regex>,
+    organizer: Option<Regex>,
+    pub action: RuleAction,
+    pub send_response: bool,
+}
+
+impl Rule {
+    fn matches(&self, event: &GraphEvent) -> bool {
+        let subject_matches = self
+            .subject
+            .as_ref()
+            .is_none_or(|re| re.is_match(&event.subject));
+        let organizer_matches = self.organizer.as_ref().is_none_or(|re| {
+            event.organizer.as_ref().is_some_and(|o| {
+                re.is_match(&o.email_address.address) || re.is_match(&o.email_address.name)
+            })
+        });
+        subject_matches && organizer_matches
+    }
+}
+
+/// A rule's pattern, if set. An empty one would match every invite, so it
+/// counts as unset.
+pub fn set_pattern(pattern: &Option<String>) -> Option<&str> {
+    pattern.as_deref().filter(|p| !p.trim().is_empty())
+}
+
+/// Compiles the configured rules, skipping (and logging) invalid ones.
+pub fn compile_rules(configs: &[RuleConfig]) -> Vec<Rule> {
+    let compile = |pattern: &Option<String>| -> Result<Option<Regex>, regex::Error> {
+        set_pattern(pattern).map(Regex::new).transpose()
+    };
+
+    let mut rules = Vec::new();
+    for (i, config) in configs.iter().enumerate() {
+        let Some(action) = RuleAction::from_name(&config.action) else {
+            warn!("Rule {}: unknown action \"{}\"", i + 1, config.action);
+            continue;
+        };
+        if set_pattern(&config.rule_match.subject).is_none()
+            && set_pattern(&config.rule_match.organizer).is_none()
+        {
+            warn!(
+                "Rule {}: no match.subject or match.organizer, ignoring",
+                i + 1
+            );
+            continue;
+        }
+        let (subject, organizer) = match (
+            compile(&config.rule_match.subject),
+            compile(&config.rule_match.organizer),
+        ) {
+            (Ok(subject), Ok(organizer)) => (subject, organizer),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Rule {}: invalid pattern: {}", i + 1, e);
+                continue;
+            }
+        };
+
+        let mut description = Vec::new();
+        if let Some(pattern) = set_pattern(&config.rule_match.subject) {
+            description.push(format!("subject =~ {}", pattern));
+        }
+        if let Some(pattern) = set_pattern(&config.rule_match.organizer) {
+            description.push(format!("organizer =~ {}", pattern));
+        }
+        rules.push(Rule {
+            description: format!("{} -> {}", description.join(", "), action.name()),
+            subject,
+            organizer,
+            action,
+            send_response: config.send_response.unwrap_or(true),
+        });
+    }
+    rules
+}
+
+/// Runs the rules over freshly synced events. Only invites nobody has answered
+/// yet, that no rule has handled before and that haven't ended are considered,
+/// so answering an invite by hand (or undoing a rule) is never overridden and
+/// old invites in a synced window aren't answered after the fact. Returns how many
+/// invites were answered; the events are updated in place.
+pub async fn apply_rules(
+    rules: &[Rule],
+    access_token: &str,
+    pool: &SqlitePool,
+    events: &mut [GraphEvent],
+) -> usize {
+    // Answering needs write access
+    if rules.is_empty() || !crate::auth::can_write() {
+        return 0;
+    }
+
+    let mut applied = 0;
+    for event in events.iter_mut() {
+        let Some(status) = &event.response_status else {
+            continue;
+        };
+        if status.response != "notResponded" {
+            continue;
+        }
+        let now = clock::now();
+        if parse_event_time(&event.end.date_time).is_none_or(|end| end <= now) {
+            continue;
+        }
+        let Some(rule) = rules.iter().find(|rule| rule.matches(event)) else {
+            continue;
+        };
+        match crate::db::rule_action_exists(pool, &event.id).await {
+            Ok(false) => {}
+            Ok(true) => continue,
+            Err(e) => {
+                error!("Failed to read the rule log: {}", e);
+                continue;
+            }
+        }
+
+        if let Err(e) = respond_to_event(
+            access_token,
+            &event.id,
+            rule.action.endpoint(),
+            rule.send_response,
+        )
+        .await
+        {
+            error!(
+                "Rule \"{}\" failed on \"{}\": {}",
+                rule.description, event.subject, e
+            );
+            continue;
+        }
+        info!(
+            "Rule \"{}\" answered \"{}\"",
+            rule.description, event.subject
+        );
+        if let Err(e) = crate::db::log_rule_action(
+            pool,
+            &event.id,
+            &event.subject,
+            &rule.description,
+            rule.action.name(),
+        )
+        .await
+        {
+            error!("Failed to write the rule log: {}", e);
+        }
+        if let Some(status) = event.response_status.as_mut() {
+            status.response = rule.action.response().to_string();
+        }
+        applied += 1;
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RuleMatch;
+
+    fn rule(subject: Option<&str>, organizer: Option<&str>) -> RuleConfig {
+        RuleConfig {
+            rule_match: RuleMatch {
+                subject: subject.map(str::to_string),
+                organizer: organizer.map(str::to_string),
+            },
+            action: "decline".to_string(),
+            send_response: None,
+        }
+    }
+
+    fn invite(subject: &str, organizer_name: &str, organizer_address: &str) -> GraphEvent {
+        serde_json::from_value(serde_json::json!({
+            "id": "event",
+            "subject": subject,
+            "start": { "dateTime": "2025-06-03T09:00:00.0000000", "timeZone": "UTC" },
+            "end": { "dateTime": "2025-06-03T10:00:00.0000000", "timeZone": "UTC" },
+            "organizer": {
+                "emailAddress": { "name": organizer_name, "address": organizer_address }
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn organizer_pattern_matches_the_name_or_the_address() {
+        let rules = compile_rules(&[
+            rule(None, Some("^Ana Lima$")),
+            rule(None, Some("@vendor\\.example$")),
+        ]);
+        assert_eq!(rules.len(), 2);
+
+        let by_name = invite("Sync", "Ana Lima", "ana@corp.example");
+        assert!(rules[0].matches(&by_name));
+        assert!(!rules[1].matches(&by_name));
+
+        let by_address = invite("Sync", "Sales", "sales@vendor.example");
+        assert!(!rules[0].matches(&by_address));
+        assert!(rules[1].matches(&by_address));
+    }
+
+    #[test]
+    fn every_set_pattern_must_match() {
+        let rules = compile_rules(&[rule(Some("(?i)optional"), Some("@vendor\\.example$"))]);
+
+        assert!(rules[0].matches(&invite("Optional: demo", "Sales", "sales@vendor.example")));
+        assert!(!rules[0].matches(&invite("Demo", "Sales", "sales@vendor.example")));
+        assert!(!rules[0].matches(&invite("Optional: demo", "Ana", "ana@corp.example")));
+    }
+
+    #[test]
+    fn invalid_and_empty_patterns_are_skipped() {
+        let mut unknown_action = rule(Some("Standup"), None);
+        unknown_action.action = "ignore".to_string();
+        let rules = compile_rules(&[
+            rule(Some("(unclosed"), None),
+            rule(Some("Standup"), Some("[z-a]")),
+            rule(Some(""), None),
+            rule(Some("  "), Some("")),
+            rule(None, None),
+            unknown_action,
+            rule(Some("Standup"), Some("")),
+        ]);
+
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].description, "subject =~ Standup -> decline");
+        // The empty organizer pattern doesn't restrict the rule
+        assert!(rules[0].matches(&invite("Standup", "Ana", "ana@corp.example")));
+    }
+}
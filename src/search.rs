@@ -0,0 +1,170 @@
+//! Event search. Matches from the local cache show up immediately; Graph is
+//! asked too so events outside the synced window can be found, unless the
+//! search is limited to the cache (`Ctrl+F`).
+
+use crate::api::GraphEvent;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use ratatui::widgets::ListState;
+
+/// Most results listed per source.
+pub const MAX_SEARCH_RESULTS: usize = 50;
+
+/// Search text with an optional date range, typed as
+/// "budget review from:2025-03-01 to:2025-03-31".
+#[derive(Clone, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let parse_date = |value: &str| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date \"{}\", use YYYY-MM-DD", value))
+        };
+
+        let mut words = Vec::new();
+        let mut from = None;
+        let mut to = None;
+        for word in input.split_whitespace() {
+            if let Some(value) = word.strip_prefix("from:") {
+                from = Some(parse_date(value)?);
+            } else if let Some(value) = word.strip_prefix("to:") {
+                to = Some(parse_date(value)?);
+            } else {
+                words.push(word);
+            }
+        }
+        if words.is_empty() {
+            return Err("Enter something to search for.".to_string());
+        }
+        Ok(SearchQuery {
+            text: words.join(" "),
+            from,
+            to,
+        })
+    }
+
+    /// Start of the range, in UTC.
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        self.from.map(local_midnight)
+    }
+
+    /// End of the range (exclusive), in UTC.
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        self.to.and_then(|to| to.succ_opt()).map(local_midnight)
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_local_timezone(Local)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// Where a search result came from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SearchSource {
+    Cached,
+    Server,
+}
+
+impl SearchSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchSource::Cached => "cached",
+            SearchSource::Server => "server",
+        }
+    }
+}
+
+pub struct SearchResult {
+    pub event: GraphEvent,
+    pub source: SearchSource,
+}
+
+/// State of the search results popup. `server_pending` is set while Graph
+/// hasn't answered yet.
+pub struct SearchState {
+    pub query: SearchQuery,
+    pub cache_only: bool,
+    pub results: Vec<SearchResult>,
+    pub cache_pending: bool,
+    pub server_pending: bool,
+    pub server_error: Option<String>,
+    pub list_state: ListState,
+}
+
+impl SearchState {
+    pub fn new(query: SearchQuery, cache_only: bool) -> Self {
+        SearchState {
+            query,
+            cache_only,
+            results: Vec::new(),
+            cache_pending: true,
+            server_pending: !cache_only,
+            server_error: None,
+            list_state: ListState::default(),
+        }
+    }
+
+    /// Adds the matches from the local cache. They always arrive before the
+    /// server's.
+    pub fn merge_cached_results(&mut self, events: Vec<GraphEvent>) {
+        self.cache_pending = false;
+        self.add_results(events, SearchSource::Cached);
+    }
+
+    /// Adds the events Graph found that aren't already listed from the cache.
+    pub fn merge_server_results(&mut self, result: Result<Vec<GraphEvent>, String>) {
+        self.server_pending = false;
+        match result {
+            Ok(events) => {
+                let new_events: Vec<GraphEvent> = events
+                    .into_iter()
+                    .filter(|e| !self.results.iter().any(|r| r.event.id == e.id))
+                    .collect();
+                self.add_results(new_events, SearchSource::Server);
+            }
+            Err(e) => self.server_error = Some(e),
+        }
+    }
+
+    fn add_results(&mut self, events: Vec<GraphEvent>, source: SearchSource) {
+        let selected_id = self.selected().map(|r| r.event.id.clone());
+        self.results.extend(
+            events
+                .into_iter()
+                .map(|event| SearchResult { event, source }),
+        );
+        // Most recent first, the way people remember meetings
+        self.results
+            .sort_by(|a, b| b.event.start.date_time.cmp(&a.event.start.date_time));
+        // Keep the selected result under the cursor while more arrive
+        if let Some(index) =
+            selected_id.and_then(|id| self.results.iter().position(|r| r.event.id == id))
+        {
+            self.list_state.select(Some(index));
+        } else if !self.results.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    pub fn selected(&self) -> Option<&SearchResult> {
+        self.list_state.selected().and_then(|i| self.results.get(i))
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.results.len();
+        if len == 0 {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        self.list_state
+            .select(Some((current + delta).rem_euclid(len as isize) as usize));
+    }
+}
@@ -0,0 +1,187 @@
+//! Read-only SQL against the event cache, for questions the views don't
+//! answer ("how many meetings with Ana this quarter?"). Queries run on their
+//! own read-only connection with `query_only` set, so nothing typed here can
+//! change the cache, and only single SELECT statements are accepted. A query
+//! is stopped after `QUERY_TIMEOUT`, or when Esc is pressed.
+
+use futures::TryStreamExt;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Column, ConnectOptions, Executor, Row, Statement, TypeInfo, ValueRef};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Most rows shown for one query.
+pub const MAX_ROWS: usize = 500;
+
+/// How long a query may run before it is stopped.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// SQLite instructions run between checks whether to stop the query.
+const PROGRESS_STEPS: i32 = 10_000;
+
+/// The outcome of a query, as text ready for a table.
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// More rows matched than `MAX_ROWS`.
+    pub truncated: bool,
+}
+
+/// State of the console popup.
+#[derive(Default)]
+pub struct SqlConsole {
+    pub input: String,
+    pub result: Option<Result<QueryResult, String>>,
+    /// First result row shown.
+    pub scroll: usize,
+    pub running: bool,
+    /// Set to stop the running query.
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl SqlConsole {
+    pub fn scroll_by(&mut self, delta: isize) {
+        let rows = match &self.result {
+            Some(Ok(result)) => result.rows.len(),
+            _ => 0,
+        };
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(rows.saturating_sub(1));
+    }
+
+    /// Stops the running query, if any.
+    pub fn interrupt(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether `sql` has a `;` outside string literals and quoted names.
+fn has_separator(sql: &str) -> bool {
+    let mut quote = None;
+    for c in sql.chars() {
+        match (quote, c) {
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '[') => quote = Some(']'),
+            (None, ';') => return true,
+            // A doubled quote inside a string ends it and starts it again
+            (Some(end), c) if c == end => quote = None,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Checks that `sql` is one SELECT (or WITH ... SELECT) statement.
+fn check_query(sql: &str) -> Result<&str, String> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    let first_word = sql
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if first_word != "select" && first_word != "with" {
+        return Err("Only SELECT queries can be run here.".to_string());
+    }
+    if has_separator(sql) {
+        return Err("Run one statement at a time.".to_string());
+    }
+    Ok(sql)
+}
+
+fn cell_text(row: &sqlx::sqlite::SqliteRow, index: usize) -> String {
+    let Ok(value) = row.try_get_raw(index) else {
+        return String::new();
+    };
+    if value.is_null() {
+        return "NULL".to_string();
+    }
+    match value.type_info().name() {
+        "INTEGER" => row.try_get::<i64, _>(index).map(|v| v.to_string()),
+        "REAL" => row.try_get::<f64, _>(index).map(|v| v.to_string()),
+        "BLOB" => row
+            .try_get::<Vec<u8>, _>(index)
+            .map(|v| format!("<{} bytes>", v.len())),
+        _ => row.try_get::<String, _>(index),
+    }
+    .unwrap_or_default()
+}
+
+/// Runs a read-only query against the cache, until `cancel` is set or
+/// `QUERY_TIMEOUT` is over.
+pub async fn run_query(sql: &str, cancel: Arc<AtomicBool>) -> Result<QueryResult, String> {
+    let sql = check_query(sql)?;
+    let mut connection = SqliteConnectOptions::new()
+        .filename(crate::config::get_db_path())
+        .read_only(true)
+        .pragma("query_only", "ON")
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?;
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    let stop = cancel.clone();
+    connection
+        .lock_handle()
+        .await
+        .map_err(|e| e.to_string())?
+        .set_progress_handler(PROGRESS_STEPS, move || {
+            !stop.load(Ordering::Relaxed) && Instant::now() < deadline
+        });
+    let stopped = |e: sqlx::Error| {
+        if cancel.load(Ordering::Relaxed) {
+            "Query stopped.".to_string()
+        } else if Instant::now() >= deadline {
+            format!("Query stopped after {} seconds.", QUERY_TIMEOUT.as_secs())
+        } else {
+            e.to_string()
+        }
+    };
+
+    // From the statement, so a query without rows still has its header
+    let columns = (&mut connection)
+        .prepare(sql)
+        .await
+        .map_err(stopped)?
+        .columns()
+        .iter()
+        .map(|c| c.name().to_string())
+        .collect();
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut stream = sqlx::query(sql).fetch(&mut connection);
+    while let Some(row) = stream.try_next().await.map_err(stopped)? {
+        if rows.len() == MAX_ROWS {
+            truncated = true;
+            break;
+        }
+        rows.push((0..row.len()).map(|i| cell_text(&row, i)).collect());
+    }
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semicolons_in_strings_are_allowed() {
+        assert_eq!(
+            check_query("SELECT subject FROM events WHERE subject LIKE '%a;b%';"),
+            Ok("SELECT subject FROM events WHERE subject LIKE '%a;b%'")
+        );
+        assert!(check_query("SELECT 'it''s; fine', \"odd;name\" FROM events").is_ok());
+    }
+
+    #[test]
+    fn one_statement_at_a_time() {
+        assert!(check_query("SELECT 1; SELECT 2").is_err());
+        assert!(check_query("SELECT ';'; DELETE FROM events").is_err());
+        assert!(check_query("DELETE FROM events").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::api::GraphEvent;
+use crate::auth::keyring_service;
+use crate::clock;
+use crate::config::StatusIntegrationConfig;
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Utc};
+use keyring::Entry;
+use log::{error, info, warn};
+
+#[derive(Clone)]
+enum StatusProvider {
+    Slack,
+    Zulip { site: String, email: String },
+}
+
+/// Mirrors the currently running event into the user's Slack or Zulip status.
+pub struct StatusManager {
+    provider: Option<StatusProvider>,
+    token: Option<String>,
+    emoji: String,
+    active_event: Option<String>,
+}
+
+fn keyring_username(provider: &str) -> Option<&'static str> {
+    match provider.to_lowercase().as_str() {
+        "slack" => Some("slack_token"),
+        "zulip" => Some("zulip_api_key"),
+        _ => None,
+    }
+}
+
+pub fn save_token(
+    config: &StatusIntegrationConfig,
+    token: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let username = keyring_username(&config.provider)
+        .ok_or_else(|| format!("Unknown status provider '{}'", config.provider))?;
+    let entry = Entry::new(&keyring_service(), username)?;
+    entry.set_password(token)?;
+    Ok(())
+}
+
+fn load_token(provider: &str) -> Option<String> {
+    let username = keyring_username(provider)?;
+    Entry::new(&keyring_service(), username)
+        .ok()?
+        .get_password()
+        .ok()
+}
+
+impl StatusManager {
+    pub fn new(config: Option<StatusIntegrationConfig>) -> Self {
+        let mut manager = Self {
+            provider: None,
+            token: None,
+            emoji: ":calendar:".to_string(),
+            active_event: None,
+        };
+
+        let Some(config) = config else {
+            return manager;
+        };
+
+        let provider = match config.provider.to_lowercase().as_str() {
+            "slack" => Some(StatusProvider::Slack),
+            "zulip" => match (&config.zulip_site, &config.zulip_email) {
+                (Some(site), Some(email)) => Some(StatusProvider::Zulip {
+                    site: site.trim_end_matches('/').to_string(),
+                    email: email.clone(),
+                }),
+                _ => {
+                    warn!("Zulip status integration requires zulip_site and zulip_email.");
+                    None
+                }
+            },
+            other => {
+                warn!("Unknown status integration provider: {}", other);
+                None
+            }
+        };
+
+        manager.token = load_token(&config.provider);
+        if provider.is_some() && manager.token.is_none() {
+            warn!("Status integration enabled but no token found in the keyring.");
+        }
+        manager.provider = provider;
+        if let Some(emoji) = config.emoji {
+            manager.emoji = emoji;
+        }
+        manager
+    }
+
+    /// Called periodically with the loaded events; sets the status when an event
+    /// starts and clears it once no event is running anymore.
+    pub fn update(&mut self, events: &[GraphEvent]) {
+        if self.provider.is_none() || self.token.is_none() {
+            return;
+        }
+
+        let now = clock::now_utc();
+        let current = events.iter().find_map(|event| {
+            let start =
+                NaiveDateTime::parse_from_str(&event.start.date_time, "%Y-%m-%dT%H:%M:%S%.f")
+                    .ok()?;
+            let end =
+                NaiveDateTime::parse_from_str(&event.end.date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+            let start = DateTime::<Utc>::from_naive_utc_and_offset(start, Utc);
+            let end = DateTime::<Utc>::from_naive_utc_and_offset(end, Utc);
+
+            // All-day events would otherwise keep the status set for the whole day.
+            if start <= now && now < end && end - start < Duration::hours(24) {
+                Some((event.id.clone(), end))
+            } else {
+                None
+            }
+        });
+
+        match current {
+            Some((id, end)) if self.active_event.as_ref() != Some(&id) => {
+                let text = format!(
+                    "In a meeting until {}",
+                    end.with_timezone(&Local).format("%H:%M")
+                );
+                info!("Setting chat status: {}", text);
+                self.spawn_push(Some((text, end.timestamp())));
+                self.active_event = Some(id);
+            }
+            None if self.active_event.is_some() => {
+                info!("Clearing chat status.");
+                self.spawn_push(None);
+                self.active_event = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Clears a status we set, waiting for the request so it completes before exit.
+    pub async fn clear(&mut self) {
+        if self.active_event.take().is_none() {
+            return;
+        }
+        if let (Some(provider), Some(token)) = (&self.provider, &self.token) {
+            if let Err(e) = push_status(provider, token, &self.emoji, None).await {
+                error!("Failed to clear chat status: {}", e);
+            }
+        }
+    }
+
+    fn spawn_push(&self, status: Option<(String, i64)>) {
+        if let (Some(provider), Some(token)) = (self.provider.clone(), self.token.clone()) {
+            let emoji = self.emoji.clone();
+            tokio::spawn(async move {
+                if let Err(e) = push_status(&provider, &token, &emoji, status).await {
+                    error!("Failed to update chat status: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn push_status(
+    provider: &StatusProvider,
+    token: &str,
+    emoji: &str,
+    status: Option<(String, i64)>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let client = crate::http::client();
+    let (text, emoji, expiration) = match &status {
+        Some((text, expiration)) => (text.as_str(), emoji, *expiration),
+        None => ("", "", 0),
+    };
+
+    match provider {
+        StatusProvider::Slack => {
+            let body = serde_json::json!({
+                "profile": {
+                    "status_text": text,
+                    "status_emoji": emoji,
+                    "status_expiration": expiration,
+                }
+            });
+            let response: serde_json::Value = client
+                .post("https://slack.com/api/users.profile.set")
+                .bearer_auth(token)
+                .json(&body)
+                .send()
+                .await?
+                .json()
+                .await?;
+            if response["ok"].as_bool() != Some(true) {
+                return Err(format!("Slack API error: {}", response["error"]).into());
+            }
+        }
+        StatusProvider::Zulip { site, email } => {
+            client
+                .post(format!("{}/api/v1/users/me/status", site))
+                .basic_auth(email, Some(token))
+                .form(&[
+                    ("status_text", text),
+                    ("emoji_name", emoji.trim_matches(':')),
+                ])
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Read-only calendars from .ics feeds set up in `[[subscriptions]]`. Their
+//! IDs start with "ics:" followed by the URL. A feed is downloaded at most
+//! every `refresh_minutes` and read again for each date window synced, so
+//! its events land in the cache like any other calendar's.
+
+use crate::api::{GraphCalendar, GraphEvent};
+use crate::config::SubscriptionConfig;
+use chrono::{DateTime, Utc};
+use log::info;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Start of the IDs of subscribed calendars.
+pub const ID_PREFIX: &str = "ics:";
+
+const DEFAULT_REFRESH_MINUTES: u64 = 60;
+
+static SUBSCRIPTIONS: OnceLock<Vec<SubscriptionConfig>> = OnceLock::new();
+
+/// Downloaded feeds by URL, with when they were downloaded.
+type Feeds = HashMap<String, (Instant, Arc<String>)>;
+
+static FEEDS: Mutex<Option<Feeds>> = Mutex::new(None);
+
+/// Whether a calendar or event comes from a subscription.
+pub fn is_subscription_id(id: &str) -> bool {
+    id.starts_with(ID_PREFIX)
+}
+
+/// Uses the subscriptions set up in Settings.toml and returns their
+/// calendars. Later calls keep the first subscriptions.
+pub fn configure(subscriptions: &[SubscriptionConfig]) -> Vec<GraphCalendar> {
+    let _ = SUBSCRIPTIONS.set(subscriptions.to_vec());
+    subscriptions
+        .iter()
+        .map(|subscription| GraphCalendar {
+            id: format!("{}{}", ID_PREFIX, subscription.url),
+            name: subscription.name.clone(),
+            can_share: Some(false),
+            hex_color: subscription.color.clone(),
+            is_default_calendar: Some(false),
+            owner: None,
+        })
+        .collect()
+}
+
+/// webcal:// is HTTP under another name.
+fn feed_url(url: &str) -> String {
+    let lowercase = url.to_ascii_lowercase();
+    if lowercase.starts_with("webcals://") {
+        format!("https://{}", &url["webcals://".len()..])
+    } else if lowercase.starts_with("webcal://") {
+        format!("https://{}", &url["webcal://".len()..])
+    } else {
+        url.to_string()
+    }
+}
+
+/// The feed's text, downloaded again when the last download is older than
+/// the subscription's refresh interval.
+async fn feed(subscription: &SubscriptionConfig) -> Result<Arc<String>, Error> {
+    let refresh = Duration::from_secs(
+        60 * subscription
+            .refresh_minutes
+            .unwrap_or(DEFAULT_REFRESH_MINUTES),
+    );
+    let cached = FEEDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|feeds| feeds.get(&subscription.url).cloned());
+    if let Some((downloaded, text)) = cached {
+        if downloaded.elapsed() < refresh {
+            return Ok(text);
+        }
+    }
+
+    let text = crate::http::client()
+        .get(feed_url(&subscription.url))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    info!(
+        "Downloaded the \"{}\" feed ({} bytes)",
+        subscription.name,
+        text.len()
+    );
+    let text = Arc::new(text);
+    FEEDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .insert(subscription.url.clone(), (Instant::now(), text.clone()));
+    Ok(text)
+}
+
+/// The events of a subscribed calendar overlapping the range.
+pub async fn list_events(
+    calendar_id: &str,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<GraphEvent>, Error> {
+    let url = calendar_id.strip_prefix(ID_PREFIX).unwrap_or(calendar_id);
+    let subscription = SUBSCRIPTIONS
+        .get()
+        .and_then(|subscriptions| subscriptions.iter().find(|s| s.url == url))
+        .ok_or("No such subscription in Settings.toml")?;
+    let text = feed(subscription).await?;
+    Ok(crate::ics::read_feed(
+        &text,
+        calendar_id,
+        start_date,
+        end_date,
+    ))
+}
@@ -0,0 +1,104 @@
+//! Refresh token storage for systems without a keyring (containers, minimal
+//! window managers without a Secret Service). The token is encrypted with
+//! ChaCha20-Poly1305 into a file in the config directory (`token.enc` for the
+//! Microsoft login); the key is kept apart from it, in the local data
+//! directory, so a copied or synced config directory doesn't give the token
+//! away. Every token file shares the one key.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+fn token_path(file_name: &str) -> PathBuf {
+    crate::config::get_data_path(file_name)
+}
+
+fn key_path() -> PathBuf {
+    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("365cal-tui");
+    if let Some(profile) = crate::config::profile() {
+        path.push("profiles");
+        path.push(profile);
+    }
+    path.join("token.key")
+}
+
+/// Writes a file only the current user can read.
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)?;
+    Ok(())
+}
+
+/// The encryption key, created on first use.
+fn load_or_create_key() -> Result<Key, Error> {
+    let path = key_path();
+    match fs::read(&path) {
+        Ok(bytes) if bytes.len() == KEY_LEN => Ok(*Key::from_slice(&bytes)),
+        Ok(_) => Err(format!("{} is not a valid key file", path.display()).into()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            write_private(&path, &key)?;
+            Ok(key)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save(file_name: &str, refresh_token: &str) -> Result<(), Error> {
+    let cipher = ChaCha20Poly1305::new(&load_or_create_key()?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, refresh_token.as_bytes())
+        .map_err(|_| "Could not encrypt the refresh token")?;
+    let mut contents = nonce.to_vec();
+    contents.extend_from_slice(&ciphertext);
+    write_private(&token_path(file_name), &contents)
+}
+
+/// The stored token; `None` when there is none or it can't be decrypted
+/// (e.g. the key file was lost), which means logging in again.
+pub fn load(file_name: &str) -> Option<String> {
+    let contents = fs::read(token_path(file_name)).ok()?;
+    if contents.len() <= NONCE_LEN {
+        return None;
+    }
+    let key = fs::read(key_path()).ok().filter(|k| k.len() == KEY_LEN)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let (nonce, ciphertext) = contents.split_at(NONCE_LEN);
+    let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()?;
+    String::from_utf8(plaintext).ok()
+}
+
+fn remove(path: &Path) -> Result<(), Error> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+pub fn delete(file_name: &str) -> Result<(), Error> {
+    remove(&token_path(file_name))
+}
+
+/// Deletes the token and the key, when logging out for good.
+pub fn forget(file_name: &str) -> Result<(), Error> {
+    remove(&token_path(file_name))?;
+    remove(&key_path())
+}
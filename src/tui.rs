@@ -1,122 +1,1224 @@
 use crate::{
-    api::list_events,
-    app::{App, ColorEvent, CurrentView, EventViewMode, MY_CALENDARS_ID},
-    ui::ui,
+    api::{
+        create_event, find_meeting_times, find_rooms, get_event_body, get_meeting_artifacts,
+        get_schedule, list_due_tasks, list_events_deltas, search_events, update_event, BatchError,
+        EventDelta, GraphEvent, NewEvent,
+    },
+    app::{
+        display_subject, parse_event_time, App, ColorCalendar, ColorEvent, CurrentView,
+        EventEditPrompt, EventViewMode, InputAction, NewEventTarget, RoomOption, ToastKind,
+    },
+    clock,
+    notifications::{NotificationAction, NotificationSound},
+    planner::ScheduledTask,
+    rules::{apply_rules, Rule},
+    search::{SearchQuery, SearchState, MAX_SEARCH_RESULTS},
+    ui::{footer::breadcrumb_text, main_layout, tab_labels, text::wrapped_height, ui},
     AppEvent,
 };
 use chrono::{
-    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, Utc, Weekday,
+    DateTime, Datelike, Duration as ChronoDuration, Local, Months, NaiveDate, NaiveDateTime, Utc,
+    Weekday,
+};
+use crossterm::{
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyModifiers,
+        MouseButton, MouseEventKind,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use crossterm::event::{self, Event as CEvent, KeyCode, MouseButton, MouseEventKind};
-use futures::future::join_all;
 use log::{error, info, warn};
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
+    style::Color,
     terminal::Terminal,
 };
-use unicode_width::UnicodeWidthStr;
 use std::io;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use tokio::sync::mpsc;
+use unicode_width::UnicodeWidthStr;
 
-/// Asynchronously fetches events and handles token refresh logic.
-async fn refresh_events(app: &mut App, tx: mpsc::Sender<AppEvent>) {
-    let calendars_to_fetch = if let Some(id) = &app.current_calendar_id {
-        if id == MY_CALENDARS_ID {
-            app.calendars
-                .iter()
-                .filter(|c| c.calendar.can_share.unwrap_or(false))
-                .cloned()
-                .collect()
-        } else {
-            app.calendars
-                .iter()
-                .filter(|c| c.calendar.id == *id)
-                .cloned()
-                .collect()
-        }
-    } else {
-        app.calendars.clone()
-    };
+/// How long the screen stays inverted for a `terminal_alert = "flash"`.
+const FLASH_DURATION: Duration = Duration::from_millis(400);
+
+/// How long the sync lease lasts without being renewed. Instances sharing the
+/// database take over once the syncing one has been gone this long.
+pub const LEASE_TTL: ChronoDuration = ChronoDuration::seconds(90);
+
+/// How often the lease is renewed, and viewers look for fresh data.
+const LEASE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// `app_state` key the syncing instance bumps after writing new events.
+const EVENTS_CHANGED_KEY: &str = "events_changed_at";
+
+/// Why the due tasks could not be loaded when Graph turned the login down.
+const TASKS_NOT_GRANTED: &str = "Your login doesn't grant the Tasks.Read permission.";
+
+/// How far the wall clock must run ahead of the monotonic clock between two
+/// loop iterations to be treated as a resume from sleep.
+const WAKE_DETECTION_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// Loads the selected calendars' events, first from the local database and
+/// then from Graph. Both run on a background task that reports back with
+/// `AppEvent`s, so neither the disk nor the network can hold up the UI.
+fn refresh_events(app: &mut App, tx: mpsc::Sender<AppEvent>) {
+    let calendars_to_fetch = app.calendars_in_context();
+    // Whatever an earlier refresh still sends is for another view
+    app.refresh_generation += 1;
+    let generation = app.refresh_generation;
 
     let (start_date, end_date) = get_view_date_range(app);
-    
-    // 1. Load from DB (Instant)
-    let mut all_events = Vec::new();
-    for cal in &calendars_to_fetch {
-        if let Ok(events) = crate::db::get_events(&app.db_pool, &cal.calendar.id).await {
-             let color = cal.color;
-             let icon = cal.icon.clone();
-             all_events.extend(events.into_iter().map(|event| ColorEvent { event, color, icon: icon.clone() }));
-        }
-    }
-    
-    if !all_events.is_empty() {
-        all_events.sort_by(|a, b| a.event.start.date_time.cmp(&b.event.start.date_time));
-        app.events = all_events;
-        if app.event_list_state.selected().is_none() {
-            app.event_list_state.select(Some(0));
-        }
-    }
 
     info!(
         "Refreshing events for {} calendars...",
         calendars_to_fetch.len()
     );
 
-    // 2. Spawn API Fetch (Background)
+    // Colleague free/busy is only drawn on the week and day views
+    let shows_schedule = matches!(
+        app.event_view_mode,
+        EventViewMode::Week | EventViewMode::WorkWeek | EventViewMode::Day
+    );
+    if app.schedule_emails.is_empty() || !shows_schedule {
+        app.schedule_overlay.clear();
+    } else {
+        let access_token = app.access_token.clone();
+        let emails = app.schedule_emails.clone();
+        let tx_clone = tx.clone();
+        tokio::spawn(async move {
+            match get_schedule(&access_token, &emails, start_date, end_date).await {
+                Ok(schedule) => {
+                    let _ = tx_clone.send(AppEvent::ScheduleLoaded(schedule)).await;
+                }
+                Err(e) => error!("Error fetching free/busy schedule: {}", e),
+            }
+        });
+    }
+
+    if app.settings.dashboard_tasks.unwrap_or(false)
+        && app.event_view_mode == EventViewMode::Dashboard
+    {
+        let access_token = app.access_token.clone();
+        let today = clock::now().date_naive();
+        let tx_clone = tx.clone();
+        tokio::spawn(async move {
+            let result = list_due_tasks(&access_token, today).await.map_err(|e| {
+                error!("Error fetching the tasks due today: {}", e);
+                let status = e.downcast_ref::<reqwest::Error>().and_then(|e| e.status());
+                match status {
+                    Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) => {
+                        TASKS_NOT_GRANTED.to_string()
+                    }
+                    _ => e.to_string(),
+                }
+            });
+            let _ = tx_clone.send(AppEvent::DueTasksLoaded(result)).await;
+        });
+    }
+
+    // Spawn the cache load and API fetch (Background)
     let access_token = app.access_token.clone();
     let db_pool = app.db_pool.clone();
     let calendars = calendars_to_fetch;
+    let rules = app.rules.clone();
+    let local_color = app.theme.teal;
+    let keep_bodies = !app.minimal_mode();
+    let max_events = app.max_events_per_fetch();
     let tx_clone = tx.clone();
-    
+
+    if !app.is_primary {
+        // Another instance syncs; show what it has cached
+        tokio::spawn(async move {
+            let cached_events = load_cached_events(&db_pool, &calendars, local_color).await;
+            let _ = tx_clone
+                .send(AppEvent::EventsLoaded(generation, cached_events))
+                .await;
+            send_last_synced(&tx_clone, &db_pool, &calendars, start_date, end_date).await;
+        });
+        return;
+    }
+
     tokio::spawn(async move {
-        let mut futures = Vec::new();
-        for color_cal in &calendars {
-            futures.push(list_events(
-                &access_token,
-                &color_cal.calendar.id,
-                start_date,
-                end_date,
-            ));
-        }
-        let results = join_all(futures).await;
-        
-        let needs_token_refresh = results.iter().any(|res| {
-            if let Err(err) = res {
-                if let Some(req_err) = err.downcast_ref::<reqwest::Error>() {
-                    return req_err.status() == Some(reqwest::StatusCode::UNAUTHORIZED);
+        // 1. Cached events, shown right away
+        let cached_events = load_cached_events(&db_pool, &calendars, local_color).await;
+        let _ = tx_clone
+            .send(AppEvent::CachedEventsLoaded(generation, cached_events))
+            .await;
+        send_last_synced(&tx_clone, &db_pool, &calendars, start_date, end_date).await;
+
+        // 2. Changes from Graph, merged into the cache. Each page is shown as
+        // it arrives, so a big first sync doesn't leave the list empty.
+        let (pages_tx, mut pages_rx) = mpsc::unbounded_channel();
+        let page_forwarder = {
+            let tx = tx_clone.clone();
+            tokio::spawn(async move {
+                while let Some(page) = pages_rx.recv().await {
+                    let _ = tx.send(AppEvent::EventsPageLoaded(generation, page)).await;
                 }
-            }
-            false
-        });
-        
+            })
+        };
+        let (results, truncated) = sync_calendars(
+            &access_token,
+            &db_pool,
+            &rules,
+            &calendars,
+            &pages_tx,
+            start_date,
+            end_date,
+            keep_bodies,
+            max_events,
+        )
+        .await;
+        // Pages must not arrive after the final load below
+        drop(pages_tx);
+        let _ = page_forwarder.await;
+
+        let needs_token_refresh = results
+            .iter()
+            .any(|res| res.as_ref().is_err_and(|err| is_unauthorized(err.as_ref())));
+
         if needs_token_refresh {
             warn!("Access token expired. Requesting refresh.");
             let _ = tx_clone.send(AppEvent::TokenExpired).await;
             return;
         }
-        
-        let mut fetched_events = Vec::new();
-        for (i, result) in results.into_iter().enumerate() {
+
+        let mut rules_applied = 0;
+        let mut errors = Vec::new();
+        for result in results {
             match result {
-                Ok(events) => {
-                    if let Err(e) = crate::db::save_events_with_range(&db_pool, &events, &calendars[i].calendar.id, &start_date, &end_date).await {
-                        error!("Failed to save events to DB: {}", e);
+                Ok(applied) => rules_applied += applied,
+                Err(e) => {
+                    error!("Error syncing events: {}", e);
+                    errors.push(e.to_string());
+                }
+            }
+        }
+
+        if rules_applied > 0 {
+            let _ = tx_clone.send(AppEvent::RulesApplied(rules_applied)).await;
+        }
+        // Let viewer instances know there is something new to load
+        if let Err(e) =
+            crate::db::set_state(&db_pool, EVENTS_CHANGED_KEY, &clock::now_utc().to_rfc3339()).await
+        {
+            error!("Failed to mark the events as changed: {}", e);
+        }
+        let fetched_events = load_cached_events(&db_pool, &calendars, local_color).await;
+        let _ = tx_clone
+            .send(AppEvent::EventsLoaded(generation, fetched_events))
+            .await;
+        send_last_synced(&tx_clone, &db_pool, &calendars, start_date, end_date).await;
+        let _ = tx_clone.send(AppEvent::SyncFinished(errors)).await;
+        let _ = tx_clone
+            .send(AppEvent::SyncTruncated(truncated.then_some(max_events)))
+            .await;
+    });
+}
+
+/// Days before and after today the cache is warmed up for.
+const WARM_UP_PAST_DAYS: i64 = 7;
+const WARM_UP_FUTURE_DAYS: i64 = 30;
+
+/// Calendars per warm-up sync, and the pause between them, so the warm-up
+/// stays out of the way of the views' own syncs.
+const WARM_UP_BATCH_SIZE: usize = 3;
+const WARM_UP_PAUSE: Duration = Duration::from_secs(5);
+
+/// Once a day, after a sync went through, syncs the week before and the month
+/// after today for all visible calendars in small batches, so moving around
+/// that range is instant and works offline. The events only go to the cache;
+/// views pick them up when they load it.
+fn warm_up_cache(app: &mut App, tx: mpsc::Sender<AppEvent>) {
+    let today = clock::now().date_naive();
+    // Minimal mode doesn't prefetch
+    if !app.settings.warm_up_cache.unwrap_or(true)
+        || app.minimal_mode()
+        || !app.is_primary
+        || app.cache_warmed_on == Some(today)
+    {
+        return;
+    }
+    app.cache_warmed_on = Some(today);
+
+    let start_date = crate::cli::to_utc(today - ChronoDuration::days(WARM_UP_PAST_DAYS));
+    let end_date = crate::cli::to_utc(today + ChronoDuration::days(WARM_UP_FUTURE_DAYS));
+    let access_token = app.access_token.clone();
+    let db_pool = app.db_pool.clone();
+    let calendars = app.visible_calendars();
+    let rules = app.rules.clone();
+    let max_events = app.max_events_per_fetch();
+    tokio::spawn(async move {
+        // Nobody looks at the pages; the views read the cache
+        let (pages_tx, _) = mpsc::unbounded_channel();
+        for batch in calendars.chunks(WARM_UP_BATCH_SIZE) {
+            tokio::time::sleep(WARM_UP_PAUSE).await;
+            let (results, _) = sync_calendars(
+                &access_token,
+                &db_pool,
+                &rules,
+                batch,
+                &pages_tx,
+                start_date,
+                end_date,
+                true,
+                max_events,
+            )
+            .await;
+            for result in results {
+                match result {
+                    // The next refresh renews the token, then the warm-up starts over
+                    Err(e) if is_unauthorized(e.as_ref()) => {
+                        let _ = tx.send(AppEvent::CacheWarmUpInterrupted).await;
+                        return;
+                    }
+                    Err(e) => error!("Error warming up the cache: {}", e),
+                    Ok(_) => {}
+                }
+            }
+        }
+        info!("Warmed up the cache for {} calendars", calendars.len());
+        if let Err(e) =
+            crate::db::set_state(&db_pool, EVENTS_CHANGED_KEY, &clock::now_utc().to_rfc3339()).await
+        {
+            error!("Failed to mark the events as changed: {}", e);
+        }
+    });
+}
+
+/// Tells the UI when the calendars' date window was last synced.
+async fn send_last_synced(
+    tx: &mpsc::Sender<AppEvent>,
+    db_pool: &sqlx::SqlitePool,
+    calendars: &[ColorCalendar],
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) {
+    let calendar_ids: Vec<String> = calendars.iter().map(|c| c.calendar.id.clone()).collect();
+    match crate::db::last_synced(db_pool, &calendar_ids, &start_date, &end_date).await {
+        Ok(synced_at) => {
+            let _ = tx.send(AppEvent::LastSyncedLoaded(synced_at)).await;
+        }
+        Err(e) => error!("Failed to read the last sync time: {}", e),
+    }
+}
+
+/// Renews (or tries to take) the sync lease, and looks up when the syncing
+/// instance last changed the cached events.
+fn check_lease(app: &App, tx: mpsc::Sender<AppEvent>) {
+    let db_pool = app.db_pool.clone();
+    let instance_id = app.instance_id.clone();
+    tokio::spawn(async move {
+        let primary = match crate::db::acquire_lease(&db_pool, &instance_id, LEASE_TTL).await {
+            Ok(primary) => primary,
+            Err(e) => {
+                error!("Failed to renew the sync lease: {}", e);
+                return;
+            }
+        };
+        let events_changed_at = crate::db::get_state(&db_pool, EVENTS_CHANGED_KEY)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to read the events change marker: {}", e);
+                None
+            });
+        let _ = tx
+            .send(AppEvent::LeaseChecked {
+                primary,
+                events_changed_at,
+            })
+            .await;
+    });
+}
+
+/// Looks up recordings and transcripts of the ended Teams meeting shown in
+/// the detail view, once per event, when `meeting_recordings` is on.
+fn request_meeting_artifacts(app: &mut App, tx: mpsc::Sender<AppEvent>) {
+    if !app.settings.meeting_recordings.unwrap_or(false) {
+        return;
+    }
+    let Some(event) = app.get_selected_event().map(|e| e.event.clone()) else {
+        return;
+    };
+    // Only Teams meetings have recordings on Graph
+    let Some(join_url) = event
+        .join_url()
+        .filter(|_| crate::api::is_graph_id(&event.id))
+    else {
+        return;
+    };
+    let ended = parse_event_time(&event.end.date_time).is_some_and(|end| end < clock::now());
+    if !ended || app.meeting_artifacts.contains_key(&event.id) {
+        return;
+    }
+    app.meeting_artifacts.insert(event.id.clone(), None);
+
+    let access_token = app.access_token.clone();
+    let join_url = join_url.to_string();
+    tokio::spawn(async move {
+        match get_meeting_artifacts(&access_token, &join_url).await {
+            Ok(Some(artifacts)) => {
+                let _ = tx
+                    .send(AppEvent::MeetingArtifactsLoaded(event.id, artifacts))
+                    .await;
+            }
+            Ok(None) => info!("No online meeting found for \"{}\"", event.subject),
+            Err(e) => error!("Failed to look up meeting recordings: {}", e),
+        }
+    });
+}
+
+/// In minimal mode, fetches the body of the event open in the detail view,
+/// which isn't kept with the events.
+fn request_event_body(app: &mut App, tx: mpsc::Sender<AppEvent>) {
+    if !app.minimal_mode() {
+        return;
+    }
+    // Planner blocks (without a calendar) only exist locally, and Google or
+    // subscribed events aren't on Graph
+    let Some(selected) = app.get_selected_event().filter(|e| {
+        e.calendar_id
+            .as_deref()
+            .is_some_and(crate::api::is_graph_id)
+    }) else {
+        return;
+    };
+    let event = &selected.event;
+    if event.body.is_some()
+        || app
+            .fetched_body
+            .as_ref()
+            .is_some_and(|(id, _)| *id == event.id)
+    {
+        return;
+    }
+    let event_id = event.id.clone();
+    app.fetched_body = Some((event_id.clone(), None));
+
+    let access_token = app.access_token.clone();
+    tokio::spawn(async move {
+        match get_event_body(&access_token, &event_id).await {
+            Ok(body) => {
+                let _ = tx.send(AppEvent::EventBodyLoaded(event_id, body)).await;
+            }
+            Err(e) => error!("Failed to fetch the event body: {}", e),
+        }
+    });
+}
+
+pub fn is_unauthorized(err: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.status() == Some(reqwest::StatusCode::UNAUTHORIZED))
+        || err
+            .downcast_ref::<BatchError>()
+            .is_some_and(|e| e.status == 401)
+}
+
+/// Brings the cache of the calendars' date window up to date: Microsoft
+/// calendars from Graph, the others from Google or their feed. Returns how many
+/// invites the rules answered, per calendar, and whether `max_events` cut a
+/// Graph sync short.
+#[allow(clippy::too_many_arguments)]
+async fn sync_calendars(
+    access_token: &str,
+    db_pool: &sqlx::SqlitePool,
+    rules: &[Rule],
+    calendars: &[ColorCalendar],
+    pages: &mpsc::UnboundedSender<Vec<ColorEvent>>,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    keep_bodies: bool,
+    max_events: usize,
+) -> (
+    Vec<Result<usize, Box<dyn std::error::Error + Send + Sync>>>,
+    bool,
+) {
+    let (graph_calendars, read_only_calendars): (Vec<ColorCalendar>, Vec<ColorCalendar>) =
+        calendars
+            .iter()
+            .cloned()
+            .partition(|c| crate::api::is_graph_id(&c.calendar.id));
+    let (mut results, truncated) = sync_graph_calendars(
+        access_token,
+        db_pool,
+        rules,
+        &graph_calendars,
+        pages,
+        start_date,
+        end_date,
+        keep_bodies,
+        max_events,
+    )
+    .await;
+    for calendar in &read_only_calendars {
+        results.push(
+            sync_read_only_calendar(db_pool, calendar, pages, start_date, end_date, keep_bodies)
+                .await
+                .map(|_| 0),
+        );
+    }
+    (results, truncated)
+}
+
+/// Replaces the cached window of a Google or subscribed calendar with a
+/// fresh copy. They are read-only, so the rules don't apply.
+async fn sync_read_only_calendar(
+    db_pool: &sqlx::SqlitePool,
+    calendar: &ColorCalendar,
+    pages: &mpsc::UnboundedSender<Vec<ColorEvent>>,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    keep_bodies: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let calendar_id = calendar.calendar.id.as_str();
+    let events = if crate::google::is_google_id(calendar_id) {
+        crate::google::list_events(calendar_id, start_date, end_date).await
+    } else {
+        crate::subscriptions::list_events(calendar_id, start_date, end_date).await
+    };
+    // As a plain message, so a 401 isn't taken for an expired Graph token
+    let mut events = events.map_err(|e| format!("Calendar {}: {}", calendar.calendar.name, e))?;
+    info!("Synced calendar {}: {} events", calendar_id, events.len());
+    if !keep_bodies {
+        for event in &mut events {
+            event.body = None;
+        }
+    }
+    let _ = pages.send(
+        events
+            .iter()
+            .map(|event| ColorEvent {
+                event: event.clone(),
+                color: calendar.color,
+                icon: calendar.icon.clone(),
+                calendar_id: Some(calendar_id.to_string()),
+            })
+            .collect(),
+    );
+    crate::db::save_events_with_range(db_pool, &events, calendar_id, &start_date, &end_date)
+        .await?;
+    // There is no delta link; the sync state only records when this was
+    crate::db::save_delta_link(db_pool, calendar_id, &start_date, &end_date, "", None).await?;
+    Ok(())
+}
+
+/// Brings the cache of the Microsoft calendars' date window up to date with
+/// delta syncs, batched into as few Graph requests as possible, letting the rules
+/// answer new invites on the way. Calendars whose stored delta link Graph no
+/// longer accepts start over with a full sync of the window; other failures
+/// keep the link for the next try and are returned. Returns how many
+/// invites the rules answered, per calendar. Without `keep_bodies`, event
+/// bodies are dropped as they arrive and never cached.
+#[allow(clippy::too_many_arguments)]
+async fn sync_graph_calendars(
+    access_token: &str,
+    db_pool: &sqlx::SqlitePool,
+    rules: &[Rule],
+    calendars: &[ColorCalendar],
+    pages: &mpsc::UnboundedSender<Vec<ColorEvent>>,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    keep_bodies: bool,
+    max_events: usize,
+) -> (
+    Vec<Result<usize, Box<dyn std::error::Error + Send + Sync>>>,
+    bool,
+) {
+    let mut stored_links = Vec::new();
+    for calendar in calendars {
+        let link =
+            crate::db::get_delta_link(db_pool, &calendar.calendar.id, &start_date, &end_date)
+                .await
+                .unwrap_or_else(|e| {
+                    error!("Failed to read the sync state: {}", e);
+                    None
+                });
+        stored_links.push(link);
+    }
+    let sync = |indices: Vec<usize>, links: Vec<Option<String>>| async move {
+        let requests: Vec<(&str, Option<&str>)> = indices
+            .iter()
+            .zip(&links)
+            .map(|(&index, link)| (calendars[index].calendar.id.as_str(), link.as_deref()))
+            .collect();
+        let on_page = |request: usize, events: &[GraphEvent]| {
+            let calendar = &calendars[indices[request]];
+            let page = events
+                .iter()
+                .map(|event| {
+                    let mut event = event.clone();
+                    if !keep_bodies {
+                        event.body = None;
+                    }
+                    ColorEvent {
+                        event,
+                        color: calendar.color,
+                        icon: calendar.icon.clone(),
+                        calendar_id: Some(calendar.calendar.id.clone()),
+                    }
+                })
+                .collect();
+            let _ = pages.send(page);
+        };
+        match list_events_deltas(
+            access_token,
+            &requests,
+            start_date,
+            end_date,
+            max_events,
+            on_page,
+        )
+        .await
+        {
+            Ok(mut results) => {
+                if !keep_bodies {
+                    for event in results.iter_mut().flatten().flat_map(|d| &mut d.changed) {
+                        event.body = None;
                     }
-                    let color = calendars[i].color;
-                    let icon = calendars[i].icon.clone();
-                    fetched_events.extend(events.into_iter().map(|event| ColorEvent { event, color, icon: icon.clone() }));
                 }
-                Err(e) => error!("Error fetching events: {}", e),
+                results
+            }
+            // The whole batch failed, so every calendar in it did
+            Err(e) => {
+                let status = if is_unauthorized(e.as_ref()) { 401 } else { 0 };
+                requests
+                    .iter()
+                    .map(|_| {
+                        Err(BatchError {
+                            status,
+                            code: None,
+                            message: e.to_string(),
+                        })
+                    })
+                    .collect()
+            }
+        }
+    };
+
+    let mut results = sync((0..calendars.len()).collect(), stored_links.clone()).await;
+    let stale: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(index, result)| {
+            result.as_ref().is_err_and(BatchError::sync_state_lost)
+                && stored_links[*index].is_some()
+        })
+        .map(|(index, _)| index)
+        .collect();
+    if !stale.is_empty() {
+        for &index in &stale {
+            if let Err(e) = &results[index] {
+                warn!(
+                    "Delta sync of calendar {} failed ({}), starting over.",
+                    calendars[index].calendar.id, e
+                );
             }
         }
-        
-        let _ = tx_clone.send(AppEvent::EventsLoaded(fetched_events)).await;
+        let retried = sync(stale.clone(), stale.iter().map(|_| None).collect()).await;
+        for (index, result) in stale.into_iter().zip(retried) {
+            results[index] = result;
+            stored_links[index] = None;
+        }
+    }
+
+    let truncated = results.iter().flatten().any(|delta| delta.truncated);
+    let mut applied = Vec::new();
+    for ((calendar, result), stored_link) in calendars.iter().zip(results).zip(stored_links) {
+        applied.push(match result {
+            Ok(delta) => {
+                store_delta(
+                    access_token,
+                    db_pool,
+                    rules,
+                    calendar,
+                    delta,
+                    stored_link.is_none(),
+                    start_date,
+                    end_date,
+                )
+                .await
+            }
+            Err(e) => Err(e.into()),
+        });
+    }
+    (applied, truncated)
+}
+
+/// Stores one calendar's synced changes, after the rules had their say.
+/// Returns how many invites the rules answered. A full sync replaces the
+/// cached events of the window, unless it was truncated: then the events past
+/// the limit are still cached and only upserted, and the sync resumes from
+/// where it stopped next time. Once its last page arrives, the events of the
+/// window it didn't bring in any of its rounds are deleted.
+#[allow(clippy::too_many_arguments)]
+async fn store_delta(
+    access_token: &str,
+    db_pool: &sqlx::SqlitePool,
+    rules: &[Rule],
+    calendar: &ColorCalendar,
+    mut delta: EventDelta,
+    full_sync: bool,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let calendar_id = calendar.calendar.id.as_str();
+    info!(
+        "Synced calendar {}: {} changed, {} removed{}",
+        calendar_id,
+        delta.changed.len(),
+        delta.removed.len(),
+        if full_sync { " (full sync)" } else { "" }
+    );
+
+    // A full sync spread over several refreshes started with its first round
+    let full_sync_started = if full_sync {
+        Some(clock::now_utc())
+    } else {
+        crate::db::full_sync_started(db_pool, calendar_id, &start_date, &end_date).await?
+    };
+    let rules_applied = apply_rules(rules, access_token, db_pool, &mut delta.changed).await;
+    if full_sync && !delta.truncated {
+        crate::db::save_events_with_range(
+            db_pool,
+            &delta.changed,
+            calendar_id,
+            &start_date,
+            &end_date,
+        )
+        .await?;
+    } else {
+        crate::db::apply_event_changes(db_pool, calendar_id, &delta.changed, &delta.removed)
+            .await?;
+        if let (Some(started), false) = (full_sync_started, delta.truncated) {
+            let removed = crate::db::delete_events_synced_before(
+                db_pool,
+                calendar_id,
+                &start_date,
+                &end_date,
+                &started,
+            )
+            .await?;
+            info!(
+                "Full sync of calendar {} finished, {} stale events removed",
+                calendar_id, removed
+            );
+        }
+    }
+    if let Some(link) = delta.delta_link.as_ref() {
+        crate::db::save_delta_link(db_pool, calendar_id, &start_date, &end_date, link, None)
+            .await?;
+    } else if let Some(link) = delta.next_link.as_ref() {
+        crate::db::save_delta_link(
+            db_pool,
+            calendar_id,
+            &start_date,
+            &end_date,
+            link,
+            full_sync_started.as_ref(),
+        )
+        .await?;
+    }
+    Ok(rules_applied)
+}
+
+/// Cached events of the given calendars, plus the local time blocks.
+async fn load_cached_events(
+    db_pool: &sqlx::SqlitePool,
+    calendars: &[ColorCalendar],
+    local_color: Color,
+) -> Vec<ColorEvent> {
+    let mut events = Vec::new();
+    for cal in calendars {
+        match crate::db::get_events(db_pool, &cal.calendar.id).await {
+            Ok(cached) => {
+                let color = cal.color;
+                let icon = cal.icon.clone();
+                let calendar_id = Some(cal.calendar.id.clone());
+                events.extend(cached.into_iter().map(|event| ColorEvent {
+                    event,
+                    color,
+                    icon: icon.clone(),
+                    calendar_id: calendar_id.clone(),
+                }));
+            }
+            Err(e) => error!("Failed to load cached events: {}", e),
+        }
+    }
+    events.extend(load_local_events(db_pool, local_color).await);
+    events
+}
+
+/// Loads today's meetings from the cache for the chat status: those in the
+/// user's own calendars they haven't declined and that keep them busy.
+/// Planner blocks are left out; they aren't meetings.
+fn request_todays_meetings(app: &App, tx: mpsc::Sender<AppEvent>) {
+    let calendars = app.own_calendars();
+    let db_pool = app.db_pool.clone();
+    let today = clock::now().date_naive();
+    let start = crate::cli::to_utc(today);
+    let end = crate::cli::to_utc(today + ChronoDuration::days(1));
+    tokio::spawn(async move {
+        let mut meetings = Vec::new();
+        for calendar in &calendars {
+            let calendar_id = &calendar.calendar.id;
+            match crate::db::get_events_between(&db_pool, calendar_id, &start, &end).await {
+                Ok(events) => meetings.extend(events.into_iter().filter(|event| {
+                    let declined = event
+                        .response_status
+                        .as_ref()
+                        .is_some_and(|status| status.response == "declined");
+                    !declined && event.show_as.as_deref() != Some("free")
+                })),
+                Err(e) => error!("Failed to read today's events: {}", e),
+            }
+        }
+        let _ = tx.send(AppEvent::TodaysMeetingsLoaded(meetings)).await;
     });
 }
 
+/// Time blocks planned locally with the focus-day planner. They are shown
+/// whichever calendar is selected.
+async fn load_local_events(db_pool: &sqlx::SqlitePool, color: Color) -> Vec<ColorEvent> {
+    match crate::db::get_local_events(db_pool).await {
+        Ok(events) => events
+            .into_iter()
+            .map(|event| ColorEvent {
+                event,
+                color,
+                icon: None,
+                calendar_id: None,
+            })
+            .collect(),
+        Err(e) => {
+            error!("Failed to load local events: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Shows matches from the local cache right away and, unless `cache_only`,
+/// asks Graph for events outside the synced window, which are merged in when
+/// they arrive.
+fn start_search(app: &mut App, query: SearchQuery, cache_only: bool, tx: mpsc::Sender<AppEvent>) {
+    app.search = Some(SearchState::new(query.clone(), cache_only));
+
+    let db_pool = app.db_pool.clone();
+    let access_token = app.access_token.clone();
+    tokio::spawn(async move {
+        let cached = crate::db::search_events(
+            &db_pool,
+            &query.text,
+            query.start(),
+            query.end(),
+            MAX_SEARCH_RESULTS,
+        )
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to search cached events: {}", e);
+            Vec::new()
+        });
+        let _ = tx.send(AppEvent::CachedSearchResults(cached)).await;
+        if cache_only {
+            return;
+        }
+
+        let result = search_events(
+            &access_token,
+            &query.text,
+            query.start(),
+            query.end(),
+            MAX_SEARCH_RESULTS,
+        )
+        .await
+        .map_err(|e| {
+            error!("Error searching events: {}", e);
+            e.to_string()
+        });
+        let _ = tx.send(AppEvent::SearchResults(result)).await;
+    });
+}
+
+/// Asks Graph for meeting slots for the attendees in `app.find_time`, looking
+/// at the next week.
+fn spawn_find_meeting_times(app: &App, tx: mpsc::Sender<AppEvent>) {
+    let Some(find_time) = &app.find_time else {
+        return;
+    };
+    let access_token = app.access_token.clone();
+    let attendees = find_time.attendees.clone();
+    let duration_minutes = find_time.duration_minutes;
+    let start_date = clock::now_utc();
+    let end_date = start_date + ChronoDuration::days(7);
+
+    tokio::spawn(async move {
+        let result = find_meeting_times(
+            &access_token,
+            &attendees,
+            duration_minutes,
+            start_date,
+            end_date,
+        )
+        .await
+        .map_err(|e| {
+            error!("Error finding meeting times: {}", e);
+            e.to_string()
+        });
+        let _ = tx.send(AppEvent::MeetingTimesFound(result)).await;
+    });
+}
+
+/// Most schedules asked for in one getSchedule request.
+const MAX_SCHEDULES_PER_REQUEST: usize = 20;
+
+/// Looks up the meeting rooms for `app.room_picker`, and whether each is free
+/// at the new event's time.
+fn spawn_find_rooms(app: &App, tx: mpsc::Sender<AppEvent>) {
+    let Some(picker) = &app.room_picker else {
+        return;
+    };
+    let access_token = app.access_token.clone();
+    let room_list = app.settings.room_list.clone();
+    let new_event = picker.new_event.clone();
+    tokio::spawn(async move {
+        let result = find_free_rooms(&access_token, room_list.as_deref(), &new_event)
+            .await
+            .map_err(|e| {
+                error!("Error finding meeting rooms: {}", e);
+                e.to_string()
+            });
+        let _ = tx.send(AppEvent::RoomsFound(result)).await;
+    });
+}
+
+async fn find_free_rooms(
+    access_token: &str,
+    room_list: Option<&str>,
+    new_event: &NewEvent,
+) -> Result<Vec<RoomOption>, Box<dyn std::error::Error + Send + Sync>> {
+    let rooms = find_rooms(access_token, room_list).await?;
+    let (Some(start), Some(end)) = (
+        parse_event_time(&new_event.start),
+        parse_event_time(&new_event.end),
+    ) else {
+        return Ok(rooms
+            .into_iter()
+            .map(|room| RoomOption { room, free: None })
+            .collect());
+    };
+    let mut options = Vec::new();
+    for chunk in rooms.chunks(MAX_SCHEDULES_PER_REQUEST) {
+        let emails: Vec<String> = chunk.iter().map(|room| room.address.clone()).collect();
+        // Without their schedules the rooms can still be picked
+        let schedules = get_schedule(
+            access_token,
+            &emails,
+            start.with_timezone(&Utc),
+            end.with_timezone(&Utc),
+        )
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not read the rooms' schedules: {}", e);
+            Vec::new()
+        });
+        for room in chunk {
+            let free = schedules
+                .iter()
+                .find(|s| s.schedule_id.eq_ignore_ascii_case(&room.address))
+                .filter(|s| s.error.is_none())
+                .map(|s| s.schedule_items.iter().all(|item| item.status == "free"));
+            options.push(RoomOption {
+                room: room.clone(),
+                free,
+            });
+        }
+    }
+    Ok(options)
+}
+
+fn spawn_create_event(
+    app: &App,
+    new_event: NewEvent,
+    task: Option<ScheduledTask>,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    let access_token = app.access_token.clone();
+    tokio::spawn(async move {
+        let result = create_event(&access_token, &new_event, None)
+            .await
+            .map(|_| new_event.subject.clone())
+            .map_err(|e| {
+                error!("Error creating event: {}", e);
+                e.to_string()
+            });
+        let _ = tx.send(AppEvent::EventCreated(result, task)).await;
+    });
+}
+
+fn spawn_update_event(app: &App, prompt: EventEditPrompt, tx: mpsc::Sender<AppEvent>) {
+    let access_token = app.access_token.clone();
+    tokio::spawn(async move {
+        let result = update_event(
+            &access_token,
+            &prompt.calendar_id,
+            &prompt.event_id,
+            &prompt.changes(),
+        )
+        .await
+        .map(|_| prompt.new.subject.clone())
+        .map_err(|e| {
+            error!("Error updating event: {}", e);
+            e.to_string()
+        });
+        let _ = tx.send(AppEvent::EventUpdated(result)).await;
+    });
+}
+
+/// Opens Settings.toml in `$VISUAL` or `$EDITOR` with the TUI suspended,
+/// then checks what was saved. Changes apply on the next start.
+fn edit_settings(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+) -> io::Result<()> {
+    let path = crate::config::get_config_path();
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Editors are often set with arguments, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let Some(program) = words.next() else {
+        return Ok(());
+    };
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    let result = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status();
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+
+    match result {
+        Err(e) => app.set_status(&format!("Could not start {}: {}", program, e)),
+        Ok(_) => match crate::config::load_config() {
+            Ok((_, warnings)) => match warnings.first() {
+                None => app.set_status("Settings saved; restart the app to apply them"),
+                Some(warning) => app.set_status(&format!(
+                    "Settings saved with {} warning(s), first: {}",
+                    warnings.len(),
+                    warning
+                )),
+            },
+            Err(e) => app.set_status(&format!("Settings.toml has an error: {}", e)),
+        },
+    }
+    Ok(())
+}
+
+/// Remembers the ticked calendars for the next session.
+fn save_checked_calendars(app: &App) {
+    let mut ids: Vec<&String> = app.checked_calendars.iter().collect();
+    ids.sort();
+    let value = serde_json::to_string(&ids).unwrap_or_default();
+    let db_pool = app.db_pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = crate::db::set_state(&db_pool, "checked_calendars", &value).await {
+            error!("Failed to save the ticked calendars: {}", e);
+        }
+    });
+}
+
+/// Logs in again in the browser, without leaving the TUI.
+fn start_relogin(app: &mut App, tx: mpsc::Sender<AppEvent>) {
+    app.relogin_prompt = None;
+    let for_write = std::mem::take(&mut app.relogin_for_write);
+    app.set_status("Log in in the browser window that opened...");
+    let settings = app.settings.clone();
+    let db_pool = app.db_pool.clone();
+    tokio::spawn(async move {
+        if for_write {
+            crate::auth::request_write_access(&db_pool).await;
+        }
+        let result = crate::auth::browser_login(&settings).await.map_err(|e| {
+            error!("Logging in again failed: {}", e);
+            e.to_string()
+        });
+        if result.is_ok() {
+            crate::auth::record_login(&db_pool).await;
+        }
+        let _ = tx.send(AppEvent::LoggedIn(result)).await;
+    });
+}
+
+/// Stores an attendance mark set with `m`.
+fn save_attendance(app: &App, event: GraphEvent, attended: Option<bool>) {
+    let db_pool = app.db_pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = crate::db::set_attendance(&db_pool, &event, attended).await {
+            error!("Failed to save the attendance mark: {}", e);
+        }
+    });
+}
+
+/// Remembers the List view's grouping and folded calendars for the next session.
+fn save_list_grouping(app: &App) {
+    let mut collapsed: Vec<&String> = app.collapsed_calendars.iter().collect();
+    collapsed.sort();
+    let value = serde_json::json!({
+        "grouped": app.group_by_calendar,
+        "collapsed": collapsed,
+    })
+    .to_string();
+    let db_pool = app.db_pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = crate::db::set_state(&db_pool, "list_grouping", &value).await {
+            error!("Failed to save the list grouping: {}", e);
+        }
+    });
+}
+
+/// Remembers the events flagged for an alarm for the next session.
+fn save_flagged_events(app: &App) {
+    let mut ids: Vec<&String> = app.flagged_events.iter().collect();
+    ids.sort();
+    let value = serde_json::to_string(&ids).unwrap_or_default();
+    let db_pool = app.db_pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = crate::db::set_state(&db_pool, "flagged_events", &value).await {
+            error!("Failed to save the flagged events: {}", e);
+        }
+    });
+}
+
+/// Saves a new event that is clear of conflicts or was confirmed. Planner
+/// tasks are assigned only now, which ticks them off and builds the event.
+/// Looks for conflicts with a new event in the cache of the calendars "All
+/// Calendars" shows and the local time blocks, whatever is loaded for the
+/// view. Once they are in, the event is saved or the conflicts put up.
+fn check_new_event(
+    app: &mut App,
+    new_event: NewEvent,
+    target: NewEventTarget,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    if !app.may_create(target) {
+        return;
+    }
+    let (Some(start), Some(end)) = (
+        parse_event_time(&new_event.start),
+        parse_event_time(&new_event.end),
+    ) else {
+        save_new_event(app, new_event, target, tx);
+        return;
+    };
+    let (start, end) = (start.with_timezone(&Utc), end.with_timezone(&Utc));
+    let calendars = app.visible_calendars();
+    let local_color = app.theme.teal;
+    let db_pool = app.db_pool.clone();
+    tokio::spawn(async move {
+        let mut events = Vec::new();
+        for calendar in &calendars {
+            let calendar_id = &calendar.calendar.id;
+            match crate::db::get_events_between(&db_pool, calendar_id, &start, &end).await {
+                Ok(cached) => events.extend(cached.into_iter().map(|event| ColorEvent {
+                    event,
+                    color: calendar.color,
+                    icon: calendar.icon.clone(),
+                    calendar_id: Some(calendar_id.clone()),
+                })),
+                Err(e) => error!("Failed to read the cached events: {}", e),
+            }
+        }
+        events.extend(load_local_events(&db_pool, local_color).await);
+        let conflicts = crate::app::conflicts_among(events, &new_event);
+        let _ = tx
+            .send(AppEvent::ConflictsChecked(new_event, target, conflicts))
+            .await;
+    });
+}
+
+fn save_new_event(
+    app: &mut App,
+    new_event: NewEvent,
+    target: NewEventTarget,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    let NewEventTarget::PlannerTask { local } = target else {
+        spawn_create_event(app, new_event, None, tx);
+        return;
+    };
+    let Some(planner) = app.planner.as_mut() else {
+        return;
+    };
+    let (new_event, task) = match planner.assign() {
+        Ok(assigned) => assigned,
+        Err(e) => {
+            app.set_status(&e);
+            return;
+        }
+    };
+    if !local {
+        spawn_create_event(app, new_event, Some(task), tx);
+        return;
+    }
+    let db_pool = app.db_pool.clone();
+    tokio::spawn(async move {
+        let result = crate::db::save_local_event(&db_pool, &new_event)
+            .await
+            .map(|_| new_event.subject.clone())
+            .map_err(|e| {
+                error!("Failed to save local block: {}", e);
+                e.to_string()
+            });
+        let _ = tx.send(AppEvent::LocalBlockSaved(result, task)).await;
+    });
+}
+
+fn ring_bell(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) {
+    use std::io::Write;
+    let backend = terminal.backend_mut();
+    let _ = backend.write_all(b"\x07").and_then(|_| backend.flush());
+}
+
+/// Toasts the events just announced, for terminals where desktop
+/// notifications don't show, and plays the notification sound.
+fn announce_reminders(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    announced: Vec<(String, DateTime<Utc>)>,
+) {
+    if announced.is_empty() {
+        return;
+    }
+    match app.notification_manager.sound() {
+        Some(NotificationSound::Bell) => ring_bell(terminal),
+        Some(NotificationSound::File(path)) => crate::notifications::play_sound(path),
+        None => {}
+    }
+    for (subject, start) in announced {
+        let title = format!(
+            "Starting at {}",
+            start.with_timezone(&Local).format("%H:%M")
+        );
+        app.toast(
+            ToastKind::Reminder,
+            &title,
+            display_subject(&subject, app.busy_only),
+        );
+    }
+}
+
 /// The main application loop. Handles events and updates the app state.
 pub async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
@@ -127,24 +1229,104 @@ pub async fn run_app(
     let theme = app.theme.clone();
 
     if !app.calendars.is_empty() {
-        refresh_events(app, tx.clone()).await;
+        refresh_events(app, tx.clone());
     }
 
     app.start_transition(500);
 
     let mut last_notification_check = std::time::Instant::now();
+    let mut last_lease_check = std::time::Instant::now();
+    let mut last_wall_clock = SystemTime::now();
+    let mut last_iteration = std::time::Instant::now();
+    let mut wake_refresh_pending = false;
 
     loop {
+        if app.current_view == CurrentView::EventDetail {
+            request_meeting_artifacts(app, tx.clone());
+            request_event_body(app, tx.clone());
+        }
+        // Graph is throttling or failing and a request waits for a retry
+        if let Some(notice) = crate::api::take_retry_notice() {
+            app.set_status(&notice);
+        }
         terminal.draw(|f| ui(f, app, &theme))?;
 
-        // Check notifications every minute
-        if last_notification_check.elapsed() >= Duration::from_secs(60) {
-            let events: Vec<_> = app.events.iter().map(|e| e.event.clone()).collect();
-            app.notification_manager.check_and_notify(&events);
+        // The monotonic clock stops while the machine is suspended and the wall
+        // clock doesn't, so a wall-clock gap well beyond the monotonic one means
+        // it slept. A slow iteration (an editor, a long await) moves both alike.
+        // The refresh timer is monotonic and would otherwise leave the data stale.
+        let now_wall_clock = SystemTime::now();
+        let wall_clock_gap = now_wall_clock
+            .duration_since(last_wall_clock)
+            .unwrap_or_default();
+        let slept = wall_clock_gap.saturating_sub(last_iteration.elapsed());
+        last_wall_clock = now_wall_clock;
+        last_iteration = std::time::Instant::now();
+        let resumed_from_sleep = slept >= WAKE_DETECTION_THRESHOLD;
+        if resumed_from_sleep {
+            info!(
+                "Wall clock jumped {}s (resume from sleep?). Forcing sync.",
+                slept.as_secs()
+            );
+            wake_refresh_pending = true;
+        }
+
+        if resumed_from_sleep || last_lease_check.elapsed() >= LEASE_CHECK_INTERVAL {
+            check_lease(app, tx.clone());
+            last_lease_check = std::time::Instant::now();
+        }
+
+        // Check notifications and chat status every minute. Viewer instances
+        // leave both to the one holding the sync lease.
+        if app.is_primary
+            && (resumed_from_sleep || last_notification_check.elapsed() >= Duration::from_secs(60))
+        {
+            let announced = app.notification_manager.check_and_notify(&app.events);
+            announce_reminders(terminal, app, announced);
+            if app.notification_manager.quiet_hours_ended() {
+                let count = app.notification_manager.missed().len();
+                app.toast(
+                    ToastKind::Reminder,
+                    "Do not disturb is over",
+                    &format!("{} missed reminder(s), press M to see them.", count),
+                );
+            }
+            let due = app.notification_manager.terminal_alerts_due(&app.events);
+            if let (Some(alert), Some((subject, start))) =
+                (app.notification_manager.terminal_alert(), due.first())
+            {
+                if alert.rings_bell() {
+                    ring_bell(terminal);
+                }
+                if alert.flashes() {
+                    app.flash_until = Some(std::time::Instant::now() + FLASH_DURATION);
+                }
+                app.set_status(&format!(
+                    "Starting at {}: {}",
+                    start.with_timezone(&Local).format("%H:%M"),
+                    display_subject(subject, app.busy_only)
+                ));
+            }
+            request_todays_meetings(app, tx.clone());
             last_notification_check = std::time::Instant::now();
         }
 
-        let mut needs_refresh = false;
+        if app.is_primary {
+            app.notification_manager.retry_failed();
+        }
+
+        // Alarms ring on every instance, whoever syncs
+        if app.alarm.is_none() {
+            app.alarm = app.alarms.due(&app.events, &app.flagged_events);
+            if app.alarm.is_some() {
+                ring_bell(terminal);
+            }
+        }
+
+        // Other views keep their own state, so the sync waits until the user is
+        // back in the events view.
+        let mut needs_refresh =
+            wake_refresh_pending && matches!(app.current_view, CurrentView::Events);
 
         let poll_timeout = if app.transition.is_some() {
             Duration::from_millis(16)
@@ -157,6 +1339,12 @@ pub async fn run_app(
                 app.transition = None;
             }
         }
+        if app
+            .flash_until
+            .is_some_and(|until| std::time::Instant::now() >= until)
+        {
+            app.flash_until = None;
+        }
 
         if event::poll(poll_timeout)? {
             match event::read()? {
@@ -165,6 +1353,246 @@ pub async fn run_app(
                         continue;
                     }
 
+                    if let Some(input) = &mut app.input {
+                        match key.code {
+                            KeyCode::Esc => app.input = None,
+                            KeyCode::Enter => {
+                                if let Some(prompt) = app.input.take() {
+                                    match app.submit_input(prompt) {
+                                        InputAction::None => {}
+                                        InputAction::Refresh => refresh_events(app, tx.clone()),
+                                        InputAction::FindMeetingTimes => {
+                                            spawn_find_meeting_times(app, tx.clone())
+                                        }
+                                        InputAction::FindRooms => spawn_find_rooms(app, tx.clone()),
+                                        InputAction::CreateEvent(new_event) => check_new_event(
+                                            app,
+                                            new_event,
+                                            NewEventTarget::Outlook,
+                                            tx.clone(),
+                                        ),
+                                        InputAction::Search { query, cache_only } => {
+                                            start_search(app, query, cache_only, tx.clone())
+                                        }
+                                    }
+                                }
+                            }
+                            KeyCode::Backspace => {
+                                input.buffer.pop();
+                            }
+                            KeyCode::Char(c) => input.buffer.push(c),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if app.conflict_prompt.is_some() {
+                        match key.code {
+                            KeyCode::Char('y') | KeyCode::Enter => {
+                                if let Some(prompt) = app.conflict_prompt.take() {
+                                    save_new_event(
+                                        app,
+                                        prompt.new_event,
+                                        prompt.target,
+                                        tx.clone(),
+                                    );
+                                }
+                            }
+                            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                                app.conflict_prompt = None;
+                                app.set_status("Event not created");
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if app.edit_prompt.is_some() {
+                        match key.code {
+                            KeyCode::Char('y') | KeyCode::Enter => {
+                                if let Some(prompt) = app.edit_prompt.take() {
+                                    spawn_update_event(app, prompt, tx.clone());
+                                }
+                            }
+                            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                                app.edit_prompt = None;
+                                app.set_status("Changes discarded");
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if app.relogin_prompt.is_some() {
+                        match key.code {
+                            KeyCode::Char('y') | KeyCode::Enter => start_relogin(app, tx.clone()),
+                            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                                app.relogin_prompt = None;
+                                app.relogin_for_write = false;
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if app.logout_prompt {
+                        match key.code {
+                            KeyCode::Char('y') | KeyCode::Enter => {
+                                app.logged_out = true;
+                                return Ok(());
+                            }
+                            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                                app.logout_prompt = false;
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if let Some(find_time) = &mut app.find_time {
+                        let len = find_time.suggestions.as_ref().map_or(0, |s| s.len());
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') => app.find_time = None,
+                            KeyCode::Down if len > 0 => {
+                                let i =
+                                    find_time.list_state.selected().map_or(0, |i| (i + 1) % len);
+                                find_time.list_state.select(Some(i));
+                            }
+                            KeyCode::Up if len > 0 => {
+                                let i = find_time
+                                    .list_state
+                                    .selected()
+                                    .map_or(0, |i| (i + len - 1) % len);
+                                find_time.list_state.select(Some(i));
+                            }
+                            KeyCode::Enter => app.pick_meeting_suggestion(),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if let Some(picker) = &mut app.room_picker {
+                        // "No room" comes first
+                        let len = 1 + picker
+                            .rooms
+                            .as_ref()
+                            .and_then(|r| r.as_ref().ok())
+                            .map_or(0, |r| r.len());
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') => {
+                                app.room_picker = None;
+                                app.set_status("Event not created");
+                            }
+                            KeyCode::Down => {
+                                let i = picker.list_state.selected().map_or(0, |i| (i + 1) % len);
+                                picker.list_state.select(Some(i));
+                            }
+                            KeyCode::Up => {
+                                let i = picker
+                                    .list_state
+                                    .selected()
+                                    .map_or(0, |i| (i + len - 1) % len);
+                                picker.list_state.select(Some(i));
+                            }
+                            KeyCode::Enter => {
+                                if let Some(picker) = app.room_picker.take() {
+                                    check_new_event(
+                                        app,
+                                        picker.pick(),
+                                        NewEventTarget::Outlook,
+                                        tx.clone(),
+                                    );
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    // Alarms stay until explicitly dismissed or snoozed
+                    if let Some(alarm) = &app.alarm {
+                        match key.code {
+                            KeyCode::Enter | KeyCode::Char('d') => app.alarm = None,
+                            KeyCode::Char('s') => {
+                                app.alarms.snooze(alarm.clone());
+                                app.alarm = None;
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if let Some(console) = &mut app.sql_console {
+                        match key.code {
+                            // Esc stops a running query before it closes the console
+                            KeyCode::Esc if console.running => console.interrupt(),
+                            KeyCode::Esc => app.sql_console = None,
+                            KeyCode::Enter
+                                if !console.running && !console.input.trim().is_empty() =>
+                            {
+                                console.running = true;
+                                console.cancel = Default::default();
+                                let sql = console.input.clone();
+                                let cancel = console.cancel.clone();
+                                let tx = tx.clone();
+                                tokio::spawn(async move {
+                                    let result = crate::sql_console::run_query(&sql, cancel).await;
+                                    let _ = tx.send(AppEvent::SqlQueryFinished(result)).await;
+                                });
+                            }
+                            KeyCode::Down => console.scroll_by(1),
+                            KeyCode::Up => console.scroll_by(-1),
+                            KeyCode::PageDown => console.scroll_by(10),
+                            KeyCode::PageUp => console.scroll_by(-10),
+                            KeyCode::Backspace => {
+                                console.input.pop();
+                            }
+                            KeyCode::Char(c) => console.input.push(c),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    // The month quick-peek closes on any key
+                    if app.day_peek.take().is_some() {
+                        continue;
+                    }
+
+                    if let Some(search) = &mut app.search {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') => app.search = None,
+                            KeyCode::Down => search.move_selection(1),
+                            KeyCode::Up => search.move_selection(-1),
+                            KeyCode::Enter if app.open_search_result() => {
+                                refresh_events(app, tx.clone())
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if let Some(planner) = &mut app.planner {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') => app.planner = None,
+                            KeyCode::Tab | KeyCode::Left | KeyCode::Right => planner.toggle_focus(),
+                            KeyCode::Down => planner.move_selection(1),
+                            KeyCode::Up => planner.move_selection(-1),
+                            KeyCode::Enter | KeyCode::Char('l') => {
+                                let target = NewEventTarget::PlannerTask {
+                                    local: key.code == KeyCode::Char('l'),
+                                };
+                                match planner.next_event() {
+                                    Ok(new_event) => {
+                                        check_new_event(app, new_event, target, tx.clone())
+                                    }
+                                    Err(e) => app.set_status(&e),
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
                     if app.show_help {
                         match key.code {
                             KeyCode::Esc
@@ -178,15 +1606,56 @@ pub async fn run_app(
                         continue;
                     }
 
+                    if app.show_diagnostics {
+                        // Any key closes the diagnostics popup
+                        app.show_diagnostics = false;
+                        continue;
+                    }
+
+                    if app.show_missed {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('M') => {
+                                app.show_missed = false;
+                            }
+                            KeyCode::Char('x') => app.notification_manager.clear_missed(),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
                     if app.show_legend {
                         match key.code {
                             KeyCode::Esc
                             | KeyCode::Char('q')
                             | KeyCode::Char('l')
-                            | KeyCode::Char('L')
-                            | KeyCode::Enter => {
+                            | KeyCode::Char('L') => {
                                 app.show_legend = false;
                             }
+                            KeyCode::Down => app.move_legend_selection(1),
+                            KeyCode::Up => app.move_legend_selection(-1),
+                            KeyCode::Enter | KeyCode::Char(' ') => {
+                                if let Some(index) = app.legend_state.selected() {
+                                    app.toggle_legend_calendar(index);
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    if app.show_categories {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('c') => {
+                                app.show_categories = false;
+                            }
+                            KeyCode::Down => app.move_category_selection(1),
+                            KeyCode::Up => app.move_category_selection(-1),
+                            KeyCode::Enter | KeyCode::Char(' ') => {
+                                if let Some(index) = app.category_state.selected() {
+                                    app.toggle_category(index);
+                                }
+                            }
+                            KeyCode::Char('a') => app.clear_category_filter(),
                             _ => {}
                         }
                         continue;
@@ -197,56 +1666,130 @@ pub async fn run_app(
                         continue;
                     }
 
-                    if let KeyCode::Char('l') | KeyCode::Char('L') = key.code {
-                        app.show_legend = true;
+                    if let KeyCode::Char('D') = key.code {
+                        app.show_diagnostics = true;
+                        continue;
+                    }
+
+                    if let KeyCode::Char('M') = key.code {
+                        app.show_missed = true;
+                        continue;
+                    }
+
+                    if let KeyCode::Char(':') = key.code {
+                        app.sql_console = Some(Default::default());
+                        continue;
+                    }
+
+                    if let KeyCode::Char('l') | KeyCode::Char('L') = key.code {
+                        app.open_legend();
+                        continue;
+                    }
+
+                    if let KeyCode::Char('B') = key.code {
+                        app.toggle_busy_only();
+                        continue;
+                    }
+
+                    if let KeyCode::Char('c') = key.code {
+                        let db_pool = app.db_pool.clone();
+                        let tx = tx.clone();
+                        tokio::spawn(async move {
+                            let categories = crate::db::get_categories(&db_pool)
+                                .await
+                                .unwrap_or_else(|e| {
+                                    error!("Failed to read categories: {}", e);
+                                    Vec::new()
+                                });
+                            let _ = tx.send(AppEvent::CategoriesLoaded(categories)).await;
+                        });
+                        continue;
+                    }
+
+                    if key.code == KeyCode::Char('f')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        app.open_search_prompt(true);
                         continue;
                     }
 
                     match app.current_view {
                         CurrentView::Calendars => match key.code {
                             KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Char('X') => app.logout_prompt = true,
+                            KeyCode::Char(',') => edit_settings(terminal, app)?,
                             KeyCode::Down => app.next_item(),
                             KeyCode::Up => app.previous_item(),
                             KeyCode::Enter => {
                                 if let Some(selected) = app.calendar_list_state.selected() {
-                                    if selected == 0 {
-                                        app.current_calendar_id = None;
-                                    } else if selected == 1 {
-                                        app.current_calendar_id = Some(MY_CALENDARS_ID.to_string());
-                                    } else {
-                                        let calendar_index = selected - 2;
-                                        app.current_calendar_id =
-                                            Some(app.calendars[calendar_index].calendar.id.clone());
-                                    }
-                                    app.current_view = CurrentView::Events;
-                                    app.start_transition(300);
+                                    app.open_calendar_row(selected);
                                     needs_refresh = true;
                                 }
                             }
+                            KeyCode::Char(' ') => {
+                                if let Some(selected) = app.calendar_list_state.selected() {
+                                    app.toggle_calendar_check(selected);
+                                    save_checked_calendars(app);
+                                }
+                            }
                             _ => {}
                         },
                         CurrentView::Events => match key.code {
                             KeyCode::Char('q') => return Ok(()),
                             KeyCode::Char('b') | KeyCode::Esc => {
-                                app.current_view = CurrentView::Calendars;
-                                app.event_view_mode = EventViewMode::List;
-                                app.displayed_date = Local::now().date_naive();
-                                app.start_transition(300);
+                                if app.drill_back_to_week() {
+                                    needs_refresh = true;
+                                } else {
+                                    app.current_view = CurrentView::Calendars;
+                                    app.event_view_mode = EventViewMode::List;
+                                    app.displayed_date = clock::now().date_naive();
+                                    app.start_transition(300);
+                                }
                             }
-                            KeyCode::Char('r') => needs_refresh = true,
+                            KeyCode::Char('r') => {
+                                app.announce_sync = true;
+                                needs_refresh = true;
+                            }
+                            KeyCode::Char(',') => edit_settings(terminal, app)?,
                             KeyCode::Tab => {
+                                app.show_past = false;
                                 app.toggle_event_view();
                                 needs_refresh = true;
                             }
-                            KeyCode::Enter => {
-                                if app.get_selected_event().is_some() {
-                                    app.detail_view_scroll = 0;
-                                    app.current_view = CurrentView::EventDetail;
-                                }
+                            KeyCode::Char('h') => {
+                                app.toggle_past();
+                                needs_refresh = true;
+                            }
+                            KeyCode::Enter
+                                if matches!(
+                                    app.event_view_mode,
+                                    EventViewMode::Week | EventViewMode::WorkWeek
+                                ) && !app.shows_list() =>
+                            {
+                                app.drill_into_day();
+                                needs_refresh = true;
+                            }
+                            // A folded calendar heading unfolds instead of opening an event
+                            KeyCode::Enter if app.selection_folded() => {
+                                app.toggle_calendar_collapse();
+                                save_list_grouping(app);
+                            }
+                            KeyCode::Enter if app.get_selected_event().is_some() => {
+                                app.detail_view_scroll = 0;
+                                app.current_view = CurrentView::EventDetail;
+                            }
+                            KeyCode::Down if app.at_list_end(true) => {
+                                app.extend_list(true);
+                                needs_refresh = true;
+                            }
+                            KeyCode::Up if app.at_list_end(false) => {
+                                app.extend_list(false);
+                                needs_refresh = true;
                             }
                             KeyCode::Down => app.next_item(),
                             KeyCode::Up => app.previous_item(),
                             KeyCode::Char('a') => {
+                                app.show_past = false;
                                 match app.event_view_mode {
                                     EventViewMode::List | EventViewMode::Month => {
                                         app.previous_month()
@@ -255,42 +1798,118 @@ pub async fn run_app(
                                         app.previous_week()
                                     }
                                     EventViewMode::Day => {
-                                        app.displayed_date =
-                                            app.displayed_date.pred_opt().unwrap();
+                                        app.displayed_date = app.displayed_date.pred_opt().unwrap();
                                     }
+                                    EventViewMode::Dashboard => {}
                                 }
                                 needs_refresh = true;
                             }
                             KeyCode::Char('d') => {
+                                app.show_past = false;
                                 match app.event_view_mode {
                                     EventViewMode::List | EventViewMode::Month => app.next_month(),
                                     EventViewMode::Week | EventViewMode::WorkWeek => {
                                         app.next_week()
                                     }
                                     EventViewMode::Day => {
-                                        app.displayed_date =
-                                            app.displayed_date.succ_opt().unwrap();
+                                        app.displayed_date = app.displayed_date.succ_opt().unwrap();
                                     }
+                                    EventViewMode::Dashboard => {}
                                 }
                                 needs_refresh = true;
                             }
                             KeyCode::Left => app.jump_to_previous_day(),
                             KeyCode::Right => app.jump_to_next_day(),
+                            KeyCode::Char('o') => app.open_schedule_prompt(),
+                            KeyCode::Char('f') => app.open_find_time_prompt(),
+                            KeyCode::Char('p') => needs_refresh = app.open_planner(),
+                            KeyCode::Char('/') => app.open_search_prompt(false),
+                            KeyCode::Char('e') => app.open_edit_prompt(),
+                            KeyCode::Char('j') => app.join_selected_meeting(),
+                            KeyCode::Char('g') => {
+                                app.toggle_list_grouping();
+                                save_list_grouping(app);
+                            }
+                            KeyCode::Char('z') if app.toggle_calendar_collapse() => {
+                                save_list_grouping(app)
+                            }
+                            KeyCode::Char('!') if app.toggle_flag() => save_flagged_events(app),
+                            KeyCode::Char('m') => {
+                                if let Some((event, attended)) = app.cycle_attendance() {
+                                    save_attendance(app, event, attended);
+                                }
+                            }
+                            KeyCode::Char(' ') if app.event_view_mode == EventViewMode::Month => {
+                                app.day_peek = Some(app.focused_day());
+                            }
+                            KeyCode::Char('+') | KeyCode::Char('=')
+                                if app.event_view_mode == EventViewMode::Day =>
+                            {
+                                app.zoom_day_grid(true)
+                            }
+                            KeyCode::Char('-') if app.event_view_mode == EventViewMode::Day => {
+                                app.zoom_day_grid(false)
+                            }
                             _ => {}
                         },
                         CurrentView::EventDetail => match key.code {
                             KeyCode::Char('q') => return Ok(()),
                             KeyCode::Char('b') | KeyCode::Esc => {
+                                app.show_all_attendees = false;
+                                app.show_raw_body = false;
                                 app.current_view = CurrentView::Events;
                                 app.start_transition(300);
                             }
                             KeyCode::Down => app.scroll_down(),
                             KeyCode::Up => app.scroll_up(),
+                            KeyCode::Char(c @ '1'..='9') => {
+                                app.open_detail_link(c as usize - '0' as usize)
+                            }
+                            KeyCode::Char('O') => app.open_in_outlook(),
+                            KeyCode::Char('E') => app.export_selected_event(),
+                            KeyCode::Char('!') if app.toggle_flag() => save_flagged_events(app),
+                            KeyCode::Char('m') => {
+                                if let Some((event, attended)) = app.cycle_attendance() {
+                                    save_attendance(app, event, attended);
+                                }
+                            }
+                            KeyCode::Char('j') => app.join_selected_meeting(),
+                            KeyCode::Char('e') => app.open_edit_prompt(),
+                            KeyCode::Char('x') => {
+                                app.show_all_attendees = !app.show_all_attendees;
+                                app.detail_view_scroll = 0;
+                            }
+                            KeyCode::Char('v') => {
+                                app.show_raw_body = !app.show_raw_body;
+                                app.detail_view_scroll = 0;
+                            }
                             _ => {}
                         },
                     }
                 }
                 CEvent::Mouse(mouse) => {
+                    if app.day_peek.is_some() {
+                        if let MouseEventKind::Down(_) = mouse.kind {
+                            app.day_peek = None;
+                        }
+                        continue;
+                    }
+
+                    if app.input.is_some()
+                        || app.find_time.is_some()
+                        || app.room_picker.is_some()
+                        || app.planner.is_some()
+                        || app.search.is_some()
+                        || app.sql_console.is_some()
+                        || app.alarm.is_some()
+                        || app.conflict_prompt.is_some()
+                        || app.edit_prompt.is_some()
+                        || app.logout_prompt
+                        || app.relogin_prompt.is_some()
+                    {
+                        continue;
+                    }
+
                     if app.show_help {
                         // Click anywhere to close help
                         if let MouseEventKind::Down(_) = mouse.kind {
@@ -299,10 +1918,54 @@ pub async fn run_app(
                         continue;
                     }
 
+                    if app.show_diagnostics {
+                        if let MouseEventKind::Down(_) = mouse.kind {
+                            app.show_diagnostics = false;
+                        }
+                        continue;
+                    }
+
+                    if app.show_missed {
+                        if let MouseEventKind::Down(_) = mouse.kind {
+                            app.show_missed = false;
+                        }
+                        continue;
+                    }
+
                     if app.show_legend {
-                        // Click anywhere to close legend
+                        // Clicking a calendar toggles it, clicking outside closes the legend
+                        if let MouseEventKind::Down(_) = mouse.kind {
+                            let area = app.legend_area;
+                            let inside = mouse.column > area.left()
+                                && mouse.column < area.right().saturating_sub(1)
+                                && mouse.row > area.top()
+                                && mouse.row < area.bottom().saturating_sub(1);
+                            if inside {
+                                let index = (mouse.row - area.top() - 1) as usize
+                                    + app.legend_state.offset();
+                                app.toggle_legend_calendar(index);
+                            } else {
+                                app.show_legend = false;
+                            }
+                        }
+                        continue;
+                    }
+
+                    if app.show_categories {
+                        // Clicking a category ticks it, clicking outside closes the menu
                         if let MouseEventKind::Down(_) = mouse.kind {
-                            app.show_legend = false;
+                            let area = app.category_area;
+                            let inside = mouse.column > area.left()
+                                && mouse.column < area.right().saturating_sub(1)
+                                && mouse.row > area.top()
+                                && mouse.row < area.bottom().saturating_sub(1);
+                            if inside {
+                                let index = (mouse.row - area.top() - 1) as usize
+                                    + app.category_state.offset();
+                                app.toggle_category(index);
+                            } else {
+                                app.show_categories = false;
+                            }
                         }
                         continue;
                     }
@@ -312,7 +1975,7 @@ pub async fn run_app(
                         if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
                             let x = mouse.column;
                             let y = mouse.row;
-                            
+
                             // Re-calculate the centered rect for the popup
                             // This duplicates logic from ui.rs, which is not ideal but necessary for hit testing
                             // unless we store the area in App state.
@@ -320,7 +1983,7 @@ pub async fn run_app(
                             let size = terminal.size()?;
                             let percent_x = 80;
                             let percent_y = 80;
-                            
+
                             let popup_layout = Layout::default()
                                 .direction(Direction::Vertical)
                                 .constraints([
@@ -339,8 +2002,14 @@ pub async fn run_app(
                                 ])
                                 .split(popup_layout[1])[1];
 
-                            if x < popup_area.left() || x >= popup_area.right() || y < popup_area.top() || y >= popup_area.bottom() {
+                            if x < popup_area.left()
+                                || x >= popup_area.right()
+                                || y < popup_area.top()
+                                || y >= popup_area.bottom()
+                            {
                                 // Clicked outside
+                                app.show_all_attendees = false;
+                                app.show_raw_body = false;
                                 app.current_view = CurrentView::Events;
                                 app.start_transition(300);
                                 continue;
@@ -365,110 +2034,18 @@ pub async fn run_app(
                             }
 
                             // Check for Footer Navigation Click
-                            // We need to know where the footer title is.
-                            // Since we don't store it in App, we approximate or need to store it.
-                            // Storing in App is better. But for now, let's recalculate.
-                            let size = terminal.size()?;
-                            let main_chunks = Layout::default()
-                                .direction(Direction::Vertical)
-                                .margin(1)
-                                .constraints([
-                                    Constraint::Length(3), // Header
-                                    Constraint::Min(0),    // Content
-                                    Constraint::Length(1), // Footer
-                                ].as_ref())
-                                .split(size);
-                            
-                            let footer_chunks = Layout::default()
-                                .direction(Direction::Horizontal)
-                                .constraints([
-                                    Constraint::Length(10), // Help
-                                    Constraint::Min(0),     // Title
-                                    Constraint::Length(20), // Date/Time
-                                ])
-                                .split(main_chunks[2]);
-                            
-                            let title_area = footer_chunks[1];
-                            if x >= title_area.left() && x < title_area.right() && y >= title_area.top() && y < title_area.bottom() {
-                                // Reconstruct the footer title to calculate its width
-                                let calendar_name = app.current_calendar_id.as_ref()
-                                    .and_then(|id| app.calendars.iter().find(|c| c.calendar.id == *id).map(|c| c.calendar.name.clone()))
-                                    .unwrap_or_else(|| "All Calendars".to_string());
-
-                                let footer_text = match app.event_view_mode {
-                                    EventViewMode::List => format!(
-                                        " {} {} {} ",
-                                        app.symbols.left_arrow, calendar_name, app.symbols.right_arrow
-                                    ),
-                                    EventViewMode::Month => {
-                                        let displayed_date = app.displayed_date;
-                                        let month_str = format!(
-                                            "{} {}",
-                                            [
-                                                "",
-                                                "January",
-                                                "February",
-                                                "March",
-                                                "April",
-                                                "May",
-                                                "June",
-                                                "July",
-                                                "August",
-                                                "September",
-                                                "October",
-                                                "November",
-                                                "December"
-                                            ][displayed_date.month() as usize],
-                                            displayed_date.year()
-                                        );
-                                        format!(
-                                            " {} {} - {} {} ",
-                                            app.symbols.left_arrow, calendar_name, month_str, app.symbols.right_arrow
-                                        )
-                                    }
-                                    EventViewMode::Week => {
-                                        let mut week_start = app.displayed_date;
-                                        while week_start.weekday() != Weekday::Sun {
-                                            week_start = week_start.pred_opt().unwrap();
-                                        }
-                                        let week_end = week_start + ChronoDuration::days(6);
-                                        format!(
-                                            " {} {} ({} to {}) {} ",
-                                            app.symbols.left_arrow,
-                                            calendar_name,
-                                            week_start.format("%d/%m"),
-                                            week_end.format("%d/%m"),
-                                            app.symbols.right_arrow
-                                        )
-                                    }
-                                    EventViewMode::WorkWeek => {
-                                        let mut week_start = app.displayed_date;
-                                        while week_start.weekday() != Weekday::Mon {
-                                            week_start = week_start.pred_opt().unwrap();
-                                        }
-                                        let week_end = week_start + ChronoDuration::days(4);
-                                        format!(
-                                            " {} {} ({} to {}) {} ",
-                                            app.symbols.left_arrow,
-                                            calendar_name,
-                                            week_start.format("%d/%m"),
-                                            week_end.format("%d/%m"),
-                                            app.symbols.right_arrow
-                                        )
-                                    }
-                                    EventViewMode::Day => {
-                                        let current_day = app.displayed_date;
-                                        format!(
-                                            " {} {} ({}) {} ",
-                                            app.symbols.left_arrow,
-                                            calendar_name,
-                                            current_day.format("%a, %d %b %Y"),
-                                            app.symbols.right_arrow
-                                        )
-                                    }
-                                };
+                            // The breadcrumb's area is recorded while drawing the footer;
+                            // it is empty when the module isn't configured.
+                            let title_area = app.footer_breadcrumb_area;
+                            if x >= title_area.left()
+                                && x < title_area.right()
+                                && y >= title_area.top()
+                                && y < title_area.bottom()
+                            {
+                                let footer_text = breadcrumb_text(app);
 
-                                let text_width = UnicodeWidthStr::width(footer_text.as_str()) as u16;
+                                let text_width =
+                                    UnicodeWidthStr::width(footer_text.as_str()) as u16;
                                 // Footer is Right Aligned
                                 let end_x = title_area.right();
                                 let start_x = end_x.saturating_sub(text_width);
@@ -480,59 +2057,81 @@ pub async fn run_app(
                                     // Let's assume a generous hit area of 4 chars from edges.
                                     if x < start_x + 4 {
                                         // Previous
+                                        app.show_past = false;
                                         match app.event_view_mode {
-                                            EventViewMode::List | EventViewMode::Month => app.previous_month(),
-                                            EventViewMode::Week | EventViewMode::WorkWeek => app.previous_week(),
-                                            EventViewMode::Day => { app.displayed_date = app.displayed_date.pred_opt().unwrap(); }
+                                            EventViewMode::List | EventViewMode::Month => {
+                                                app.previous_month()
+                                            }
+                                            EventViewMode::Week | EventViewMode::WorkWeek => {
+                                                app.previous_week()
+                                            }
+                                            EventViewMode::Day => {
+                                                app.displayed_date =
+                                                    app.displayed_date.pred_opt().unwrap();
+                                            }
+                                            EventViewMode::Dashboard => {}
                                         }
                                         needs_refresh = true;
                                     } else if x >= end_x - 4 {
                                         // Next
+                                        app.show_past = false;
                                         match app.event_view_mode {
-                                            EventViewMode::List | EventViewMode::Month => app.next_month(),
-                                            EventViewMode::Week | EventViewMode::WorkWeek => app.next_week(),
-                                            EventViewMode::Day => { app.displayed_date = app.displayed_date.succ_opt().unwrap(); }
+                                            EventViewMode::List | EventViewMode::Month => {
+                                                app.next_month()
+                                            }
+                                            EventViewMode::Week | EventViewMode::WorkWeek => {
+                                                app.next_week()
+                                            }
+                                            EventViewMode::Day => {
+                                                app.displayed_date =
+                                                    app.displayed_date.succ_opt().unwrap();
+                                            }
+                                            EventViewMode::Dashboard => {}
                                         }
                                         needs_refresh = true;
                                     }
                                 }
                             }
 
-
                             // Check for Tabs Click
                             // Replicate layout logic to find tabs area
                             let size = terminal.size()?;
-                            let main_chunks = Layout::default()
-                                .direction(Direction::Vertical)
-                                .margin(1)
-                                .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
-                                .split(size);
+                            let main_chunks = main_layout(size, app.compact);
                             let header_chunks = Layout::default()
                                 .direction(Direction::Horizontal)
                                 .constraints([
-                                    Constraint::Length(68), // Tabs (tuned to 68 to remove extra space)
+                                    Constraint::Length(80), // Tabs (tuned to 80 to remove extra space)
                                     Constraint::Min(0),     // Title (takes remaining space)
                                 ])
                                 .split(main_chunks[0]);
-                            
+
                             let tabs_area = header_chunks[0];
-                            if x >= tabs_area.left() && x < tabs_area.right() && y >= tabs_area.top() && y < tabs_area.bottom() {
+                            if x >= tabs_area.left()
+                                && x < tabs_area.right()
+                                && y >= tabs_area.top()
+                                && y < tabs_area.bottom()
+                            {
                                 // Inside tabs area
                                 // Revert to logic compatible with Tabs widget
                                 let relative_x = x.saturating_sub(tabs_area.left() + 1); // +1 for left border
-                                
-                                let calendar_icon = format!(" {} Cals ", app.symbols.calendar);
-                                let list_icon = "  List ".to_string();
-                                let week_icon = format!(" {} Week ", app.symbols.clock);
-                                let work_icon = "  Work ".to_string();
-                                let day_icon = "  Day ".to_string();
-                                let month_icon = "  Month ".to_string();
+
+                                let [calendar_icon, today_icon, list_icon, week_icon, work_icon, day_icon, month_icon] =
+                                    tab_labels(app, app.compact);
 
                                 let tab_data = [
                                     (calendar_icon, CurrentView::Calendars, None),
+                                    (
+                                        today_icon,
+                                        CurrentView::Events,
+                                        Some(EventViewMode::Dashboard),
+                                    ),
                                     (list_icon, CurrentView::Events, Some(EventViewMode::List)),
                                     (week_icon, CurrentView::Events, Some(EventViewMode::Week)),
-                                    (work_icon, CurrentView::Events, Some(EventViewMode::WorkWeek)),
+                                    (
+                                        work_icon,
+                                        CurrentView::Events,
+                                        Some(EventViewMode::WorkWeek),
+                                    ),
                                     (day_icon, CurrentView::Events, Some(EventViewMode::Day)),
                                     (month_icon, CurrentView::Events, Some(EventViewMode::Month)),
                                 ];
@@ -548,14 +2147,15 @@ pub async fn run_app(
                                     // If we are at the first item, it occupies text.width.
                                     // Then a divider.
                                     // Let's approximate: text.width + 3 to be safe and cover spacing.
-                                    let width = text.width() as u16 + 3; 
-                                    
+                                    let width = text.width() as u16 + 3;
+
                                     if relative_x < current_width_sum + width {
                                         // Clicked this tab
                                         app.current_view = *view;
                                         if let Some(m) = mode {
                                             app.event_view_mode = *m;
-                                            refresh_events(app, tx.clone()).await;
+                                            app.day_drilled_from = None;
+                                            refresh_events(app, tx.clone());
                                         }
                                         break;
                                     }
@@ -589,46 +2189,35 @@ pub async fn run_app(
                                             // However, for small lists (calendars), it fits on screen.
                                             if index < app.calendars.len() + 2 {
                                                 app.calendar_list_state.select(Some(index));
-                                                // Trigger selection action
-                                                if index == 0 {
-                                                    app.current_calendar_id = None;
-                                                } else if index == 1 {
-                                                    app.current_calendar_id =
-                                                        Some(MY_CALENDARS_ID.to_string());
+                                                // The checkbox after the highlight symbol ticks, the rest opens
+                                                if index >= 2 && x < area.left() + 7 {
+                                                    app.toggle_calendar_check(index);
+                                                    save_checked_calendars(app);
                                                 } else {
-                                                    let calendar_index = index - 2;
-                                                    app.current_calendar_id = Some(
-                                                        app.calendars[calendar_index]
-                                                            .calendar
-                                                            .id
-                                                            .clone(),
-                                                    );
+                                                    app.open_calendar_row(index);
+                                                    needs_refresh = true;
                                                 }
-                                                app.current_view = CurrentView::Events;
-                                                app.start_transition(300);
-                                                needs_refresh = true;
                                             }
                                         }
                                     }
                                 }
                                 CurrentView::Events => {
-                                    if let EventViewMode::List = app.event_view_mode {
+                                    if app.shows_list() {
                                         let area = app.event_list_area;
                                         if x >= area.left()
                                             && x < area.right()
-                                            && y >= area.top()
-                                            && y < area.bottom()
+                                            && y > area.top()
+                                            && y < area.bottom() - 1
                                         {
-                                            if y > area.top() && y < area.bottom() - 1 {
-                                                let visual_index = (y - area.top() - 1) as usize;
-                                                let offset = app.event_list_state.offset();
-                                                let index = offset + visual_index;
-
-                                                if index < app.events.len() {
-                                                    app.event_list_state.select(Some(index));
-                                                    app.detail_view_scroll = 0;
-                                                    app.current_view = CurrentView::EventDetail;
-                                                }
+                                            let visual_index = (y - area.top() - 1) as usize;
+                                            let offset = app.event_list_state.offset();
+
+                                            if let Some(index) =
+                                                app.event_at_list_row(offset + visual_index)
+                                            {
+                                                app.event_list_state.select(Some(index));
+                                                app.detail_view_scroll = 0;
+                                                app.current_view = CurrentView::EventDetail;
                                             }
                                         }
                                     } else if let EventViewMode::Month = app.event_view_mode {
@@ -657,9 +2246,16 @@ pub async fn run_app(
                                             .position(|r| y >= r.top() && y < r.bottom())
                                         {
                                             let row_area = row_chunks[row];
+                                            let columns = app.month_columns();
                                             let col_chunks = Layout::default()
                                                 .direction(Direction::Horizontal)
-                                                .constraints(vec![Constraint::Ratio(1, 7); 7])
+                                                .constraints(vec![
+                                                    Constraint::Ratio(
+                                                        1,
+                                                        columns.len() as u32
+                                                    );
+                                                    columns.len()
+                                                ])
                                                 .split(row_area);
 
                                             if let Some(col) = col_chunks
@@ -674,7 +2270,7 @@ pub async fn run_app(
                                                     starting_day = starting_day.pred_opt().unwrap();
                                                 }
 
-                                                let days_offset = (row * 7 + col) as i64;
+                                                let days_offset = (row * 7 + columns[col]) as i64;
                                                 let clicked_date = starting_day
                                                     + ChronoDuration::days(days_offset);
 
@@ -698,13 +2294,19 @@ pub async fn run_app(
                                                             ),
                                                         ) {
                                                             let start_date = start.date();
-                                                            let effective_end_date = if end.time() == chrono::NaiveTime::MIN && end.date() > start_date {
+                                                            let effective_end_date = if end.time()
+                                                                == chrono::NaiveTime::MIN
+                                                                && end.date() > start_date
+                                                            {
                                                                 end.date().pred_opt().unwrap()
                                                             } else {
                                                                 end.date()
                                                             };
 
-                                                            if start_date <= clicked_date && effective_end_date >= clicked_date {
+                                                            if start_date <= clicked_date
+                                                                && effective_end_date
+                                                                    >= clicked_date
+                                                            {
                                                                 return Some(i);
                                                             }
                                                         }
@@ -731,73 +2333,56 @@ pub async fn run_app(
                                                 }
 
                                                 // Switch to List View for this date if clicked on header or empty space
-                                                app.displayed_date = clicked_date;
-                                                app.event_view_mode = EventViewMode::List;
-                                                app.start_transition(300);
+                                                app.drill_into_list(clicked_date);
                                                 needs_refresh = true;
                                             }
                                         }
-                                    } else if let EventViewMode::Week
-                                        | EventViewMode::WorkWeek = app.event_view_mode
+                                    } else if let EventViewMode::Week | EventViewMode::WorkWeek =
+                                        app.event_view_mode
+                                    {
+                                        // Week/Work Week View Click Logic
+                                        let inner_area =
+                                            app.event_list_area.inner(ratatui::layout::Margin {
+                                                vertical: 1,
+                                                horizontal: 1,
+                                            });
+                                        if x >= inner_area.left()
+                                            && x < inner_area.right()
+                                            && y >= inner_area.top()
+                                            && y < inner_area.bottom()
                                         {
-                                            // Week/Work Week View Click Logic
-                                            let inner_area = app.event_list_area.inner(
-                                                ratatui::layout::Margin {
-                                                    vertical: 1,
-                                                    horizontal: 1,
-                                                },
-                                            );
-                                            if x >= inner_area.left()
-                                                && x < inner_area.right()
-                                                && y >= inner_area.top()
-                                                && y < inner_area.bottom()
+                                            let (first_weekday, num_days) = app.week_layout();
+                                            let num_days = num_days as usize;
+
+                                            let col_chunks = Layout::default()
+                                                .direction(Direction::Horizontal)
+                                                .constraints(vec![
+                                                    Constraint::Ratio(
+                                                        1,
+                                                        num_days as u32
+                                                    );
+                                                    num_days
+                                                ])
+                                                .split(inner_area);
+
+                                            if let Some(col) = col_chunks
+                                                .iter()
+                                                .position(|c| x >= c.left() && x < c.right())
                                             {
-                                                let num_days = if let EventViewMode::Week =
-                                                    app.event_view_mode
-                                                {
-                                                    7
-                                                } else {
-                                                    5
-                                                };
-
-                                                let col_chunks = Layout::default()
-                                                    .direction(Direction::Horizontal)
-                                                    .constraints(vec![
-                                                        Constraint::Ratio(
-                                                            1,
-                                                            num_days as u32
-                                                        );
-                                                        num_days
-                                                    ])
-                                                    .split(inner_area);
-
-                                                if let Some(col) = col_chunks
-                                                    .iter()
-                                                    .position(|c| x >= c.left() && x < c.right())
-                                                {
-                                                    // Calculate date
-                                                    let mut start_date = app.displayed_date;
-                                                    if let EventViewMode::Week = app.event_view_mode
-                                                    {
-                                                        while start_date.weekday() != Weekday::Sun {
-                                                            start_date =
-                                                                start_date.pred_opt().unwrap();
-                                                        }
-                                                    } else {
-                                                        while start_date.weekday() != Weekday::Mon {
-                                                            start_date =
-                                                                start_date.pred_opt().unwrap();
-                                                        }
-                                                    }
+                                                // Calculate date
+                                                let mut start_date = app.displayed_date;
+                                                while start_date.weekday() != first_weekday {
+                                                    start_date = start_date.pred_opt().unwrap();
+                                                }
 
-                                                    let clicked_date = start_date
-                                                        + ChronoDuration::days(col as i64);
+                                                let clicked_date =
+                                                    start_date + ChronoDuration::days(col as i64);
 
-                                                    // Check if we clicked on an event
-                                                    let local_y = y - inner_area.top();
+                                                // Check if we clicked on an event
+                                                let local_y = y - inner_area.top();
 
-                                                    // Filter events for this day
-                                                    let day_events: Vec<(usize, String)> = app
+                                                // Filter events for this day
+                                                let day_events: Vec<(usize, String)> = app
                                                         .events
                                                         .iter()
                                                         .enumerate()
@@ -825,11 +2410,13 @@ pub async fn run_app(
                                                                         .with_timezone(&Local);
                                                                     let end_local = DateTime::<Utc>::from_naive_utc_and_offset(end, Utc)
                                                                         .with_timezone(&Local);
-                                                                    
+
                                                                     let event_str = format!(
-                                                                        "■ {}-{} {}",
+                                                                        "{}{}-{} {}{}",
+                                                                        e.icon.as_deref().unwrap_or("■ "),
                                                                         start_local.format("%H:%M"),
                                                                         end_local.format("%H:%M"),
+                                                                        app.event_badges(&e.event),
                                                                         e.event.subject
                                                                     );
                                                                     return Some((i, event_str));
@@ -839,153 +2426,74 @@ pub async fn run_app(
                                                         })
                                                         .collect();
 
-                                                    // Get actual column width from the layout chunk
-                                                    let col_rect = col_chunks[col];
-                                                    let content_width = col_rect.width.saturating_sub(2) as usize;
-
-                                                    if local_y > 0 {
-                                                        let content_y = (local_y - 1) as usize; // Adjust for top border
-                                                        let mut accumulated_height = 0;
-                                                        let mut event_clicked = false;
-
-                                                        for (index, text) in day_events {
-                                                            // Calculate wrapped height using word wrapping approximation
-                                                            let height = if content_width > 0 {
-                                                                let mut lines = 1;
-                                                                let mut current_line_len = 0;
-                                                                for word in text.split_whitespace() {
-                                                                    let word_len = UnicodeWidthStr::width(word);
-                                                                    if current_line_len + word_len + (if current_line_len > 0 { 1 } else { 0 }) > content_width {
-                                                                        lines += 1;
-                                                                        current_line_len = word_len;
-                                                                    } else {
-                                                                        current_line_len += word_len + (if current_line_len > 0 { 1 } else { 0 });
-                                                                    }
-                                                                }
-                                                                lines
-                                                            } else {
-                                                                1
-                                                            };
-                                                            
-                                                            if content_y >= accumulated_height && content_y < accumulated_height + height {
-                                                                app.event_list_state.select(Some(index));
-                                                                app.detail_view_scroll = 0;
-                                                                app.current_view = CurrentView::EventDetail;
-                                                                event_clicked = true;
-                                                                break; 
-                                                            }
-                                                            accumulated_height += height;
-                                                        }
-
-                                                        if !event_clicked {
-                                                            // Switch to List View
-                                                            app.displayed_date = clicked_date;
-                                                            app.event_view_mode = EventViewMode::List;
-                                                            app.start_transition(300);
-                                                            needs_refresh = true;
-                                                        }
-                                                    } else {
-                                                        // Clicked on header (local_y == 0)
-                                                        // Switch to List View
-                                                        app.displayed_date = clicked_date;
-                                                        app.event_view_mode = EventViewMode::List;
-                                                        app.start_transition(300);
-                                                        needs_refresh = true;
-                                                    }
-                                                }
-                                            }
-                                        } else if let EventViewMode::Day = app.event_view_mode {
-                                            // Day View Click Logic
-                                            let inner_area = app.event_list_area.inner(
-                                                ratatui::layout::Margin {
-                                                    vertical: 1,
-                                                    horizontal: 1,
-                                                },
-                                            );
-                                            if x >= inner_area.left()
-                                                && x < inner_area.right()
-                                                && y >= inner_area.top()
-                                                && y < inner_area.bottom()
-                                            {
-                                                let clicked_date = app.displayed_date;
-                                                let local_y = y - inner_area.top();
-
-                                                // Filter events for this day
-                                                let day_events: Vec<(usize, String)> = app
-                                                    .events
-                                                    .iter()
-                                                    .enumerate()
-                                                    .filter_map(|(i, e)| {
-                                                        if let (Ok(start), Ok(end)) = (
-                                                            NaiveDateTime::parse_from_str(
-                                                                &e.event.start.date_time,
-                                                                "%Y-%m-%dT%H:%M:%S%.f",
-                                                            ),
-                                                            NaiveDateTime::parse_from_str(
-                                                                &e.event.end.date_time,
-                                                                "%Y-%m-%dT%H:%M:%S%.f",
-                                                            ),
-                                                        ) {
-                                                            let start_date = start.date();
-                                                            let effective_end_date = if end.time() == chrono::NaiveTime::MIN && end.date() > start_date {
-                                                                end.date().pred_opt().unwrap()
-                                                            } else {
-                                                                end.date()
-                                                            };
-
-                                                            if start_date <= clicked_date && effective_end_date >= clicked_date {
-                                                                let start_local = DateTime::<Utc>::from_naive_utc_and_offset(start, Utc)
-                                                                    .with_timezone(&Local);
-                                                                let end_local = DateTime::<Utc>::from_naive_utc_and_offset(end, Utc)
-                                                                    .with_timezone(&Local);
-                                                                
-                                                                let event_str = format!(
-                                                                    "■ {}-{} {}",
-                                                                    start_local.format("%H:%M"),
-                                                                    end_local.format("%H:%M"),
-                                                                    e.event.subject
-                                                                );
-                                                                return Some((i, event_str));
-                                                            }
-                                                        }
-                                                        None
-                                                    })
-                                                    .collect();
-
-                                                let content_width = inner_area.width as usize;
+                                                // Get actual column width from the layout chunk
+                                                let col_rect = col_chunks[col];
+                                                let content_width =
+                                                    col_rect.width.saturating_sub(2) as usize;
 
-                                                // No extra top border inside inner_area
-                                                let content_y = local_y as usize;
-                                                let mut accumulated_height = 0;
+                                                if local_y > 0 {
+                                                    let content_y = (local_y - 1) as usize; // Adjust for top border
+                                                    let mut accumulated_height = 0;
+                                                    let mut event_clicked = false;
 
                                                     for (index, text) in day_events {
-                                                        let height = if content_width > 0 {
-                                                            let mut lines = 1;
-                                                            let mut current_line_len = 0;
-                                                            for word in text.split_whitespace() {
-                                                                let word_len = UnicodeWidthStr::width(word);
-                                                                if current_line_len + word_len + (if current_line_len > 0 { 1 } else { 0 }) > content_width {
-                                                                    lines += 1;
-                                                                    current_line_len = word_len;
-                                                                } else {
-                                                                    current_line_len += word_len + (if current_line_len > 0 { 1 } else { 0 });
-                                                                }
-                                                            }
-                                                            lines
-                                                        } else {
-                                                            1
-                                                        };
-                                                        
-                                                        if content_y >= accumulated_height && content_y < accumulated_height + height {
-                                                            app.event_list_state.select(Some(index));
+                                                        let height =
+                                                            wrapped_height(&text, content_width);
+
+                                                        if content_y >= accumulated_height
+                                                            && content_y
+                                                                < accumulated_height + height
+                                                        {
+                                                            app.event_list_state
+                                                                .select(Some(index));
                                                             app.detail_view_scroll = 0;
-                                                            app.current_view = CurrentView::EventDetail;
-                                                            continue; 
+                                                            app.current_view =
+                                                                CurrentView::EventDetail;
+                                                            event_clicked = true;
+                                                            break;
                                                         }
                                                         accumulated_height += height;
                                                     }
+
+                                                    if !event_clicked {
+                                                        // Switch to List View
+                                                        app.drill_into_list(clicked_date);
+                                                        needs_refresh = true;
+                                                    }
+                                                } else {
+                                                    // Clicked on header (local_y == 0)
+                                                    // Switch to List View
+                                                    app.drill_into_list(clicked_date);
+                                                    needs_refresh = true;
+                                                }
+                                            }
+                                        }
+                                    } else if let EventViewMode::Day = app.event_view_mode {
+                                        // Day View Click Logic
+                                        let inner_area =
+                                            app.event_list_area.inner(ratatui::layout::Margin {
+                                                vertical: 1,
+                                                horizontal: 1,
+                                            });
+                                        if x >= inner_area.left()
+                                            && x < inner_area.right()
+                                            && y >= inner_area.top()
+                                            && y < inner_area.bottom()
+                                        {
+                                            let row = (y - inner_area.top()) as usize;
+                                            let column = x - inner_area.left();
+                                            let hit = app.day_grid_rows.get(row).and_then(|hits| {
+                                                hits.iter()
+                                                    .find(|(columns, _)| columns.contains(&column))
+                                                    .map(|(_, index)| *index)
+                                            });
+                                            if let Some(index) = hit {
+                                                app.event_list_state.select(Some(index));
+                                                app.detail_view_scroll = 0;
+                                                app.current_view = CurrentView::EventDetail;
                                             }
                                         }
+                                    }
                                 }
                                 _ => {}
                             }
@@ -994,7 +2502,7 @@ pub async fn run_app(
                             match app.current_view {
                                 CurrentView::Calendars => app.next_item(),
                                 CurrentView::Events => {
-                                    if let EventViewMode::List = app.event_view_mode {
+                                    if app.shows_list() {
                                         app.next_item();
                                     } else {
                                         // For other views, maybe next month/week?
@@ -1022,7 +2530,7 @@ pub async fn run_app(
                         MouseEventKind::ScrollUp => match app.current_view {
                             CurrentView::Calendars => app.previous_item(),
                             CurrentView::Events => {
-                                if let EventViewMode::List = app.event_view_mode {
+                                if app.shows_list() {
                                     app.previous_item();
                                 } else {
                                     match app.event_view_mode {
@@ -1060,18 +2568,201 @@ pub async fn run_app(
                         needs_refresh = true;
                     }
                 }
-                AppEvent::EventsLoaded(mut events) => {
+                AppEvent::CachedEventsLoaded(generation, _)
+                | AppEvent::EventsPageLoaded(generation, _)
+                | AppEvent::EventsLoaded(generation, _)
+                    if generation < app.refresh_generation =>
+                {
+                    info!("Dropping events of an earlier refresh");
+                }
+                AppEvent::CachedEventsLoaded(_, mut events) => {
+                    if !events.is_empty() {
+                        events
+                            .sort_by(|a, b| a.event.start.date_time.cmp(&b.event.start.date_time));
+                        let previous = app.selected_event_id();
+                        app.set_events(events);
+                        if let Some(date) = app.focus_date {
+                            app.select_first_event_on(date);
+                        } else if previous.is_none() || app.selected_event_id() != previous {
+                            app.select_nearest_event();
+                        }
+                    }
+                }
+                AppEvent::EventsPageLoaded(_, events) => {
+                    app.merge_events(events);
+                    if app.event_list_state.selected().is_none() && !app.events.is_empty() {
+                        app.select_nearest_event();
+                    }
+                }
+                AppEvent::EventsLoaded(_, mut events) => {
                     events.sort_by(|a, b| a.event.start.date_time.cmp(&b.event.start.date_time));
-                    // Check notifications for new events
-                    app.notification_manager.check_and_notify(&events.iter().map(|e| e.event.clone()).collect::<Vec<_>>());
-                    
-                    app.events = events;
+                    // Check notifications for new events. The past view only holds
+                    // events that already happened, so there is nothing to announce.
+                    if !app.show_past && app.is_primary {
+                        let announced = app.notification_manager.check_and_notify(&events);
+                        announce_reminders(terminal, app, announced);
+                    }
+
+                    let previous = app.selected_event_id();
+                    app.set_events(events);
                     if !app.events.is_empty() {
-                        app.select_nearest_event();
+                        app.select_after_load(previous.as_deref());
                     } else {
                         app.event_list_state.select(None);
                     }
+                    app.refresh_planner_slots();
+                }
+                AppEvent::AccountLoaded(account) => app.account = Some(account),
+                AppEvent::SyncFinished(errors) => {
+                    if let Some(error) = errors.first() {
+                        // Once per outage, not on every sync that fails
+                        if !app.sync_failing {
+                            app.toast(ToastKind::Error, "Sync failed", error);
+                        }
+                        app.sync_failing = true;
+                    } else {
+                        if app.announce_sync {
+                            app.toast(ToastKind::Info, "Synced", "Your calendars are up to date.");
+                        }
+                        app.sync_failing = false;
+                    }
+                    app.announce_sync = false;
+                }
+                AppEvent::SyncTruncated(limit) => {
+                    if let (Some(limit), None) = (limit, app.sync_truncated) {
+                        app.set_status(&format!("Synced the first {} events of a calendar, the next refresh fetches more", limit));
+                    }
+                    app.sync_truncated = limit;
+                    // Sent at the end of every sync that went through
+                    warm_up_cache(app, tx.clone());
+                }
+                AppEvent::CacheWarmUpInterrupted => {
+                    app.cache_warmed_on = None;
+                }
+                AppEvent::LastSyncedLoaded(synced_at) => {
+                    app.last_synced = synced_at;
+                }
+                AppEvent::ScheduleLoaded(schedule) => {
+                    app.schedule_overlay = schedule;
+                }
+                AppEvent::TodaysMeetingsLoaded(meetings) => app.status_manager.update(&meetings),
+                AppEvent::ConflictsChecked(new_event, target, conflicts) => {
+                    if let Some(new_event) = app.guard_new_event(new_event, target, conflicts) {
+                        save_new_event(app, new_event, target, tx.clone());
+                    }
+                }
+                AppEvent::DueTasksLoaded(Ok(tasks)) => {
+                    app.due_tasks = tasks;
+                    app.due_tasks_error = None;
+                }
+                AppEvent::DueTasksLoaded(Err(e)) => {
+                    // Offered once; the error stays in the Due box
+                    if e == TASKS_NOT_GRANTED
+                        && app.due_tasks_error.is_none()
+                        && app.relogin_prompt.is_none()
+                    {
+                        app.relogin_prompt = Some(
+                            "The dashboard's tasks need the Tasks.Read permission. Log in again to grant it?".to_string(),
+                        );
+                    }
+                    app.due_tasks.clear();
+                    app.due_tasks_error = Some(e);
+                }
+                AppEvent::RoomsFound(rooms) => app.set_rooms(rooms),
+                AppEvent::MeetingTimesFound(result) => match result {
+                    Ok(result) => app.set_meeting_suggestions(
+                        result.meeting_time_suggestions,
+                        result.empty_suggestions_reason,
+                    ),
+                    Err(e) => {
+                        app.find_time = None;
+                        app.set_status(&format!("Could not find meeting times: {}", e));
+                    }
+                },
+                AppEvent::EventCreated(result, task) => match result {
+                    Ok(subject) => {
+                        // Only now that the event exists is its task done
+                        if let Some(task) = task {
+                            task.mark_done();
+                        }
+                        app.set_status(&format!("Created \"{}\"", subject));
+                        needs_refresh = true;
+                    }
+                    Err(e) => app.toast(ToastKind::Error, "Failed to create event", &e),
+                },
+                AppEvent::EventUpdated(result) => match result {
+                    Ok(subject) => {
+                        app.set_status(&format!("Saved \"{}\"", subject));
+                        needs_refresh = true;
+                    }
+                    Err(e) => app.toast(ToastKind::Error, "Failed to save event", &e),
+                },
+                AppEvent::SqlQueryFinished(result) => {
+                    if let Some(console) = &mut app.sql_console {
+                        console.running = false;
+                        console.scroll = 0;
+                        console.result = Some(result);
+                    }
+                }
+                AppEvent::CachedSearchResults(events) => {
+                    if let Some(search) = &mut app.search {
+                        search.merge_cached_results(events);
+                    }
+                }
+                AppEvent::SearchResults(result) => {
+                    if let Some(search) = &mut app.search {
+                        search.merge_server_results(result);
+                    }
+                }
+                AppEvent::CategoriesLoaded(categories) => app.open_category_menu(categories),
+                AppEvent::LocalBlockSaved(result, task) => match result {
+                    Ok(subject) => {
+                        task.mark_done();
+                        app.set_status(&format!("Blocked \"{}\" locally", subject));
+                        needs_refresh = true;
+                    }
+                    Err(e) => app.toast(ToastKind::Error, "Failed to save local block", &e),
+                },
+                AppEvent::RulesApplied(count) => {
+                    app.set_status(&format!("Rules answered {} invite(s)", count));
                 }
+                AppEvent::LeaseChecked {
+                    primary,
+                    events_changed_at,
+                } => {
+                    if primary != app.is_primary {
+                        app.is_primary = primary;
+                        if primary {
+                            info!("Took over the sync lease.");
+                            app.set_status("No other instance is syncing; this one takes over");
+                            needs_refresh = true;
+                        } else {
+                            warn!("Lost the sync lease to another instance.");
+                            app.set_status(
+                                "Another instance is syncing; this one only shows the cache",
+                            );
+                        }
+                    } else if !primary && events_changed_at != app.events_changed_at {
+                        needs_refresh = matches!(app.current_view, CurrentView::Events);
+                    }
+                    app.events_changed_at = events_changed_at;
+                }
+                AppEvent::MeetingArtifactsLoaded(event_id, artifacts) => {
+                    app.meeting_artifacts.insert(event_id, Some(artifacts));
+                }
+                AppEvent::EventBodyLoaded(event_id, body) => {
+                    if app
+                        .fetched_body
+                        .as_ref()
+                        .is_some_and(|(id, _)| *id == event_id)
+                    {
+                        app.fetched_body = Some((event_id, Some(body)));
+                    }
+                }
+                AppEvent::NotificationAction(action) => match action {
+                    NotificationAction::Join(event_id) => app.join_meeting(&event_id),
+                    NotificationAction::Open(event_id) => app.show_event(&event_id),
+                },
                 AppEvent::TokenExpired => {
                     warn!("Token expired. Refreshing...");
                     if app.refresh_auth_token().await.is_ok() {
@@ -1083,13 +2774,31 @@ pub async fn run_app(
                         needs_refresh = true;
                     } else {
                         error!("Failed to refresh token.");
+                        app.relogin_prompt = Some(
+                            "Your login has expired. Log in again to keep syncing?".to_string(),
+                        );
                     }
                 }
+                AppEvent::LoginRejected => {
+                    app.relogin_prompt = Some(
+                        "Microsoft no longer accepts your login. Log in again to keep syncing?"
+                            .to_string(),
+                    );
+                }
+                AppEvent::LoggedIn(result) => match result {
+                    Ok(access_token) => {
+                        app.access_token = access_token;
+                        app.set_status("Logged in again.");
+                        needs_refresh = true;
+                    }
+                    Err(e) => app.toast(ToastKind::Error, "Login failed", &e),
+                },
             }
         }
 
         if needs_refresh {
-            refresh_events(app, tx.clone()).await;
+            refresh_events(app, tx.clone());
+            wake_refresh_pending = false;
         }
     }
 }
@@ -1104,29 +2813,41 @@ fn get_view_date_range(app: &App) -> (DateTime<Utc>, DateTime<Utc>) {
     };
 
     match app.event_view_mode {
+        EventViewMode::Dashboard => {
+            // The whole current month for the mini calendar, plus a month ahead
+            // so the countdown and invitations can look past the month's end.
+            let today = clock::now().date_naive();
+            let start = today.with_day(1).unwrap();
+            let end = today + ChronoDuration::days(31);
+            (to_utc(start), to_utc(end))
+        }
+        EventViewMode::List if app.show_past => {
+            let today = clock::now().date_naive();
+            let start = today - ChronoDuration::days(app.past_days());
+            (to_utc(start), clock::now_utc())
+        }
         EventViewMode::List | EventViewMode::Month => {
             let y = app.displayed_date.year();
             let m = app.displayed_date.month();
-            let start = NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+            let mut start = NaiveDate::from_ymd_opt(y, m, 1).unwrap();
             let next_m = if m == 12 { 1 } else { m + 1 };
             let next_y = if m == 12 { y + 1 } else { y };
-            let end = NaiveDate::from_ymd_opt(next_y, next_m, 1).unwrap();
-            (to_utc(start), to_utc(end))
-        }
-        EventViewMode::Week => {
-            let mut start = app.displayed_date;
-            while start.weekday() != Weekday::Sun {
-                start = start.pred_opt().unwrap();
+            let mut end = NaiveDate::from_ymd_opt(next_y, next_m, 1).unwrap();
+            // Months loaded by scrolling past the ends of the list
+            if app.event_view_mode == EventViewMode::List {
+                let (before, after) = app.list_months_extended;
+                start = start - Months::new(before);
+                end = end + Months::new(after);
             }
-            let end = start + ChronoDuration::days(7);
             (to_utc(start), to_utc(end))
         }
-        EventViewMode::WorkWeek => {
+        EventViewMode::Week | EventViewMode::WorkWeek => {
+            let (first_weekday, days) = app.week_layout();
             let mut start = app.displayed_date;
-            while start.weekday() != Weekday::Mon {
+            while start.weekday() != first_weekday {
                 start = start.pred_opt().unwrap();
             }
-            let end = start + ChronoDuration::days(5);
+            let end = start + ChronoDuration::days(days);
             (to_utc(start), to_utc(end))
         }
         EventViewMode::Day => {
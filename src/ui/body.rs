@@ -0,0 +1,102 @@
+//! Turns event bodies (mostly Outlook's HTML) into text for the detail view.
+//! The renderer is picked with `body_renderer`; `v` in the detail view shows
+//! the raw source instead, to see what a mangled invite actually contains.
+
+use super::Theme;
+use crate::api::ItemBody;
+use html2text::render::RichAnnotation;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BodyRenderer {
+    /// Plain text from html2text (the default).
+    Plain,
+    /// html2text's annotated output, keeping bold, italics, links and colors.
+    Rich,
+    /// The body as received, markup included.
+    Raw,
+}
+
+impl BodyRenderer {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "plain" => Some(Self::Plain),
+            "rich" => Some(Self::Rich),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+}
+
+/// A rendered body: styled lines to show, and the same text unstyled for
+/// finding links in it.
+pub struct RenderedBody {
+    pub text: String,
+    pub lines: Vec<Line<'static>>,
+}
+
+impl RenderedBody {
+    fn from_text(text: String) -> Self {
+        let lines = text
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        Self { text, lines }
+    }
+}
+
+fn annotation_style(annotations: &[RichAnnotation], theme: &Theme) -> Style {
+    let mut style = Style::default();
+    for annotation in annotations {
+        style = match annotation {
+            RichAnnotation::Link(_) => style.fg(theme.blue).add_modifier(Modifier::UNDERLINED),
+            RichAnnotation::Image(_) => style.add_modifier(Modifier::DIM),
+            RichAnnotation::Emphasis => style.add_modifier(Modifier::ITALIC),
+            RichAnnotation::Strong => style.add_modifier(Modifier::BOLD),
+            RichAnnotation::Strikeout => style.add_modifier(Modifier::CROSSED_OUT),
+            RichAnnotation::Code | RichAnnotation::Preformat(_) => style.fg(theme.teal),
+            RichAnnotation::Colour(c) => style.fg(Color::Rgb(c.r, c.g, c.b)),
+            // Backgrounds meant for white pages rarely read well in a terminal
+            _ => style,
+        };
+    }
+    style
+}
+
+fn render_rich(content: &str, width: usize, theme: &Theme) -> Option<RenderedBody> {
+    let tagged_lines = html2text::from_read_rich(content.as_bytes(), width).ok()?;
+    let mut text = String::new();
+    let mut lines = Vec::new();
+    for tagged_line in tagged_lines {
+        let mut spans = Vec::new();
+        for tagged in tagged_line.tagged_strings() {
+            text.push_str(&tagged.s);
+            spans.push(Span::styled(
+                tagged.s.clone(),
+                annotation_style(&tagged.tag, theme),
+            ));
+        }
+        text.push('\n');
+        lines.push(Line::from(spans));
+    }
+    Some(RenderedBody { text, lines })
+}
+
+/// Renders a body to fit `width` columns.
+pub fn render_body(
+    body: &ItemBody,
+    renderer: BodyRenderer,
+    width: usize,
+    theme: &Theme,
+) -> RenderedBody {
+    match renderer {
+        BodyRenderer::Raw => RenderedBody::from_text(body.content.clone()),
+        BodyRenderer::Rich => render_rich(&body.content, width, theme)
+            .unwrap_or_else(|| RenderedBody::from_text(body.content.clone())),
+        BodyRenderer::Plain => RenderedBody::from_text(
+            html2text::from_read(body.content.as_bytes(), width)
+                .unwrap_or_else(|_| body.content.clone()),
+        ),
+    }
+}
@@ -1,14 +1,20 @@
-use crate::app::App;
+use crate::app::{parse_event_time, App};
+use crate::clock;
 use crate::ui::centered_rect;
+use crate::ui::text::{truncate_to_width, wrap_line};
 use crate::ui::Theme;
-use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDateTime, Utc, Weekday};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    Timelike, Utc, Weekday,
+};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style, Stylize},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 pub fn draw_calendar_list(
     f: &mut Frame,
@@ -43,25 +49,36 @@ pub fn draw_calendar_list(
     };
 
     let (all_icon, all_style) = get_override("All Calendars", "✨");
-    items.push(ListItem::new(format!("{} All Calendars", all_icon)).style(all_style));
+    items.push(ListItem::new(format!("    {} All Calendars", all_icon)).style(all_style));
 
     let (my_icon, my_style) = get_override("My Calendars", "👤");
-    items.push(ListItem::new(format!("{} My Calendars", my_icon)).style(my_style));
+    items.push(ListItem::new(format!("    {} My Calendars", my_icon)).style(my_style));
     for c in &app.calendars {
         let icon = c.icon.clone().unwrap_or_else(|| "■ ".to_string());
+        let checkbox = if app.checked_calendars.contains(&c.calendar.id) {
+            "[x] "
+        } else {
+            "[ ] "
+        };
         let line = Line::from(vec![
+            Span::raw(checkbox),
             Span::styled(icon, Style::default().fg(c.color)),
             Span::raw(format!(" {}", c.calendar.name)),
         ]);
         items.push(ListItem::new(line).style(Style::default().fg(theme.foreground)));
     }
     let items_len = items.len();
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border_color));
+    if !app.checked_calendars.is_empty() {
+        block = block.title_bottom(format!(
+            " {} ticked | Enter: show them | Space: tick ",
+            app.checked_calendars.len()
+        ));
+    }
     let list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(border_color)),
-        )
+        .block(block)
         .highlight_style(Style::default().fg(theme.blue).add_modifier(Modifier::BOLD))
         .highlight_symbol("❯ ");
     app.calendar_list_area = area;
@@ -90,7 +107,7 @@ pub fn draw_month_view(
     _calendar_name: &str,
     border_color: ratatui::style::Color,
 ) {
-    let today = Local::now().date_naive();
+    let today = clock::now().date_naive();
     let displayed_date = app.displayed_date;
     let _month_str = format!(
         "{} {}",
@@ -122,11 +139,16 @@ pub fn draw_month_view(
         .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
         .split(inner_area);
     let weekdays = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
-    let header_spans: Vec<Span> = weekdays
+    let columns = app.month_columns();
+    let header_spans: Vec<Span> = columns
         .iter()
-        .map(|&d| {
+        .map(|&column| {
             Span::styled(
-                format!("{:^width$}", d, width = chunks[0].width as usize / 7),
+                format!(
+                    "{:^width$}",
+                    weekdays[column],
+                    width = chunks[0].width as usize / columns.len()
+                ),
                 Style::default().fg(theme.blue).bold(),
             )
         })
@@ -145,18 +167,25 @@ pub fn draw_month_view(
     for (week_index, week_area) in week_chunks.iter().enumerate() {
         let day_chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(vec![Constraint::Ratio(1, 7); 7])
+            .constraints(vec![
+                Constraint::Ratio(1, columns.len() as u32);
+                columns.len()
+            ])
             .split(*week_area);
-        for day_index in 0..7 {
-            let current_day =
-                starting_day + ChronoDuration::days((week_index * 7 + day_index) as i64);
+        for (day_index, column) in columns.iter().enumerate() {
+            let current_day = starting_day + ChronoDuration::days((week_index * 7 + column) as i64);
             if current_day.month() == displayed_date.month() {
                 let day_number = current_day.day().to_string();
                 let mut day_style = Style::default().fg(theme.foreground);
                 if current_day == today {
-                    day_style = Style::default().fg(theme.background).bg(theme.blue).bold();
+                    day_style = Style::default()
+                        .fg(theme.text_on(theme.blue))
+                        .bg(theme.blue)
+                        .bold();
                 }
-                let mut day_events_text = vec![Line::from(Span::styled(day_number, day_style))];
+                let mut day_events_text = Vec::new();
+                let mut day_colors: Vec<ratatui::style::Color> = Vec::new();
+                let mut day_has_selection = false;
                 for (i, color_event) in app.events.iter().enumerate() {
                     let e = &color_event.event;
                     if let (Ok(start_naive), Ok(end_naive)) = (
@@ -181,9 +210,11 @@ pub fn draw_month_view(
                                     .with_timezone(&Local);
 
                             let is_selected = Some(i) == app.event_list_state.selected();
+                            day_colors.push(color_event.color);
+                            day_has_selection |= is_selected;
                             let style = if is_selected {
                                 Style::default()
-                                    .fg(theme.background)
+                                    .fg(theme.text_on(theme.blue))
                                     .bg(theme.blue)
                                     .add_modifier(Modifier::BOLD)
                             } else {
@@ -209,6 +240,28 @@ pub fn draw_month_view(
                         }
                     }
                 }
+                // Cells too short for any event line get a "•3" count badge next
+                // to the day number, colored by the calendar with the most events.
+                let cell_height = day_chunks[day_index].height.saturating_sub(2);
+                let mut header_spans = vec![Span::styled(day_number, day_style)];
+                if cell_height <= 1 && !day_colors.is_empty() {
+                    let badge_style = if day_has_selection {
+                        Style::default()
+                            .fg(theme.text_on(theme.blue))
+                            .bg(theme.blue)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        let color = dominant_color(&day_colors);
+                        Style::default()
+                            .fg(theme.text_on(color))
+                            .bg(color)
+                            .add_modifier(Modifier::BOLD)
+                    };
+                    header_spans.push(Span::raw(" "));
+                    header_spans.push(Span::styled(format!("•{}", day_colors.len()), badge_style));
+                    day_events_text.clear();
+                }
+                day_events_text.insert(0, Line::from(header_spans));
                 let paragraph =
                     Paragraph::new(Text::from(day_events_text).alignment(Alignment::Left)).block(
                         Block::default()
@@ -221,6 +274,152 @@ pub fn draw_month_view(
     }
 }
 
+/// Transient popup listing every event of the focused month-view day.
+pub fn draw_day_peek(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(day) = app.day_peek else {
+        return;
+    };
+    let indices = app.event_indices_on_day(day);
+
+    let size = f.size();
+    let width = (size.width * 50 / 100).max(30).min(size.width);
+    let inner_width = width.saturating_sub(2) as usize;
+    let mut lines: Vec<Line> = Vec::new();
+    for i in &indices {
+        let color_event = &app.events[*i];
+        let e = &color_event.event;
+        let time = match (
+            parse_event_time(&e.start.date_time),
+            parse_event_time(&e.end.date_time),
+        ) {
+            (Some(start), Some(end)) if end - start >= ChronoDuration::hours(24) => {
+                "All day    ".to_string()
+            }
+            (Some(start), Some(end)) => {
+                format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+            }
+            _ => "?".to_string(),
+        };
+        let icon = color_event.icon.clone().unwrap_or_else(|| "■ ".to_string());
+        let prefix_width = icon.width() + time.width() + 2;
+        lines.push(Line::from(vec![
+            Span::styled(icon, Style::default().fg(color_event.color)),
+            Span::styled(time, Style::default().fg(theme.blue)),
+            Span::raw("  "),
+            Span::styled(
+                truncate_to_width(
+                    &format!("{}{}", app.event_badges(e), app.display_subject(e)),
+                    inner_width.saturating_sub(prefix_width),
+                ),
+                Style::default().fg(theme.foreground),
+            ),
+        ]));
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No events",
+            Style::default().fg(theme.foreground),
+        )));
+    }
+
+    let height = (lines.len() as u16 + 2).min(size.height);
+    let area = Rect {
+        x: size.x + (size.width - width) / 2,
+        y: size.y + (size.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.yellow))
+            .title(format!(" {} ", day.format("%A, %d %B")))
+            .title_bottom(format!(" {} events ", indices.len())),
+    );
+    f.render_widget(paragraph, area);
+}
+
+fn dominant_color(colors: &[ratatui::style::Color]) -> ratatui::style::Color {
+    let mut counts: Vec<(ratatui::style::Color, usize)> = Vec::new();
+    for color in colors {
+        match counts.iter_mut().find(|(c, _)| c == color) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((*color, 1)),
+        }
+    }
+    // max_by_key returns the last maximum; reverse so ties go to the first calendar seen.
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(color, _)| color)
+        .unwrap_or_default()
+}
+
+/// Hatched lines for the colleagues' busy blocks on `day`, sorted by start time.
+/// They go below the user's own events so click hit-testing is unaffected.
+fn schedule_overlay_lines(
+    app: &App,
+    day: NaiveDate,
+    max_width: usize,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    // Colleagues' names and schedules aren't for shared screens either
+    if app.busy_only {
+        return Vec::new();
+    }
+    let mut blocks = Vec::new();
+    for info in &app.schedule_overlay {
+        let name = info
+            .schedule_id
+            .split('@')
+            .next()
+            .unwrap_or(&info.schedule_id);
+        for item in &info.schedule_items {
+            if item.status == "free" {
+                continue;
+            }
+            let (Some(start), Some(end)) = (
+                parse_event_time(&item.start.date_time),
+                parse_event_time(&item.end.date_time),
+            ) else {
+                continue;
+            };
+            let ends_after_day_start =
+                end.date_naive() > day || (end.date_naive() == day && end.time() > NaiveTime::MIN);
+            if start.date_naive() > day || !ends_after_day_start {
+                continue;
+            }
+            let status = if item.status == "busy" {
+                String::new()
+            } else {
+                format!(" ({})", item.status)
+            };
+            blocks.push((
+                start,
+                Line::from(Span::styled(
+                    format!(
+                        "░░ {}-{} {}{}",
+                        start.format("%H:%M"),
+                        end.format("%H:%M"),
+                        name,
+                        status
+                    ),
+                    Style::default()
+                        .fg(theme.peach)
+                        .add_modifier(Modifier::DIM | Modifier::ITALIC),
+                )),
+            ));
+        }
+    }
+    blocks.sort_by_key(|(start, _)| *start);
+    blocks
+        .into_iter()
+        .flat_map(|(_, line)| wrap_line(line, max_width))
+        .collect()
+}
+
 pub fn draw_week_view(
     f: &mut Frame,
     app: &mut App,
@@ -229,7 +428,10 @@ pub fn draw_week_view(
     _calendar_name: &str,
     border_color: ratatui::style::Color,
 ) {
-    let today = Local::now().date_naive();
+    if app.hides_weekends() {
+        return draw_work_week_view(f, app, area, theme, _calendar_name, border_color);
+    }
+    let today = clock::now().date_naive();
     let mut week_start = app.displayed_date;
     while week_start.weekday() != Weekday::Sun {
         week_start = week_start.pred_opt().unwrap();
@@ -248,10 +450,14 @@ pub fn draw_week_view(
     let weekdays = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
     for i in 0..7 {
         let day_area = day_chunks[i];
+        let content_width = day_area.width.saturating_sub(2) as usize;
         let current_day = week_start + ChronoDuration::days(i as i64);
         let mut day_style = Style::default().fg(theme.foreground);
         if current_day == today {
-            day_style = Style::default().fg(theme.background).bg(theme.blue).bold();
+            day_style = Style::default()
+                .fg(theme.text_on(theme.blue))
+                .bg(theme.blue)
+                .bold();
         }
         let title_span = Span::styled(
             format!(" {} {} ", weekdays[i], current_day.day()),
@@ -282,7 +488,7 @@ pub fn draw_week_view(
                     let is_selected = Some(i) == app.event_list_state.selected();
                     let style = if is_selected {
                         Style::default()
-                            .fg(theme.background)
+                            .fg(theme.text_on(theme.blue))
                             .bg(theme.blue)
                             .add_modifier(Modifier::BOLD)
                     } else {
@@ -294,10 +500,11 @@ pub fn draw_week_view(
                         Span::styled(icon, style),
                         Span::styled(
                             format!(
-                                "{}-{} {}",
+                                "{}-{} {}{}",
                                 start_local.format("%H:%M"),
                                 end_local.format("%H:%M"),
-                                e.subject
+                                app.event_badges(e),
+                                app.display_subject(e)
                             ),
                             if is_selected {
                                 style
@@ -306,18 +513,22 @@ pub fn draw_week_view(
                             },
                         ),
                     ]);
-                    day_events_text.push(event_line);
+                    day_events_text.extend(wrap_line(event_line, content_width));
                 }
             }
         }
-        let paragraph = Paragraph::new(day_events_text)
-            .block(
-                Block::default()
-                    .title(title_span)
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(theme.mauve)),
-            )
-            .wrap(Wrap { trim: true });
+        day_events_text.extend(schedule_overlay_lines(
+            app,
+            current_day,
+            content_width,
+            theme,
+        ));
+        let paragraph = Paragraph::new(day_events_text).block(
+            Block::default()
+                .title(title_span)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.mauve)),
+        );
         f.render_widget(paragraph, day_area);
     }
 }
@@ -330,7 +541,7 @@ pub fn draw_work_week_view(
     _calendar_name: &str,
     border_color: ratatui::style::Color,
 ) {
-    let today = Local::now().date_naive();
+    let today = clock::now().date_naive();
     let mut week_start = app.displayed_date;
     while week_start.weekday() != Weekday::Mon {
         week_start = week_start.pred_opt().unwrap();
@@ -349,10 +560,14 @@ pub fn draw_work_week_view(
     let weekdays = ["Mon", "Tue", "Wed", "Thu", "Fri"];
     for i in 0..5 {
         let day_area = day_chunks[i];
+        let content_width = day_area.width.saturating_sub(2) as usize;
         let current_day = week_start + ChronoDuration::days(i as i64);
         let mut day_style = Style::default().fg(theme.foreground);
         if current_day == today {
-            day_style = Style::default().fg(theme.background).bg(theme.blue).bold();
+            day_style = Style::default()
+                .fg(theme.text_on(theme.blue))
+                .bg(theme.blue)
+                .bold();
         }
         let title_span = Span::styled(
             format!(" {} {} ", weekdays[i], current_day.day()),
@@ -383,7 +598,7 @@ pub fn draw_work_week_view(
                     let is_selected = Some(i) == app.event_list_state.selected();
                     let style = if is_selected {
                         Style::default()
-                            .fg(theme.background)
+                            .fg(theme.text_on(theme.blue))
                             .bg(theme.blue)
                             .add_modifier(Modifier::BOLD)
                     } else {
@@ -395,10 +610,11 @@ pub fn draw_work_week_view(
                         Span::styled(icon, style),
                         Span::styled(
                             format!(
-                                "{}-{} {}",
+                                "{}-{} {}{}",
                                 start_local.format("%H:%M"),
                                 end_local.format("%H:%M"),
-                                e.subject
+                                app.event_badges(e),
+                                app.display_subject(e)
                             ),
                             if is_selected {
                                 style
@@ -407,22 +623,74 @@ pub fn draw_work_week_view(
                             },
                         ),
                     ]);
-                    day_events_text.push(event_line);
+                    day_events_text.extend(wrap_line(event_line, content_width));
                 }
             }
         }
-        let paragraph = Paragraph::new(day_events_text)
-            .block(
-                Block::default()
-                    .title(title_span)
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(theme.mauve)),
-            )
-            .wrap(Wrap { trim: true });
+        day_events_text.extend(schedule_overlay_lines(
+            app,
+            current_day,
+            content_width,
+            theme,
+        ));
+        let paragraph = Paragraph::new(day_events_text).block(
+            Block::default()
+                .title(title_span)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.mauve)),
+        );
         f.render_widget(paragraph, day_area);
     }
 }
 
+/// Width of the time labels on the left of the day grid ("09:30 ").
+const DAY_GRID_LABEL_WIDTH: usize = 6;
+
+/// Places overlapping events of the day grid in parallel lanes. Returns each
+/// event's lane and how many lanes its group of overlapping events needs.
+/// Times are rounded out to whole rows, so events sharing a row never collide.
+fn assign_lanes(
+    events: &[(usize, DateTime<Local>, DateTime<Local>)],
+    day_start: DateTime<Local>,
+    first_minute: i64,
+    minutes_per_row: i64,
+) -> Vec<(usize, usize)> {
+    let row_of =
+        |time: DateTime<Local>| ((time - day_start).num_minutes() - first_minute) / minutes_per_row;
+    let mut lanes = vec![(0, 1); events.len()];
+    let mut lane_ends: Vec<i64> = Vec::new();
+    let mut group: Vec<usize> = Vec::new();
+    let mut group_end = i64::MIN;
+
+    for (k, &(_, start, end)) in events.iter().enumerate() {
+        let first_row = row_of(start);
+        // The row holding the event's last minute
+        let last_row = row_of(end - ChronoDuration::minutes(1)).max(first_row);
+        if first_row > group_end {
+            for &g in &group {
+                lanes[g].1 = lane_ends.len();
+            }
+            group.clear();
+            lane_ends.clear();
+        }
+        let lane = match lane_ends.iter().position(|&lane_end| lane_end < first_row) {
+            Some(lane) => lane,
+            None => {
+                lane_ends.push(last_row);
+                lane_ends.len() - 1
+            }
+        };
+        lane_ends[lane] = last_row;
+        lanes[k].0 = lane;
+        group.push(k);
+        group_end = group_end.max(last_row);
+    }
+    for &g in &group {
+        lanes[g].1 = lane_ends.len();
+    }
+    lanes
+}
+
 pub fn draw_day_view(
     f: &mut Frame,
     app: &mut App,
@@ -431,69 +699,67 @@ pub fn draw_day_view(
     _calendar_name: &str,
     border_color: ratatui::style::Color,
 ) {
-    let _today = Local::now().date_naive();
     let current_day = app.displayed_date;
+    let minutes_per_row = app.day_grid_minutes;
     let main_block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(border_color));
+        .border_style(Style::default().fg(border_color))
+        .title(format!(" {} min ", minutes_per_row))
+        .title_alignment(Alignment::Right);
     let inner_area = main_block.inner(area);
     f.render_widget(main_block, area);
     app.event_list_area = area;
+    app.day_grid_rows.clear();
+    let content_width = inner_area.width as usize;
 
-    let mut day_events_text = vec![];
+    let (Some(day_start), Some(day_end)) = (
+        current_day
+            .and_time(NaiveTime::MIN)
+            .and_local_timezone(Local)
+            .earliest(),
+        (current_day + ChronoDuration::days(1))
+            .and_time(NaiveTime::MIN)
+            .and_local_timezone(Local)
+            .earliest(),
+    ) else {
+        return;
+    };
+
+    // Events lasting a day or more go above the grid, the rest into it
+    let mut all_day = Vec::new();
+    let mut timed = Vec::new();
     for (i, color_event) in app.events.iter().enumerate() {
         let e = &color_event.event;
-        if let (Ok(start_naive), Ok(end_naive)) = (
+        let (Ok(start_naive), Ok(end_naive)) = (
             NaiveDateTime::parse_from_str(&e.start.date_time, "%Y-%m-%dT%H:%M:%S%.f"),
             NaiveDateTime::parse_from_str(&e.end.date_time, "%Y-%m-%dT%H:%M:%S%.f"),
-        ) {
+        ) else {
+            continue;
+        };
+        let start_local =
+            DateTime::<Utc>::from_naive_utc_and_offset(start_naive, Utc).with_timezone(&Local);
+        let end_local =
+            DateTime::<Utc>::from_naive_utc_and_offset(end_naive, Utc).with_timezone(&Local);
+
+        if end_naive - start_naive >= ChronoDuration::hours(24) {
             let start_date = start_naive.date();
             let effective_end_date =
-                if end_naive.time() == chrono::NaiveTime::MIN && end_naive.date() > start_date {
+                if end_naive.time() == NaiveTime::MIN && end_naive.date() > start_date {
                     end_naive.date().pred_opt().unwrap()
                 } else {
                     end_naive.date()
                 };
-
             if start_date <= current_day && effective_end_date >= current_day {
-                let start_local = DateTime::<Utc>::from_naive_utc_and_offset(start_naive, Utc)
-                    .with_timezone(&Local);
-                let end_local = DateTime::<Utc>::from_naive_utc_and_offset(end_naive, Utc)
-                    .with_timezone(&Local);
-
-                let is_selected = Some(i) == app.event_list_state.selected();
-                let style = if is_selected {
-                    Style::default()
-                        .fg(theme.background)
-                        .bg(theme.blue)
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default().fg(color_event.color)
-                };
-
-                let icon = color_event.icon.clone().unwrap_or_else(|| "■ ".to_string());
-                let event_line = Line::from(vec![
-                    Span::styled(icon, style),
-                    Span::styled(
-                        format!(
-                            "{}-{} {}",
-                            start_local.format("%H:%M"),
-                            end_local.format("%H:%M"),
-                            e.subject
-                        ),
-                        if is_selected {
-                            style
-                        } else {
-                            Style::default().fg(theme.foreground)
-                        },
-                    ),
-                ]);
-                day_events_text.push(event_line);
+                all_day.push((i, start_local, end_local));
             }
+        } else if start_local < day_end && end_local > day_start {
+            timed.push((i, start_local.max(day_start), end_local.min(day_end)));
         }
     }
 
-    if day_events_text.is_empty() {
+    let overlay = schedule_overlay_lines(app, current_day, content_width, theme);
+
+    if all_day.is_empty() && timed.is_empty() && overlay.is_empty() {
         let empty_message = vec![
             Line::from(Span::styled(
                 "We don't have anything scheduled for today!",
@@ -514,10 +780,162 @@ pub fn draw_day_view(
 
         let area = centered_rect(60, 20, inner_area);
         f.render_widget(paragraph, area);
-    } else {
-        let events_list = Paragraph::new(day_events_text)
-            .block(Block::default().borders(Borders::NONE))
-            .wrap(Wrap { trim: true });
-        f.render_widget(events_list, inner_area);
+        return;
     }
+
+    let selected = app.event_list_state.selected();
+    let event_style = |i: usize, color: ratatui::style::Color| {
+        if Some(i) == selected {
+            Style::default()
+                .fg(theme.text_on(theme.blue))
+                .bg(theme.blue)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color)
+        }
+    };
+    let event_text = |i: usize, start: DateTime<Local>, end: DateTime<Local>| {
+        let e = &app.events[i].event;
+        format!(
+            "{}-{} {}{}",
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            app.event_badges(e),
+            app.display_subject(e)
+        )
+    };
+
+    let mut lines = Vec::new();
+    let mut rows = Vec::new();
+    for &(i, start, end) in &all_day {
+        let color_event = &app.events[i];
+        let style = event_style(i, color_event.color);
+        let icon = color_event.icon.clone().unwrap_or_else(|| "■ ".to_string());
+        let text = truncate_to_width(
+            &event_text(i, start, end),
+            content_width.saturating_sub(icon.width()),
+        );
+        lines.push(Line::from(vec![
+            Span::styled(icon, style),
+            Span::styled(
+                text,
+                if Some(i) == selected {
+                    style
+                } else {
+                    Style::default().fg(theme.foreground)
+                },
+            ),
+        ]));
+        rows.push(vec![(0..inner_area.width, i)]);
+    }
+
+    // The grid spans the working hours, stretched to fit the day's events
+    let (workday_start, workday_end) = app.settings.workday_hours();
+    let minute_of = |time: DateTime<Local>| {
+        if time >= day_end {
+            24 * 60
+        } else {
+            (time - day_start).num_minutes()
+        }
+    };
+    let mut first_minute = (workday_start - NaiveTime::MIN).num_minutes();
+    let mut last_minute = (workday_end - NaiveTime::MIN).num_minutes();
+    for &(_, start, end) in &timed {
+        first_minute = first_minute.min(minute_of(start));
+        last_minute = last_minute.max(minute_of(end));
+    }
+    first_minute = first_minute / minutes_per_row * minutes_per_row;
+    last_minute = (last_minute + minutes_per_row - 1) / minutes_per_row * minutes_per_row;
+    let row_count = ((last_minute - first_minute) / minutes_per_row).max(1) as usize;
+
+    // Scroll so the selected event, or else the current time, is in view
+    let grid_height = (inner_area.height as usize)
+        .saturating_sub(lines.len() + overlay.len())
+        .max(1);
+    let now = clock::now();
+    let anchor_minute = timed
+        .iter()
+        .find(|(i, _, _)| Some(*i) == selected)
+        .map(|&(_, start, _)| minute_of(start))
+        .or_else(|| (now >= day_start && now < day_end).then(|| minute_of(now)))
+        .or_else(|| timed.iter().map(|&(_, start, _)| minute_of(start)).min())
+        .unwrap_or(first_minute);
+    let anchor_row = ((anchor_minute - first_minute).max(0) / minutes_per_row) as usize;
+    let first_row = anchor_row
+        .saturating_sub(grid_height / 3)
+        .min(row_count.saturating_sub(grid_height));
+
+    let slot_width = content_width.saturating_sub(DAY_GRID_LABEL_WIDTH + 2);
+    timed.sort_by_key(|&(_, start, end)| (start, std::cmp::Reverse(end)));
+    let lanes = assign_lanes(&timed, day_start, first_minute, minutes_per_row);
+    for row in first_row..row_count.min(first_row + grid_height) {
+        let slot_start =
+            day_start + ChronoDuration::minutes(first_minute + row as i64 * minutes_per_row);
+        let slot_end = slot_start + ChronoDuration::minutes(minutes_per_row);
+
+        let label_style = if now >= slot_start && now < slot_end {
+            Style::default()
+                .fg(theme.peach)
+                .add_modifier(Modifier::BOLD)
+        } else if slot_start.minute() == 0 {
+            Style::default().fg(theme.foreground)
+        } else {
+            Style::default()
+                .fg(theme.foreground)
+                .add_modifier(Modifier::DIM)
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(
+                    "{:<width$}",
+                    slot_start.format("%H:%M").to_string(),
+                    width = DAY_GRID_LABEL_WIDTH
+                ),
+                label_style,
+            ),
+            Span::styled("│ ", Style::default().fg(theme.mauve)),
+        ];
+
+        // Overlapping events sit side by side, each in its own lane
+        let mut hits = Vec::new();
+        let active: Vec<_> = timed
+            .iter()
+            .zip(&lanes)
+            .filter(|((_, start, end), _)| *start < slot_end && *end > slot_start)
+            .collect();
+        if let Some((_, &(_, lane_count))) = active.first() {
+            let lane_width = slot_width / lane_count;
+            let mut x = (DAY_GRID_LABEL_WIDTH + 2) as u16;
+            for lane in 0..lane_count {
+                let Some((&(i, start, end), _)) =
+                    active.iter().find(|(_, (l, _))| *l == lane).copied()
+                else {
+                    spans.push(Span::raw(" ".repeat(lane_width)));
+                    x += lane_width as u16;
+                    continue;
+                };
+                let color_event = &app.events[i];
+                let style = event_style(i, color_event.color);
+                let text = if start >= slot_start || row == first_row {
+                    let icon = color_event.icon.as_deref().unwrap_or("■ ");
+                    format!("{}{}", icon, event_text(i, start, end))
+                } else {
+                    "┃".to_string()
+                };
+                // Keep a gap before the next lane
+                let text = truncate_to_width(&text, lane_width.saturating_sub(1));
+                let padding = " ".repeat(lane_width.saturating_sub(1 + text.width()));
+                spans.push(Span::styled(format!("{}{}", text, padding), style));
+                spans.push(Span::raw(" "));
+                hits.push((x..x + lane_width as u16, i));
+                x += lane_width as u16;
+            }
+        }
+        lines.push(Line::from(spans));
+        rows.push(hits);
+    }
+
+    lines.extend(overlay);
+    app.day_grid_rows = rows;
+    f.render_widget(Paragraph::new(lines), inner_area);
 }
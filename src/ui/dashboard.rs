@@ -0,0 +1,316 @@
+use crate::app::{parse_event_time, App, ColorEvent};
+use crate::clock;
+use crate::ui::text::truncate_to_width;
+use crate::ui::Theme;
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate, Weekday};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+pub fn format_countdown(duration: ChronoDuration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes >= 24 * 60 {
+        format!("{}d {}h", minutes / (24 * 60), (minutes / 60) % 24)
+    } else if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+fn event_line<'a>(
+    app: &App,
+    index: usize,
+    color_event: &'a ColorEvent,
+    max_width: usize,
+    theme: &Theme,
+) -> Line<'a> {
+    let e = &color_event.event;
+    let time = match (
+        parse_event_time(&e.start.date_time),
+        parse_event_time(&e.end.date_time),
+    ) {
+        (Some(start), Some(end)) => {
+            if start.date_naive() == clock::now().date_naive() {
+                format!("{}-{} ", start.format("%H:%M"), end.format("%H:%M"))
+            } else {
+                format!("{} {} ", start.format("%d/%m"), start.format("%H:%M"))
+            }
+        }
+        _ => String::new(),
+    };
+
+    let is_selected = Some(index) == app.event_list_state.selected();
+    let text_style = if is_selected {
+        Style::default()
+            .fg(theme.text_on(theme.blue))
+            .bg(theme.blue)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.foreground)
+    };
+    let icon = color_event.icon.clone().unwrap_or_else(|| "■ ".to_string());
+    let text = truncate_to_width(
+        &format!("{}{}{}", time, app.event_badges(e), app.display_subject(e)),
+        max_width.saturating_sub(icon.width()),
+    );
+    Line::from(vec![
+        Span::styled(icon, Style::default().fg(color_event.color)),
+        Span::styled(text, text_style),
+    ])
+}
+
+pub fn draw_dashboard(
+    f: &mut Frame,
+    app: &mut App,
+    area: Rect,
+    theme: &Theme,
+    border_color: ratatui::style::Color,
+) {
+    app.event_list_area = area;
+    let now = clock::now();
+    let (remaining_today, invitations) = app.dashboard_event_indices();
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(area);
+    // Tasks take the lines they need, up to a third of the column
+    let shows_tasks = app.settings.dashboard_tasks.unwrap_or(false);
+    let tasks_height = if shows_tasks {
+        let lines = if app.due_tasks_error.is_some() {
+            2
+        } else {
+            app.due_tasks.len().max(1)
+        };
+        (lines as u16 + 2).min(columns[0].height / 3)
+    } else {
+        0
+    };
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(0),
+            Constraint::Length(tasks_height),
+        ])
+        .split(columns[0]);
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Min(0)])
+        .split(columns[1]);
+
+    // --- Next meeting countdown ---
+    let mut countdown_lines = Vec::new();
+    let mut next_event = None;
+    for color_event in &app.events {
+        let e = &color_event.event;
+        let (Some(start), Some(end)) = (
+            parse_event_time(&e.start.date_time),
+            parse_event_time(&e.end.date_time),
+        ) else {
+            continue;
+        };
+        if start <= now && end > now && end - start < ChronoDuration::hours(24) {
+            countdown_lines.push(Line::from(vec![
+                Span::styled("Now: ", Style::default().fg(theme.green).bold()),
+                Span::raw(format!(
+                    "{} (ends in {})",
+                    app.display_subject(e),
+                    format_countdown(end - now)
+                )),
+            ]));
+        } else if start > now && next_event.is_none() {
+            next_event = Some((app.display_subject(e).to_string(), start));
+        }
+    }
+    match next_event {
+        Some((subject, start)) => countdown_lines.push(Line::from(vec![
+            Span::styled("Next: ", Style::default().fg(theme.yellow).bold()),
+            Span::raw(format!(
+                "{} in {} ({})",
+                subject,
+                format_countdown(start - now),
+                start.format("%H:%M")
+            )),
+        ])),
+        None => countdown_lines.push(Line::from(Span::styled(
+            "No upcoming meetings.",
+            Style::default().fg(theme.green),
+        ))),
+    }
+    f.render_widget(
+        Paragraph::new(countdown_lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Next Meeting ")
+                .border_style(Style::default().fg(border_color)),
+        ),
+        left[0],
+    );
+
+    // --- Today's remaining events ---
+    let today_lines: Vec<Line> = if remaining_today.is_empty() {
+        vec![Line::from(Span::styled(
+            "Nothing else scheduled for today!",
+            Style::default().fg(theme.green),
+        ))]
+    } else {
+        let max_width = left[1].width.saturating_sub(2) as usize;
+        remaining_today
+            .iter()
+            .map(|&i| event_line(app, i, &app.events[i], max_width, theme))
+            .collect()
+    };
+    f.render_widget(
+        Paragraph::new(today_lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Today, {} ", now.format("%a %d %b")))
+                .border_style(Style::default().fg(theme.mauve)),
+        ),
+        left[1],
+    );
+
+    // --- To Do tasks and flagged emails due ---
+    if shows_tasks {
+        let max_width = left[2].width.saturating_sub(2) as usize;
+        let task_lines: Vec<Line> = if let Some(error) = &app.due_tasks_error {
+            vec![
+                Line::from(Span::styled(
+                    truncate_to_width(&format!("Could not load the tasks: {}", error), max_width),
+                    Style::default().fg(theme.red),
+                )),
+                Line::from(Span::styled(
+                    truncate_to_width(
+                        "Missing Tasks.Read? Log in again: X logs out, then start the app.",
+                        max_width,
+                    ),
+                    Style::default().fg(theme.foreground),
+                )),
+            ]
+        } else if app.due_tasks.is_empty() {
+            vec![Line::from(Span::styled(
+                "No tasks due today.",
+                Style::default().fg(theme.green),
+            ))]
+        } else {
+            app.due_tasks
+                .iter()
+                .map(|task| {
+                    let (marker, marker_style) = if task.due < now.date_naive() {
+                        (
+                            format!("{} ", task.due.format("%d %b")),
+                            Style::default().fg(theme.red),
+                        )
+                    } else {
+                        ("Today  ".to_string(), Style::default().fg(theme.yellow))
+                    };
+                    let title_style = if task.important {
+                        Style::default().fg(theme.foreground).bold()
+                    } else {
+                        Style::default().fg(theme.foreground)
+                    };
+                    let text = truncate_to_width(
+                        &format!("{} ({})", task.title, task.list),
+                        max_width.saturating_sub(marker.width()),
+                    );
+                    Line::from(vec![
+                        Span::styled(marker, marker_style),
+                        Span::styled(text, title_style),
+                    ])
+                })
+                .collect()
+        };
+        f.render_widget(
+            Paragraph::new(task_lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Due ({}) ", app.due_tasks.len()))
+                    .border_style(Style::default().fg(theme.red)),
+            ),
+            left[2],
+        );
+    }
+
+    // --- Mini month calendar ---
+    let today = now.date_naive();
+    let event_days: Vec<NaiveDate> = app
+        .events
+        .iter()
+        .filter_map(|e| parse_event_time(&e.event.start.date_time))
+        .map(|start| start.date_naive())
+        .collect();
+    let mut month_lines = vec![Line::from(Span::styled(
+        "Mo Tu We Th Fr Sa Su",
+        Style::default().fg(theme.blue).bold(),
+    ))];
+    let first_day = today.with_day(1).unwrap();
+    let mut week_start = first_day;
+    while week_start.weekday() != Weekday::Mon {
+        week_start = week_start.pred_opt().unwrap();
+    }
+    while week_start.month() == today.month() || week_start < first_day {
+        let mut spans = Vec::new();
+        for offset in 0..7 {
+            let day = week_start + ChronoDuration::days(offset);
+            let text = if day.month() == today.month() {
+                format!("{:>2} ", day.day())
+            } else {
+                "   ".to_string()
+            };
+            let style = if day == today {
+                Style::default()
+                    .fg(theme.text_on(theme.blue))
+                    .bg(theme.blue)
+                    .bold()
+            } else if day.month() == today.month() && event_days.contains(&day) {
+                Style::default()
+                    .fg(theme.green)
+                    .add_modifier(Modifier::UNDERLINED)
+            } else {
+                Style::default().fg(theme.foreground)
+            };
+            spans.push(Span::styled(text, style));
+        }
+        month_lines.push(Line::from(spans));
+        week_start += ChronoDuration::days(7);
+    }
+    f.render_widget(
+        Paragraph::new(month_lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", today.format("%B %Y")))
+                .border_style(Style::default().fg(theme.mauve)),
+        ),
+        right[0],
+    );
+
+    // --- Unanswered invitations ---
+    let invitation_lines: Vec<Line> = if invitations.is_empty() {
+        vec![Line::from(Span::styled(
+            "All caught up!",
+            Style::default().fg(theme.green),
+        ))]
+    } else {
+        let max_width = right[1].width.saturating_sub(2) as usize;
+        invitations
+            .iter()
+            .map(|&i| event_line(app, i, &app.events[i], max_width, theme))
+            .collect()
+    };
+    f.render_widget(
+        Paragraph::new(invitation_lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Invitations ({}) ", invitations.len()))
+                .border_style(Style::default().fg(theme.peach)),
+        ),
+        right[1],
+    );
+}
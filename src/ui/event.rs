@@ -1,15 +1,21 @@
-use crate::app::App;
+use crate::api::GraphEvent;
+use crate::app::{parse_event_time, App, ListRow};
+use crate::clock;
+use crate::ui::body::render_body;
+use crate::ui::text::truncate_to_width;
 use crate::ui::Theme;
-use chrono::{DateTime, Datelike, Local, NaiveDateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, Utc};
 use ratatui::{
-    layout::Rect,
+    layout::{Alignment, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
     widgets::{
-        Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, Wrap,
+        block::Title, Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar,
+        ScrollbarOrientation, Wrap,
     },
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 pub fn draw_event_list(
     f: &mut Frame,
@@ -19,7 +25,10 @@ pub fn draw_event_list(
     _calendar_name: &str,
     border_color: ratatui::style::Color,
 ) {
-    let items: Vec<ListItem> = app
+    // Grouped events sit under their day and calendar headings
+    let grouped = app.list_grouped();
+    let indent = if grouped { "    " } else { "" };
+    let mut items: Vec<ListItem> = app
         .events
         .iter()
         .map(|color_event| {
@@ -27,31 +36,132 @@ pub fn draw_event_list(
             let date_format = "%Y-%m-%dT%H:%M:%S%.f";
             let start_naive = NaiveDateTime::parse_from_str(&e.start.date_time, date_format);
             let end_naive = NaiveDateTime::parse_from_str(&e.end.date_time, date_format);
+            let is_past = end_naive.as_ref().is_ok_and(|end| {
+                DateTime::<Utc>::from_naive_utc_and_offset(*end, Utc) <= clock::now_utc()
+            });
             let line_content = match (start_naive, end_naive) {
                 (Ok(s), Ok(e_dt)) => {
                     let start_utc = DateTime::<Utc>::from_naive_utc_and_offset(s, Utc);
                     let end_utc = DateTime::<Utc>::from_naive_utc_and_offset(e_dt, Utc);
                     let local_start = start_utc.with_timezone(&Local);
                     let local_end = end_utc.with_timezone(&Local);
-                    format!(
-                        "{} | {} - {} | {}",
-                        local_start.format("%d/%m"),
-                        local_start.format("%H:%M"),
-                        local_end.format("%H:%M"),
-                        e.subject
-                    )
+                    if app.compact {
+                        format!(
+                            "{} {} {}{}",
+                            local_start.format("%d/%m"),
+                            local_start.format("%H:%M"),
+                            app.event_badges(e),
+                            app.display_subject(e)
+                        )
+                    } else {
+                        format!(
+                            "{} | {} - {} | {}{}",
+                            local_start.format("%d/%m"),
+                            local_start.format("%H:%M"),
+                            local_end.format("%H:%M"),
+                            app.event_badges(e),
+                            app.display_subject(e)
+                        )
+                    }
                 }
-                _ => format!("[Invalid Date] | {}", e.subject),
+                _ => format!(
+                    "[Invalid Date] | {}{}",
+                    app.event_badges(e),
+                    app.display_subject(e)
+                ),
             };
             let icon = color_event.icon.clone().unwrap_or_else(|| "■ ".to_string());
+            // Borders and the highlight symbol take four columns
+            let max_width = (area.width as usize).saturating_sub(4 + indent.width() + icon.width());
             let line = Line::from(vec![
+                Span::raw(indent),
                 Span::styled(icon, Style::default().fg(color_event.color)),
-                Span::raw(line_content),
+                Span::raw(truncate_to_width(&line_content, max_width)),
             ]);
-            ListItem::new(line).style(Style::default().fg(theme.foreground))
+            let style = if is_past {
+                Style::default()
+                    .fg(theme.foreground)
+                    .add_modifier(Modifier::DIM)
+            } else {
+                Style::default().fg(theme.foreground)
+            };
+            ListItem::new(line).style(style)
         })
         .collect();
 
+    // Rows are events plus the "now" separator, so the list widget gets its
+    // own state with the selection shifted past the separator. The offset
+    // is kept in rows and copied back for the mouse handler.
+    let separator = app.now_separator();
+    if let Some(index) = separator {
+        let width = (area.width as usize).saturating_sub(4);
+        let label = format!(" now {} ", clock::now().format("%H:%M"));
+        let side = width.saturating_sub(label.width()) / 2;
+        let rule = "─".repeat(side);
+        items.insert(
+            index,
+            ListItem::new(Line::from(format!("{}{}{}", rule, label, rule))).style(
+                Style::default()
+                    .fg(theme.peach)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+    let mut list_state = app.event_list_state.clone();
+    list_state.select(app.event_list_state.selected().map(|i| {
+        if separator.is_some_and(|s| i >= s) {
+            i + 1
+        } else {
+            i
+        }
+    }));
+    if grouped {
+        let rows = app.list_rows();
+        list_state.select(
+            app.event_list_state
+                .selected()
+                .and_then(|selected| rows.iter().position(|row| row.shows(selected))),
+        );
+        let mut events: Vec<Option<ListItem>> = items.into_iter().map(Some).collect();
+        items = rows
+            .iter()
+            .map(|row| match row {
+                ListRow::Day(day) => ListItem::new(day.format("%A, %d %B").to_string()).style(
+                    Style::default()
+                        .fg(theme.mauve)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                ListRow::Calendar {
+                    calendar_id,
+                    events,
+                    collapsed,
+                } => {
+                    let color = events
+                        .first()
+                        .and_then(|&i| app.events.get(i))
+                        .map_or(theme.foreground, |e| e.color);
+                    let label = format!(
+                        "  {} {} ({})",
+                        if *collapsed { "▸" } else { "▾" },
+                        app.calendar_name(calendar_id.as_deref()),
+                        events.len()
+                    );
+                    ListItem::new(label).style(Style::default().fg(color))
+                }
+                ListRow::Event(index) => events
+                    .get_mut(*index)
+                    .and_then(Option::take)
+                    .unwrap_or_else(|| ListItem::new("")),
+            })
+            .collect();
+    }
+    if std::mem::take(&mut app.center_list_on_now) {
+        if let Some(index) = separator {
+            let rows = area.height.saturating_sub(2) as usize;
+            *list_state.offset_mut() = index.saturating_sub(rows / 2);
+        }
+    }
+
     let _month_str = format!(
         "{} {}",
         [
@@ -86,12 +196,13 @@ pub fn draw_event_list(
         )
         .highlight_symbol("❯ ");
     app.event_list_area = area;
-    f.render_stateful_widget(list, area, &mut app.event_list_state);
+    f.render_stateful_widget(list, area, &mut list_state);
+    *app.event_list_state.offset_mut() = list_state.offset();
 
     app.event_list_scroll_state = app
         .event_list_scroll_state
         .content_length(items_len)
-        .position(app.event_list_state.selected().unwrap_or(0));
+        .position(list_state.selected().unwrap_or(0));
 
     f.render_stateful_widget(
         Scrollbar::default()
@@ -120,24 +231,156 @@ pub fn draw_event_detail_view(f: &mut Frame, app: &mut App, area: Rect, theme: &
     if let Some(color_event) = app.get_selected_event() {
         let event = &color_event.event;
 
+        // Only the time is shown in busy-only mode
+        if app.busy_only {
+            let time = match (
+                parse_event_time(&event.start.date_time),
+                parse_event_time(&event.end.date_time),
+            ) {
+                (Some(start), Some(end)) => format!(
+                    "{} {} - {}",
+                    start.format("%d/%m/%Y"),
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                ),
+                _ => "Invalid time".to_string(),
+            };
+            let paragraph = Paragraph::new(vec![
+                Line::from(Span::styled(
+                    "Busy",
+                    Style::default()
+                        .fg(theme.yellow)
+                        .add_modifier(Modifier::BOLD),
+                )),
+                Line::from(time),
+            ])
+            .style(Style::default().fg(theme.foreground));
+            f.render_widget(paragraph, inner_area);
+            app.detail_links.clear();
+            return;
+        }
+
         // Layout:
         // Top: Subject (1 line)
         // Row 1: Time (Start/End) | Location
         // Row 2: Organizer | Attendees
         // Bottom: Description (Remaining)
 
+        // Render the body up front so links found in it can get their own row
+        // In minimal mode bodies aren't kept with the events, only fetched
+        let fetched_body = app
+            .fetched_body
+            .as_ref()
+            .filter(|(id, _)| *id == event.id)
+            .and_then(|(_, body)| body.as_ref());
+        let formatted_body = event.body.as_ref().or(fetched_body).and_then(|body| {
+            if body.content.is_empty() {
+                None
+            } else {
+                let width = (inner_area.width as usize).saturating_sub(2); // Margin
+                Some(render_body(body, app.body_renderer(), width, theme))
+            }
+        });
+
+        let mut links = crate::links::detect_links(
+            &[
+                event.subject.as_str(),
+                formatted_body
+                    .as_ref()
+                    .map_or("", |body| body.text.as_str()),
+            ],
+            app.settings.jira_base_url.as_deref(),
+        );
+        // Recordings and transcripts live in the meeting's chat in Teams
+        if let (Some(Some(artifacts)), Some(join_url)) =
+            (app.meeting_artifacts.get(&event.id), event.join_url())
+        {
+            let label = match (artifacts.recordings > 0, artifacts.transcripts > 0) {
+                (true, true) => Some("Recording & transcript available"),
+                (true, false) => Some("Recording available"),
+                (false, true) => Some("Transcript available"),
+                (false, false) => None,
+            };
+            if let Some(label) = label {
+                links.insert(
+                    0,
+                    crate::links::DetectedLink {
+                        label: label.to_string(),
+                        url: join_url.to_string(),
+                    },
+                );
+            }
+        }
+        // Join links found in the body too, for invites Graph doesn't know are meetings
+        if let Some(join) = app.join_link(event) {
+            if !links.iter().any(|l| l.url == join.url) {
+                links.insert(
+                    0,
+                    crate::links::DetectedLink {
+                        label: format!("Join {} (j)", join.provider),
+                        url: join.url,
+                    },
+                );
+            }
+        }
+        let links_height = if links.is_empty() { 0 } else { 3 };
+
         let chunks = ratatui::layout::Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .constraints([
-                ratatui::layout::Constraint::Length(3), // Subject
-                ratatui::layout::Constraint::Length(3), // Time & Location
-                ratatui::layout::Constraint::Length(3), // Organizer & Attendees
-                ratatui::layout::Constraint::Min(0),    // Description
+                ratatui::layout::Constraint::Length(3),            // Subject
+                ratatui::layout::Constraint::Length(3),            // Time & Location
+                ratatui::layout::Constraint::Length(3),            // Organizer & Attendees
+                ratatui::layout::Constraint::Length(links_height), // Links
+                ratatui::layout::Constraint::Min(0),               // Description
             ])
             .split(inner_area);
 
         // --- Subject ---
         let icon = color_event.icon.clone().unwrap_or_else(|| "■ ".to_string());
+        let mut subject_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Subject ")
+            .border_style(Style::default().fg(theme.blue));
+        if event.web_link.is_some() {
+            subject_block =
+                subject_block.title(Title::from(" O: Outlook ").alignment(Alignment::Right));
+        }
+        if crate::alarms::is_alarmed(event, &app.flagged_events) {
+            subject_block = subject_block.title(
+                Title::from(Span::styled(
+                    " Alarm at start ",
+                    Style::default().fg(theme.red),
+                ))
+                .alignment(Alignment::Right),
+            );
+        }
+        match app.attendance.get(event.correlation_id()) {
+            Some(true) => {
+                subject_block = subject_block.title(
+                    Title::from(Span::styled(" Attended ", Style::default().fg(theme.green)))
+                        .alignment(Alignment::Right),
+                );
+            }
+            Some(false) => {
+                subject_block = subject_block.title(
+                    Title::from(Span::styled(" Skipped ", Style::default().fg(theme.red)))
+                        .alignment(Alignment::Right),
+                );
+            }
+            None => {}
+        }
+        let external_domains = app.external_attendee_domains(event);
+        if !external_domains.is_empty() {
+            subject_block = subject_block.title_bottom(Span::styled(
+                format!(
+                    " {}External participants: {} ",
+                    app.symbols.external,
+                    external_domains.join(", ")
+                ),
+                Style::default().fg(theme.peach),
+            ));
+        }
         let subject_paragraph = Paragraph::new(Line::from(vec![
             Span::styled(icon, Style::default().fg(color_event.color)),
             Span::styled(
@@ -147,12 +390,7 @@ pub fn draw_event_detail_view(f: &mut Frame, app: &mut App, area: Rect, theme: &
                     .add_modifier(Modifier::BOLD),
             ),
         ]))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Subject ")
-                .border_style(Style::default().fg(theme.blue)),
-        );
+        .block(subject_block);
         f.render_widget(subject_paragraph, chunks[0]);
 
         // --- Row 1: Time & Location ---
@@ -183,12 +421,30 @@ pub fn draw_event_detail_view(f: &mut Frame, app: &mut App, area: Rect, theme: &
             "Invalid time".to_string()
         };
 
-        let time_paragraph = Paragraph::new(time_str).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Time ")
-                .border_style(Style::default().fg(theme.green)),
-        );
+        let mut time_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Time ")
+            .border_style(Style::default().fg(theme.green));
+        if let Some(transition) = app.dst_transitions.get(&event.id) {
+            time_block = time_block.title(
+                Title::from(Span::styled(
+                    format!(
+                        " {}Clocks change {} ",
+                        app.symbols.dst_warning,
+                        transition.format("%a %H:%M")
+                    ),
+                    Style::default().fg(theme.red),
+                ))
+                .alignment(Alignment::Right),
+            );
+        }
+        if let Some(history) = change_history(event) {
+            time_block = time_block.title_bottom(Span::styled(
+                format!(" {} ", history),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        let time_paragraph = Paragraph::new(time_str).block(time_block);
         f.render_widget(time_paragraph, row1_chunks[0]);
 
         // Location
@@ -229,8 +485,15 @@ pub fn draw_event_detail_view(f: &mut Frame, app: &mut App, area: Rect, theme: &
         f.render_widget(organizer_paragraph, row2_chunks[0]);
 
         // Attendees
+        let collapse_attendees = app.is_large_meeting(event) && !app.show_all_attendees;
         let attendees_list: String = if event.attendees.is_empty() {
             "None".to_string()
+        } else if collapse_attendees {
+            format!(
+                "{}{} attendees (press 'x' to expand)",
+                app.symbols.large_meeting,
+                event.attendees.len()
+            )
         } else {
             event
                 .attendees
@@ -255,21 +518,53 @@ pub fn draw_event_detail_view(f: &mut Frame, app: &mut App, area: Rect, theme: &
             .wrap(Wrap { trim: true });
         f.render_widget(attendees_paragraph, row2_chunks[1]);
 
+        // --- Links ---
+        if !links.is_empty() {
+            let mut link_spans = Vec::new();
+            for (i, link) in links.iter().enumerate().take(9) {
+                link_spans.push(Span::styled(
+                    format!("[{}] ", i + 1),
+                    Style::default().fg(theme.yellow),
+                ));
+                link_spans.push(Span::styled(
+                    format!("{}  ", link.label),
+                    Style::default()
+                        .fg(theme.blue)
+                        .add_modifier(Modifier::UNDERLINED),
+                ));
+            }
+            let links_paragraph = Paragraph::new(Line::from(link_spans)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Links (1-9 to open) ")
+                    .border_style(Style::default().fg(theme.yellow)),
+            );
+            f.render_widget(links_paragraph, chunks[3]);
+        }
+
         // --- Description ---
+        // Large meetings show their full attendee list here, where it can scroll
+        let expand_attendees = app.is_large_meeting(event) && app.show_all_attendees;
+        let description_title = if expand_attendees {
+            format!(" Attendees ({}) ", event.attendees.len())
+        } else if app.show_raw_body {
+            " Description (source, v: rendered) ".to_string()
+        } else {
+            " Description ".to_string()
+        };
         let mut description_text: Vec<Line> = Vec::new();
-        if let Some(body) = &event.body {
-            if body.content.is_empty() {
-                description_text.push(Line::from("None"));
-            } else {
-                let width = (chunks[3].width as usize).saturating_sub(2); // Margin
-                let formatted_content = html2text::from_read(body.content.as_bytes(), width)
-                    .unwrap_or_else(|_| body.content.clone());
-                for line in formatted_content.lines() {
-                    description_text.push(Line::from(line.to_string()));
+        if expand_attendees {
+            for attendee in &event.attendees {
+                if let Some(email) = &attendee.email_address {
+                    description_text
+                        .push(Line::from(format!("{} <{}>", email.name, email.address)));
                 }
             }
         } else {
-            description_text.push(Line::from("None"));
+            match formatted_body {
+                Some(body) => description_text.extend(body.lines),
+                None => description_text.push(Line::from("None")),
+            }
         }
 
         let description_len = description_text.len();
@@ -277,12 +572,12 @@ pub fn draw_event_detail_view(f: &mut Frame, app: &mut App, area: Rect, theme: &
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(" Description ")
+                    .title(description_title)
                     .border_style(Style::default().fg(theme.foreground)),
             )
             .wrap(Wrap { trim: false })
             .scroll((app.detail_view_scroll, 0));
-        f.render_widget(description_paragraph, chunks[3]);
+        f.render_widget(description_paragraph, chunks[4]);
 
         // Scrollbar for description
         app.detail_scroll_state = app
@@ -295,12 +590,54 @@ pub fn draw_event_detail_view(f: &mut Frame, app: &mut App, area: Rect, theme: &
                 .orientation(ScrollbarOrientation::VerticalRight)
                 .begin_symbol(Some("↑"))
                 .end_symbol(Some("↓")),
-            chunks[3],
+            chunks[4],
             &mut app.detail_scroll_state,
         );
+
+        app.detail_links = links;
     } else {
         let error_paragraph =
             Paragraph::new("Error: No event selected.").style(Style::default().fg(theme.red));
         f.render_widget(error_paragraph, inner_area);
     }
 }
+
+/// "created 3d ago, updated 2h ago", from the event's Graph timestamps. The
+/// update is left out when it's the creation itself.
+fn change_history(event: &GraphEvent) -> Option<String> {
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+            .map(|time| time.with_timezone(&Utc))
+    };
+    let created = parse(&event.created_date_time);
+    let modified = parse(&event.last_modified_date_time);
+
+    let mut parts = Vec::new();
+    if let Some(created) = created {
+        parts.push(format!("created {}", time_ago(created)));
+    }
+    if let Some(modified) = modified {
+        if created.is_none_or(|created| modified - created > Duration::minutes(1)) {
+            parts.push(format!("updated {}", time_ago(modified)));
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// A short relative time like "5m ago" or "3d ago".
+fn time_ago(time: DateTime<Utc>) -> String {
+    let elapsed = clock::now_utc() - time;
+    if elapsed < Duration::minutes(1) {
+        "just now".to_string()
+    } else if elapsed < Duration::hours(1) {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if elapsed < Duration::days(1) {
+        format!("{}h ago", elapsed.num_hours())
+    } else if elapsed < Duration::days(60) {
+        format!("{}d ago", elapsed.num_days())
+    } else {
+        format!("on {}", time.with_timezone(&Local).format("%d/%m/%Y"))
+    }
+}
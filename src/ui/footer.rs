@@ -0,0 +1,362 @@
+use crate::app::{parse_event_time, App, CurrentView, EventViewMode};
+use crate::clock;
+use crate::ui::dashboard::format_countdown;
+use crate::ui::text::truncate_to_width;
+use crate::ui::Theme;
+use chrono::{Datelike, Duration};
+use log::warn;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::Paragraph,
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Subjects in the countdown are cut so it leaves room for the other modules.
+const MAX_SUBJECT_WIDTH: usize = 30;
+/// The account badge is cut so it leaves room for the other modules.
+const MAX_ACCOUNT_WIDTH: usize = 40;
+/// Calendar names in the compact breadcrumb are cut to this width.
+const COMPACT_NAME_WIDTH: usize = 12;
+
+/// Footer layout used when the `footer` setting is not set.
+pub const DEFAULT_FOOTER: [&str; 5] = ["help", "breadcrumb", "account", "sync", "clock"];
+
+/// A widget that can be placed in the footer row through the `footer` setting.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FooterModule {
+    Help,
+    Breadcrumb,
+    Countdown,
+    Sync,
+    Account,
+    Clock,
+}
+
+impl FooterModule {
+    /// Looks up a module by the name used in the config file.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "help" => Some(FooterModule::Help),
+            "breadcrumb" | "title" => Some(FooterModule::Breadcrumb),
+            "countdown" => Some(FooterModule::Countdown),
+            "sync" => Some(FooterModule::Sync),
+            "account" => Some(FooterModule::Account),
+            "clock" => Some(FooterModule::Clock),
+            _ => None,
+        }
+    }
+
+    fn text(&self, app: &App) -> String {
+        match self {
+            FooterModule::Help => format!(" {} Help ", app.symbols.help),
+            FooterModule::Breadcrumb => breadcrumb_text(app),
+            FooterModule::Countdown => countdown_text(app),
+            FooterModule::Sync => sync_text(app),
+            FooterModule::Account => account_text(app),
+            FooterModule::Clock => {
+                let now = clock::now();
+                format!(" {} {} ", now.format("%d/%m"), now.format("%H:%M"))
+            }
+        }
+    }
+
+    fn constraint(&self, text: &str) -> Constraint {
+        match self {
+            FooterModule::Help => Constraint::Length(10),
+            FooterModule::Breadcrumb => Constraint::Min(0),
+            FooterModule::Countdown | FooterModule::Sync | FooterModule::Account => {
+                Constraint::Length(text.width() as u16)
+            }
+            FooterModule::Clock => Constraint::Length(20),
+        }
+    }
+
+    fn style(&self, app: &App, theme: &Theme) -> Style {
+        match self {
+            FooterModule::Help => Style::default().fg(theme.blue),
+            FooterModule::Breadcrumb => Style::default()
+                .fg(theme.mauve)
+                .add_modifier(Modifier::BOLD),
+            FooterModule::Countdown => Style::default().fg(theme.yellow),
+            // Old data is muted, so it doesn't pass for fresh at a glance
+            FooterModule::Sync if app.sync_truncated.is_some() => Style::default().fg(theme.yellow),
+            FooterModule::Sync if app.sync_is_stale() => Style::default()
+                .fg(theme.red)
+                .add_modifier(Modifier::DIM | Modifier::ITALIC),
+            FooterModule::Sync => Style::default()
+                .fg(theme.foreground)
+                .add_modifier(Modifier::DIM),
+            FooterModule::Account => Style::default().fg(theme.teal),
+            FooterModule::Clock => Style::default().fg(theme.foreground),
+        }
+    }
+
+    fn alignment(&self) -> Alignment {
+        match self {
+            FooterModule::Help | FooterModule::Countdown => Alignment::Left,
+            FooterModule::Breadcrumb
+            | FooterModule::Sync
+            | FooterModule::Account
+            | FooterModule::Clock => Alignment::Right,
+        }
+    }
+}
+
+/// How long ago the shown calendars were synced, flagged when that is too
+/// long ago for the cached events to be trusted.
+fn sync_text(app: &App) -> String {
+    if let Some(limit) = app.sync_truncated {
+        return format!(" synced first {} events, more next refresh ", limit);
+    }
+    let Some(synced_at) = app.last_synced else {
+        return " not synced yet ".to_string();
+    };
+    let age = clock::now_utc() - synced_at;
+    let synced = if age < Duration::minutes(1) {
+        "synced just now".to_string()
+    } else {
+        format!("synced {} ago", format_countdown(age))
+    };
+    if app.sync_is_stale() {
+        format!(" cached, {} ", synced)
+    } else {
+        format!(" {} ", synced)
+    }
+}
+
+/// Who is signed in, e.g. "me@contoso.com · Contoso", so it's clear which
+/// account (and which profile) the calendar belongs to.
+fn account_text(app: &App) -> String {
+    let Some(account) = &app.account else {
+        return String::new();
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    if let Some(profile) = crate::config::profile() {
+        parts.push(profile);
+    }
+    if let Some(who) = account.address.as_deref().or(account.name.as_deref()) {
+        parts.push(who);
+    }
+    if let Some(organization) = &account.organization {
+        parts.push(organization);
+    }
+    if parts.is_empty() {
+        return String::new();
+    }
+    format!(
+        " {} ",
+        truncate_to_width(&parts.join(" · "), MAX_ACCOUNT_WIDTH)
+    )
+}
+
+/// The modules configured for the footer, in order.
+pub fn footer_modules(app: &App) -> Vec<FooterModule> {
+    // Only the breadcrumb fits on small terminals
+    if app.compact {
+        return vec![FooterModule::Breadcrumb];
+    }
+    // Busy-only mode is meant for shared screens: keep just what the room needs
+    if app.busy_only {
+        return vec![FooterModule::Breadcrumb, FooterModule::Clock];
+    }
+    match &app.settings.footer {
+        Some(names) => names
+            .iter()
+            .filter_map(|name| {
+                let module = FooterModule::from_name(name);
+                if module.is_none() {
+                    warn!("Unknown footer module: {}", name);
+                }
+                module
+            })
+            .collect(),
+        None => DEFAULT_FOOTER
+            .iter()
+            .filter_map(|name| FooterModule::from_name(name))
+            .collect(),
+    }
+}
+
+/// Name of the calendar (or aggregate) whose events are shown, followed by
+/// the ticked categories when the category filter is on.
+fn calendar_name(app: &App) -> String {
+    let name = app.context_name();
+    if app.category_filter.is_empty() {
+        return name;
+    }
+    let mut categories: Vec<&str> = app.category_filter.iter().map(String::as_str).collect();
+    categories.sort_unstable();
+    format!("{} [{}]", name, categories.join(", "))
+}
+
+/// The "< Calendar - period >" title. Its arrows are clickable, see `tui.rs`.
+pub fn breadcrumb_text(app: &App) -> String {
+    if let CurrentView::Calendars = app.current_view {
+        return " Calendars ".to_string();
+    }
+    let calendar_name = calendar_name(app);
+    let (left, right) = (&app.symbols.left_arrow, &app.symbols.right_arrow);
+    if app.compact {
+        return compact_breadcrumb_text(app, &calendar_name, left, right);
+    }
+
+    match app.event_view_mode {
+        EventViewMode::Dashboard => format!(
+            " {} {} - Today, {} {} ",
+            left,
+            calendar_name,
+            clock::now().format("%a %d %b"),
+            right
+        ),
+        EventViewMode::List if app.show_past => format!(
+            " {} {} - Past {} days {} ",
+            left,
+            calendar_name,
+            app.past_days(),
+            right
+        ),
+        EventViewMode::List => format!(" {} {} {} ", left, calendar_name, right),
+        EventViewMode::Month => format!(
+            " {} {} - {} {} ",
+            left,
+            calendar_name,
+            app.displayed_date.format("%B %Y"),
+            right
+        ),
+        EventViewMode::Week | EventViewMode::WorkWeek => {
+            let (first_weekday, days) = app.week_layout();
+            let mut week_start = app.displayed_date;
+            while week_start.weekday() != first_weekday {
+                week_start = week_start.pred_opt().unwrap();
+            }
+            let week_end = week_start + Duration::days(days - 1);
+            format!(
+                " {} {} ({} to {}) {} ",
+                left,
+                calendar_name,
+                week_start.format("%d/%m"),
+                week_end.format("%d/%m"),
+                right
+            )
+        }
+        EventViewMode::Day => format!(
+            " {} {} ({}) {} ",
+            left,
+            calendar_name,
+            app.displayed_date.format("%a, %d %b %Y"),
+            right
+        ),
+    }
+}
+
+/// Shorter breadcrumb for the compact layout: abbreviated calendar name and
+/// numeric dates.
+fn compact_breadcrumb_text(app: &App, calendar_name: &str, left: &str, right: &str) -> String {
+    let period = match app.event_view_mode {
+        EventViewMode::Dashboard => "Today".to_string(),
+        EventViewMode::List if app.show_past => format!("-{}d", app.past_days()),
+        EventViewMode::List | EventViewMode::Month => {
+            app.displayed_date.format("%m/%Y").to_string()
+        }
+        EventViewMode::Week | EventViewMode::WorkWeek => {
+            let (first_weekday, _) = app.week_layout();
+            let mut week_start = app.displayed_date;
+            while week_start.weekday() != first_weekday {
+                week_start = week_start.pred_opt().unwrap();
+            }
+            format!("wk {}", week_start.format("%d/%m"))
+        }
+        EventViewMode::Day => app.displayed_date.format("%d/%m").to_string(),
+    };
+    format!(
+        " {} {} {} {} ",
+        left,
+        truncate_to_width(calendar_name, COMPACT_NAME_WIDTH),
+        period,
+        right
+    )
+}
+
+/// Time until the next event starts, or until the running one ends.
+fn countdown_text(app: &App) -> String {
+    let now = clock::now();
+    let mut next = None;
+    for color_event in &app.events {
+        let e = &color_event.event;
+        let (Some(start), Some(end)) = (
+            parse_event_time(&e.start.date_time),
+            parse_event_time(&e.end.date_time),
+        ) else {
+            continue;
+        };
+        // All-day events would otherwise show as running all day
+        if end - start >= Duration::hours(24) {
+            continue;
+        }
+        if start <= now && end > now {
+            return format!(
+                " Now: {} ({} left) ",
+                truncate_to_width(app.display_subject(e), MAX_SUBJECT_WIDTH),
+                format_countdown(end - now)
+            );
+        }
+        if start > now && next.is_none_or(|(s, _)| start < s) {
+            next = Some((start, app.display_subject(e)));
+        }
+    }
+    match next {
+        Some((start, subject)) => {
+            format!(
+                " Next: {} in {} ",
+                truncate_to_width(subject, MAX_SUBJECT_WIDTH),
+                format_countdown(start - now)
+            )
+        }
+        None => String::new(),
+    }
+}
+
+pub fn draw_footer(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    app.help_area = Rect::default();
+    app.footer_breadcrumb_area = Rect::default();
+
+    let modules = footer_modules(app);
+    let texts: Vec<String> = modules.iter().map(|m| m.text(app)).collect();
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            modules
+                .iter()
+                .zip(&texts)
+                .map(|(module, text)| module.constraint(text))
+                .collect::<Vec<_>>(),
+        )
+        .split(area);
+
+    for ((module, text), chunk) in modules.iter().zip(texts).zip(chunks.iter()) {
+        // Status messages temporarily take the breadcrumb's place
+        if let (FooterModule::Breadcrumb, Some(status)) = (module, app.current_status()) {
+            let paragraph = Paragraph::new(format!(" {} ", status))
+                .style(
+                    Style::default()
+                        .fg(theme.yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .alignment(Alignment::Right);
+            f.render_widget(paragraph, *chunk);
+            continue;
+        }
+
+        match module {
+            FooterModule::Help => app.help_area = *chunk,
+            FooterModule::Breadcrumb => app.footer_breadcrumb_area = *chunk,
+            _ => {}
+        }
+        let paragraph = Paragraph::new(text)
+            .style(module.style(app, theme))
+            .alignment(module.alignment());
+        f.render_widget(paragraph, *chunk);
+    }
+}
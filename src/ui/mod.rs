@@ -1,24 +1,39 @@
-use crate::app::{App, CurrentView, EventViewMode};
-use chrono::{Datelike, Duration, Local, Weekday};
+use crate::app::{
+    display_subject, parse_event_time, App, ColorCalendar, CurrentView, EventViewMode, ToastKind,
+};
+use crate::search::SearchSource;
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Row, Table, Tabs, Widget},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Row, Table, Tabs, Widget, Wrap},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
+pub mod body;
 pub mod calendar;
+pub mod dashboard;
 pub mod event;
+pub mod footer;
+pub mod planner;
+pub mod sql_console;
+pub mod text;
 
 use calendar::{
-    draw_calendar_list, draw_day_view, draw_month_view, draw_week_view, draw_work_week_view,
+    draw_calendar_list, draw_day_peek, draw_day_view, draw_month_view, draw_week_view,
+    draw_work_week_view,
 };
+use dashboard::draw_dashboard;
 use event::{draw_event_detail_view, draw_event_list};
+use footer::draw_footer;
+use planner::draw_planner_popup;
+use sql_console::draw_sql_console;
 
 use crate::config::{ConfigSymbols, ConfigTheme};
 use std::collections::HashMap;
+use std::rc::Rc;
 
 #[derive(Clone)]
 pub struct Symbols {
@@ -29,6 +44,9 @@ pub struct Symbols {
     pub right_arrow: String,
     pub up_arrow: String,
     pub down_arrow: String,
+    pub large_meeting: String,
+    pub dst_warning: String,
+    pub external: String,
 }
 
 impl Default for Symbols {
@@ -62,6 +80,12 @@ impl Symbols {
             right_arrow: config.right_arrow.clone().unwrap_or(default.right_arrow),
             up_arrow: config.up_arrow.clone().unwrap_or(default.up_arrow),
             down_arrow: config.down_arrow.clone().unwrap_or(default.down_arrow),
+            large_meeting: config
+                .large_meeting
+                .clone()
+                .unwrap_or(default.large_meeting),
+            dst_warning: config.dst_warning.clone().unwrap_or(default.dst_warning),
+            external: config.external.clone().unwrap_or(default.external),
         }
     }
 
@@ -74,6 +98,9 @@ impl Symbols {
             right_arrow: "".to_string(),
             up_arrow: "".to_string(),
             down_arrow: "".to_string(),
+            large_meeting: " ".to_string(),
+            dst_warning: " ".to_string(),
+            external: "󰖟 ".to_string(),
         }
     }
 
@@ -86,6 +113,9 @@ impl Symbols {
             right_arrow: "►".to_string(),
             up_arrow: "▲".to_string(),
             down_arrow: "▼".to_string(),
+            large_meeting: "👥".to_string(),
+            dst_warning: "⚠ ".to_string(),
+            external: "🌐".to_string(),
         }
     }
 
@@ -98,6 +128,9 @@ impl Symbols {
             right_arrow: ">".to_string(),
             up_arrow: "^".to_string(),
             down_arrow: "v".to_string(),
+            large_meeting: "[L]".to_string(),
+            dst_warning: "[!]".to_string(),
+            external: "[E]".to_string(),
         }
     }
 }
@@ -130,31 +163,78 @@ impl Theme {
     }
 
     pub fn from_config(config: &ConfigTheme) -> Self {
-        fn parse_color(s: &str) -> Color {
+        fn parse_color(s: &str) -> Option<Color> {
             if s.starts_with('#') && s.len() == 7 {
-                let r = u8::from_str_radix(&s[1..3], 16).unwrap_or(255);
-                let g = u8::from_str_radix(&s[3..5], 16).unwrap_or(255);
-                let b = u8::from_str_radix(&s[5..7], 16).unwrap_or(255);
-                Color::Rgb(r, g, b)
+                let r = u8::from_str_radix(&s[1..3], 16).ok()?;
+                let g = u8::from_str_radix(&s[3..5], 16).ok()?;
+                let b = u8::from_str_radix(&s[5..7], 16).ok()?;
+                Some(Color::Rgb(r, g, b))
             } else {
-                Color::White // Fallback
+                None
             }
         }
 
+        // Malformed colors (reported when the config is loaded) keep the default
+        let default = Self::default();
         Self {
-            background: parse_color(&config.background),
-            foreground: parse_color(&config.foreground),
-            yellow: parse_color(&config.yellow),
-            blue: parse_color(&config.blue),
-            mauve: parse_color(&config.mauve),
-            green: parse_color(&config.green),
-            red: parse_color(&config.red),
-            peach: parse_color(&config.peach),
-            teal: parse_color(&config.teal),
+            background: parse_color(&config.background).unwrap_or(default.background),
+            foreground: parse_color(&config.foreground).unwrap_or(default.foreground),
+            yellow: parse_color(&config.yellow).unwrap_or(default.yellow),
+            blue: parse_color(&config.blue).unwrap_or(default.blue),
+            mauve: parse_color(&config.mauve).unwrap_or(default.mauve),
+            green: parse_color(&config.green).unwrap_or(default.green),
+            red: parse_color(&config.red).unwrap_or(default.red),
+            peach: parse_color(&config.peach).unwrap_or(default.peach),
+            teal: parse_color(&config.teal).unwrap_or(default.teal),
+        }
+    }
+
+    /// Text color readable on `fill`: the theme's background color when it
+    /// contrasts enough (WCAG AA, 4.5:1), else black or white, whichever
+    /// contrasts more. Custom themes and calendar colors can make light text
+    /// on a light fill otherwise.
+    pub fn text_on(&self, fill: Color) -> Color {
+        let Some(fill_luminance) = relative_luminance(fill) else {
+            return self.background;
+        };
+        if relative_luminance(self.background)
+            .is_some_and(|text| contrast_ratio(text, fill_luminance) >= MIN_CONTRAST)
+        {
+            return self.background;
+        }
+        if contrast_ratio(0.0, fill_luminance) >= contrast_ratio(1.0, fill_luminance) {
+            Color::Rgb(0, 0, 0)
+        } else {
+            Color::Rgb(255, 255, 255)
         }
     }
 }
 
+/// Contrast ratio WCAG asks of normal text.
+const MIN_CONTRAST: f64 = 4.5;
+
+/// Relative luminance of an RGB color, as defined by WCAG 2. Named terminal
+/// colors depend on the terminal's palette, so they have none.
+fn relative_luminance(color: Color) -> Option<f64> {
+    let Color::Rgb(r, g, b) = color else {
+        return None;
+    };
+    let channel = |value: u8| {
+        let c = value as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b))
+}
+
+/// Contrast ratio between two luminances, from 1 (none) to 21.
+fn contrast_ratio(a: f64, b: f64) -> f64 {
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
 impl Default for Theme {
     fn default() -> Self {
         Self {
@@ -191,15 +271,19 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-pub fn ui(f: &mut Frame, app: &mut App, theme: &Theme) {
-    f.render_widget(
-        Block::default().style(Style::default().bg(theme.background)),
-        f.size(),
-    );
+/// Below this width or height the compact layout is used.
+pub const COMPACT_WIDTH: u16 = 80;
+pub const COMPACT_HEIGHT: u16 = 20;
 
-    let main_chunks = Layout::default()
+pub fn is_compact(size: Rect) -> bool {
+    size.width < COMPACT_WIDTH || size.height < COMPACT_HEIGHT
+}
+
+/// Header, content and footer areas. Shared with the mouse handling in `tui.rs`.
+pub fn main_layout(size: Rect, compact: bool) -> Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Vertical)
-        .margin(1)
+        .margin(if compact { 0 } else { 1 })
         .constraints(
             [
                 Constraint::Length(3), // Header
@@ -208,12 +292,39 @@ pub fn ui(f: &mut Frame, app: &mut App, theme: &Theme) {
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(size)
+}
+
+/// Labels of the view tabs, in order. The compact layout drops the icons and
+/// abbreviates them so all tabs fit in about 40 columns.
+pub fn tab_labels(app: &App, compact: bool) -> [String; 7] {
+    if compact {
+        return [" Cal ", " Tdy ", " Lst ", " Wk ", " Wrk ", " Day ", " Mon "].map(String::from);
+    }
+    [
+        format!(" {} Cals ", app.symbols.calendar),
+        "  Today ".to_string(), // Not configurable yet
+        "  List ".to_string(),  // Not configurable yet
+        format!(" {} Week ", app.symbols.clock),
+        "  Work ".to_string(),  // Not configurable yet
+        "  Day ".to_string(),   // Not configurable yet
+        "  Month ".to_string(), // Not configurable yet
+    ]
+}
+
+pub fn ui(f: &mut Frame, app: &mut App, theme: &Theme) {
+    f.render_widget(
+        Block::default().style(Style::default().bg(theme.background)),
+        f.size(),
+    );
+
+    app.compact = is_compact(f.size());
+    let main_chunks = main_layout(f.size(), app.compact);
 
     let header_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(68), // Tabs (tuned to 68 to remove extra space)
+            Constraint::Length(80), // Tabs (tuned to 80 to remove extra space)
             Constraint::Min(0),     // Title (takes remaining space)
         ])
         .split(main_chunks[0]);
@@ -222,29 +333,26 @@ pub fn ui(f: &mut Frame, app: &mut App, theme: &Theme) {
     let selected_index = match app.current_view {
         CurrentView::Calendars => 0,
         CurrentView::Events | CurrentView::EventDetail => match app.event_view_mode {
-            EventViewMode::List => 1,
-            EventViewMode::Week => 2,
-            EventViewMode::WorkWeek => 3,
-            EventViewMode::Day => 4,
-            EventViewMode::Month => 5,
+            EventViewMode::Dashboard => 1,
+            EventViewMode::List => 2,
+            EventViewMode::Week => 3,
+            EventViewMode::WorkWeek => 4,
+            EventViewMode::Day => 5,
+            EventViewMode::Month => 6,
         },
     };
 
-    let calendar_icon = format!(" {} Cals ", app.symbols.calendar);
-    let list_icon = "  List "; // Not configurable yet
-    let week_icon = format!(" {} Week ", app.symbols.clock);
-    let work_icon = "  Work "; // Not configurable yet
-    let day_icon = "  Day "; // Not configurable yet
-    let month_icon = "  Month "; // Not configurable yet
-
-    let tab_data = vec![
-        (calendar_icon.as_str(), theme.blue),
-        (list_icon, theme.green),
-        (week_icon.as_str(), theme.yellow),
-        (work_icon, theme.peach),
-        (day_icon, theme.teal),
-        (month_icon, theme.red),
+    let labels = tab_labels(app, app.compact);
+    let tab_colors = [
+        theme.blue,
+        theme.mauve,
+        theme.green,
+        theme.yellow,
+        theme.peach,
+        theme.teal,
+        theme.red,
     ];
+    let tab_data: Vec<(&str, Color)> = labels.iter().map(String::as_str).zip(tab_colors).collect();
 
     let active_color = tab_data[selected_index].1;
 
@@ -257,7 +365,7 @@ pub fn ui(f: &mut Frame, app: &mut App, theme: &Theme) {
                     *text,
                     Style::default()
                         .bg(*color)
-                        .fg(theme.background)
+                        .fg(theme.text_on(*color))
                         .add_modifier(Modifier::BOLD),
                 ))
             } else {
@@ -281,165 +389,27 @@ pub fn ui(f: &mut Frame, app: &mut App, theme: &Theme) {
             Style::default()
                 .add_modifier(Modifier::BOLD)
                 .bg(active_color)
-                .fg(theme.background),
+                .fg(theme.text_on(active_color)),
         )
         .divider(Span::raw("|"));
     f.render_widget(tabs, header_chunks[0]);
 
-    // Window Title (Moved to Footer)
-
-    // Footer Layout
-    let footer_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Length(10), // Help
-            Constraint::Min(0),     // Title
-            Constraint::Length(20), // Date/Time
-        ])
-        .split(main_chunks[2]);
-
-    // Help Text (Footer Left)
-    let help_text = format!(" {} Help ", app.symbols.help);
-    let help_paragraph = Paragraph::new(help_text)
-        .style(Style::default().fg(theme.blue))
-        .alignment(Alignment::Left);
-    f.render_widget(help_paragraph, footer_chunks[0]);
-    // Note: Help area for click detection might need adjustment if we want it clickable in footer
-    // For now, let's keep it clickable but we need to update app.help_area
-    app.help_area = footer_chunks[0];
-
-    // Title (Footer Center/Right)
-    let title_text = match app.current_view {
-        CurrentView::Calendars => " Calendars ".to_string(),
-        CurrentView::Events | CurrentView::EventDetail => {
-            let calendar_name = app
-                .current_calendar_id
-                .as_ref()
-                .and_then(|id| {
-                    if id == crate::app::MY_CALENDARS_ID {
-                        Some("My Calendars".to_string())
-                    } else {
-                        app.calendars
-                            .iter()
-                            .find(|c| &c.calendar.id == id)
-                            .map(|c| c.calendar.name.clone())
-                    }
-                })
-                .unwrap_or_else(|| "All Calendars".to_string());
-
-            match app.event_view_mode {
-                EventViewMode::List => format!(
-                    " {} {} {} ",
-                    app.symbols.left_arrow, calendar_name, app.symbols.right_arrow
-                ),
-                EventViewMode::Month => {
-                    let displayed_date = app.displayed_date;
-                    let month_str = format!(
-                        "{} {}",
-                        [
-                            "",
-                            "January",
-                            "February",
-                            "March",
-                            "April",
-                            "May",
-                            "June",
-                            "July",
-                            "August",
-                            "September",
-                            "October",
-                            "November",
-                            "December"
-                        ][displayed_date.month() as usize],
-                        displayed_date.year()
-                    );
-                    format!(
-                        " {} {} - {} {} ",
-                        app.symbols.left_arrow, calendar_name, month_str, app.symbols.right_arrow
-                    )
-                }
-                EventViewMode::Week => {
-                    let mut week_start = app.displayed_date;
-                    while week_start.weekday() != Weekday::Sun {
-                        week_start = week_start.pred_opt().unwrap();
-                    }
-                    let week_end = week_start + Duration::days(6);
-                    format!(
-                        " {} {} ({} to {}) {} ",
-                        app.symbols.left_arrow,
-                        calendar_name,
-                        week_start.format("%d/%m"),
-                        week_end.format("%d/%m"),
-                        app.symbols.right_arrow
-                    )
-                }
-                EventViewMode::WorkWeek => {
-                    let mut week_start = app.displayed_date;
-                    while week_start.weekday() != Weekday::Mon {
-                        week_start = week_start.pred_opt().unwrap();
-                    }
-                    let week_end = week_start + Duration::days(4);
-                    format!(
-                        " {} {} ({} to {}) {} ",
-                        app.symbols.left_arrow,
-                        calendar_name,
-                        week_start.format("%d/%m"),
-                        week_end.format("%d/%m"),
-                        app.symbols.right_arrow
-                    )
-                }
-                EventViewMode::Day => {
-                    let current_day = app.displayed_date;
-                    format!(
-                        " {} {} ({}) {} ",
-                        app.symbols.left_arrow,
-                        calendar_name,
-                        current_day.format("%a, %d %b %Y"),
-                        app.symbols.right_arrow
-                    )
-                }
-            }
-        }
-    };
-
-    let title_paragraph = Paragraph::new(title_text)
-        .style(
-            Style::default()
-                .fg(theme.mauve)
-                .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Right)
-        .block(Block::default().borders(Borders::NONE));
-    f.render_widget(title_paragraph, footer_chunks[1]);
-
-    // Date/Time (Footer Right)
-    let now = Local::now();
-    let datetime_str = format!(" {} {} ", now.format("%d/%m"), now.format("%H:%M"));
-    let datetime_paragraph = Paragraph::new(datetime_str)
-        .style(Style::default().fg(theme.foreground))
-        .alignment(Alignment::Right);
-    f.render_widget(datetime_paragraph, footer_chunks[2]);
+    draw_footer(f, app, main_chunks[2], theme);
 
     let content_area = main_chunks[1];
     match app.current_view {
         CurrentView::Calendars => draw_calendar_list(f, app, content_area, theme, active_color),
         CurrentView::Events | CurrentView::EventDetail => {
-            let calendar_name = app
-                .current_calendar_id
-                .as_ref()
-                .and_then(|id| {
-                    if id == crate::app::MY_CALENDARS_ID {
-                        Some("My Calendars".to_string())
-                    } else {
-                        app.calendars
-                            .iter()
-                            .find(|c| &c.calendar.id == id)
-                            .map(|c| c.calendar.name.clone())
-                    }
-                })
-                .unwrap_or_else(|| "All Calendars".to_string());
+            let calendar_name = app.context_name();
 
             match app.event_view_mode {
+                EventViewMode::Dashboard => {
+                    draw_dashboard(f, app, content_area, theme, active_color)
+                }
+                // Grids don't fit on small terminals, so every range is listed
+                _ if app.compact => {
+                    draw_event_list(f, app, content_area, theme, &calendar_name, active_color)
+                }
                 EventViewMode::List => {
                     draw_event_list(f, app, content_area, theme, &calendar_name, active_color)
                 }
@@ -476,7 +446,771 @@ pub fn ui(f: &mut Frame, app: &mut App, theme: &Theme) {
         draw_help_popup(f, app, area, theme);
     }
 
-    // Legend Popup removed (merged into Help)
+    if app.show_diagnostics {
+        let area = centered_rect(60, 60, f.size());
+        draw_diagnostics_popup(f, app, area, theme);
+    }
+
+    if app.show_missed {
+        let area = centered_rect(60, 50, f.size());
+        draw_missed_popup(f, app, area, theme);
+    }
+
+    if app.find_time.is_some() {
+        let area = centered_rect(60, 50, f.size());
+        draw_find_time_popup(f, app, area, theme);
+    }
+
+    if app.room_picker.is_some() {
+        let area = centered_rect(60, 50, f.size());
+        draw_room_picker_popup(f, app, area, theme);
+    }
+
+    if app.day_peek.is_some() {
+        draw_day_peek(f, app, theme);
+    }
+
+    if app.search.is_some() {
+        let area = centered_rect(70, 60, f.size());
+        draw_search_popup(f, app, area, theme);
+    }
+
+    if app.planner.is_some() {
+        let area = centered_rect(70, 60, f.size());
+        draw_planner_popup(f, app, area, theme);
+    }
+
+    if app.sql_console.is_some() {
+        let area = centered_rect(90, 80, f.size());
+        draw_sql_console(f, app, area, theme);
+    }
+
+    if app.input.is_some() {
+        draw_input_prompt(f, app, theme);
+    }
+
+    if app.alarm.is_some() {
+        draw_alarm_popup(f, app, theme);
+    }
+
+    if app.conflict_prompt.is_some() {
+        draw_conflict_popup(f, app, theme);
+    }
+
+    if app.edit_prompt.is_some() {
+        draw_edit_popup(f, app, theme);
+    }
+
+    if app.logout_prompt {
+        draw_logout_popup(f, app, theme);
+    }
+
+    if app.relogin_prompt.is_some() {
+        draw_relogin_popup(f, app, theme);
+    }
+
+    if app.show_legend {
+        draw_legend_popup(f, app, theme);
+    }
+
+    if app.show_categories {
+        draw_category_popup(f, app, theme);
+    }
+
+    draw_toasts(f, app, theme);
+
+    // Terminal alert: invert everything briefly
+    if app.flash_until.is_some() {
+        let area = f.size();
+        f.buffer_mut()
+            .set_style(area, Style::default().add_modifier(Modifier::REVERSED));
+    }
+}
+
+/// Toasts stacked in the bottom-right corner of the content, newest at the
+/// bottom.
+fn draw_toasts(f: &mut Frame, app: &App, theme: &Theme) {
+    let content = main_layout(f.size(), app.compact)[1];
+    let width = content.width.min(40);
+    let text_width = width.saturating_sub(2) as usize;
+    let mut bottom = content.bottom();
+    for toast in app.current_toasts().collect::<Vec<_>>().into_iter().rev() {
+        let lines: Vec<Line> = text::wrap_text(&toast.message, text_width)
+            .into_iter()
+            .map(Line::from)
+            .collect();
+        let height = lines.len().max(1) as u16 + 2;
+        if bottom < content.top() + height {
+            break;
+        }
+        bottom -= height;
+        let area = Rect {
+            x: content.right() - width,
+            y: bottom,
+            width,
+            height,
+        };
+        let color = match toast.kind {
+            ToastKind::Reminder => theme.yellow,
+            ToastKind::Info => theme.green,
+            ToastKind::Error => theme.red,
+        };
+        f.render_widget(Clear, area);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().fg(theme.foreground))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color))
+                    .title(format!(" {} ", toast.title)),
+            );
+        f.render_widget(paragraph, area);
+    }
+}
+
+fn draw_input_prompt(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(input) = &app.input else {
+        return;
+    };
+    let size = f.size();
+    let width = (size.width * 60 / 100).max(20).min(size.width);
+    let area = Rect {
+        x: size.x + (size.width - width) / 2,
+        y: size.y + size.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(size.height),
+    };
+    f.render_widget(Clear, area);
+
+    // Keep the end of the text (and the cursor) visible when it overflows
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let mut visible = input.buffer.as_str();
+    while visible.width() >= inner_width && !visible.is_empty() {
+        let mut chars = visible.chars();
+        chars.next();
+        visible = chars.as_str();
+    }
+
+    let paragraph = Paragraph::new(visible).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.yellow))
+            .title(input.kind.title()),
+    );
+    f.render_widget(paragraph, area);
+    f.set_cursor(area.x + 1 + visible.width() as u16, area.y + 1);
+}
+
+/// The events a new event would overlap, asking whether to create it anyway.
+/// The reminders held back by do not disturb.
+fn draw_missed_popup(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let missed = app.notification_manager.missed();
+    let items: Vec<ListItem> = if missed.is_empty() {
+        vec![ListItem::new("No missed reminders.")]
+    } else {
+        missed
+            .iter()
+            .map(|reminder| {
+                let start = reminder.start_time_utc.with_timezone(&chrono::Local);
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        start.format("%a %H:%M  ").to_string(),
+                        Style::default().fg(theme.yellow),
+                    ),
+                    Span::raw(display_subject(&reminder.subject, app.busy_only).to_string()),
+                ]))
+            })
+            .collect()
+    };
+    let list = List::new(items)
+        .style(Style::default().fg(theme.foreground))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.mauve))
+                .title(" Missed Reminders ")
+                .title_bottom(" x: clear | Esc: close "),
+        );
+    f.render_widget(list, area);
+}
+
+fn draw_diagnostics_popup(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let hours = crate::metrics::hourly();
+    let requests: u32 = hours.iter().map(|h| h.requests).sum();
+    let bytes: u64 = hours.iter().map(|h| h.bytes).sum();
+    let throttled: u32 = hours.iter().map(|h| h.throttled).sum();
+    let refresh_minutes = app.settings.refresh_interval_minutes.unwrap_or(5);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(0)])
+        .split(area);
+
+    let throttled_style = if throttled > 0 {
+        Style::default().fg(theme.red)
+    } else {
+        Style::default()
+    };
+    let summary = Paragraph::new(vec![
+        Line::from(format!("Requests: {}", requests)),
+        Line::from(format!("Received: {}", format_bytes(bytes))),
+        Line::from(Span::styled(
+            format!("Throttled: {}", throttled),
+            throttled_style,
+        )),
+    ])
+    .style(Style::default().fg(theme.foreground))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.mauve))
+            .title(" Diagnostics (last 24h) ")
+            .title(
+                Line::from(format!(" Refresh every {} min ", refresh_minutes))
+                    .alignment(ratatui::layout::Alignment::Right),
+            ),
+    );
+    f.render_widget(summary, chunks[0]);
+
+    let rows: Vec<Row> = hours
+        .iter()
+        .map(|h| {
+            let row = Row::new(vec![
+                h.hour.format("%a %H:00").to_string(),
+                h.requests.to_string(),
+                format_bytes(h.bytes),
+                h.throttled.to_string(),
+            ]);
+            if h.throttled > 0 {
+                row.style(Style::default().fg(theme.red))
+            } else {
+                row
+            }
+        })
+        .collect();
+    let empty = rows.is_empty();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+        ],
+    )
+    .header(
+        Row::new(vec!["Hour", "Requests", "Received", "Throttled"])
+            .style(
+                Style::default()
+                    .fg(theme.yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .bottom_margin(1),
+    )
+    .column_spacing(1)
+    .style(Style::default().fg(theme.foreground))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.mauve))
+            .title(if empty {
+                " Per Hour (no requests yet) "
+            } else {
+                " Per Hour "
+            })
+            .title_bottom(" Any key to close "),
+    );
+    f.render_widget(table, chunks[1]);
+}
+
+/// Human-readable size, e.g. "1.4 MB".
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+fn draw_alarm_popup(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(alarm) = &app.alarm else {
+        return;
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            display_subject(&alarm.subject, app.busy_only).to_string(),
+            Style::default()
+                .fg(theme.yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(format!(
+            "Started at {} (until {})",
+            alarm.start.format("%H:%M"),
+            alarm.end.format("%H:%M")
+        )),
+    ];
+
+    let area = centered_rect(50, 25, f.size());
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.foreground))
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.red).add_modifier(Modifier::BOLD))
+                .title(" Meeting Starting ")
+                .title_bottom(format!(
+                    " Enter: dismiss | s: snooze {} min ",
+                    crate::alarms::SNOOZE.num_minutes()
+                )),
+        );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_relogin_popup(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(reason) = &app.relogin_prompt else {
+        return;
+    };
+    let lines = vec![
+        Line::from(reason.as_str()),
+        Line::from(""),
+        Line::from("The login opens in your browser; the app keeps running meanwhile."),
+    ];
+
+    let area = centered_rect(50, 25, f.size());
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.foreground))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.yellow))
+                .title(" Log In Again ")
+                .title_bottom(" y: log in | n: later "),
+        );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_logout_popup(f: &mut Frame, app: &App, theme: &Theme) {
+    let who = app
+        .account
+        .as_ref()
+        .and_then(|a| a.address.clone().or(a.name.clone()))
+        .unwrap_or_else(|| "this account".to_string());
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("Log out of "),
+            Span::styled(who, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("?"),
+        ]),
+        Line::from(""),
+        Line::from("The app closes and the saved login is deleted. Cached events stay."),
+    ];
+
+    let area = centered_rect(50, 25, f.size());
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.foreground))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.red))
+                .title(" Log Out ")
+                .title_bottom(" y: log out | n: cancel "),
+        );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_conflict_popup(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(prompt) = &app.conflict_prompt else {
+        return;
+    };
+    let time_range = |start: &str, end: &str| match (parse_event_time(start), parse_event_time(end))
+    {
+        (Some(start), Some(end)) => {
+            format!(
+                "{} - {}",
+                start.format("%a %d/%m %H:%M"),
+                end.format("%H:%M")
+            )
+        }
+        _ => "Invalid time".to_string(),
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                prompt.new_event.subject.clone(),
+                Style::default()
+                    .fg(theme.yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                " ({})",
+                time_range(&prompt.new_event.start, &prompt.new_event.end)
+            )),
+        ]),
+        Line::from(format!("overlaps {} event(s):", prompt.conflicts.len())),
+        Line::from(""),
+    ];
+    for conflict in &prompt.conflicts {
+        lines.push(Line::from(vec![
+            Span::styled("■ ", Style::default().fg(conflict.color)),
+            Span::raw(format!(
+                "{}  ",
+                time_range(
+                    &conflict.event.start.date_time,
+                    &conflict.event.end.date_time
+                )
+            )),
+            Span::raw(app.display_subject(&conflict.event).to_string()),
+        ]));
+    }
+
+    let area = centered_rect(60, 40, f.size());
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.foreground))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.red))
+                .title(" Conflicts ")
+                .title_bottom(" y: create anyway | n: cancel "),
+        );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_edit_popup(f: &mut Frame, app: &App, theme: &Theme) {
+    let Some(prompt) = &app.edit_prompt else {
+        return;
+    };
+    let time_range = |fields: &crate::app::EditableFields| {
+        let end_format = if fields.start.date_naive() == fields.end.date_naive() {
+            "%H:%M"
+        } else {
+            "%a %d/%m %H:%M"
+        };
+        format!(
+            "{} - {}",
+            fields.start.format("%a %d/%m %H:%M"),
+            fields.end.format(end_format)
+        )
+    };
+    let or_none = |value: &str| {
+        if value.is_empty() {
+            "(none)".to_string()
+        } else {
+            value.to_string()
+        }
+    };
+    let rows = [
+        (
+            "Subject",
+            prompt.old.subject.clone(),
+            prompt.new.subject.clone(),
+        ),
+        ("Time", time_range(&prompt.old), time_range(&prompt.new)),
+        (
+            "Location",
+            or_none(&prompt.old.location),
+            or_none(&prompt.new.location),
+        ),
+    ];
+
+    let mut lines = Vec::new();
+    for (label, old, new) in rows {
+        let label = Span::styled(
+            format!("{:<10}", label),
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        if old == new {
+            lines.push(Line::from(vec![
+                label,
+                Span::styled(old, Style::default().add_modifier(Modifier::DIM)),
+            ]));
+        } else {
+            lines.push(Line::from(vec![
+                label,
+                Span::styled(format!("- {}", old), Style::default().fg(theme.red)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::raw(" ".repeat(10)),
+                Span::styled(format!("+ {}", new), Style::default().fg(theme.green)),
+            ]));
+        }
+    }
+
+    let area = centered_rect(60, 40, f.size());
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.foreground))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.yellow))
+                .title(" Save Changes? ")
+                .title_bottom(" y: save | n: discard "),
+        );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_find_time_popup(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    let Some(find_time) = &mut app.find_time else {
+        return;
+    };
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.yellow))
+        .title(format!(
+            " Find a time: {} min with {} ",
+            find_time.duration_minutes,
+            find_time.attendees.join(", ")
+        ));
+
+    let suggestions = match &find_time.suggestions {
+        None => {
+            let paragraph = Paragraph::new("Searching for free slots...")
+                .style(Style::default().fg(theme.foreground))
+                .block(block);
+            f.render_widget(paragraph, area);
+            return;
+        }
+        Some(suggestions) if suggestions.is_empty() => {
+            let reason = find_time
+                .empty_reason
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+            let paragraph = Paragraph::new(format!("No suggestions found (reason: {}).", reason))
+                .style(Style::default().fg(theme.red))
+                .block(block);
+            f.render_widget(paragraph, area);
+            return;
+        }
+        Some(suggestions) => suggestions,
+    };
+
+    let items: Vec<ListItem> = suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, suggestion)| {
+            let slot = &suggestion.meeting_time_slot;
+            let time = match (
+                parse_event_time(&slot.start.date_time),
+                parse_event_time(&slot.end.date_time),
+            ) {
+                (Some(start), Some(end)) => format!(
+                    "{} {}-{}",
+                    start.format("%a %d/%m"),
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                ),
+                _ => slot.start.date_time.clone(),
+            };
+            let reason = suggestion.suggestion_reason.clone().unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:>2}. ", i + 1), Style::default().fg(theme.blue)),
+                Span::styled(time, Style::default().fg(theme.foreground)),
+                Span::styled(
+                    format!("  {:>3.0}%  ", suggestion.confidence),
+                    Style::default().fg(theme.green),
+                ),
+                Span::styled(reason, Style::default().fg(theme.peach)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block.title_bottom(" Enter: create event | Esc: close "))
+        .highlight_style(
+            Style::default()
+                .fg(theme.yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("❯ ");
+    f.render_stateful_widget(list, area, &mut find_time.list_state);
+}
+
+fn draw_room_picker_popup(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    let Some(picker) = &mut app.room_picker else {
+        return;
+    };
+    f.render_widget(Clear, area);
+
+    let time = match (
+        parse_event_time(&picker.new_event.start),
+        parse_event_time(&picker.new_event.end),
+    ) {
+        (Some(start), Some(end)) => format!(
+            "{} {}-{}",
+            start.format("%a %d/%m"),
+            start.format("%H:%M"),
+            end.format("%H:%M")
+        ),
+        _ => picker.new_event.start.clone(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.yellow))
+        .title(format!(
+            " Room for \"{}\", {} ",
+            picker.new_event.subject, time
+        ))
+        .title_bottom(" Enter: create event | Esc: cancel ");
+
+    let mut items = vec![ListItem::new(Line::from(Span::styled(
+        "No room",
+        Style::default().fg(theme.foreground),
+    )))];
+    match &picker.rooms {
+        None => items.push(ListItem::new(Line::from(Span::styled(
+            "Looking up rooms...",
+            Style::default().fg(theme.blue),
+        )))),
+        Some(Err(e)) => items.push(ListItem::new(Line::from(Span::styled(
+            format!("Could not list the rooms: {}", e),
+            Style::default().fg(theme.red),
+        )))),
+        Some(Ok(rooms)) => items.extend(rooms.iter().map(|option| {
+            let (availability, color) = match option.free {
+                Some(true) => ("free", theme.green),
+                Some(false) => ("busy", theme.red),
+                None => ("?", theme.peach),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<6}", availability), Style::default().fg(color)),
+                Span::styled(
+                    option.room.name.clone(),
+                    Style::default().fg(theme.foreground),
+                ),
+                Span::styled(
+                    format!("  {}", option.room.address),
+                    Style::default().fg(theme.blue),
+                ),
+            ]))
+        })),
+    }
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .fg(theme.yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("❯ ");
+    f.render_stateful_widget(list, area, &mut picker.list_state);
+}
+
+fn draw_search_popup(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    let busy_only = app.busy_only;
+    let Some(search) = &mut app.search else {
+        return;
+    };
+    f.render_widget(Clear, area);
+
+    let mut title = if search.cache_only {
+        format!(" Search cache: \"{}\" ", search.query.text)
+    } else {
+        format!(" Search: \"{}\" ", search.query.text)
+    };
+    match (search.query.from, search.query.to) {
+        (Some(from), Some(to)) => title.push_str(&format!("({} to {}) ", from, to)),
+        (Some(from), None) => title.push_str(&format!("(from {}) ", from)),
+        (None, Some(to)) => title.push_str(&format!("(until {}) ", to)),
+        (None, None) => {}
+    }
+    let status = if search.cache_pending {
+        " Searching the cache... ".to_string()
+    } else if search.server_pending {
+        " Searching the server... ".to_string()
+    } else if let Some(e) = &search.server_error {
+        format!(" Server search failed: {} ", e)
+    } else {
+        format!(
+            " {} results | Enter: go to day | Esc: close ",
+            search.results.len()
+        )
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.yellow))
+        .title(title)
+        .title_bottom(status);
+
+    if search.results.is_empty() {
+        let message = if search.cache_pending {
+            "Searching..."
+        } else if search.server_pending {
+            "No cached matches, waiting for the server..."
+        } else {
+            "No events found."
+        };
+        let paragraph = Paragraph::new(message)
+            .style(Style::default().fg(theme.foreground))
+            .block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = search
+        .results
+        .iter()
+        .map(|result| {
+            let when = parse_event_time(&result.event.start.date_time)
+                .map(|start| start.format("%a %d %b %Y %H:%M").to_string())
+                .unwrap_or_else(|| result.event.start.date_time.clone());
+            let label_color = match result.source {
+                SearchSource::Cached => theme.green,
+                SearchSource::Server => theme.peach,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("[{}] ", result.source.label()),
+                    Style::default().fg(label_color),
+                ),
+                Span::styled(format!("{}  ", when), Style::default().fg(theme.blue)),
+                Span::styled(
+                    if busy_only {
+                        "Busy".to_string()
+                    } else {
+                        result.event.subject.clone()
+                    },
+                    Style::default().fg(theme.foreground),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .fg(theme.yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("❯ ");
+    f.render_stateful_widget(list, area, &mut search.list_state);
 }
 
 fn draw_help_popup(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
@@ -496,11 +1230,41 @@ fn draw_help_popup(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
         Row::new(vec![app.symbols.help.as_str(), "Toggle Help"]),
         Row::new(vec!["q", "Quit"]),
         Row::new(vec!["r", "Refresh Events"]),
-        Row::new(vec!["b", "Back"]),
-        Row::new(vec!["Enter", "Select / Details"]),
+        Row::new(vec!["b", "Back (Day to Week, if Zoomed In)"]),
+        Row::new(vec!["Enter", "Select / Details / Zoom Into Day (Week)"]),
+        Row::new(vec![
+            "Space",
+            "Tick Calendar (Calendars, Enter Shows Ticked)",
+        ]),
         Row::new(vec!["Tab", "Cycle Views"]),
+        Row::new(vec!["l", "Legend (Show/Hide Calendars)"]),
+        Row::new(vec!["c", "Filter by Category"]),
+        Row::new(vec![",", "Edit Settings.toml"]),
+        Row::new(vec!["B", "Busy-only Mode (Shared Screens)"]),
         Row::new(vec![up_down_arrow.as_str(), "Navigate List / Scroll"]),
         Row::new(vec!["a/d", "Navigate Month/Week"]),
+        Row::new(vec!["1-9", "Open Link (Details)"]),
+        Row::new(vec!["x", "Expand Attendees (Details)"]),
+        Row::new(vec!["v", "Rendered/Source Description (Details)"]),
+        Row::new(vec!["O", "Open in Outlook Web (Details)"]),
+        Row::new(vec!["E", "Save as .ics File (Details)"]),
+        Row::new(vec!["m", "Mark Attended / Skipped (past)"]),
+        Row::new(vec!["j", "Join Online Meeting"]),
+        Row::new(vec!["!", "Flag for an Alarm at Start"]),
+        Row::new(vec!["e", "Edit Subject/Time/Location"]),
+        Row::new(vec!["o", "Overlay Colleagues' Free/Busy"]),
+        Row::new(vec!["f", "Find a Meeting Time"]),
+        Row::new(vec!["p", "Plan Today (Tasks into Free Slots)"]),
+        Row::new(vec!["/", "Search Events (Cache + Server)"]),
+        Row::new(vec!["Ctrl+F", "Search All Cached Events (Offline)"]),
+        Row::new(vec!["h", "Toggle Past Events (List)"]),
+        Row::new(vec!["g", "Group by Calendar (List)"]),
+        Row::new(vec!["z", "Fold/Unfold Calendar (Grouped List)"]),
+        Row::new(vec!["Space", "Peek at Day's Events (Month)"]),
+        Row::new(vec!["+/-", "Zoom Time Grid In/Out (Day)"]),
+        Row::new(vec!["D", "Diagnostics (Graph Requests per Hour)"]),
+        Row::new(vec!["M", "Missed Reminders (Do Not Disturb)"]),
+        Row::new(vec!["X", "Log Out (Calendars)"]),
     ];
 
     let table = Table::new(
@@ -528,14 +1292,11 @@ fn draw_help_popup(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     f.render_widget(table, chunks[0]);
 
     // Legend Section
-    let mut legend_lines: Vec<Line> = Vec::new();
-    for color_calendar in &app.calendars {
-        let line = Line::from(vec![
-            Span::styled("■ ", Style::default().fg(color_calendar.color)),
-            Span::raw(color_calendar.calendar.name.clone()),
-        ]);
-        legend_lines.push(line);
-    }
+    let legend_lines: Vec<Line> = app
+        .calendars_in_context()
+        .iter()
+        .map(|color_calendar| legend_line(app, color_calendar, theme))
+        .collect();
 
     let legend_paragraph = Paragraph::new(legend_lines)
         .style(Style::default().fg(theme.foreground))
@@ -548,6 +1309,143 @@ fn draw_help_popup(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     f.render_widget(legend_paragraph, chunks[1]);
 }
 
+/// A calendar's color (or icon) and name; hidden calendars are dimmed.
+fn legend_line(app: &App, color_calendar: &ColorCalendar, theme: &Theme) -> Line<'static> {
+    let hidden = app.hidden_calendars.contains(&color_calendar.calendar.id);
+    let icon = color_calendar
+        .icon
+        .clone()
+        .unwrap_or_else(|| "■ ".to_string());
+    let mut name_style = Style::default().fg(theme.foreground);
+    if hidden {
+        name_style = name_style.add_modifier(Modifier::DIM | Modifier::CROSSED_OUT);
+    }
+    Line::from(vec![
+        Span::raw(if hidden { "[ ] " } else { "[x] " }),
+        Span::styled(icon, Style::default().fg(color_calendar.color)),
+        Span::styled(color_calendar.calendar.name.clone(), name_style),
+    ])
+}
+
+/// The calendars of the current selection, each of which can be hidden, and
+/// what the markers in the event views mean.
+fn draw_legend_popup(f: &mut Frame, app: &mut App, theme: &Theme) {
+    let area = centered_rect(50, 60, f.size());
+    f.render_widget(Clear, area);
+
+    let symbols_height = 7;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(symbols_height)])
+        .split(area);
+
+    let items: Vec<ListItem> = app
+        .calendars_in_context()
+        .iter()
+        .map(|color_calendar| ListItem::new(legend_line(app, color_calendar, theme)))
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.mauve))
+                .title(" Legend ")
+                .title_bottom(" Space: show/hide | Esc: close "),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(theme.yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    app.legend_area = chunks[0];
+    f.render_stateful_widget(list, chunks[0], &mut app.legend_state);
+
+    let threshold = app.settings.large_meeting_threshold.unwrap_or(50);
+    let marker_lines = vec![
+        Line::from(vec![
+            Span::styled(
+                app.symbols.large_meeting.clone(),
+                Style::default().fg(theme.foreground),
+            ),
+            Span::raw(format!(" More than {} attendees", threshold)),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                app.symbols.dst_warning.clone(),
+                Style::default().fg(theme.foreground),
+            ),
+            Span::raw(" Close to a daylight saving time change"),
+        ]),
+        Line::from(vec![
+            Span::styled("■ ", Style::default().fg(theme.teal)),
+            Span::raw("Blocked locally with the planner"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "░░ ",
+                Style::default()
+                    .fg(theme.peach)
+                    .add_modifier(Modifier::DIM | Modifier::ITALIC),
+            ),
+            Span::raw("Colleague busy (o)"),
+        ]),
+        Line::from(Span::styled(
+            "Dimmed: already over",
+            Style::default().add_modifier(Modifier::DIM),
+        )),
+    ];
+    let markers = Paragraph::new(marker_lines)
+        .style(Style::default().fg(theme.foreground))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.mauve))
+                .title(" Markers "),
+        );
+    f.render_widget(markers, chunks[1]);
+}
+
+/// Checkbox list of the known event categories. With none ticked, every
+/// event is shown.
+fn draw_category_popup(f: &mut Frame, app: &mut App, theme: &Theme) {
+    let area = centered_rect(40, 50, f.size());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.mauve))
+        .title(" Categories ")
+        .title_bottom(" Space: tick | a: show all | Esc: close ");
+    app.category_area = area;
+
+    if app.known_categories.is_empty() {
+        let empty = Paragraph::new("No event in the cache has a category.")
+            .style(Style::default().fg(theme.foreground))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .known_categories
+        .iter()
+        .map(|category| {
+            let ticked = app.category_filter.contains(category);
+            ListItem::new(Line::from(vec![
+                Span::raw(if ticked { "[x] " } else { "[ ] " }),
+                Span::styled(category.clone(), Style::default().fg(theme.foreground)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .fg(theme.yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(list, area, &mut app.category_state);
+}
+
 struct DissolveEffect {
     progress: f32,
 }
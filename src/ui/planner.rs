@@ -0,0 +1,113 @@
+use crate::app::App;
+use crate::planner::PlannerPane;
+use crate::ui::Theme;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+/// Focus-day planner: open tasks on the left, today's free slots on the right.
+pub fn draw_planner_popup(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
+    let Some(planner) = &mut app.planner else {
+        return;
+    };
+    f.render_widget(Clear, area);
+
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.yellow))
+        .title(" Plan today ")
+        .title_bottom(" Tab: switch | Enter: add to calendar | l: block locally | Esc: close ");
+    let inner = outer.inner(area);
+    f.render_widget(outer, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(inner);
+
+    let pane_block = |title: String, focused: bool| {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if focused {
+                theme.mauve
+            } else {
+                theme.foreground
+            }))
+            .title(title)
+    };
+    let highlight = Style::default()
+        .fg(theme.yellow)
+        .add_modifier(Modifier::BOLD);
+
+    let tasks_block = pane_block(
+        format!(" Tasks ({}) ", planner.tasks.len()),
+        planner.focus == PlannerPane::Tasks,
+    );
+    if planner.tasks.is_empty() {
+        let paragraph =
+            Paragraph::new(format!("No open tasks in {}", planner.tasks_file.display()))
+                .style(Style::default().fg(theme.foreground))
+                .block(tasks_block);
+        f.render_widget(paragraph, chunks[0]);
+    } else {
+        let items: Vec<ListItem> = planner
+            .tasks
+            .iter()
+            .map(|task| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(task.title.clone(), Style::default().fg(theme.foreground)),
+                    Span::styled(
+                        format!("  {}m", task.minutes),
+                        Style::default().fg(theme.peach),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(tasks_block)
+            .highlight_style(highlight)
+            .highlight_symbol("❯ ");
+        f.render_stateful_widget(list, chunks[0], &mut planner.task_state);
+    }
+
+    let slots_block = pane_block(
+        " Free today ".to_string(),
+        planner.focus == PlannerPane::Slots,
+    );
+    if planner.slots.is_empty() {
+        let paragraph = Paragraph::new("No free time left today")
+            .style(Style::default().fg(theme.red))
+            .block(slots_block);
+        f.render_widget(paragraph, chunks[1]);
+    } else {
+        let items: Vec<ListItem> = planner
+            .slots
+            .iter()
+            .map(|slot| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{}-{}",
+                            slot.start.format("%H:%M"),
+                            slot.end.format("%H:%M")
+                        ),
+                        Style::default().fg(theme.foreground),
+                    ),
+                    Span::styled(
+                        format!("  {}m", slot.minutes()),
+                        Style::default().fg(theme.green),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(slots_block)
+            .highlight_style(highlight)
+            .highlight_symbol("❯ ");
+        f.render_stateful_widget(list, chunks[1], &mut planner.slot_state);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::app::App;
+use crate::sql_console::MAX_ROWS;
+use crate::ui::Theme;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Widest a result column gets; longer values are cut.
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// The SQL console: the query being typed on top, its results below.
+pub fn draw_sql_console(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let Some(console) = &app.sql_console else {
+        return;
+    };
+    f.render_widget(Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled("sql> ", Style::default().fg(theme.mauve)),
+        Span::styled(
+            console.input.as_str(),
+            Style::default().fg(theme.foreground),
+        ),
+        Span::styled("█", Style::default().fg(theme.foreground)),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.yellow))
+            .title(" SQL console (read-only) ")
+            .title_bottom(" Enter: run | Up/Down: scroll | Esc: close "),
+    );
+    f.render_widget(input, chunks[0]);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.yellow));
+    let result = match &console.result {
+        _ if console.running => {
+            f.render_widget(
+                Paragraph::new("Running... (Esc to stop)").block(block),
+                chunks[1],
+            );
+            return;
+        }
+        None => {
+            let hint = "Query the cache, e.g. SELECT subject, start_time FROM events \
+                        WHERE attendees LIKE '%ana@%' ORDER BY start_time";
+            f.render_widget(
+                Paragraph::new(hint)
+                    .style(Style::default().fg(theme.foreground))
+                    .wrap(Wrap { trim: true })
+                    .block(block),
+                chunks[1],
+            );
+            return;
+        }
+        Some(Err(e)) => {
+            f.render_widget(
+                Paragraph::new(e.as_str())
+                    .style(Style::default().fg(theme.red))
+                    .wrap(Wrap { trim: true })
+                    .block(block),
+                chunks[1],
+            );
+            return;
+        }
+        Some(Ok(result)) => result,
+    };
+
+    let count = if result.truncated {
+        format!(" first {} rows ", MAX_ROWS)
+    } else {
+        format!(" {} rows ", result.rows.len())
+    };
+    let widths: Vec<Constraint> = result
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let widest = result
+                .rows
+                .iter()
+                .map(|row| row.get(i).map_or(0, |cell| cell.width()))
+                .max()
+                .unwrap_or(0)
+                .max(name.width());
+            Constraint::Length(widest.min(MAX_COLUMN_WIDTH) as u16)
+        })
+        .collect();
+    let rows = result
+        .rows
+        .iter()
+        .skip(console.scroll)
+        .map(|row| Row::new(row.iter().map(String::as_str)));
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(result.columns.iter().map(String::as_str))
+                .style(
+                    Style::default()
+                        .fg(theme.yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .bottom_margin(1),
+        )
+        .column_spacing(2)
+        .style(Style::default().fg(theme.foreground))
+        .block(block.title(count));
+    f.render_widget(table, chunks[1]);
+}
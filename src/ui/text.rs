@@ -0,0 +1,146 @@
+//! Display-width aware text helpers. Emoji and CJK characters take two terminal
+//! columns, so char or byte counts can't be used to lay out event text. The
+//! views and the mouse hit-testing both go through these so they always agree
+//! on where a line breaks.
+
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use std::ops::Range;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// Cuts `text` to at most `max_width` columns, ending with "…" when shortened.
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let w = char_width(c);
+        // Leave one column for the ellipsis
+        if width + w > max_width - 1 {
+            break;
+        }
+        truncated.push(c);
+        width += w;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Greedy word wrap over `chars`, returning the char range of each line. Runs of
+/// whitespace at line breaks are dropped and words wider than a line are split.
+fn wrap_ranges(chars: &[char], max_width: usize) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && !chars[i].is_whitespace() {
+            i += 1;
+        }
+        words.push(start..i);
+    }
+
+    let width_of =
+        |range: Range<usize>| -> usize { chars[range].iter().map(|c| char_width(*c)).sum() };
+
+    let mut lines = Vec::new();
+    let mut current: Option<(Range<usize>, usize)> = None;
+    for word in words {
+        let word_width = width_of(word.clone());
+
+        if let Some((range, width)) = current.take() {
+            let gap = width_of(range.end..word.start);
+            if width + gap + word_width <= max_width {
+                current = Some((range.start..word.end, width + gap + word_width));
+                continue;
+            }
+            lines.push(range);
+        }
+
+        // Start a new line with this word, splitting it while it doesn't fit
+        let mut start = word.start;
+        let mut rest_width = word_width;
+        while rest_width > max_width && max_width > 0 {
+            let mut end = start;
+            let mut width = 0;
+            while end < word.end && (width + char_width(chars[end]) <= max_width || end == start) {
+                width += char_width(chars[end]);
+                end += 1;
+            }
+            lines.push(start..end);
+            rest_width -= width;
+            start = end;
+        }
+        if start < word.end {
+            current = Some((start..word.end, rest_width));
+        }
+    }
+    if let Some((range, _)) = current {
+        lines.push(range);
+    }
+
+    // An empty line still takes up a row
+    if lines.is_empty() {
+        lines.push(0..0);
+    }
+    lines
+}
+
+/// Wraps plain text to lines of at most `max_width` columns.
+pub fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    wrap_ranges(&chars, max_width)
+        .into_iter()
+        .map(|range| chars[range].iter().collect())
+        .collect()
+}
+
+/// Number of rows `text` takes up once wrapped to `max_width` columns.
+pub fn wrapped_height(text: &str, max_width: usize) -> usize {
+    wrap_text(text, max_width).len()
+}
+
+/// Wraps a styled line like [`wrap_text`], keeping each character's span style.
+pub fn wrap_line(line: Line<'_>, max_width: usize) -> Vec<Line<'static>> {
+    let styled: Vec<(char, Style)> = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+    let chars: Vec<char> = styled.iter().map(|(c, _)| *c).collect();
+
+    wrap_ranges(&chars, max_width)
+        .into_iter()
+        .map(|range| {
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            let mut content = String::new();
+            let mut current_style = None;
+            for (c, style) in &styled[range] {
+                if let Some(previous) = current_style {
+                    if previous != *style {
+                        spans.push(Span::styled(std::mem::take(&mut content), previous));
+                    }
+                }
+                current_style = Some(*style);
+                content.push(*c);
+            }
+            if let Some(style) = current_style {
+                spans.push(Span::styled(content, style));
+            }
+            Line::from(spans).style(line.style)
+        })
+        .collect()
+}